@@ -1,5 +1,8 @@
 use ark_ec::pairing::{Pairing, PairingOutput};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
 use ark_std::{cfg_iter, fmt::Debug, vec::Vec};
 
 #[cfg(feature = "parallel")]
@@ -7,15 +10,51 @@ use rayon::prelude::*;
 
 use crate::aggregation::error::AggregationError;
 use crate::aggregation::key::{PreparedVKey, WKey};
+use crate::aggregation::torus::{deserialize_gt, gt_serialized_size, serialize_gt};
 
 /// Commits to either a single vector of group G1 elements or 2 vectors, 1 of group G1 and 1 of group G2 elements.
-/// Both commitment outputs a pair of $F_q^k$ element.
-#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq)]
+/// Both commitment outputs a pair of $F_q^k$ element. These are compressed with the torus when
+/// serialized with `Compress::Yes`.
+#[derive(Clone, Debug, PartialEq)]
 pub struct PairCommitment<E: Pairing> {
     pub t: PairingOutput<E>,
     pub u: PairingOutput<E>,
 }
 
+impl<E: Pairing> CanonicalSerialize for PairCommitment<E> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        serialize_gt(&self.t, &mut writer, compress)?;
+        serialize_gt(&self.u, &mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        2 * gt_serialized_size::<E>(compress)
+    }
+}
+
+impl<E: Pairing> Valid for PairCommitment<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.t.check()?;
+        self.u.check()
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for PairCommitment<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let t = deserialize_gt(&mut reader, compress, validate)?;
+        let u = deserialize_gt(&mut reader, compress, validate)?;
+        Ok(Self { t, u })
+    }
+}
+
 impl<E: Pairing> PairCommitment<E> {
     /// Commits to a single vector of group G1 elements.
     pub fn single(
@@ -1,3 +1,4 @@
+use crate::progress::Cancelled;
 use ark_serialize::SerializationError;
 use ark_std::string::{String, ToString};
 
@@ -9,6 +10,14 @@ pub enum AggregationError {
     MalformedVerifyingKey,
     Serialization(String),
     InvalidSRS(String),
+    /// A `ProgressObserver` cancelled the aggregation
+    Cancelled,
+}
+
+impl From<Cancelled> for AggregationError {
+    fn from(_: Cancelled) -> Self {
+        Self::Cancelled
+    }
 }
 
 impl From<SerializationError> for AggregationError {
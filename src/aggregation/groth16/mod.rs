@@ -2,4 +2,8 @@ pub mod proof;
 pub mod prover;
 pub mod verifier;
 
-pub use {proof::AggregateProof, prover::aggregate_proofs, verifier::verify_aggregate_proof};
+pub use {
+    proof::AggregateProof,
+    prover::{aggregate_proofs, aggregate_proofs_safe, aggregate_proofs_with_observer},
+    verifier::{verify_aggregate_proof, verify_aggregate_proof_safe},
+};
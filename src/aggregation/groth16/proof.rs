@@ -2,14 +2,21 @@ use crate::aggregation::commitment::PairCommitment;
 use crate::aggregation::error::AggregationError;
 use crate::aggregation::kzg::KZGOpening;
 use crate::aggregation::srs;
+use crate::aggregation::torus::{
+    deserialize_gt, deserialize_gt_pairs, gt_pairs_serialized_size, gt_serialized_size,
+    serialize_gt, serialize_gt_pairs,
+};
 use ark_ec::pairing::{Pairing, PairingOutput};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
 use ark_std::{string::ToString, vec::Vec};
 
 /// AggregateProof contains all elements to verify n aggregated Groth16 proofs
 /// using inner pairing product arguments. This proof can be created by any
 /// party in possession of valid Groth16 proofs.
-#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct AggregateProof<E: Pairing> {
     /// commitment to A and B using the pair commitment scheme needed to verify
     /// TIPP relation.
@@ -33,6 +40,77 @@ impl<E: Pairing> PartialEq for AggregateProof<E> {
     }
 }
 
+impl<E: Pairing> CanonicalSerialize for AggregateProof<E> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.com_ab.serialize_with_mode(&mut writer, compress)?;
+        self.com_c.serialize_with_mode(&mut writer, compress)?;
+        serialize_gt(&self.z_ab, &mut writer, compress)?;
+        self.z_c.serialize_with_mode(&mut writer, compress)?;
+        self.tmipp.serialize_with_mode(&mut writer, compress)?;
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.com_ab.serialized_size(compress)
+            + self.com_c.serialized_size(compress)
+            + gt_serialized_size::<E>(compress)
+            + self.z_c.serialized_size(compress)
+            + self.tmipp.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing> Valid for AggregateProof<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.com_ab.check()?;
+        self.com_c.check()?;
+        self.z_ab.check()?;
+        self.z_c.check()?;
+        self.tmipp.check()?;
+        Ok(())
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for AggregateProof<E> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_gt_mode(reader, compress, compress, validate)
+    }
+}
+
+impl<E: Pairing> AggregateProof<E> {
+    /// Same as `deserialize_with_mode` but reads the GT elements in `gt_compress` mode, like
+    /// `Compress::No` for proofs written before GT elements were compressed
+    pub(crate) fn deserialize_with_gt_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        gt_compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let com_ab =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, gt_compress, validate)?;
+        let com_c =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, gt_compress, validate)?;
+        let z_ab = deserialize_gt(&mut reader, gt_compress, validate)?;
+        let z_c = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let tmipp =
+            TippMippProof::deserialize_with_gt_mode(&mut reader, compress, gt_compress, validate)?;
+        Ok(Self {
+            com_ab,
+            com_c,
+            z_ab,
+            z_c,
+            tmipp,
+        })
+    }
+}
+
 impl<E: Pairing> AggregateProof<E> {
     /// Performs some high level checks on the length of vectors and others to
     /// make sure all items in the proofs are consistent with each other.
@@ -68,7 +146,7 @@ impl<E: Pairing> AggregateProof<E> {
 /// It contains all elements derived in the GIPA loop for both TIPP and MIPP at
 /// the same time. Serialization is done manually here for better inspection
 /// (CanonicalSerialization is implemented manually, not via the macro).
-#[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Debug, Clone)]
 pub struct GipaProof<E: Pairing> {
     pub nproofs: u32,
     pub comms_ab: Vec<(PairCommitment<E>, PairCommitment<E>)>,
@@ -99,6 +177,104 @@ impl<E: Pairing> PartialEq for GipaProof<E> {
     }
 }
 
+impl<E: Pairing> CanonicalSerialize for GipaProof<E> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.nproofs.serialize_with_mode(&mut writer, compress)?;
+        self.comms_ab.serialize_with_mode(&mut writer, compress)?;
+        self.comms_c.serialize_with_mode(&mut writer, compress)?;
+        serialize_gt_pairs(&self.z_ab, &mut writer, compress)?;
+        self.z_c.serialize_with_mode(&mut writer, compress)?;
+        self.final_a.serialize_with_mode(&mut writer, compress)?;
+        self.final_b.serialize_with_mode(&mut writer, compress)?;
+        self.final_c.serialize_with_mode(&mut writer, compress)?;
+        self.final_vkey.serialize_with_mode(&mut writer, compress)?;
+        self.final_wkey.serialize_with_mode(&mut writer, compress)?;
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.nproofs.serialized_size(compress)
+            + self.comms_ab.serialized_size(compress)
+            + self.comms_c.serialized_size(compress)
+            + gt_pairs_serialized_size(&self.z_ab, compress)
+            + self.z_c.serialized_size(compress)
+            + self.final_a.serialized_size(compress)
+            + self.final_b.serialized_size(compress)
+            + self.final_c.serialized_size(compress)
+            + self.final_vkey.serialized_size(compress)
+            + self.final_wkey.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing> Valid for GipaProof<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.nproofs.check()?;
+        self.comms_ab.check()?;
+        self.comms_c.check()?;
+        for (l, r) in &self.z_ab {
+            l.check()?;
+            r.check()?;
+        }
+        self.z_c.check()?;
+        self.final_a.check()?;
+        self.final_b.check()?;
+        self.final_c.check()?;
+        self.final_vkey.check()?;
+        self.final_wkey.check()?;
+        Ok(())
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for GipaProof<E> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_gt_mode(reader, compress, compress, validate)
+    }
+}
+
+impl<E: Pairing> GipaProof<E> {
+    pub(crate) fn deserialize_with_gt_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        gt_compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let nproofs = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let comms_ab =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, gt_compress, validate)?;
+        let comms_c =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, gt_compress, validate)?;
+        let z_ab = deserialize_gt_pairs(&mut reader, gt_compress, validate)?;
+        let z_c = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_a = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_b = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_c = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_vkey =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_wkey =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        Ok(Self {
+            nproofs,
+            comms_ab,
+            comms_c,
+            z_ab,
+            z_c,
+            final_a,
+            final_b,
+            final_c,
+            final_vkey,
+            final_wkey,
+        })
+    }
+}
+
 impl<E: Pairing> GipaProof<E> {
     fn log_proofs(nproofs: usize) -> usize {
         (nproofs as f32).log2().ceil() as usize
@@ -115,7 +291,7 @@ impl<E: Pairing> GipaProof<E> {
 
 /// It contains the GIPA recursive elements as well as the KZG openings for v
 /// and w
-#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, Clone)]
+#[derive(CanonicalSerialize, Debug, Clone)]
 pub struct TippMippProof<E: Pairing> {
     pub gipa: GipaProof<E>,
     pub vkey_opening: KZGOpening<E::G2Affine>,
@@ -129,3 +305,40 @@ impl<E: Pairing> PartialEq for TippMippProof<E> {
             && self.wkey_opening == other.wkey_opening
     }
 }
+
+impl<E: Pairing> Valid for TippMippProof<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.gipa.check()?;
+        self.vkey_opening.check()?;
+        self.wkey_opening.check()
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for TippMippProof<E> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_gt_mode(reader, compress, compress, validate)
+    }
+}
+
+impl<E: Pairing> TippMippProof<E> {
+    pub(crate) fn deserialize_with_gt_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        gt_compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let gipa =
+            GipaProof::deserialize_with_gt_mode(&mut reader, compress, gt_compress, validate)?;
+        let vkey_opening = KZGOpening::deserialize_with_mode(&mut reader, compress, validate)?;
+        let wkey_opening = KZGOpening::deserialize_with_mode(&mut reader, compress, validate)?;
+        Ok(Self {
+            gipa,
+            vkey_opening,
+            wkey_opening,
+        })
+    }
+}
@@ -2,9 +2,9 @@ use ark_ec::pairing::PairingOutput;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::{batch_inversion, Field, PrimeField};
 use ark_std::ops::{AddAssign, MulAssign};
-use ark_std::{cfg_iter, cfg_iter_mut, string::ToString, vec::Vec, Zero, format};
+use ark_std::{cfg_iter, cfg_iter_mut, format, string::ToString, vec::Vec, Zero};
 
-use ark_groth16::Proof;
+use ark_groth16::{Proof, VerifyingKey};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
@@ -12,16 +12,20 @@ use rayon::prelude::*;
 use crate::aggregation::commitment::PairCommitment;
 use crate::aggregation::error::AggregationError;
 use crate::aggregation::key::{PreparedVKey, VKey, WKey};
+use crate::progress::{observe_phase, NoObserver, Phase, ProgressObserver};
 
 use super::proof::{AggregateProof, GipaProof, TippMippProof};
 use crate::aggregation::srs::{PreparedProverSRS, ProverSRS};
+use crate::aggregation::statement::bind_statement;
 use crate::aggregation::utils::{
     compress, inner_product_and_double_commitments, inner_product_and_single_commitments,
-    prove_commitments,
+    pad_by_repeating_last, prove_commitments,
 };
+use crate::fingerprint::groth16_vk_fingerprint;
 use dock_crypto_utils::{ff::powers, transcript::Transcript};
 
-/// Aggregate `n` zkSnark proofs, where `n` must be a power of two.
+/// Aggregate `n >= 1` zkSnark proofs. If `n` is not a power of two (or is 1), the proofs are padded
+/// to `padded_proof_count(n)` by repeating the last proof so the SRS must be specialized for `n`.
 /// WARNING: transcript_include represents everything that should be included in
 /// the transcript from outside the boundary of this function. This is especially
 /// relevant for ALL public inputs of ALL individual proofs. In the regular case,
@@ -32,21 +36,33 @@ use dock_crypto_utils::{ff::powers, transcript::Transcript};
 /// number of proofs and public inputs (+100ms in our case). In the case of Filecoin, the only
 /// non-fixed part of the public inputs are the challenges derived from a seed. Even though this
 /// seed comes from a random beacon, we are hashing this as a safety precaution.
+/// `aggregate_proofs_safe` appends the public inputs, the verifying key and the SRS
+/// size to the transcript itself.
 pub fn aggregate_proofs<E: Pairing, T: Transcript>(
     srs: impl Into<PreparedProverSRS<E>>,
     transcript: &mut T,
     proofs: &[Proof<E>],
 ) -> Result<AggregateProof<E>, AggregationError> {
-    if proofs.len() < 2 {
-        return Err(AggregationError::InvalidProof(
-            "invalid proof size < 2".to_string(),
-        ));
-    }
-    if !proofs.len().is_power_of_two() {
+    aggregate_proofs_with_observer(srs, transcript, proofs, &NoObserver)
+}
+
+/// Same as `aggregate_proofs` but tells `observer` about the progress, and returns
+/// `AggregationError::Cancelled` if it cancels
+pub fn aggregate_proofs_with_observer<E: Pairing, T: Transcript, O: ProgressObserver + ?Sized>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    proofs: &[Proof<E>],
+    observer: &O,
+) -> Result<AggregateProof<E>, AggregationError> {
+    if proofs.is_empty() {
         return Err(AggregationError::InvalidProof(
-            "invalid proof size: not power of two".to_string(),
+            "no proofs to aggregate".to_string(),
         ));
     }
+    // The inner product arguments need a power of two number of proofs so pad by repeating
+    // the last proof. The verifier pads the public inputs the same way.
+    let proofs = pad_by_repeating_last(proofs);
+    let proofs = proofs.as_ref();
 
     let srs = srs.into();
     if !srs.has_correct_len(proofs.len()) {
@@ -65,11 +81,20 @@ pub fn aggregate_proofs<E: Pairing, T: Transcript>(
         .map(|e| E::G2Prepared::from(*e))
         .collect::<Vec<_>>();
 
-    // A and B are committed together in this scheme
-    // T_AB, U_AB
-    let com_ab = PairCommitment::<E>::double(vkey_prep.clone(), &srs.wkey, &a, b_prep)?;
-    // T_C, U_C
-    let com_c = PairCommitment::<E>::single(vkey_prep, &c)?;
+    let (com_ab, com_c) = observe_phase(
+        observer,
+        Phase::AggregationCommitments,
+        proofs.len(),
+        || {
+            // A and B are committed together in this scheme
+            // T_AB, U_AB
+            let com_ab = PairCommitment::<E>::double(vkey_prep.clone(), &srs.wkey, &a, b_prep)?;
+            // T_C, U_C
+            let com_c = PairCommitment::<E>::single(vkey_prep, &c)?;
+            Ok::<_, AggregationError>((com_ab, com_c))
+        },
+        |_| proofs.len(),
+    )?;
 
     // Derive a random scalar to perform a linear combination of proofs
     transcript.append(b"AB-commitment", &com_ab);
@@ -102,16 +127,24 @@ pub fn aggregate_proofs<E: Pairing, T: Transcript>(
     let wkey_r_inv = srs.wkey.scale(&r_inv)?;
 
     // we prove tipp and mipp using the same recursive loop
-    let proof = prove_tipp_mipp(
-        &srs,
-        transcript,
-        &a,
-        &b_r,
-        &c,
-        &wkey_r_inv,
-        &r_vec,
-        &z_ab,
-        &z_c,
+    let proof = observe_phase(
+        observer,
+        Phase::AggregationInnerProducts,
+        proofs.len(),
+        || {
+            prove_tipp_mipp(
+                &srs,
+                transcript,
+                &a,
+                &b_r,
+                &c,
+                &wkey_r_inv,
+                &r_vec,
+                &z_ab,
+                &z_c,
+            )
+        },
+        |_| proofs.len(),
     )?;
 
     Ok(AggregateProof {
@@ -123,6 +156,27 @@ pub fn aggregate_proofs<E: Pairing, T: Transcript>(
     })
 }
 
+/// Same as `aggregate_proofs` but first appends the fingerprint of `vk`, the size of the SRS and
+/// the `public_inputs` of all `proofs` to the transcript with `append_statement`, so the caller
+/// needn't. Verify with `verify_aggregate_proof_safe`.
+pub fn aggregate_proofs_safe<E: Pairing, T: Transcript>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    vk: &VerifyingKey<E>,
+    proofs: &[Proof<E>],
+    public_inputs: &[Vec<E::ScalarField>],
+) -> Result<AggregateProof<E>, AggregationError> {
+    let srs = srs.into();
+    bind_statement(
+        transcript,
+        &groth16_vk_fingerprint(vk),
+        srs.n,
+        proofs.len(),
+        public_inputs,
+    )?;
+    aggregate_proofs(srs, transcript, proofs)
+}
+
 /// Proves a TIPP relation between A and B as well as a MIPP relation with C and
 /// r. Commitment keys must be of size of A, B and C. In the context of Groth16
 /// aggregation, we have that B = B^r and wkey is scaled by r^{-1}. The
@@ -11,7 +11,9 @@ use dock_crypto_utils::randomized_pairing_check::RandomizedPairingChecker;
 use rayon::prelude::*;
 
 use crate::aggregation::srs::{VerifierSRS, VerifierSRSProjective};
-use crate::aggregation::utils::{final_verification_check, verify_kzg};
+use crate::aggregation::statement::append_statement;
+use crate::aggregation::utils::{final_verification_check, pad_for_verification, verify_kzg};
+use crate::fingerprint::groth16_vk_fingerprint;
 
 use crate::aggregation::commitment::PairCommitment;
 use crate::aggregation::error::AggregationError;
@@ -34,6 +36,8 @@ use super::proof::AggregateProof;
 /// number of proofs and public inputs (+100ms in our case). In the case of Filecoin, the only
 /// non-fixed part of the public inputs are the challenges derived from a seed. Even though this
 /// seed comes from a random beacon, we are hashing this as a safety precaution.
+/// `verify_aggregate_proof_safe` appends the public inputs, the verifying key and the SRS
+/// size to the transcript itself.
 pub fn verify_aggregate_proof<E: Pairing, R: Rng, T: Transcript>(
     ip_verifier_srs: &VerifierSRS<E>,
     pvk: &PreparedVerifyingKey<E>,
@@ -50,13 +54,10 @@ pub fn verify_aggregate_proof<E: Pairing, R: Rng, T: Transcript>(
         }
     }
 
-    if public_inputs.len() != proof.tmipp.gipa.nproofs as usize {
-        return Err(AggregationError::InvalidProof(format!(
-            "public inputs len {} != number of proofs {}",
-            public_inputs.len(),
-            proof.tmipp.gipa.nproofs
-        )));
-    }
+    // The prover padded the proofs to a power of two by repeating the last one
+    let public_inputs =
+        pad_for_verification(public_inputs, proof.tmipp.gipa.nproofs, "public inputs")?;
+    let public_inputs = public_inputs.as_ref();
 
     // Random linear combination of proofs
     transcript.append(b"AB-commitment", &proof.com_ab);
@@ -95,6 +96,34 @@ pub fn verify_aggregate_proof<E: Pairing, R: Rng, T: Transcript>(
     )
 }
 
+/// Verifies a proof created by `aggregate_proofs_safe`. Appends the fingerprint of the verifying
+/// key, the size of the SRS and `public_inputs` to the transcript as the prover did.
+pub fn verify_aggregate_proof_safe<E: Pairing, R: Rng, T: Transcript>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &AggregateProof<E>,
+    rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    append_statement(
+        transcript,
+        &groth16_vk_fingerprint(&pvk.vk),
+        ip_verifier_srs.n,
+        public_inputs,
+    );
+    verify_aggregate_proof(
+        ip_verifier_srs,
+        pvk,
+        public_inputs,
+        proof,
+        rng,
+        transcript,
+        pairing_check,
+    )
+}
+
 /// verify_tipp_mipp returns a pairing equation to check the tipp proof.  $r$ is
 /// the randomness used to produce a random linear combination of A and B and
 /// used in the MIPP part with C
@@ -0,0 +1,13 @@
+//! Aggregation of LegoGroth16 proofs of different circuits, i.e. with different verifying keys.
+//! Along with the TIPP for A and B, it proves TIPPs for C and the δs and for D and the γs of the
+//! verifying keys of the proofs, all in the same GIPA loop.
+
+pub mod proof;
+pub mod prover;
+pub mod verifier;
+
+pub use {
+    proof::{HeterogeneousAggregateProof, MultiTippProof, VerifyingKeysCommitment},
+    prover::{aggregate_proofs, aggregate_proofs_safe, aggregate_proofs_with_observer},
+    verifier::{verify_aggregate_proof, verify_aggregate_proof_safe},
+};
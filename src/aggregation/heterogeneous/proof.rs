@@ -0,0 +1,157 @@
+use crate::aggregation::commitment::PairCommitment;
+use crate::aggregation::error::AggregationError;
+use crate::aggregation::key::WKey;
+use crate::aggregation::kzg::KZGOpening;
+use crate::aggregation::srs;
+use crate::aggregation::statement::verifying_keys_fingerprint;
+use crate::aggregation::utils::pad_by_repeating_last;
+use crate::fingerprint::Fingerprint;
+use crate::VerifyingKey;
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{format, string::ToString, vec::Vec};
+
+/// The number of TIPP relations, (A, B), (C, δ) and (D, γ)
+pub(crate) const NUM_RELATIONS: usize = 3;
+
+/// HeterogeneousAggregateProof contains all elements to verify n aggregated LegoGroth16 proofs
+/// that can have different verifying keys.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, Clone, PartialEq)]
+pub struct HeterogeneousAggregateProof<E: Pairing> {
+    /// commitment to A and B using the pair commitment scheme
+    pub com_ab: PairCommitment<E>,
+    /// commitment to C only, the verifier adds the commitment to the δs
+    pub com_c: PairCommitment<E>,
+    /// commitment to D only, the verifier adds the commitment to the γs
+    pub com_d: PairCommitment<E>,
+    /// $\prod e(A_i, B_i^{r^i})$ is the left side of the aggregated LegoGroth16 equation
+    pub z_ab: PairingOutput<E>,
+    /// $\prod e(C_i, \delta_i^{r^i})$ is used on the right side of the aggregated equation
+    pub z_c_delta: PairingOutput<E>,
+    /// $\prod e(D_i, \gamma_i^{r^i})$ is used on the right side of the aggregated equation
+    pub z_d_gamma: PairingOutput<E>,
+    pub tipp: MultiTippProof<E>,
+}
+
+/// It contains the elements derived in the GIPA loop for the TIPP relations, in the order
+/// (A, B), (C, δ) and (D, γ), and the KZG openings for the final commitment keys v and w
+/// shared by all of them.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, Clone, PartialEq)]
+pub struct MultiTippProof<E: Pairing> {
+    pub nproofs: u32,
+    /// For each GIPA round, the commitments to the cross halves of each relation
+    pub comms: Vec<Vec<(PairCommitment<E>, PairCommitment<E>)>>,
+    /// For each GIPA round, the inner products of the cross halves of each relation
+    pub z: Vec<Vec<(PairingOutput<E>, PairingOutput<E>)>>,
+    pub final_g1: Vec<E::G1Affine>,
+    pub final_g2: Vec<E::G2Affine>,
+    /// final commitment keys $v$ and $w$ - there is only one element at the
+    /// end for v1 and v2 hence it's a tuple.
+    pub final_vkey: (E::G2Affine, E::G2Affine),
+    pub final_wkey: (E::G1Affine, E::G1Affine),
+    pub vkey_opening: KZGOpening<E::G2Affine>,
+    pub wkey_opening: KZGOpening<E::G1Affine>,
+}
+
+/// Commitments to the δ and γ of the verifying key of each proof, using the `w` key of the
+/// prover SRS. These only depend on the `w` key and on which verifying key each proof is for, so
+/// the verifier computes them once for each such layout of keys and caches them; they cost a
+/// pairing per distinct key. They must not be taken from the prover.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, Clone, PartialEq)]
+pub struct VerifyingKeysCommitment<E: Pairing> {
+    pub delta: PairCommitment<E>,
+    pub gamma: PairCommitment<E>,
+    /// `verifying_keys_fingerprint` of the verifying keys committed to, before padding. The
+    /// verifier checks it against the keys of the statements so that a commitment to other keys
+    /// is rejected.
+    pub vks_fingerprint: Fingerprint,
+}
+
+impl<E: Pairing> HeterogeneousAggregateProof<E> {
+    /// Performs some high level checks on the length of vectors and others to
+    /// make sure all items in the proofs are consistent with each other.
+    pub fn parsing_check(&self) -> Result<(), AggregationError> {
+        let tipp = &self.tipp;
+        if tipp.nproofs < 2 || tipp.nproofs as usize > srs::MAX_SRS_SIZE {
+            return Err(AggregationError::InvalidProof(
+                "Proof length out of bounds".to_string(),
+            ));
+        }
+        if !tipp.nproofs.is_power_of_two() {
+            return Err(AggregationError::InvalidProof(
+                "Proof length not a power of two".to_string(),
+            ));
+        }
+        let ref_len = tipp.nproofs.trailing_zeros() as usize;
+        let all_same = tipp.comms.len() == ref_len
+            && tipp.z.len() == ref_len
+            && tipp.comms.iter().all(|c| c.len() == NUM_RELATIONS)
+            && tipp.z.iter().all(|z| z.len() == NUM_RELATIONS)
+            && tipp.final_g1.len() == NUM_RELATIONS
+            && tipp.final_g2.len() == NUM_RELATIONS;
+        if !all_same {
+            return Err(AggregationError::InvalidProof(
+                "Proof vectors unequal sizes".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<E: Pairing> VerifyingKeysCommitment<E> {
+    /// `wkey` is the `w` key of the prover SRS specialized for the number of proofs, which is all
+    /// that is needed so the verifier does not have to hold the whole prover SRS. `vks` contains
+    /// the verifying key of each proof in the order they are aggregated. They are padded like the
+    /// proofs.
+    /// This is O(n) in the number of proofs for each layout of keys: after padding, the `w` key
+    /// elements are added up for each proof and each proof's key is hashed into the fingerprint,
+    /// besides a pairing per distinct key.
+    pub fn new(wkey: &WKey<E>, vks: &[&VerifyingKey<E>]) -> Result<Self, AggregationError> {
+        if vks.is_empty() {
+            return Err(AggregationError::InvalidProof(
+                "no verifying keys".to_string(),
+            ));
+        }
+        let vks_fingerprint = verifying_keys_fingerprint(vks.iter().copied());
+        let vks = pad_by_repeating_last(vks);
+        if !wkey.has_correct_len(vks.len()) {
+            return Err(AggregationError::InvalidSRS(format!(
+                "SRS len {} != proofs len {}",
+                wkey.len(),
+                vks.len()
+            )));
+        }
+        Ok(Self {
+            delta: commit_grouped(wkey, vks.iter().map(|vk| vk.delta_g2)),
+            gamma: commit_grouped(wkey, vks.iter().map(|vk| vk.gamma_g2)),
+            vks_fingerprint,
+        })
+    }
+}
+
+/// Commits to `g2` with `wkey` as in the second half of `PairCommitment::double`. As there are
+/// only a few distinct elements, the `wkey` elements for each are added up first so that the
+/// number of pairings is only the number of distinct elements.
+fn commit_grouped<E: Pairing>(
+    wkey: &WKey<E>,
+    g2: impl Iterator<Item = E::G2Affine>,
+) -> PairCommitment<E> {
+    let mut distinct: Vec<(E::G2Affine, E::G1, E::G1)> = Vec::new();
+    for ((h, w_a), w_b) in g2.zip(wkey.a.iter()).zip(wkey.b.iter()) {
+        match distinct.iter_mut().find(|(d, _, _)| *d == h) {
+            Some((_, sum_a, sum_b)) => {
+                *sum_a += w_a;
+                *sum_b += w_b;
+            }
+            None => distinct.push((h, w_a.into_group(), w_b.into_group())),
+        }
+    }
+    let h = distinct.iter().map(|d| d.0).collect::<Vec<_>>();
+    let sum_a = E::G1::normalize_batch(&distinct.iter().map(|d| d.1).collect::<Vec<_>>());
+    let sum_b = E::G1::normalize_batch(&distinct.iter().map(|d| d.2).collect::<Vec<_>>());
+    PairCommitment {
+        t: E::multi_pairing(sum_a, h.clone()),
+        u: E::multi_pairing(sum_b, h),
+    }
+}
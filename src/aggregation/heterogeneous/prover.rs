@@ -0,0 +1,325 @@
+use ark_ec::pairing::PairingOutput;
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{batch_inversion, Field, PrimeField};
+use ark_std::{cfg_iter, format, string::ToString, vec, vec::Vec};
+
+use crate::{Proof, VerifyingKey};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::aggregation::commitment::PairCommitment;
+use crate::aggregation::error::AggregationError;
+use crate::aggregation::key::{PreparedVKey, WKey};
+use crate::aggregation::srs::{PreparedProverSRS, ProverSRS};
+use crate::aggregation::statement::{bind_statement, verifying_keys_fingerprint};
+use crate::aggregation::utils::{
+    compress, inner_product_and_double_commitments, pad_by_repeating_last, prove_commitments,
+};
+use crate::progress::{observe_phase, NoObserver, Phase, ProgressObserver};
+use dock_crypto_utils::{ff::powers, transcript::Transcript};
+
+use super::proof::{HeterogeneousAggregateProof, MultiTippProof, VerifyingKeysCommitment};
+
+/// Aggregate `n >= 1` LegoGroth16 proofs where `vks[i]` is the verifying key of `proofs[i]`. The
+/// keys can be all different or repeat. Proofs are padded as in `legogroth16::aggregate_proofs`.
+/// The same WARNING about the transcript applies as in `legogroth16::aggregate_proofs`.
+pub fn aggregate_proofs<E: Pairing, T: Transcript>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    proofs: &[Proof<E>],
+    vks: &[&VerifyingKey<E>],
+) -> Result<HeterogeneousAggregateProof<E>, AggregationError> {
+    aggregate_proofs_with_observer(srs, transcript, proofs, vks, &NoObserver)
+}
+
+/// Same as `aggregate_proofs` but first appends the fingerprint of all `vks` from
+/// `verifying_keys_fingerprint`, the size of the SRS and the `public_inputs` of all `proofs` to
+/// the transcript with `append_statement`, so the caller needn't. Verify with
+/// `verify_aggregate_proof_safe`.
+pub fn aggregate_proofs_safe<E: Pairing, T: Transcript>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    proofs: &[Proof<E>],
+    vks: &[&VerifyingKey<E>],
+    public_inputs: &[Vec<E::ScalarField>],
+) -> Result<HeterogeneousAggregateProof<E>, AggregationError> {
+    let srs = srs.into();
+    bind_statement(
+        transcript,
+        &verifying_keys_fingerprint(vks.iter().copied()),
+        srs.n,
+        proofs.len(),
+        public_inputs,
+    )?;
+    aggregate_proofs(srs, transcript, proofs, vks)
+}
+
+/// Same as `aggregate_proofs` but tells `observer` about the progress, and returns
+/// `AggregationError::Cancelled` if it cancels
+pub fn aggregate_proofs_with_observer<E: Pairing, T: Transcript, O: ProgressObserver + ?Sized>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    proofs: &[Proof<E>],
+    vks: &[&VerifyingKey<E>],
+    observer: &O,
+) -> Result<HeterogeneousAggregateProof<E>, AggregationError> {
+    if proofs.is_empty() {
+        return Err(AggregationError::InvalidProof(
+            "no proofs to aggregate".to_string(),
+        ));
+    }
+    if proofs.len() != vks.len() {
+        return Err(AggregationError::InvalidProof(format!(
+            "proofs len {} != verifying keys len {}",
+            proofs.len(),
+            vks.len()
+        )));
+    }
+    let proofs = pad_by_repeating_last(proofs);
+    let proofs = proofs.as_ref();
+
+    let srs = srs.into();
+    if !srs.has_correct_len(proofs.len()) {
+        return Err(AggregationError::InvalidSRS(
+            format!("SRS len {} != proofs len {}", srs.len(), proofs.len()).to_string(),
+        ));
+    }
+    let (vkey_prep, srs) = srs.extract_prepared();
+    let vks_com = VerifyingKeysCommitment::new(&srs.wkey, vks)?;
+    let vks = pad_by_repeating_last(vks);
+
+    let a = proofs.iter().map(|proof| proof.a).collect::<Vec<_>>();
+    let b = proofs.iter().map(|proof| proof.b).collect::<Vec<_>>();
+    let c = proofs.iter().map(|proof| proof.c).collect::<Vec<_>>();
+    let d = proofs.iter().map(|proof| proof.d).collect::<Vec<_>>();
+    let delta = vks.iter().map(|vk| vk.delta_g2).collect::<Vec<_>>();
+    let gamma = vks.iter().map(|vk| vk.gamma_g2).collect::<Vec<_>>();
+
+    let b_prep = cfg_iter!(b)
+        .map(|e| E::G2Prepared::from(*e))
+        .collect::<Vec<_>>();
+
+    let (com_ab, com_c, com_d) = observe_phase(
+        observer,
+        Phase::AggregationCommitments,
+        proofs.len(),
+        || {
+            // T_AB, U_AB
+            let com_ab = PairCommitment::<E>::double(vkey_prep.clone(), &srs.wkey, &a, b_prep)?;
+            // T_C, U_C and T_D, U_D. The verifier knows the commitments to δs and γs
+            let com_c = PairCommitment::<E>::single(vkey_prep.clone(), &c)?;
+            let com_d = PairCommitment::<E>::single(vkey_prep, &d)?;
+            Ok::<_, AggregationError>((com_ab, com_c, com_d))
+        },
+        |_| proofs.len(),
+    )?;
+
+    // Derive a random scalar to perform a linear combination of proofs
+    transcript.append(b"AB-commitment", &com_ab);
+    transcript.append(b"C-commitment", &com_c);
+    transcript.append(b"D-commitment", &com_d);
+    transcript.append(b"VK-commitment", &vks_com);
+    let r = transcript.challenge_scalar::<E::ScalarField>(b"r-random-fiatshamir");
+
+    // 1,r, r^2, r^3, r^4 ...
+    let r_vec: Vec<E::ScalarField> = powers(&r, proofs.len());
+    // 1,r^-1, r^-2, r^-3
+    let mut r_inv = r_vec.clone();
+    batch_inversion(&mut r_inv);
+
+    let r_repr = cfg_iter!(r_vec)
+        .map(|r| r.into_bigint())
+        .collect::<Vec<_>>();
+    let scale_by_r = |v: &[E::G2Affine]| {
+        let scaled = cfg_iter!(v)
+            .zip(cfg_iter!(r_repr))
+            .map(|(vi, ri)| vi.mul_bigint(*ri))
+            .collect::<Vec<_>>();
+        E::G2::normalize_batch(&scaled)
+    };
+
+    // B^{r}, δ^{r} and γ^{r}
+    let b_r = scale_by_r(&b);
+    let delta_r = scale_by_r(&delta);
+    let gamma_r = scale_by_r(&gamma);
+
+    let z_ab = E::multi_pairing(&a, &b_r);
+    let z_c_delta = E::multi_pairing(&c, &delta_r);
+    let z_d_gamma = E::multi_pairing(&d, &gamma_r);
+
+    // w^{r^{-1}}
+    let wkey_r_inv = srs.wkey.scale(&r_inv)?;
+
+    let tipp = observe_phase(
+        observer,
+        Phase::AggregationInnerProducts,
+        proofs.len(),
+        || {
+            prove_multi_tipp(
+                &srs,
+                transcript,
+                vec![a, c, d],
+                vec![b_r, delta_r, gamma_r],
+                &wkey_r_inv,
+                &r,
+                &[z_ab, z_c_delta, z_d_gamma],
+            )
+        },
+        |_| proofs.len(),
+    )?;
+
+    Ok(HeterogeneousAggregateProof {
+        com_ab,
+        com_c,
+        com_d,
+        z_ab,
+        z_c_delta,
+        z_d_gamma,
+        tipp,
+    })
+}
+
+/// Proves TIPP relations between each of `g1s` and the corresponding `g2s` in the same GIPA
+/// loop so that only one KZG proof is needed for each of v and w. As in the Groth16
+/// aggregation, the `g2s` are rescaled by r and `wkey` by r^{-1}.
+fn prove_multi_tipp<E: Pairing, T: Transcript>(
+    srs: &ProverSRS<E>,
+    transcript: &mut T,
+    mut g1s: Vec<Vec<E::G1Affine>>,
+    mut g2s: Vec<Vec<E::G2Affine>>,
+    wkey: &WKey<E>, // scaled key w^r^-1
+    r_shift: &E::ScalarField,
+    ips: &[PairingOutput<E>],
+) -> Result<MultiTippProof<E>, AggregationError> {
+    let nproofs = g1s[0].len();
+    // the values of the commitment keys rescaled at each step of the loop
+    let (mut vkey, mut wkey) = (srs.vkey.clone(), wkey.clone());
+
+    let mut comms = Vec::new();
+    let mut z = Vec::new();
+    let mut challenges: Vec<E::ScalarField> = Vec::new();
+    let mut challenges_inv: Vec<E::ScalarField> = Vec::new();
+
+    transcript.append(b"inner-products", &ips);
+    let mut c_inv: E::ScalarField =
+        transcript.challenge_scalar::<E::ScalarField>(b"first-challenge");
+    let mut c = c_inv.inverse().unwrap();
+
+    let mut i = 0;
+
+    while g1s[0].len() > 1 {
+        // recursive step
+        // Recurse with problem of half size
+        let split = g1s[0].len() / 2;
+
+        let (vk_left, vk_right) = vkey.split(split);
+        let (wk_left, wk_right) = wkey.split(split);
+
+        let vk_left_prep = PreparedVKey::from(&vk_left);
+        let vk_right_prep = PreparedVKey::from(&vk_right);
+
+        let (z_round, comms_round): (Vec<_>, Vec<_>) = g1s
+            .iter()
+            .zip(g2s.iter())
+            .map(|(x, y)| {
+                let (x_left, x_right) = x.split_at(split);
+                let (y_left, y_right) = y.split_at(split);
+                let y_left_prep = cfg_iter!(y_left)
+                    .map(|e| E::G2Prepared::from(*e))
+                    .collect::<Vec<_>>();
+                let y_right_prep = cfg_iter!(y_right)
+                    .map(|e| E::G2Prepared::from(*e))
+                    .collect::<Vec<_>>();
+                let (z_l, z_r, t_l, t_r) = inner_product_and_double_commitments(
+                    x_left,
+                    x_right,
+                    y_left_prep,
+                    y_right_prep,
+                    &wk_left,
+                    &wk_right,
+                    vk_left_prep.clone(),
+                    vk_right_prep.clone(),
+                );
+                ((z_l, z_r), (t_l, t_r))
+            })
+            .unzip();
+
+        // Fiat-Shamir challenge
+        if i == 0 {
+            // already generated c_inv and c outside of the loop
+        } else {
+            transcript.append(b"c_inv", &c_inv);
+            transcript.append(b"z", &z_round);
+            transcript.append(b"t", &comms_round);
+            c_inv = transcript.challenge_scalar::<E::ScalarField>(b"challenge_i");
+            c = c_inv.inverse().unwrap();
+        }
+
+        // Set up values for next step of recursion
+        // x[:n'] + x[n':] ^ c
+        for x in g1s.iter_mut() {
+            compress(x, split, &c);
+        }
+        // y[:n'] + y[n':] ^ c^-1
+        for y in g2s.iter_mut() {
+            compress(y, split, &c_inv);
+        }
+
+        // v_left + v_right^x^-1
+        vkey = vk_left.compress(&vk_right, &c_inv)?;
+        // w_left + w_right^x
+        wkey = wk_left.compress(&wk_right, &c)?;
+
+        comms.push(comms_round);
+        z.push(z_round);
+        challenges.push(c);
+        challenges_inv.push(c_inv);
+
+        i += 1;
+    }
+
+    let final_g1 = g1s.iter().map(|x| x[0]).collect::<Vec<_>>();
+    let final_g2 = g2s.iter().map(|y| y[0]).collect::<Vec<_>>();
+    let (final_vkey, final_wkey) = (vkey.first(), wkey.first());
+
+    // Prove final commitment keys are wellformed
+    // we reverse the transcript so the polynomial in kzg opening is constructed
+    // correctly - the formula indicates x_{l-j}. Also for deriving KZG
+    // challenge point, input must be the last challenge.
+    challenges.reverse();
+    challenges_inv.reverse();
+    let r_inverse = r_shift.inverse().unwrap();
+
+    // KZG challenge point
+    transcript.append(b"kzg-challenge", &challenges[0]);
+    transcript.append(b"vkey0", &final_vkey.0);
+    transcript.append(b"vkey1", &final_vkey.1);
+    transcript.append(b"wkey0", &final_wkey.0);
+    transcript.append(b"wkey1", &final_wkey.1);
+    let kzg_challenge = transcript.challenge_scalar::<E::ScalarField>(b"z-challenge");
+
+    // Complete KZG proofs
+    let (vkey_opening, wkey_opening) = prove_commitments::<E>(
+        &srs.h_alpha_powers_table,
+        &srs.h_beta_powers_table,
+        &srs.g_alpha_powers_table,
+        &srs.g_beta_powers_table,
+        &challenges,
+        &challenges_inv,
+        &r_inverse,
+        &kzg_challenge,
+    )?;
+
+    Ok(MultiTippProof {
+        nproofs: nproofs as u32,
+        comms,
+        z,
+        final_g1,
+        final_g2,
+        final_vkey,
+        final_wkey,
+        vkey_opening,
+        wkey_opening,
+    })
+}
@@ -0,0 +1,284 @@
+use ark_ec::pairing::PairingOutput;
+use ark_ec::{pairing::Pairing, AffineRepr, VariableBaseMSM};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_std::{cfg_iter, rand::Rng, string::ToString, vec, vec::Vec};
+use dock_crypto_utils::{ff::powers, randomized_pairing_check::RandomizedPairingChecker};
+
+use crate::PreparedVerifyingKey;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::aggregation::commitment::PairCommitment;
+use crate::aggregation::error::AggregationError;
+use crate::aggregation::srs::{VerifierSRS, VerifierSRSProjective};
+use crate::aggregation::statement::{append_statement, verifying_keys_fingerprint};
+use crate::aggregation::utils::{pad_for_verification, verify_kzg};
+use dock_crypto_utils::transcript::Transcript;
+
+use super::proof::{HeterogeneousAggregateProof, MultiTippProof, VerifyingKeysCommitment};
+
+/// Verifies proofs aggregated with `heterogeneous::aggregate_proofs`. `statements` contains the
+/// prepared verifying key and the public inputs of each proof and `vks_commitment` must have been
+/// created by the verifier from the same verifying keys in the same order, which is checked with
+/// its `vks_fingerprint`. That check hashes 32 bytes per proof, besides fingerprinting each
+/// distinct key once, so apart from it and hashing the public inputs, the work grows
+/// logarithmically with the number of proofs and linearly with the number of distinct verifying
+/// keys.
+/// The same WARNING about the transcript applies as in `legogroth16::verify_aggregate_proof`.
+pub fn verify_aggregate_proof<E: Pairing, R: Rng, T: Transcript>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    vks_commitment: &VerifyingKeysCommitment<E>,
+    statements: &[(&PreparedVerifyingKey<E>, &[E::ScalarField])],
+    proof: &HeterogeneousAggregateProof<E>,
+    mut rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    proof.parsing_check()?;
+    if verifying_keys_fingerprint(statements.iter().map(|(pvk, _)| &pvk.vk))
+        != vks_commitment.vks_fingerprint
+    {
+        return Err(AggregationError::InvalidProof(
+            "verifying keys commitment is not for the verifying keys of the statements".to_string(),
+        ));
+    }
+    for (pvk, pub_input) in statements {
+        if (pub_input.len() + 1) > pvk.vk.gamma_abc_g1.len() {
+            return Err(AggregationError::MalformedVerifyingKey);
+        }
+    }
+    check_keys_without_eta(statements, &proof.z_d_gamma)?;
+    // The prover padded the proofs to a power of two by repeating the last one
+    let statements = pad_for_verification(statements, proof.tipp.nproofs, "statements")?;
+    let statements = statements.as_ref();
+
+    // Random linear combination of proofs
+    transcript.append(b"AB-commitment", &proof.com_ab);
+    transcript.append(b"C-commitment", &proof.com_c);
+    transcript.append(b"D-commitment", &proof.com_d);
+    transcript.append(b"VK-commitment", vks_commitment);
+    let r = transcript.challenge_scalar::<E::ScalarField>(b"r-random-fiatshamir");
+
+    let mut c = RandomizedPairingChecker::new_using_rng(&mut rng, true);
+    let checker = pairing_check.unwrap_or(&mut c);
+
+    let add = |x: &PairCommitment<E>, y: &PairCommitment<E>| PairCommitment {
+        t: x.t + y.t,
+        u: x.u + y.u,
+    };
+    verify_multi_tipp(
+        &ip_verifier_srs.to_projective(),
+        &proof.tipp,
+        &[
+            proof.com_ab.clone(),
+            add(&proof.com_c, &vks_commitment.delta),
+            add(&proof.com_d, &vks_commitment.gamma),
+        ],
+        &[proof.z_ab, proof.z_c_delta, proof.z_d_gamma],
+        &r,
+        transcript,
+        checker,
+    );
+
+    // Group the proofs by their verifying key and for each key, sum the powers of r and the
+    // public inputs scaled by them
+    let r_powers = powers(&r, statements.len());
+    let mut groups: Vec<(&PreparedVerifyingKey<E>, Vec<E::ScalarField>)> = Vec::new();
+    for ((pvk, inputs), r_i) in statements.iter().zip(r_powers.iter()) {
+        let idx = match groups.iter().position(|(g, _)| g.vk == pvk.vk) {
+            Some(idx) => idx,
+            None => {
+                groups.push((pvk, vec![E::ScalarField::zero(); pvk.vk.gamma_abc_g1.len()]));
+                groups.len() - 1
+            }
+        };
+        let sums = &mut groups[idx].1;
+        sums[0] += r_i;
+        for (s, x) in sums[1..].iter_mut().zip(inputs.iter()) {
+            *s += *x * r_i;
+        }
+    }
+
+    // z_ab = \prod_k e(alpha_k, beta_k)^{s_k} * e(inputs_k, gamma_k) * z_c_delta * z_d_gamma
+    // where s_k is the sum of powers of r of proofs with key k
+    let mut target = proof.z_ab - proof.z_c_delta - proof.z_d_gamma;
+    let mut source1 = Vec::with_capacity(groups.len());
+    let mut source2 = Vec::with_capacity(groups.len());
+    for (pvk, sums) in groups {
+        target -= pvk.alpha_g1_beta_g2 * sums[0];
+        let sums = cfg_iter!(sums).map(|s| s.into_bigint()).collect::<Vec<_>>();
+        source1.push(E::G1::msm_bigint(&pvk.vk.gamma_abc_g1, &sums).into());
+        source2.push(pvk.vk.gamma_g2);
+    }
+    checker.add_multiple_sources_and_target(&source1, source2, &target);
+
+    match checker.verify() {
+        true => Ok(()),
+        false => Err(AggregationError::InvalidProof(
+            "Proof Verification Failed due to pairing checks".to_string(),
+        )),
+    }
+}
+
+/// Verifies a proof created by `aggregate_proofs_safe`. Appends the fingerprint of the verifying
+/// keys, the size of the SRS and the public inputs in `statements` to the transcript as the
+/// prover did.
+pub fn verify_aggregate_proof_safe<E: Pairing, R: Rng, T: Transcript>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    vks_commitment: &VerifyingKeysCommitment<E>,
+    statements: &[(&PreparedVerifyingKey<E>, &[E::ScalarField])],
+    proof: &HeterogeneousAggregateProof<E>,
+    rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    let public_inputs = statements.iter().map(|(_, i)| *i).collect::<Vec<_>>();
+    // `verify_aggregate_proof` rejects the proof if this is not the fingerprint of the keys in
+    // `statements`
+    append_statement(
+        transcript,
+        &vks_commitment.vks_fingerprint,
+        ip_verifier_srs.n,
+        &public_inputs,
+    );
+    verify_aggregate_proof(
+        ip_verifier_srs,
+        vks_commitment,
+        statements,
+        proof,
+        rng,
+        transcript,
+        pairing_check,
+    )
+}
+
+/// As in `calculate_d`, proofs for a key without `eta`, like one converted from Groth16, cannot
+/// have commitments as `d` is otherwise unconstrained. The `d` of each proof are only known
+/// through `z_d_gamma` so such keys cannot be mixed with keys with `eta`. If all keys are without
+/// `eta` then all `d` are 0 only if `z_d_gamma`, their random linear combination, is the identity.
+fn check_keys_without_eta<E: Pairing>(
+    statements: &[(&PreparedVerifyingKey<E>, &[E::ScalarField])],
+    z_d_gamma: &PairingOutput<E>,
+) -> Result<(), AggregationError> {
+    let without_eta = statements
+        .iter()
+        .filter(|(pvk, _)| pvk.vk.eta_gamma_inv_g1.is_zero())
+        .count();
+    if without_eta == 0 {
+        return Ok(());
+    }
+    if without_eta != statements.len() {
+        return Err(AggregationError::InvalidProof(
+            "keys without eta cannot be aggregated with keys with eta".to_string(),
+        ));
+    }
+    if !z_d_gamma.is_zero() {
+        return Err(AggregationError::InvalidProof(
+            "proofs for a key without eta cannot have commitments".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Adds the pairing checks of the TIPP relations of `proof` to `pairing_checker`. `coms` and
+/// `ips` are the commitments and inner products of all relations.
+fn verify_multi_tipp<E: Pairing, T: Transcript>(
+    v_srs: &VerifierSRSProjective<E>,
+    proof: &MultiTippProof<E>,
+    coms: &[PairCommitment<E>],
+    ips: &[PairingOutput<E>],
+    r_shift: &E::ScalarField,
+    transcript: &mut T,
+    pairing_checker: &mut RandomizedPairingChecker<E>,
+) {
+    let mut challenges = Vec::new();
+    let mut challenges_inv = Vec::new();
+
+    transcript.append(b"inner-products", &ips);
+    let mut c_inv: E::ScalarField =
+        transcript.challenge_scalar::<E::ScalarField>(b"first-challenge");
+    let mut c = c_inv.inverse().unwrap();
+
+    for (i, (comms_round, z_round)) in proof.comms.iter().zip(proof.z.iter()).enumerate() {
+        if i == 0 {
+            // already generated c_inv and c outside of the loop
+        } else {
+            transcript.append(b"c_inv", &c_inv);
+            transcript.append(b"z", z_round);
+            transcript.append(b"t", comms_round);
+            c_inv = transcript.challenge_scalar::<E::ScalarField>(b"challenge_i");
+            c = c_inv.inverse().unwrap();
+        }
+        challenges.push(c);
+        challenges_inv.push(c_inv);
+    }
+
+    // Final T, U and Z of each relation, left side multiplied by c and right side by c^-1
+    let final_res = cfg_iter!(coms)
+        .zip(cfg_iter!(ips))
+        .enumerate()
+        .map(|(j, (com, ip))| {
+            let (mut t, mut u, mut z) = (com.t, com.u, *ip);
+            for (round, (c, c_inv)) in challenges.iter().zip(challenges_inv.iter()).enumerate() {
+                let (com_l, com_r) = &proof.comms[round][j];
+                let (z_l, z_r) = &proof.z[round][j];
+                t += com_l.t * c + com_r.t * c_inv;
+                u += com_l.u * c + com_r.u * c_inv;
+                z += *z_l * c + *z_r * c_inv;
+            }
+            (t, u, z)
+        })
+        .collect::<Vec<_>>();
+
+    // we reverse the order because the KZG verification expects the challenges in
+    // reverse order.
+    challenges.reverse();
+    challenges_inv.reverse();
+
+    // KZG challenge point
+    transcript.append(b"kzg-challenge", &challenges[0]);
+    transcript.append(b"vkey0", &proof.final_vkey.0);
+    transcript.append(b"vkey1", &proof.final_vkey.1);
+    transcript.append(b"wkey0", &proof.final_wkey.0);
+    transcript.append(b"wkey1", &proof.final_wkey.1);
+    let kzg_challenge = transcript.challenge_scalar::<E::ScalarField>(b"z-challenge");
+
+    verify_kzg(
+        v_srs,
+        &proof.final_vkey,
+        &proof.vkey_opening,
+        &proof.final_wkey,
+        &proof.wkey_opening,
+        &challenges,
+        &challenges_inv,
+        &r_shift.inverse().unwrap(),
+        &kzg_challenge,
+        pairing_checker,
+    );
+
+    let v_0_prep = E::G2Prepared::from(proof.final_vkey.0);
+    let v_1_prep = E::G2Prepared::from(proof.final_vkey.1);
+    for ((x, y), (t, u, z)) in proof
+        .final_g1
+        .iter()
+        .zip(proof.final_g2.iter())
+        .zip(final_res.iter())
+    {
+        let y_prep = E::G2Prepared::from(*y);
+        // z = e(x, y)
+        pairing_checker.add_multiple_sources_and_target(&[*x], vec![y_prep.clone()], z);
+        // T = e(x, v1)e(w1, y)
+        pairing_checker.add_multiple_sources_and_target(
+            &[*x, proof.final_wkey.0],
+            vec![v_0_prep.clone(), y_prep.clone()],
+            t,
+        );
+        // U = e(x, v2)e(w2, y)
+        pairing_checker.add_multiple_sources_and_target(
+            &[*x, proof.final_wkey.1],
+            vec![v_1_prep.clone(), y_prep],
+            u,
+        );
+    }
+}
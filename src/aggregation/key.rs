@@ -1,12 +1,15 @@
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
 use ark_ff::PrimeField;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::{cfg_iter, fmt::Debug, vec::Vec};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+use ark_std::{cfg_iter, fmt::Debug, io::Read, vec::Vec};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 use super::error::AggregationError;
+use super::utils::{check_points, deserialize_points};
 
 /// This module implements two binding commitment schemes used in the Groth16
 /// aggregation.
@@ -37,7 +40,7 @@ use super::error::AggregationError;
 /// one commitment.
 /// Key is a generic commitment key that is instantiated with g and h as basis,
 /// and a and b as powers.
-#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize)]
 pub struct Key<G: AffineRepr> {
     /// Exponent is a
     pub a: Vec<G>,
@@ -45,6 +48,25 @@ pub struct Key<G: AffineRepr> {
     pub b: Vec<G>,
 }
 
+impl<G: AffineRepr> Valid for Key<G> {
+    fn check(&self) -> Result<(), SerializationError> {
+        check_points(&self.a)?;
+        check_points(&self.b)
+    }
+}
+
+impl<G: AffineRepr> CanonicalDeserialize for Key<G> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let a = deserialize_points(&mut reader, compress, validate)?;
+        let b = deserialize_points(&mut reader, compress, validate)?;
+        Ok(Self { a, b })
+    }
+}
+
 /// Commitment key used by the "single" commitment on G1 values as
 /// well as in the "pair" commitment.
 /// It contains $\{h^a^i\}_{i=1}^n$ and $\{h^b^i\}_{i=1}^n$
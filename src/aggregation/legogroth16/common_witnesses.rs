@@ -0,0 +1,291 @@
+//! Proving statements about the committed witnesses of all the aggregated proofs at once.
+//!
+//! The aggregate proof binds all `d_i` by `com_d` and contains $z_d = \sum_i r^i d_i$ which, as
+//! all `d_i` have the same bases, is a single commitment to the witnesses $\sum_i r^i w_i$ with
+//! randomness $\sum_i r^i v_i$. If the witness at index `j` is the same in all proofs then its
+//! coefficient in `z_d` is $w_j \sum_i r^i$. So the prover commits to the common witnesses
+//! as `commitment` before `r` is derived and proves knowledge of an opening of
+//! $z_d - (\sum_i r^i) * commitment$ without the bases of the common witnesses. As `r` is chosen
+//! after all `d_i` and `commitment` are fixed, the coefficient of each such base is $\sum_i
+//! r^i (w_{i, j} - w_j)$ which is 0 only if all $w_{i, j}$ equal $w_j$. `commitment` has the bases
+//! of `d` so it can be used in other protocols about the common witnesses like `d` of any proof.
+
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{cfg_iter, rand::Rng, string::ToString, vec::Vec, UniformRand};
+use dock_crypto_utils::{
+    ff::{inner_product, powers, sum_of_powers},
+    randomized_pairing_check::RandomizedPairingChecker,
+    transcript::Transcript,
+};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::aggregation::error::AggregationError;
+use crate::aggregation::srs::{PreparedProverSRS, VerifierSRS};
+use crate::aggregation::statement::{append_statement, bind_statement};
+use crate::aggregation::utils::pad_by_repeating_last;
+use crate::progress::NoObserver;
+use crate::{PreparedVerifyingKey, Proof, VerifyingKey};
+
+use super::proof::AggregateLegoProof;
+use super::prover::aggregate;
+use super::verifier::verify;
+
+/// Proof that the committed witnesses at some indices are the same in all the aggregated proofs
+/// and are the ones committed in `commitment`
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommonWitnessesProof<E: Pairing> {
+    /// Commitment to the common witnesses with the same bases as `d` and randomness with
+    /// `eta_gamma_inv_g1`
+    pub commitment: E::G1Affine,
+    /// Commitment to the blindings of the Schnorr protocol
+    pub t: E::G1Affine,
+    /// Responses for the other committed witnesses, in order of their index, and the last for the
+    /// randomness
+    pub responses: Vec<E::ScalarField>,
+}
+
+/// Aggregates `proofs` as `aggregate_proofs` and proves that the committed witnesses at indices
+/// `common`, counting from the first committed witness, are the same in all proofs. `witnesses`
+/// are the committed witnesses of each proof and `v` the randomness of each `d`. The common
+/// witnesses are committed with randomness `blinding`. The transcript differs from the one of
+/// `aggregate_proofs` so the aggregate proof must be verified with
+/// `verify_aggregate_proof_with_common_witnesses`.
+#[allow(clippy::too_many_arguments)]
+pub fn aggregate_proofs_with_common_witnesses<E: Pairing, T: Transcript, R: Rng>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    proofs: &[Proof<E>],
+    vk: &VerifyingKey<E>,
+    witnesses: &[Vec<E::ScalarField>],
+    v: &[E::ScalarField],
+    common: &[usize],
+    blinding: E::ScalarField,
+    rng: &mut R,
+) -> Result<(AggregateLegoProof<E>, CommonWitnessesProof<E>), AggregationError> {
+    if proofs.is_empty() {
+        return Err(AggregationError::InvalidProof(
+            "no proofs to aggregate".to_string(),
+        ));
+    }
+    if witnesses.len() != proofs.len() || v.len() != proofs.len() {
+        return Err(AggregationError::InvalidProof(
+            "need the committed witnesses and randomness of each proof".to_string(),
+        ));
+    }
+    let (bases, h) = commitment_bases(vk)?;
+    check_common_indices(common, bases.len())?;
+    if witnesses.iter().any(|w| w.len() != bases.len()) {
+        return Err(AggregationError::InvalidProof(
+            "unexpected number of committed witnesses".to_string(),
+        ));
+    }
+    if witnesses
+        .iter()
+        .any(|w| common.iter().any(|&j| w[j] != witnesses[0][j]))
+    {
+        return Err(AggregationError::InvalidProof(
+            "common witnesses differ between proofs".to_string(),
+        ));
+    }
+
+    let common_bases = common.iter().map(|&j| bases[j]).collect::<Vec<_>>();
+    let common_witnesses = common.iter().map(|&j| witnesses[0][j]).collect::<Vec<_>>();
+    let commitment =
+        (E::G1::msm_unchecked(&common_bases, &common_witnesses) + h * blinding).into_affine();
+    transcript.append(b"common-witnesses-commitment", &commitment);
+
+    let proofs = pad_by_repeating_last(proofs);
+    let witnesses = pad_by_repeating_last(witnesses);
+    let v = pad_by_repeating_last(v);
+    let (proof, _, r) = aggregate(srs.into(), transcript, proofs.as_ref(), &[], &NoObserver)?;
+    let r_vec = powers(&r, proofs.len());
+    let r_sum = r_vec.iter().sum::<E::ScalarField>();
+
+    // Opening of z_d - r_sum * commitment: the combined other witnesses and the randomness
+    let (mut other_bases, mut secrets): (Vec<_>, Vec<_>) = other_indices(common, bases.len())
+        .map(|j| {
+            let w_j = witnesses.iter().map(|w| w[j]).collect::<Vec<_>>();
+            (bases[j], inner_product(&r_vec, &w_j))
+        })
+        .unzip();
+    secrets.push(inner_product(&r_vec, &v) - r_sum * blinding);
+    other_bases.push(h);
+
+    let blindings = (0..secrets.len())
+        .map(|_| E::ScalarField::rand(rng))
+        .collect::<Vec<_>>();
+    let t = E::G1::msm_unchecked(&other_bases, &blindings).into_affine();
+    transcript.append(b"common-witnesses-t", &t);
+    let c = transcript.challenge_scalar::<E::ScalarField>(b"common-witnesses-challenge");
+    let responses = cfg_iter!(blindings)
+        .zip(cfg_iter!(secrets))
+        .map(|(b, s)| *b + c * s)
+        .collect();
+
+    Ok((
+        proof,
+        CommonWitnessesProof {
+            commitment,
+            t,
+            responses,
+        },
+    ))
+}
+
+/// Same as `aggregate_proofs_with_common_witnesses` but first appends the fingerprint of `vk`, the
+/// size of the SRS and the `public_inputs` of all `proofs` to the transcript with
+/// `append_statement`, and then the indices `common`, so the caller needn't. Verify with
+/// `verify_aggregate_proof_with_common_witnesses_safe`.
+#[allow(clippy::too_many_arguments)]
+pub fn aggregate_proofs_with_common_witnesses_safe<E: Pairing, T: Transcript, R: Rng>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    proofs: &[Proof<E>],
+    public_inputs: &[Vec<E::ScalarField>],
+    vk: &VerifyingKey<E>,
+    witnesses: &[Vec<E::ScalarField>],
+    v: &[E::ScalarField],
+    common: &[usize],
+    blinding: E::ScalarField,
+    rng: &mut R,
+) -> Result<(AggregateLegoProof<E>, CommonWitnessesProof<E>), AggregationError> {
+    let srs = srs.into();
+    bind_statement(
+        transcript,
+        &vk.fingerprint(),
+        srs.n,
+        proofs.len(),
+        public_inputs,
+    )?;
+    append_common_indices(transcript, common);
+    aggregate_proofs_with_common_witnesses(
+        srs, transcript, proofs, vk, witnesses, v, common, blinding, rng,
+    )
+}
+
+/// Verifies the aggregate proof and the proof of common witnesses created by
+/// `aggregate_proofs_with_common_witnesses`. The transcript must be the same as the one given to
+/// the prover.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_aggregate_proof_with_common_witnesses<E: Pairing, R: Rng, T: Transcript>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &AggregateLegoProof<E>,
+    common_witnesses_proof: &CommonWitnessesProof<E>,
+    common: &[usize],
+    rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    proof.parsing_check()?;
+    let (bases, h) = commitment_bases(&pvk.vk)?;
+    check_common_indices(common, bases.len())?;
+    if common_witnesses_proof.responses.len() != bases.len() - common.len() + 1 {
+        return Err(AggregationError::InvalidProof(
+            "unexpected number of responses".to_string(),
+        ));
+    }
+
+    transcript.append(
+        b"common-witnesses-commitment",
+        &common_witnesses_proof.commitment,
+    );
+    let r = verify(
+        ip_verifier_srs,
+        &pvk.vk,
+        public_inputs,
+        proof,
+        None,
+        rng,
+        transcript,
+        pairing_check,
+    )?;
+    let r_sum = sum_of_powers::<E::ScalarField>(&r, proof.tmipp.gipa.nproofs as usize);
+
+    transcript.append(b"common-witnesses-t", &common_witnesses_proof.t);
+    let c = transcript.challenge_scalar::<E::ScalarField>(b"common-witnesses-challenge");
+    let mut other_bases = other_indices(common, bases.len())
+        .map(|j| bases[j])
+        .collect::<Vec<_>>();
+    other_bases.push(h);
+    let lhs = E::G1::msm_unchecked(&other_bases, &common_witnesses_proof.responses);
+    let rhs = (proof.z_d.into_group() - common_witnesses_proof.commitment * r_sum) * c
+        + common_witnesses_proof.t;
+    if lhs != rhs {
+        return Err(AggregationError::InvalidProof(
+            "common witnesses proof is invalid".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Verifies a proof created by `aggregate_proofs_with_common_witnesses_safe`. Appends the
+/// fingerprint of the verifying key, the size of the SRS, `public_inputs` and `common` to the
+/// transcript as the prover did.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_aggregate_proof_with_common_witnesses_safe<E: Pairing, R: Rng, T: Transcript>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &AggregateLegoProof<E>,
+    common_witnesses_proof: &CommonWitnessesProof<E>,
+    common: &[usize],
+    rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    append_statement(
+        transcript,
+        &pvk.vk.fingerprint(),
+        ip_verifier_srs.n,
+        public_inputs,
+    );
+    append_common_indices(transcript, common);
+    verify_aggregate_proof_with_common_witnesses(
+        ip_verifier_srs,
+        pvk,
+        public_inputs,
+        proof,
+        common_witnesses_proof,
+        common,
+        rng,
+        transcript,
+        pairing_check,
+    )
+}
+
+/// The indices are part of the statement as they decide which witnesses are proven equal
+fn append_common_indices<T: Transcript>(transcript: &mut T, common: &[usize]) {
+    let common = common.iter().map(|&j| j as u64).collect::<Vec<_>>();
+    transcript.append(b"common-witnesses-indices", &common);
+}
+
+/// The bases of the committed witnesses and of the randomness in `d`
+fn commitment_bases<E: Pairing>(
+    vk: &VerifyingKey<E>,
+) -> Result<(&[E::G1Affine], E::G1Affine), AggregationError> {
+    if vk.gamma_abc_g1.len() <= vk.commit_witness_count {
+        return Err(AggregationError::MalformedVerifyingKey);
+    }
+    let start = vk.gamma_abc_g1.len() - vk.commit_witness_count;
+    Ok((&vk.gamma_abc_g1[start..], vk.eta_gamma_inv_g1))
+}
+
+/// The indices must be increasing, so unique, and less than the number of committed witnesses
+fn check_common_indices(common: &[usize], count: usize) -> Result<(), AggregationError> {
+    if common.windows(2).any(|w| w[0] >= w[1]) || common.last().is_some_and(|&j| j >= count) {
+        return Err(AggregationError::InvalidProof(
+            "common witness indices must be increasing and less than the number of committed witnesses"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn other_indices(common: &[usize], count: usize) -> impl Iterator<Item = usize> + '_ {
+    (0..count).filter(move |j| common.binary_search(j).is_err())
+}
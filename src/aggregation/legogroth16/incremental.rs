@@ -0,0 +1,192 @@
+//! Aggregating LegoGroth16 proofs as they arrive.
+//!
+//! Two finished aggregate proofs cannot be merged into one since the inner product argument
+//! of an aggregate proof only certifies the final folded elements and not the proofs it was
+//! created from, and the challenge `r` depends on the commitments to all the proofs. What can be
+//! reused are the commitments to the proofs. The commitment key `v` is the same prefix of the
+//! SRS for every number of proofs, so the `v` part of the commitments to A, C and D is updated
+//! with each appended proof. The key `w` depends on the padded number of proofs so the `w` part
+//! of the commitment to B is recomputed only when that number doubles. Aggregating then costs a
+//! few pairings for the padding plus the inner product argument.
+
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_std::{string::ToString, vec::Vec, Zero};
+
+use crate::aggregation::commitment::PairCommitment;
+use crate::aggregation::error::AggregationError;
+use crate::aggregation::srs::{GenericSRS, ProverSRS};
+use crate::aggregation::utils::{pad_by_repeating_last, padded_proof_count};
+use crate::progress::{observe_phase, NoObserver, Phase, ProgressObserver};
+use crate::Proof;
+use dock_crypto_utils::transcript::Transcript;
+
+use super::proof::AggregateLegoProof;
+use super::prover::aggregate_committed;
+
+/// Aggregates LegoGroth16 proofs appended one at a time. Aggregating the appended proofs
+/// gives the same aggregate proof as `aggregate_proofs` over those proofs with the SRS
+/// specialized for their number, so it is verified with `verify_aggregate_proof`.
+pub struct IncrementalAggregator<'a, E: Pairing> {
+    srs: &'a GenericSRS<E>,
+    /// Specialized for `padded_proof_count` of the number of appended proofs
+    prover_srs: Option<ProverSRS<E>>,
+    proofs: Vec<Proof<E>>,
+    /// Commitments to A, C and D with the `v` key, these don't depend on the number of proofs
+    com_a: PairCommitment<E>,
+    com_c: PairCommitment<E>,
+    com_d: PairCommitment<E>,
+    /// Commitment to B with the `w` key of `prover_srs`
+    com_b: PairCommitment<E>,
+}
+
+impl<'a, E: Pairing> IncrementalAggregator<'a, E> {
+    pub fn new(srs: &'a GenericSRS<E>) -> Self {
+        Self {
+            srs,
+            prover_srs: None,
+            proofs: Vec::new(),
+            com_a: zero_commitment(),
+            com_c: zero_commitment(),
+            com_d: zero_commitment(),
+            com_b: zero_commitment(),
+        }
+    }
+
+    /// Appends a proof, returns `AggregationError::InsufficientKeyLength` if the SRS is too
+    /// small for one more proof in which case the proof is not appended.
+    pub fn append(&mut self, proof: Proof<E>) -> Result<(), AggregationError> {
+        let i = self.proofs.len();
+        let n = padded_proof_count(i + 1);
+        if self.prover_srs.as_ref().map(|s| s.n) != Some(n) {
+            let (srs, _) = self.srs.specialize(n)?;
+            // The w key changed so commit to B of the existing proofs again
+            let b = self.proofs.iter().map(|p| p.b).collect::<Vec<_>>();
+            self.com_b = PairCommitment {
+                t: E::multi_pairing(&srs.wkey.a[0..i], &b),
+                u: E::multi_pairing(&srs.wkey.b[0..i], &b),
+            };
+            self.prover_srs = Some(srs);
+        }
+        let srs = self.prover_srs.as_ref().unwrap();
+        let (v1, v2) = (srs.vkey.a[i], srs.vkey.b[i]);
+        add_to_commitment(&mut self.com_a, proof.a, v1, v2);
+        add_to_commitment(&mut self.com_c, proof.c, v1, v2);
+        add_to_commitment(&mut self.com_d, proof.d, v1, v2);
+        self.com_b.t += E::pairing(srs.wkey.a[i], proof.b);
+        self.com_b.u += E::pairing(srs.wkey.b[i], proof.b);
+        self.proofs.push(proof);
+        Ok(())
+    }
+
+    /// Number of appended proofs
+    pub fn len(&self) -> usize {
+        self.proofs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.proofs.is_empty()
+    }
+
+    pub fn proofs(&self) -> &[Proof<E>] {
+        &self.proofs
+    }
+
+    /// Aggregates the appended proofs. The transcript is as in `aggregate_proofs`.
+    pub fn aggregate<T: Transcript>(
+        &self,
+        transcript: &mut T,
+    ) -> Result<AggregateLegoProof<E>, AggregationError> {
+        self.aggregate_with_observer(transcript, &NoObserver)
+    }
+
+    /// Same as `aggregate` but tells `observer` about the progress, and returns
+    /// `AggregationError::Cancelled` if it cancels
+    pub fn aggregate_with_observer<T: Transcript, O: ProgressObserver + ?Sized>(
+        &self,
+        transcript: &mut T,
+        observer: &O,
+    ) -> Result<AggregateLegoProof<E>, AggregationError> {
+        let srs = match &self.prover_srs {
+            Some(srs) if !self.proofs.is_empty() => srs,
+            _ => {
+                return Err(AggregationError::InvalidProof(
+                    "no proofs to aggregate".to_string(),
+                ))
+            }
+        };
+        let m = self.proofs.len();
+        let proofs = pad_by_repeating_last(&self.proofs);
+        let proofs = proofs.as_ref();
+
+        let (com_ab, com_c, com_d) = observe_phase(
+            observer,
+            Phase::AggregationCommitments,
+            srs.n - m,
+            || {
+                let mut com_ab = PairCommitment {
+                    t: self.com_a.t + self.com_b.t,
+                    u: self.com_a.u + self.com_b.u,
+                };
+                let mut com_c = self.com_c.clone();
+                let mut com_d = self.com_d.clone();
+                if m < srs.n {
+                    // The padding repeats the last proof so its commitment key elements can be
+                    // summed instead of pairing each of them
+                    let last = &self.proofs[m - 1];
+                    let v1 = sum(&srs.vkey.a[m..]);
+                    let v2 = sum(&srs.vkey.b[m..]);
+                    let w1 = sum(&srs.wkey.a[m..]);
+                    let w2 = sum(&srs.wkey.b[m..]);
+                    add_to_commitment(&mut com_ab, last.a, v1, v2);
+                    com_ab.t += E::pairing(w1, last.b);
+                    com_ab.u += E::pairing(w2, last.b);
+                    add_to_commitment(&mut com_c, last.c, v1, v2);
+                    add_to_commitment(&mut com_d, last.d, v1, v2);
+                }
+                Ok::<_, AggregationError>((com_ab, com_c, com_d))
+            },
+            |_| srs.n - m,
+        )?;
+
+        let (proof, _, _) = aggregate_committed(
+            srs,
+            transcript,
+            (
+                proofs.iter().map(|p| p.a).collect(),
+                proofs.iter().map(|p| p.b).collect(),
+                proofs.iter().map(|p| p.c).collect(),
+                proofs.iter().map(|p| p.d).collect(),
+            ),
+            &[],
+            (com_ab, com_c, com_d, Vec::new()),
+            observer,
+        )?;
+        Ok(proof)
+    }
+}
+
+fn zero_commitment<E: Pairing>() -> PairCommitment<E> {
+    PairCommitment {
+        t: PairingOutput::zero(),
+        u: PairingOutput::zero(),
+    }
+}
+
+/// Adds `e(x, v1)` to `t` and `e(x, v2)` to `u`
+fn add_to_commitment<E: Pairing>(
+    com: &mut PairCommitment<E>,
+    x: E::G1Affine,
+    v1: impl Into<E::G2Prepared>,
+    v2: impl Into<E::G2Prepared>,
+) {
+    com.t += E::pairing(x, v1);
+    com.u += E::pairing(x, v2);
+}
+
+fn sum<G: AffineRepr>(elems: &[G]) -> G {
+    elems
+        .iter()
+        .fold(G::Group::zero(), |acc, e| acc + e)
+        .into_affine()
+}
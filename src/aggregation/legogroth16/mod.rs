@@ -1,6 +1,29 @@
+pub mod common_witnesses;
+pub mod incremental;
 pub mod proof;
 pub mod prover;
+pub mod transparent;
 pub mod using_groth16;
 pub mod verifier;
 
-pub use {proof::AggregateLegoProof, prover::aggregate_proofs, verifier::verify_aggregate_proof};
+pub use {
+    common_witnesses::{
+        aggregate_proofs_with_common_witnesses, aggregate_proofs_with_common_witnesses_safe,
+        verify_aggregate_proof_with_common_witnesses,
+        verify_aggregate_proof_with_common_witnesses_safe, CommonWitnessesProof,
+    },
+    incremental::IncrementalAggregator,
+    proof::{
+        AggregateLegoProof, AggregateLegoProofWithLink, AggregateLinkProof,
+        TransparentAggregateLegoProof,
+    },
+    prover::{
+        aggregate_proofs, aggregate_proofs_incl_cp_links, aggregate_proofs_incl_cp_links_safe,
+        aggregate_proofs_incl_cp_links_with_observer, aggregate_proofs_safe,
+        aggregate_proofs_with_observer,
+    },
+    verifier::{
+        verify_aggregate_proof, verify_aggregate_proof_incl_cp_links,
+        verify_aggregate_proof_incl_cp_links_safe, verify_aggregate_proof_safe,
+    },
+};
@@ -2,14 +2,21 @@ use crate::aggregation::commitment::PairCommitment;
 use crate::aggregation::error::AggregationError;
 use crate::aggregation::kzg::KZGOpening;
 use crate::aggregation::srs;
+use crate::aggregation::torus::{
+    deserialize_gt, deserialize_gt_pairs, gt_pairs_serialized_size, gt_serialized_size,
+    serialize_gt, serialize_gt_pairs,
+};
 use ark_ec::pairing::{Pairing, PairingOutput};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
 use ark_std::{string::ToString, vec::Vec};
 
 /// AggregateProof contains all elements to verify n aggregated LegoGroth16 proofs
 /// using inner pairing product arguments. This proof can be created by any
 /// party in possession of valid LegoGroth16 proofs.
-#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct AggregateLegoProof<E: Pairing> {
     /// commitment to A and B using the pair commitment scheme needed to verify
     /// TIPP relation.
@@ -39,44 +46,209 @@ impl<E: Pairing> PartialEq for AggregateLegoProof<E> {
     }
 }
 
+impl<E: Pairing> CanonicalSerialize for AggregateLegoProof<E> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.com_ab.serialize_with_mode(&mut writer, compress)?;
+        self.com_c.serialize_with_mode(&mut writer, compress)?;
+        self.com_d.serialize_with_mode(&mut writer, compress)?;
+        serialize_gt(&self.z_ab, &mut writer, compress)?;
+        self.z_c.serialize_with_mode(&mut writer, compress)?;
+        self.z_d.serialize_with_mode(&mut writer, compress)?;
+        self.tmipp.serialize_with_mode(&mut writer, compress)?;
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.com_ab.serialized_size(compress)
+            + self.com_c.serialized_size(compress)
+            + self.com_d.serialized_size(compress)
+            + gt_serialized_size::<E>(compress)
+            + self.z_c.serialized_size(compress)
+            + self.z_d.serialized_size(compress)
+            + self.tmipp.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing> Valid for AggregateLegoProof<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.com_ab.check()?;
+        self.com_c.check()?;
+        self.com_d.check()?;
+        self.z_ab.check()?;
+        self.z_c.check()?;
+        self.z_d.check()?;
+        self.tmipp.check()?;
+        Ok(())
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for AggregateLegoProof<E> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_gt_mode(reader, compress, compress, validate)
+    }
+}
+
+impl<E: Pairing> AggregateLegoProof<E> {
+    /// Same as `deserialize_with_mode` but reads the GT elements in `gt_compress` mode, like
+    /// `Compress::No` for proofs written before GT elements were compressed
+    pub(crate) fn deserialize_with_gt_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        gt_compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let com_ab =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, gt_compress, validate)?;
+        let com_c =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, gt_compress, validate)?;
+        let com_d =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, gt_compress, validate)?;
+        let z_ab = deserialize_gt(&mut reader, gt_compress, validate)?;
+        let z_c = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let z_d = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let tmipp = TippMippProofLego::deserialize_with_gt_mode(
+            &mut reader,
+            compress,
+            gt_compress,
+            validate,
+        )?;
+        Ok(Self {
+            com_ab,
+            com_c,
+            com_d,
+            z_ab,
+            z_c,
+            z_d,
+            tmipp,
+        })
+    }
+}
+
 impl<E: Pairing> AggregateLegoProof<E> {
     /// Performs some high level checks on the length of vectors and others to
     /// make sure all items in the proofs are consistent with each other.
     pub fn parsing_check(&self) -> Result<(), AggregationError> {
-        let gipa = &self.tmipp.gipa;
-        // 1. Check length of the proofs
-        if gipa.nproofs < 2 || gipa.nproofs as usize > srs::MAX_SRS_SIZE {
-            return Err(AggregationError::InvalidProof(
-                "Proof length out of bounds".to_string(),
-            ));
-        }
-        // 2. Check if it's a power of two
-        if !gipa.nproofs.is_power_of_two() {
-            return Err(AggregationError::InvalidProof(
-                "Proof length not a power of two".to_string(),
-            ));
-        }
-        // 3. Check all vectors are of the same length and of the correct length
-        let ref_len = (gipa.nproofs as f32).log2().ceil() as usize;
-        let all_same = ref_len == gipa.comms_ab.len()
-            && ref_len == gipa.comms_c.len()
-            && ref_len == gipa.comms_d.len()
-            && ref_len == gipa.z_ab.len()
-            && ref_len == gipa.z_c.len()
-            && ref_len == gipa.z_d.len();
-        if !all_same {
-            return Err(AggregationError::InvalidProof(
-                "Proof vectors unequal sizes".to_string(),
-            ));
-        }
+        self.tmipp.gipa.parsing_check()
+    }
+}
+
+/// Aggregate proof created with a `TransparentSRS` by `transparent::aggregate_proofs`. It has the
+/// same elements as `AggregateLegoProof` except the KZG openings of the final commitment keys
+/// since the verifier computes those keys itself.
+#[derive(Debug, Clone)]
+pub struct TransparentAggregateLegoProof<E: Pairing> {
+    /// commitment to A and B using the pair commitment scheme needed to verify
+    /// TIPP relation.
+    pub com_ab: PairCommitment<E>,
+    /// commit to C separate since we use it only in MIPP
+    pub com_c: PairCommitment<E>,
+    /// commit to D separate since we use it only in MIPP
+    pub com_d: PairCommitment<E>,
+    /// $A^r * B = Z$ is the left value on the aggregated LegoGroth16 equation
+    pub z_ab: PairingOutput<E>,
+    /// $C^r$ is used on the right side of the aggregated LegoGroth16 equation
+    pub z_c: E::G1Affine,
+    /// $D^r$ is used on the right side of the aggregated LegoGroth16 equation
+    pub z_d: E::G1Affine,
+    pub gipa: GipaProofLego<E>,
+}
+
+impl<E: Pairing> PartialEq for TransparentAggregateLegoProof<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.com_ab == other.com_ab
+            && self.com_c == other.com_c
+            && self.com_d == other.com_d
+            && self.z_ab == other.z_ab
+            && self.z_c == other.z_c
+            && self.z_d == other.z_d
+            && self.gipa == other.gipa
+    }
+}
+
+impl<E: Pairing> CanonicalSerialize for TransparentAggregateLegoProof<E> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.com_ab.serialize_with_mode(&mut writer, compress)?;
+        self.com_c.serialize_with_mode(&mut writer, compress)?;
+        self.com_d.serialize_with_mode(&mut writer, compress)?;
+        serialize_gt(&self.z_ab, &mut writer, compress)?;
+        self.z_c.serialize_with_mode(&mut writer, compress)?;
+        self.z_d.serialize_with_mode(&mut writer, compress)?;
+        self.gipa.serialize_with_mode(&mut writer, compress)?;
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.com_ab.serialized_size(compress)
+            + self.com_c.serialized_size(compress)
+            + self.com_d.serialized_size(compress)
+            + gt_serialized_size::<E>(compress)
+            + self.z_c.serialized_size(compress)
+            + self.z_d.serialized_size(compress)
+            + self.gipa.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing> Valid for TransparentAggregateLegoProof<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.com_ab.check()?;
+        self.com_c.check()?;
+        self.com_d.check()?;
+        self.z_ab.check()?;
+        self.z_c.check()?;
+        self.z_d.check()?;
+        self.gipa.check()?;
         Ok(())
     }
 }
 
+impl<E: Pairing> CanonicalDeserialize for TransparentAggregateLegoProof<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let com_ab = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let com_c = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let com_d = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let z_ab = deserialize_gt(&mut reader, compress, validate)?;
+        let z_c = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let z_d = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let gipa = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        Ok(Self {
+            com_ab,
+            com_c,
+            com_d,
+            z_ab,
+            z_c,
+            z_d,
+            gipa,
+        })
+    }
+}
+
+impl<E: Pairing> TransparentAggregateLegoProof<E> {
+    /// Same checks as `AggregateLegoProof::parsing_check`
+    pub fn parsing_check(&self) -> Result<(), AggregationError> {
+        self.gipa.parsing_check()
+    }
+}
+
 /// It contains all elements derived in the GIPA loop for both TIPP and MIPP at
 /// the same time. Serialization is done manually here for better inspection
 /// (CanonicalSerialization is implemented manually, not via the macro).
-#[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Debug, Clone)]
 pub struct GipaProofLego<E: Pairing> {
     pub nproofs: u32,
     pub comms_ab: Vec<(PairCommitment<E>, PairCommitment<E>)>,
@@ -113,7 +285,152 @@ impl<E: Pairing> PartialEq for GipaProofLego<E> {
     }
 }
 
+impl<E: Pairing> CanonicalSerialize for GipaProofLego<E> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.nproofs.serialize_with_mode(&mut writer, compress)?;
+        self.comms_ab.serialize_with_mode(&mut writer, compress)?;
+        self.comms_c.serialize_with_mode(&mut writer, compress)?;
+        self.comms_d.serialize_with_mode(&mut writer, compress)?;
+        serialize_gt_pairs(&self.z_ab, &mut writer, compress)?;
+        self.z_c.serialize_with_mode(&mut writer, compress)?;
+        self.z_d.serialize_with_mode(&mut writer, compress)?;
+        self.final_a.serialize_with_mode(&mut writer, compress)?;
+        self.final_b.serialize_with_mode(&mut writer, compress)?;
+        self.final_c.serialize_with_mode(&mut writer, compress)?;
+        self.final_d.serialize_with_mode(&mut writer, compress)?;
+        self.final_vkey.serialize_with_mode(&mut writer, compress)?;
+        self.final_wkey.serialize_with_mode(&mut writer, compress)?;
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.nproofs.serialized_size(compress)
+            + self.comms_ab.serialized_size(compress)
+            + self.comms_c.serialized_size(compress)
+            + self.comms_d.serialized_size(compress)
+            + gt_pairs_serialized_size(&self.z_ab, compress)
+            + self.z_c.serialized_size(compress)
+            + self.z_d.serialized_size(compress)
+            + self.final_a.serialized_size(compress)
+            + self.final_b.serialized_size(compress)
+            + self.final_c.serialized_size(compress)
+            + self.final_d.serialized_size(compress)
+            + self.final_vkey.serialized_size(compress)
+            + self.final_wkey.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing> Valid for GipaProofLego<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.nproofs.check()?;
+        self.comms_ab.check()?;
+        self.comms_c.check()?;
+        self.comms_d.check()?;
+        for (l, r) in &self.z_ab {
+            l.check()?;
+            r.check()?;
+        }
+        self.z_c.check()?;
+        self.z_d.check()?;
+        self.final_a.check()?;
+        self.final_b.check()?;
+        self.final_c.check()?;
+        self.final_d.check()?;
+        self.final_vkey.check()?;
+        self.final_wkey.check()?;
+        Ok(())
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for GipaProofLego<E> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_gt_mode(reader, compress, compress, validate)
+    }
+}
+
 impl<E: Pairing> GipaProofLego<E> {
+    pub(crate) fn deserialize_with_gt_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        gt_compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let nproofs = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let comms_ab =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, gt_compress, validate)?;
+        let comms_c =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, gt_compress, validate)?;
+        let comms_d =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, gt_compress, validate)?;
+        let z_ab = deserialize_gt_pairs(&mut reader, gt_compress, validate)?;
+        let z_c = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let z_d = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_a = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_b = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_c = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_d = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_vkey =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let final_wkey =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        Ok(Self {
+            nproofs,
+            comms_ab,
+            comms_c,
+            comms_d,
+            z_ab,
+            z_c,
+            z_d,
+            final_a,
+            final_b,
+            final_c,
+            final_d,
+            final_vkey,
+            final_wkey,
+        })
+    }
+}
+
+impl<E: Pairing> GipaProofLego<E> {
+    /// Performs some high level checks on the length of vectors and others to
+    /// make sure all items in the proofs are consistent with each other.
+    pub fn parsing_check(&self) -> Result<(), AggregationError> {
+        // 1. Check length of the proofs
+        if self.nproofs < 2 || self.nproofs as usize > srs::MAX_SRS_SIZE {
+            return Err(AggregationError::InvalidProof(
+                "Proof length out of bounds".to_string(),
+            ));
+        }
+        // 2. Check if it's a power of two
+        if !self.nproofs.is_power_of_two() {
+            return Err(AggregationError::InvalidProof(
+                "Proof length not a power of two".to_string(),
+            ));
+        }
+        // 3. Check all vectors are of the same length and of the correct length
+        let ref_len = (self.nproofs as f32).log2().ceil() as usize;
+        let all_same = ref_len == self.comms_ab.len()
+            && ref_len == self.comms_c.len()
+            && ref_len == self.comms_d.len()
+            && ref_len == self.z_ab.len()
+            && ref_len == self.z_c.len()
+            && ref_len == self.z_d.len();
+        if !all_same {
+            return Err(AggregationError::InvalidProof(
+                "Proof vectors unequal sizes".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     fn log_proofs(nproofs: usize) -> usize {
         (nproofs as f32).log2().ceil() as usize
     }
@@ -131,7 +448,7 @@ impl<E: Pairing> GipaProofLego<E> {
 
 /// It contains the GIPA recursive elements as well as the KZG openings for v
 /// and w
-#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, Clone)]
+#[derive(CanonicalSerialize, Debug, Clone)]
 pub struct TippMippProofLego<E: Pairing> {
     pub gipa: GipaProofLego<E>,
     pub vkey_opening: KZGOpening<E::G2Affine>,
@@ -145,3 +462,88 @@ impl<E: Pairing> PartialEq for TippMippProofLego<E> {
             && self.wkey_opening == other.wkey_opening
     }
 }
+
+impl<E: Pairing> Valid for TippMippProofLego<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.gipa.check()?;
+        self.vkey_opening.check()?;
+        self.wkey_opening.check()
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for TippMippProofLego<E> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_gt_mode(reader, compress, compress, validate)
+    }
+}
+
+impl<E: Pairing> TippMippProofLego<E> {
+    pub(crate) fn deserialize_with_gt_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        gt_compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let gipa =
+            GipaProofLego::deserialize_with_gt_mode(&mut reader, compress, gt_compress, validate)?;
+        let vkey_opening = KZGOpening::deserialize_with_mode(&mut reader, compress, validate)?;
+        let wkey_opening = KZGOpening::deserialize_with_mode(&mut reader, compress, validate)?;
+        Ok(Self {
+            gipa,
+            vkey_opening,
+            wkey_opening,
+        })
+    }
+}
+
+/// AggregateLegoProofWithLink contains all elements to verify n aggregated LegoGroth16 proofs
+/// along with their CP_link proofs, i.e. it replaces sending and verifying all `link_d`s and
+/// `link_pi`s of `ProofWithLink`s.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, Clone, PartialEq)]
+pub struct AggregateLegoProofWithLink<E: Pairing> {
+    pub proof: AggregateLegoProof<E>,
+    pub link: AggregateLinkProof<E>,
+}
+
+/// It contains the elements of the MIPP relations for the `link_d`s and `link_pi`s. These are
+/// folded in the same GIPA loop as C and D so share its challenges and KZG openings. Each vector
+/// is indexed by the link commitment, with the last item being for `link_pi`.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, Clone, PartialEq)]
+pub struct AggregateLinkProof<E: Pairing> {
+    /// commitment to each of the `link_d` vectors and the `link_pi` vector
+    pub com: Vec<PairCommitment<E>>,
+    /// $link_d^r$ and $link_pi^r$ used in the aggregated CP_link equation
+    pub z: Vec<E::G1Affine>,
+    /// For each GIPA round, the commitments to the halves of each vector
+    pub comms: Vec<Vec<(PairCommitment<E>, PairCommitment<E>)>>,
+    /// For each GIPA round, the cross products of the halves of each vector with r
+    pub z_lr: Vec<Vec<(E::G1Affine, E::G1Affine)>>,
+    pub finals: Vec<E::G1Affine>,
+}
+
+impl<E: Pairing> AggregateLegoProofWithLink<E> {
+    /// Same as `AggregateLegoProof::parsing_check` but also checks the link elements are
+    /// consistent with `num_vectors`, the number of link commitments plus 1
+    pub fn parsing_check(&self, num_vectors: usize) -> Result<(), AggregationError> {
+        self.proof.parsing_check()?;
+        let link = &self.link;
+        let rounds = self.proof.tmipp.gipa.comms_ab.len();
+        let all_same = link.com.len() == num_vectors
+            && link.z.len() == num_vectors
+            && link.finals.len() == num_vectors
+            && link.comms.len() == rounds
+            && link.z_lr.len() == rounds
+            && link.comms.iter().all(|c| c.len() == num_vectors)
+            && link.z_lr.iter().all(|z| z.len() == num_vectors);
+        if !all_same {
+            return Err(AggregationError::InvalidProof(
+                "Link proof vectors unequal sizes".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
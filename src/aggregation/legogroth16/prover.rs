@@ -2,9 +2,9 @@ use ark_ec::pairing::PairingOutput;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::{batch_inversion, Field, PrimeField};
 use ark_std::ops::{AddAssign, MulAssign};
-use ark_std::{cfg_iter, cfg_iter_mut, string::ToString, vec::Vec, Zero, format};
+use ark_std::{cfg_iter, cfg_iter_mut, format, string::ToString, vec::Vec, Zero};
 
-use crate::Proof;
+use crate::{Proof, ProofWithLink, VerifyingKey, VerifyingKeyWithLink};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
@@ -12,16 +12,22 @@ use rayon::prelude::*;
 use crate::aggregation::commitment::PairCommitment;
 use crate::aggregation::error::AggregationError;
 use crate::aggregation::key::{PreparedVKey, VKey, WKey};
+use crate::progress::{observe_phase, NoObserver, Phase, ProgressObserver};
 
-use super::proof::{AggregateLegoProof, GipaProofLego, TippMippProofLego};
+use super::proof::{
+    AggregateLegoProof, AggregateLegoProofWithLink, AggregateLinkProof, GipaProofLego,
+    TippMippProofLego,
+};
 use crate::aggregation::srs::{PreparedProverSRS, ProverSRS};
+use crate::aggregation::statement::bind_statement;
 use crate::aggregation::utils::{
     compress, inner_product_and_double_commitments, inner_product_and_single_commitments,
-    prove_commitments,
+    pad_by_repeating_last, prove_commitments,
 };
 use dock_crypto_utils::{ff::powers, transcript::Transcript};
 
-/// Aggregate `n` zkSnark proofs, where `n` must be a power of two.
+/// Aggregate `n >= 1` zkSnark proofs. If `n` is not a power of two (or is 1), the proofs are padded
+/// to `padded_proof_count(n)` by repeating the last proof so the SRS must be specialized for `n`.
 /// WARNING: transcript_include represents everything that should be included in
 /// the transcript from outside the boundary of this function. This is especially
 /// relevant for ALL public inputs of ALL individual proofs. In the regular case,
@@ -32,23 +38,138 @@ use dock_crypto_utils::{ff::powers, transcript::Transcript};
 /// number of proofs and public inputs (+100ms in our case). In the case of Filecoin, the only
 /// non-fixed part of the public inputs are the challenges derived from a seed. Even though this
 /// seed comes from a random beacon, we are hashing this as a safety precaution.
+/// `aggregate_proofs_safe` appends the public inputs, the verifying key and the SRS
+/// size to the transcript itself.
 pub fn aggregate_proofs<E: Pairing, T: Transcript>(
     srs: impl Into<PreparedProverSRS<E>>,
     transcript: &mut T,
     proofs: &[Proof<E>],
 ) -> Result<AggregateLegoProof<E>, AggregationError> {
-    if proofs.len() < 2 {
+    aggregate_proofs_with_observer(srs, transcript, proofs, &NoObserver)
+}
+/// Same as `aggregate_proofs` but tells `observer` about the progress, and returns
+/// `AggregationError::Cancelled` if it cancels
+pub fn aggregate_proofs_with_observer<E: Pairing, T: Transcript, O: ProgressObserver + ?Sized>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    proofs: &[Proof<E>],
+    observer: &O,
+) -> Result<AggregateLegoProof<E>, AggregationError> {
+    if proofs.is_empty() {
         return Err(AggregationError::InvalidProof(
-            "invalid proof size < 2".to_string(),
+            "no proofs to aggregate".to_string(),
         ));
     }
-    if !proofs.len().is_power_of_two() {
+    // The inner product arguments need a power of two number of proofs so pad by repeating
+    // the last proof. The verifier pads the public inputs the same way.
+    let proofs = pad_by_repeating_last(proofs);
+    let (proof, _, _) = aggregate(srs.into(), transcript, proofs.as_ref(), &[], observer)?;
+    Ok(proof)
+}
+
+/// Same as `aggregate_proofs` but first appends the fingerprint of `vk`, the size of the SRS and
+/// the `public_inputs` of all `proofs` to the transcript with `append_statement`, so the caller
+/// needn't. Verify with `verify_aggregate_proof_safe`.
+pub fn aggregate_proofs_safe<E: Pairing, T: Transcript>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    vk: &VerifyingKey<E>,
+    proofs: &[Proof<E>],
+    public_inputs: &[Vec<E::ScalarField>],
+) -> Result<AggregateLegoProof<E>, AggregationError> {
+    let srs = srs.into();
+    bind_statement(
+        transcript,
+        &vk.fingerprint(),
+        srs.n,
+        proofs.len(),
+        public_inputs,
+    )?;
+    aggregate_proofs(srs, transcript, proofs)
+}
+
+/// Aggregate `n >= 1` LegoGroth16 proofs along with their CP_link proofs so that the verifier
+/// neither needs the `link_d`s nor has to verify each `link_pi`. Padding and the transcript
+/// are as in `aggregate_proofs`. All proofs must have the same number of link commitments.
+pub fn aggregate_proofs_incl_cp_links<E: Pairing, T: Transcript>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    proofs: &[ProofWithLink<E>],
+) -> Result<AggregateLegoProofWithLink<E>, AggregationError> {
+    aggregate_proofs_incl_cp_links_with_observer(srs, transcript, proofs, &NoObserver)
+}
+
+/// Same as `aggregate_proofs_incl_cp_links` but tells `observer` about the progress, and returns
+/// `AggregationError::Cancelled` if it cancels
+pub fn aggregate_proofs_incl_cp_links_with_observer<
+    E: Pairing,
+    T: Transcript,
+    O: ProgressObserver + ?Sized,
+>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    proofs: &[ProofWithLink<E>],
+    observer: &O,
+) -> Result<AggregateLegoProofWithLink<E>, AggregationError> {
+    if proofs.is_empty() {
         return Err(AggregationError::InvalidProof(
-            "invalid proof size: not power of two".to_string(),
+            "no proofs to aggregate".to_string(),
         ));
     }
+    let num_links = proofs[0].link_d.len();
+    if proofs.iter().any(|p| p.link_d.len() != num_links) {
+        return Err(AggregationError::InvalidProof(
+            "proofs have different number of link commitments".to_string(),
+        ));
+    }
+    let proofs = pad_by_repeating_last(proofs);
+    let proofs = proofs.as_ref();
+
+    // One vector for each link commitment and the last for the link proofs
+    let mut link = (0..num_links)
+        .map(|i| proofs.iter().map(|p| p.link_d[i]).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    link.push(proofs.iter().map(|p| p.link_pi).collect());
+
+    let groth16_proofs = proofs
+        .iter()
+        .map(|p| p.groth16_proof.clone())
+        .collect::<Vec<_>>();
+    let (proof, link, _) = aggregate(srs.into(), transcript, &groth16_proofs, &link, observer)?;
+    Ok(AggregateLegoProofWithLink { proof, link })
+}
 
+/// Same as `aggregate_proofs_incl_cp_links` but first appends the fingerprint of `vk`, the size
+/// of the SRS and the `public_inputs` of all `proofs` to the transcript like
+/// `aggregate_proofs_safe`. Verify with `verify_aggregate_proof_incl_cp_links_safe`.
+pub fn aggregate_proofs_incl_cp_links_safe<E: Pairing, T: Transcript>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    vk: &VerifyingKeyWithLink<E>,
+    proofs: &[ProofWithLink<E>],
+    public_inputs: &[Vec<E::ScalarField>],
+) -> Result<AggregateLegoProofWithLink<E>, AggregationError> {
     let srs = srs.into();
+    bind_statement(
+        transcript,
+        &vk.fingerprint(),
+        srs.n,
+        proofs.len(),
+        public_inputs,
+    )?;
+    aggregate_proofs_incl_cp_links(srs, transcript, proofs)
+}
+
+/// Aggregates `proofs`, whose count must be a power of two, and proves MIPP relations for the
+/// `link` vectors, if any, in the same GIPA loop as C and D. Also returns the challenge `r` of
+/// the random linear combination.
+pub(super) fn aggregate<E: Pairing, T: Transcript, O: ProgressObserver + ?Sized>(
+    srs: PreparedProverSRS<E>,
+    transcript: &mut T,
+    proofs: &[Proof<E>],
+    link: &[Vec<E::G1Affine>],
+    observer: &O,
+) -> Result<(AggregateLegoProof<E>, AggregateLinkProof<E>, E::ScalarField), AggregationError> {
     if !srs.has_correct_len(proofs.len()) {
         return Err(AggregationError::InvalidSRS(
             format!("SRS len {} != proofs len {}", srs.len(), proofs.len()).to_string(),
@@ -66,22 +187,149 @@ pub fn aggregate_proofs<E: Pairing, T: Transcript>(
         .map(|e| E::G2Prepared::from(*e))
         .collect::<Vec<_>>();
 
-    // A and B are committed together in this scheme
-    // T_AB, U_AB
-    let com_ab = PairCommitment::<E>::double(vkey_prep.clone(), &srs.wkey, &a, b_prep)?;
-    // T_C, U_C
-    let com_c = PairCommitment::<E>::single(vkey_prep.clone(), &c)?;
-    // T_D, U_D
-    let com_d = PairCommitment::<E>::single(vkey_prep, &d)?;
+    let (com_ab, com_c, com_d, com_link) = observe_phase(
+        observer,
+        Phase::AggregationCommitments,
+        proofs.len(),
+        || {
+            // A and B are committed together in this scheme
+            // T_AB, U_AB
+            let com_ab = PairCommitment::<E>::double(vkey_prep.clone(), &srs.wkey, &a, b_prep)?;
+            // T_C, U_C
+            let com_c = PairCommitment::<E>::single(vkey_prep.clone(), &c)?;
+            // T_D, U_D
+            let com_d = PairCommitment::<E>::single(vkey_prep.clone(), &d)?;
+            // T, U for each of link_d and link_pi
+            let com_link = link
+                .iter()
+                .map(|l| PairCommitment::<E>::single(vkey_prep.clone(), l))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok::<_, AggregationError>((com_ab, com_c, com_d, com_link))
+        },
+        |_| proofs.len(),
+    )?;
 
+    aggregate_committed(
+        &srs,
+        transcript,
+        (a, b, c, d),
+        link,
+        (com_ab, com_c, com_d, com_link),
+        observer,
+    )
+}
+
+/// Same as `aggregate` but with the commitments to A and B, C, D and the `link` vectors
+/// already computed
+pub(super) fn aggregate_committed<E: Pairing, T: Transcript, O: ProgressObserver + ?Sized>(
+    srs: &ProverSRS<E>,
+    transcript: &mut T,
+    (a, b, c, d): ProofVectors<E>,
+    link: &[Vec<E::G1Affine>],
+    commitments: Commitments<E>,
+    observer: &O,
+) -> Result<(AggregateLegoProof<E>, AggregateLinkProof<E>, E::ScalarField), AggregationError> {
+    let n = a.len();
+    let Combined {
+        r,
+        r_vec,
+        r_inv,
+        b_r,
+        z_ab,
+        z_c,
+        z_d,
+        z_link,
+    } = combine(transcript, &a, &b, &c, &d, link, &commitments);
+    let (com_ab, com_c, com_d, com_link) = commitments;
+
+    // w^{r^{-1}}
+    let wkey_r_inv = srs.wkey.scale(&r_inv)?;
+
+    // we prove tipp and mipp using the same recursive loop
+    let (proof, link_gipa) = observe_phase(
+        observer,
+        Phase::AggregationInnerProducts,
+        n,
+        || {
+            prove_tipp_mipp(
+                srs,
+                transcript,
+                &a,
+                &b_r,
+                &c,
+                &d,
+                link,
+                &wkey_r_inv,
+                &r_vec,
+                &z_ab,
+                &z_c,
+                &z_d,
+                &z_link,
+            )
+        },
+        |_| n,
+    )?;
+
+    let (comms, z_lr, finals) = link_gipa;
+    Ok((
+        AggregateLegoProof {
+            com_ab,
+            com_c,
+            com_d,
+            z_ab,
+            z_c,
+            z_d,
+            tmipp: proof,
+        },
+        AggregateLinkProof {
+            com: com_link,
+            z: z_link,
+            comms,
+            z_lr,
+            finals,
+        },
+        r,
+    ))
+}
+
+/// The random linear combination of the proofs with the challenge `r`
+pub(super) struct Combined<E: Pairing> {
+    pub r: E::ScalarField,
+    /// 1, r, r^2, r^3, ...
+    pub r_vec: Vec<E::ScalarField>,
+    /// 1, r^-1, r^-2, r^-3, ...
+    pub r_inv: Vec<E::ScalarField>,
+    /// B^{r}
+    pub b_r: Vec<E::G2Affine>,
+    pub z_ab: PairingOutput<E>,
+    pub z_c: E::G1Affine,
+    pub z_d: E::G1Affine,
+    pub z_link: Vec<E::G1Affine>,
+}
+
+/// Appends the commitments to the transcript, derives the challenge `r` from it and combines A
+/// and B, C, D and the `link` vectors with the powers of `r`
+pub(super) fn combine<E: Pairing, T: Transcript>(
+    transcript: &mut T,
+    a: &[E::G1Affine],
+    b: &[E::G2Affine],
+    c: &[E::G1Affine],
+    d: &[E::G1Affine],
+    link: &[Vec<E::G1Affine>],
+    (com_ab, com_c, com_d, com_link): &Commitments<E>,
+) -> Combined<E> {
+    let n = a.len();
     // Derive a random scalar to perform a linear combination of proofs
-    transcript.append(b"AB-commitment", &com_ab);
-    transcript.append(b"C-commitment", &com_c);
-    transcript.append(b"D-commitment", &com_d);
+    transcript.append(b"AB-commitment", com_ab);
+    transcript.append(b"C-commitment", com_c);
+    transcript.append(b"D-commitment", com_d);
+    if !com_link.is_empty() {
+        transcript.append(b"link-commitments", com_link);
+    }
     let r = transcript.challenge_scalar::<E::ScalarField>(b"r-random-fiatshamir");
 
     // 1,r, r^2, r^3, r^4 ...
-    let r_vec: Vec<E::ScalarField> = powers(&r, proofs.len());
+    let r_vec: Vec<E::ScalarField> = powers(&r, n);
     // 1,r^-1, r^-2, r^-3
     let mut r_inv = r_vec.clone();
     batch_inversion(&mut r_inv);
@@ -98,39 +346,28 @@ pub fn aggregate_proofs<E: Pairing, T: Transcript>(
     let b_r = E::G2::normalize_batch(&b_r_proj);
 
     // compute A * B^r for the verifier
-    let z_ab = E::multi_pairing(&a, &b_r);
+    let z_ab = E::multi_pairing(a, &b_r);
     // compute C^r for the verifier
-    let z_c = E::G1::msm_bigint(&c, &r_repr).into_affine();
+    let z_c = E::G1::msm_bigint(c, &r_repr).into_affine();
     // compute D^r for the verifier
-    let z_d = E::G1::msm_bigint(&d, &r_repr).into_affine();
-
-    // w^{r^{-1}}
-    let wkey_r_inv = srs.wkey.scale(&r_inv)?;
-
-    // we prove tipp and mipp using the same recursive loop
-    let proof = prove_tipp_mipp(
-        &srs,
-        transcript,
-        &a,
-        &b_r,
-        &c,
-        &d,
-        &wkey_r_inv,
-        &r_vec,
-        &z_ab,
-        &z_c,
-        &z_d,
-    )?;
+    let z_d = E::G1::msm_bigint(d, &r_repr).into_affine();
+    // compute link_d^r and link_pi^r for the verifier
+    let z_link = link
+        .iter()
+        .map(|l| E::G1::msm_bigint(l, &r_repr))
+        .collect::<Vec<_>>();
+    let z_link = E::G1::normalize_batch(&z_link);
 
-    Ok(AggregateLegoProof {
-        com_ab,
-        com_c,
-        com_d,
+    Combined {
+        r,
+        r_vec,
+        r_inv,
+        b_r,
         z_ab,
         z_c,
         z_d,
-        tmipp: proof,
-    })
+        z_link,
+    }
 }
 
 /// Proves a TIPP relation between A and B as well as a MIPP relation with C and
@@ -138,7 +375,8 @@ pub fn aggregate_proofs<E: Pairing, T: Transcript>(
 /// aggregation, we have that B = B^r and wkey is scaled by r^{-1}. The
 /// commitment key v is used to commit to A and C recursively in GIPA such that
 /// only one KZG proof is needed for v. In the original paper version, since the
-/// challenges of GIPA would be different, two KZG proofs would be needed.
+/// challenges of GIPA would be different, two KZG proofs would be needed. The
+/// MIPP relations for the `link` vectors are proven in the same way as for C.
 fn prove_tipp_mipp<E: Pairing, T: Transcript>(
     srs: &ProverSRS<E>,
     transcript: &mut T,
@@ -146,16 +384,18 @@ fn prove_tipp_mipp<E: Pairing, T: Transcript>(
     b: &[E::G2Affine],
     c: &[E::G1Affine],
     d: &[E::G1Affine],
+    link: &[Vec<E::G1Affine>],
     wkey: &WKey<E>, // scaled key w^r^-1
     r_vec: &[E::ScalarField],
     z_ab: &PairingOutput<E>,
     z_c: &E::G1Affine,
     z_d: &E::G1Affine,
-) -> Result<TippMippProofLego<E>, AggregationError> {
+    z_link: &[E::G1Affine],
+) -> Result<(TippMippProofLego<E>, GipaLink<E>), AggregationError> {
     let r_shift = r_vec[1].clone();
     // Run GIPA
-    let (proof, mut challenges, mut challenges_inv) = gipa_tipp_mipp(
-        transcript, a, b, c, d, &srs.vkey, &wkey, r_vec, z_ab, z_c, z_d,
+    let (proof, link_gipa, mut challenges, mut challenges_inv) = gipa_tipp_mipp(
+        transcript, a, b, c, d, link, &srs.vkey, &wkey, r_vec, z_ab, z_c, z_d, z_link,
     )?;
 
     // Prove final commitment keys are wellformed
@@ -186,35 +426,74 @@ fn prove_tipp_mipp<E: Pairing, T: Transcript>(
         &z,
     )?;
 
-    Ok(TippMippProofLego {
-        gipa: proof,
-        vkey_opening,
-        wkey_opening,
-    })
+    Ok((
+        TippMippProofLego {
+            gipa: proof,
+            vkey_opening,
+            wkey_opening,
+        },
+        link_gipa,
+    ))
 }
 
+/// A, B, C and D of each proof
+pub(super) type ProofVectors<E> = (
+    Vec<<E as Pairing>::G1Affine>,
+    Vec<<E as Pairing>::G2Affine>,
+    Vec<<E as Pairing>::G1Affine>,
+    Vec<<E as Pairing>::G1Affine>,
+);
+
+/// Commitments to A and B, C, D and each of the link vectors
+pub(super) type Commitments<E> = (
+    PairCommitment<E>,
+    PairCommitment<E>,
+    PairCommitment<E>,
+    Vec<PairCommitment<E>>,
+);
+
+/// The commitments and cross products of each GIPA round and the final values for the `link`
+/// vectors, as in `AggregateLinkProof`
+pub(super) type GipaLink<E> = (
+    Vec<Vec<(PairCommitment<E>, PairCommitment<E>)>>,
+    Vec<Vec<(<E as Pairing>::G1Affine, <E as Pairing>::G1Affine)>>,
+    Vec<<E as Pairing>::G1Affine>,
+);
+
 /// gipa_tipp_mipp performs the recursion of the GIPA protocol for TIPP and MIPP.
 /// It returns a proof containing all intermediate committed values, as well as
 /// the challenges generated necessary to do the polynomial commitment proof
 /// later in TIPP.
-fn gipa_tipp_mipp<E: Pairing>(
+pub(super) fn gipa_tipp_mipp<E: Pairing>(
     transcript: &mut impl Transcript,
     a: &[E::G1Affine],
     b: &[E::G2Affine],
     c: &[E::G1Affine],
     d: &[E::G1Affine],
+    link: &[Vec<E::G1Affine>],
     vkey: &VKey<E>,
     wkey: &WKey<E>, // scaled key w^r^-1
     r: &[E::ScalarField],
     ip_ab: &PairingOutput<E>,
     agg_c: &E::G1Affine,
     agg_d: &E::G1Affine,
-) -> Result<(GipaProofLego<E>, Vec<E::ScalarField>, Vec<E::ScalarField>), AggregationError> {
+    agg_link: &[E::G1Affine],
+) -> Result<
+    (
+        GipaProofLego<E>,
+        GipaLink<E>,
+        Vec<E::ScalarField>,
+        Vec<E::ScalarField>,
+    ),
+    AggregationError,
+> {
     // the values of vectors A and B rescaled at each step of the loop
     let (mut m_a, mut m_b) = (a.to_vec(), b.to_vec());
 
     // the values of vectors C and D rescaled at each step of the loop
     let (mut m_c, mut m_d) = (c.to_vec(), d.to_vec());
+    // the values of the link vectors rescaled at each step of the loop
+    let mut m_link = link.to_vec();
     // the values of vector r is rescaled at each step of the loop
     let mut m_r = r.to_vec();
 
@@ -228,12 +507,17 @@ fn gipa_tipp_mipp<E: Pairing>(
     let mut z_ab = Vec::new();
     let mut z_c = Vec::new();
     let mut z_d = Vec::new();
+    let mut comms_link = Vec::new();
+    let mut z_link = Vec::new();
     let mut challenges: Vec<E::ScalarField> = Vec::new();
     let mut challenges_inv: Vec<E::ScalarField> = Vec::new();
 
     transcript.append(b"inner-product-ab", ip_ab);
     transcript.append(b"comm-c", agg_c);
     transcript.append(b"comm-d", agg_d);
+    if !agg_link.is_empty() {
+        transcript.append(b"comm-link", &agg_link);
+    }
     let mut c_inv: E::ScalarField =
         transcript.challenge_scalar::<E::ScalarField>(b"first-challenge");
     let mut c = c_inv.inverse().unwrap();
@@ -312,6 +596,23 @@ fn gipa_tipp_mipp<E: Pairing>(
             vk_right_prep.clone(),
         );
 
+        // MIPP part for each link vector
+        let (zs_link, tus_link): (Vec<_>, Vec<_>) = m_link
+            .iter()
+            .map(|l| {
+                let (l_left, l_right) = l.split_at(split);
+                let (z_l, z_r, tu_l, tu_r) = inner_product_and_single_commitments(
+                    l_left,
+                    l_right,
+                    &r_left_bi,
+                    &r_right_bi,
+                    vk_left_prep.clone(),
+                    vk_right_prep.clone(),
+                );
+                ((z_l, z_r), (tu_l, tu_r))
+            })
+            .unzip();
+
         // Fiat-Shamir challenge
         // combine both TIPP and MIPP transcript
         if i == 0 {
@@ -330,6 +631,10 @@ fn gipa_tipp_mipp<E: Pairing>(
             transcript.append(b"tuc_r", &tuc_r);
             transcript.append(b"tud_l", &tud_l);
             transcript.append(b"tud_r", &tud_r);
+            if !zs_link.is_empty() {
+                transcript.append(b"zlink", &zs_link);
+                transcript.append(b"tulink", &tus_link);
+            }
             c_inv = transcript.challenge_scalar::<E::ScalarField>(b"challenge_i");
 
             // Optimization for multiexponentiation to rescale G2 elements with
@@ -350,6 +655,10 @@ fn gipa_tipp_mipp<E: Pairing>(
         // d[:n'] + d[n':]^x
         compress(&mut m_d, split, &c);
 
+        for l in m_link.iter_mut() {
+            compress(l, split, &c);
+        }
+
         cfg_iter_mut!(r_left)
             .zip(cfg_iter_mut!(r_right))
             .for_each(|(r_l, r_r)| {
@@ -371,6 +680,8 @@ fn gipa_tipp_mipp<E: Pairing>(
         z_ab.push((zab_l, zab_r));
         z_c.push((zc_l, zc_r));
         z_d.push((zd_l, zd_r));
+        comms_link.push(tus_link);
+        z_link.push(zs_link);
         challenges.push(c);
         challenges_inv.push(c_inv);
 
@@ -384,6 +695,7 @@ fn gipa_tipp_mipp<E: Pairing>(
 
     let (final_a, final_b, final_c, final_d) = (m_a[0], m_b[0], m_c[0], m_d[0]);
     let (final_vkey, final_wkey) = (vkey.first(), wkey.first());
+    let final_link = m_link.iter().map(|l| l[0]).collect();
 
     Ok((
         GipaProofLego {
@@ -401,6 +713,7 @@ fn gipa_tipp_mipp<E: Pairing>(
             final_vkey,
             final_wkey,
         },
+        (comms_link, z_link, final_link),
         challenges,
         challenges_inv,
     ))
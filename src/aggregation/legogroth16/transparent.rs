@@ -0,0 +1,205 @@
+//! Aggregation of LegoGroth16 proofs with a `TransparentSRS`, so without a second powers of tau
+//! ceremony. Proofs are committed to and folded by GIPA as with `aggregate_proofs` but the
+//! aggregate proof has no KZG openings of the final commitment keys. The verifier folds the keys
+//! itself which takes time linear in the number of proofs.
+
+use ark_ec::pairing::Pairing;
+use ark_std::{cfg_iter, rand::Rng, string::ToString, vec::Vec};
+use dock_crypto_utils::{
+    randomized_pairing_check::RandomizedPairingChecker, transcript::Transcript,
+};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::aggregation::commitment::PairCommitment;
+use crate::aggregation::error::AggregationError;
+use crate::aggregation::key::PreparedVKey;
+use crate::aggregation::statement::{append_statement, bind_statement};
+use crate::aggregation::transparent::TransparentSRS;
+use crate::aggregation::utils::pad_by_repeating_last;
+use crate::{PreparedVerifyingKey, Proof, VerifyingKey};
+
+use super::proof::TransparentAggregateLegoProof;
+use super::prover::{combine, gipa_tipp_mipp, Combined};
+use super::verifier::{
+    check_gipa_finals, gipa_verify_tipp_mipp_incl_link, verify_statement, GipaInstance,
+};
+
+/// Aggregate `n >= 1` LegoGroth16 proofs like `prover::aggregate_proofs` but with commitment keys
+/// that need no trusted setup. `srs` must be for `padded_proof_count(n)` proofs. The same
+/// warning about the transcript applies.
+pub fn aggregate_proofs<E: Pairing, T: Transcript>(
+    srs: &TransparentSRS<E>,
+    transcript: &mut T,
+    proofs: &[Proof<E>],
+) -> Result<TransparentAggregateLegoProof<E>, AggregationError> {
+    if proofs.is_empty() {
+        return Err(AggregationError::InvalidProof(
+            "no proofs to aggregate".to_string(),
+        ));
+    }
+    let proofs = pad_by_repeating_last(proofs);
+    if !srs.has_correct_len(proofs.len()) {
+        return Err(AggregationError::InvalidSRS(
+            "SRS len != padded proofs len".to_string(),
+        ));
+    }
+    let a = proofs.iter().map(|proof| proof.a).collect::<Vec<_>>();
+    let b = proofs.iter().map(|proof| proof.b).collect::<Vec<_>>();
+    let c = proofs.iter().map(|proof| proof.c).collect::<Vec<_>>();
+    let d = proofs.iter().map(|proof| proof.d).collect::<Vec<_>>();
+
+    let vkey_prep = PreparedVKey::from(&srs.vkey);
+    let b_prep = cfg_iter!(b)
+        .map(|e| E::G2Prepared::from(*e))
+        .collect::<Vec<_>>();
+    let com_ab = PairCommitment::<E>::double(vkey_prep.clone(), &srs.wkey, &a, b_prep)?;
+    let com_c = PairCommitment::<E>::single(vkey_prep.clone(), &c)?;
+    let com_d = PairCommitment::<E>::single(vkey_prep, &d)?;
+    let commitments = (com_ab, com_c, com_d, Vec::new());
+
+    let Combined {
+        r_vec,
+        r_inv,
+        b_r,
+        z_ab,
+        z_c,
+        z_d,
+        ..
+    } = combine(transcript, &a, &b, &c, &d, &[], &commitments);
+    let (com_ab, com_c, com_d, _) = commitments;
+
+    // w^{r^{-1}}
+    let wkey_r_inv = srs.wkey.scale(&r_inv)?;
+    let (gipa, _, _, _) = gipa_tipp_mipp(
+        transcript,
+        &a,
+        &b_r,
+        &c,
+        &d,
+        &[],
+        &srs.vkey,
+        &wkey_r_inv,
+        &r_vec,
+        &z_ab,
+        &z_c,
+        &z_d,
+        &[],
+    )?;
+
+    Ok(TransparentAggregateLegoProof {
+        com_ab,
+        com_c,
+        com_d,
+        z_ab,
+        z_c,
+        z_d,
+        gipa,
+    })
+}
+
+/// Same as `aggregate_proofs` but first appends the fingerprint of `vk`, the size of the SRS and
+/// the `public_inputs` of all `proofs` to the transcript with `append_statement`, so the caller
+/// needn't. Verify with `verify_aggregate_proof_safe`.
+pub fn aggregate_proofs_safe<E: Pairing, T: Transcript>(
+    srs: &TransparentSRS<E>,
+    transcript: &mut T,
+    vk: &VerifyingKey<E>,
+    proofs: &[Proof<E>],
+    public_inputs: &[Vec<E::ScalarField>],
+) -> Result<TransparentAggregateLegoProof<E>, AggregationError> {
+    bind_statement(
+        transcript,
+        &vk.fingerprint(),
+        srs.vkey.len(),
+        proofs.len(),
+        public_inputs,
+    )?;
+    aggregate_proofs(srs, transcript, proofs)
+}
+
+/// Verifies a proof created by `aggregate_proofs` with the same `srs`, like
+/// `verifier::verify_aggregate_proof`. The transcript must be the same as the one given to the
+/// prover.
+pub fn verify_aggregate_proof<E: Pairing, R: Rng, T: Transcript>(
+    srs: &TransparentSRS<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &TransparentAggregateLegoProof<E>,
+    rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    proof.parsing_check()?;
+    if !srs.has_correct_len(proof.gipa.nproofs as usize) {
+        return Err(AggregationError::InvalidSRS(
+            "SRS len != proofs len".to_string(),
+        ));
+    }
+    let instance = GipaInstance::from(proof);
+    verify_statement(
+        &pvk.vk,
+        public_inputs,
+        &instance,
+        None,
+        rng,
+        transcript,
+        pairing_check,
+        |r, transcript, checker| {
+            let (final_res, final_r, challenges, challenges_inv, link_res) =
+                gipa_verify_tipp_mipp_incl_link(&instance, None, r, transcript);
+            // Instead of KZG openings, fold the keys as the prover did
+            let (final_vkey, final_wkey) = srs.final_keys(&challenges, &challenges_inv, r)?;
+            if final_vkey != proof.gipa.final_vkey || final_wkey != proof.gipa.final_wkey {
+                return Err(AggregationError::InvalidProof(
+                    "final commitment keys are not the folded keys".to_string(),
+                ));
+            }
+            check_gipa_finals(&proof.gipa, None, &final_res, &final_r, &link_res, checker)
+        },
+    )
+    .map(|_| ())
+}
+
+/// Verifies a proof created by `aggregate_proofs_safe`. Appends the fingerprint of the verifying
+/// key, the size of the SRS and `public_inputs` to the transcript as the prover did.
+pub fn verify_aggregate_proof_safe<E: Pairing, R: Rng, T: Transcript>(
+    srs: &TransparentSRS<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &TransparentAggregateLegoProof<E>,
+    rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    append_statement(
+        transcript,
+        &pvk.vk.fingerprint(),
+        srs.vkey.len(),
+        public_inputs,
+    );
+    verify_aggregate_proof(
+        srs,
+        pvk,
+        public_inputs,
+        proof,
+        rng,
+        transcript,
+        pairing_check,
+    )
+}
+
+impl<'a, E: Pairing> From<&'a TransparentAggregateLegoProof<E>> for GipaInstance<'a, E> {
+    fn from(proof: &'a TransparentAggregateLegoProof<E>) -> Self {
+        Self {
+            com_ab: &proof.com_ab,
+            com_c: &proof.com_c,
+            com_d: &proof.com_d,
+            z_ab: &proof.z_ab,
+            z_c: &proof.z_c,
+            z_d: &proof.z_d,
+            gipa: &proof.gipa,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::PrimeField;
 use ark_groth16::Proof;
 use ark_std::{cfg_iter, format, ops::Mul, rand::Rng, string::ToString, vec::Vec};
@@ -11,8 +11,11 @@ use crate::aggregation::error::AggregationError;
 use crate::aggregation::groth16::verifier::verify_tipp_mipp;
 use crate::aggregation::groth16::{aggregate_proofs as g16_aggregate_proofs, AggregateProof};
 use crate::aggregation::srs::{PreparedProverSRS, VerifierSRS};
-use crate::aggregation::utils::aggregate_public_inputs;
-use crate::{PreparedVerifyingKey, Proof as LegoProof};
+use crate::aggregation::statement::{append_commitments, append_statement, bind_statement};
+use crate::aggregation::utils::{
+    aggregate_public_inputs, pad_by_repeating_last, pad_for_verification,
+};
+use crate::{PreparedVerifyingKey, Proof as LegoProof, VerifyingKey};
 use dock_crypto_utils::{
     ff::{powers, sum_of_powers},
     transcript::Transcript,
@@ -20,7 +23,9 @@ use dock_crypto_utils::{
 
 /// Since `proof.D` (commitment to witnesses) is needed for doing a Schnorr proof of knowledge and equality
 /// when not using CP_link Snark, `proof.D` does not need to be used in an IPA and thus aggregation protocol
-/// for Groth16 can be used with slight modification.
+/// for Groth16 can be used with slight modification. To not carry each `proof.D`, aggregate with
+/// `aggregate_proofs_with_common_witnesses` which proves statements over all the committed witnesses
+/// with the single aggregated commitment.
 pub fn aggregate_proofs<E: Pairing, T: Transcript>(
     srs: impl Into<PreparedProverSRS<E>>,
     transcript: &mut T,
@@ -39,6 +44,31 @@ pub fn aggregate_proofs<E: Pairing, T: Transcript>(
     Ok((g16_aggregate_proofs(srs, transcript, &g16_proofs)?, d))
 }
 
+/// Same as `aggregate_proofs` but first appends the fingerprint of `vk`, the size of the SRS and
+/// the `public_inputs` of all `proofs` to the transcript with `append_statement`, and the
+/// commitments `d` with `append_commitments`. The commitments are not part of the Groth16
+/// aggregate proof so without this the challenge `r` would not depend on them. Verify with
+/// `verify_aggregate_proof_safe`.
+pub fn aggregate_proofs_safe<E: Pairing, T: Transcript>(
+    srs: impl Into<PreparedProverSRS<E>>,
+    transcript: &mut T,
+    vk: &VerifyingKey<E>,
+    proofs: &[LegoProof<E>],
+    public_inputs: &[Vec<E::ScalarField>],
+) -> Result<(AggregateProof<E>, Vec<E::G1Affine>), AggregationError> {
+    let srs = srs.into();
+    bind_statement(
+        transcript,
+        &vk.fingerprint(),
+        srs.n,
+        proofs.len(),
+        public_inputs,
+    )?;
+    let d = proofs.iter().map(|p| p.d).collect::<Vec<_>>();
+    append_commitments(transcript, &d);
+    aggregate_proofs(srs, transcript, proofs)
+}
+
 pub fn verify_aggregate_proof<E: Pairing, R: Rng, T: Transcript>(
     ip_verifier_srs: &VerifierSRS<E>,
     pvk: &PreparedVerifyingKey<E>,
@@ -56,13 +86,27 @@ pub fn verify_aggregate_proof<E: Pairing, R: Rng, T: Transcript>(
         }
     }
 
-    if public_inputs.len() != proof.tmipp.gipa.nproofs as usize {
+    if d.len() != public_inputs.len() {
         return Err(AggregationError::InvalidProof(format!(
-            "public inputs len {} != number of proofs {}",
-            public_inputs.len(),
-            proof.tmipp.gipa.nproofs
+            "commitments len {} != public inputs len {}",
+            d.len(),
+            public_inputs.len()
         )));
     }
+    // As in `calculate_d`, a key without `eta`, like one converted from Groth16, cannot have
+    // commitments in the proofs
+    if pvk.vk.eta_gamma_inv_g1.is_zero() && d.iter().any(|d| !d.is_zero()) {
+        return Err(AggregationError::InvalidProof(
+            "proofs for a key without eta cannot have commitments".to_string(),
+        ));
+    }
+
+    // The prover padded the proofs to a power of two by repeating the last one
+    let public_inputs =
+        pad_for_verification(public_inputs, proof.tmipp.gipa.nproofs, "public inputs")?;
+    let public_inputs = public_inputs.as_ref();
+    let d = pad_by_repeating_last(d);
+    let d = d.as_ref();
 
     // Random linear combination of proofs
     transcript.append(b"AB-commitment", &proof.com_ab);
@@ -117,3 +161,35 @@ pub fn verify_aggregate_proof<E: Pairing, R: Rng, T: Transcript>(
         )),
     }
 }
+
+/// Verifies a proof created by `aggregate_proofs_safe`. Appends the fingerprint of the verifying
+/// key, the size of the SRS, `public_inputs` and `d` to the transcript as the prover did.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_aggregate_proof_safe<E: Pairing, R: Rng, T: Transcript>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &AggregateProof<E>,
+    d: &[E::G1Affine],
+    rng: &mut R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    append_statement(
+        transcript,
+        &pvk.vk.fingerprint(),
+        ip_verifier_srs.n,
+        public_inputs,
+    );
+    append_commitments(transcript, d);
+    verify_aggregate_proof(
+        ip_verifier_srs,
+        pvk,
+        public_inputs,
+        proof,
+        d,
+        rng,
+        transcript,
+        pairing_check,
+    )
+}
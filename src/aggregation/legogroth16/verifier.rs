@@ -1,24 +1,28 @@
 use ark_ec::pairing::PairingOutput;
-use ark_ec::{pairing::Pairing, AffineRepr, Group, VariableBaseMSM};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, Group, VariableBaseMSM};
 use ark_ff::{Field, PrimeField};
-use ark_std::ops::AddAssign;
-use ark_std::{cfg_iter, format, ops::Mul, rand::Rng, vec, vec::Vec, One, Zero};
+use ark_std::ops::{AddAssign, Neg};
+use ark_std::{cfg_iter, format, ops::Mul, rand::Rng, string::ToString, vec, vec::Vec, One, Zero};
 use dock_crypto_utils::randomized_pairing_check::RandomizedPairingChecker;
 
-use crate::PreparedVerifyingKey;
+use crate::link::VK as LinkVK;
+use crate::{PreparedVerifyingKey, VerifyingKey, VerifyingKeyWithLink};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 use crate::aggregation::srs::{VerifierSRS, VerifierSRSProjective};
-use crate::aggregation::utils::{final_verification_check, verify_kzg};
+use crate::aggregation::statement::append_statement;
+use crate::aggregation::utils::{final_verification_check, pad_for_verification, verify_kzg};
 
 use crate::aggregation::commitment::PairCommitment;
 use crate::aggregation::error::AggregationError;
 use crate::aggregation::kzg::polynomial_evaluation_product_form_from_transcript;
 use dock_crypto_utils::transcript::Transcript;
 
-use super::proof::AggregateLegoProof;
+use super::proof::{
+    AggregateLegoProof, AggregateLegoProofWithLink, AggregateLinkProof, GipaProofLego,
+};
 
 /// Verifies the aggregated proofs thanks to the LegoGroth16 verifying key, the
 /// verifier SRS from the aggregation scheme, all the public inputs of the
@@ -34,69 +38,241 @@ use super::proof::AggregateLegoProof;
 /// number of proofs and public inputs (+100ms in our case). In the case of Filecoin, the only
 /// non-fixed part of the public inputs are the challenges derived from a seed. Even though this
 /// seed comes from a random beacon, we are hashing this as a safety precaution.
+/// `verify_aggregate_proof_safe` appends the public inputs, the verifying key and the SRS
+/// size to the transcript itself.
 pub fn verify_aggregate_proof<E: Pairing, R: Rng, T: Transcript>(
     ip_verifier_srs: &VerifierSRS<E>,
     pvk: &PreparedVerifyingKey<E>,
     public_inputs: &[Vec<E::ScalarField>],
     proof: &AggregateLegoProof<E>,
-    mut rng: R,
-    mut transcript: &mut T,
+    rng: R,
+    transcript: &mut T,
     pairing_check: Option<&mut RandomizedPairingChecker<E>>,
 ) -> Result<(), AggregationError> {
     proof.parsing_check()?;
+    verify(
+        ip_verifier_srs,
+        &pvk.vk,
+        public_inputs,
+        proof,
+        None,
+        rng,
+        transcript,
+        pairing_check,
+    )
+    .map(|_| ())
+}
+
+/// Verifies a proof created by `aggregate_proofs_safe`. Appends the fingerprint of the verifying
+/// key, the size of the SRS and `public_inputs` to the transcript as the prover did.
+pub fn verify_aggregate_proof_safe<E: Pairing, R: Rng, T: Transcript>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    pvk: &PreparedVerifyingKey<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &AggregateLegoProof<E>,
+    rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    append_statement(
+        transcript,
+        &pvk.vk.fingerprint(),
+        ip_verifier_srs.n,
+        public_inputs,
+    );
+    verify_aggregate_proof(
+        ip_verifier_srs,
+        pvk,
+        public_inputs,
+        proof,
+        rng,
+        transcript,
+        pairing_check,
+    )
+}
+
+/// Verifies the proofs aggregated with `aggregate_proofs_incl_cp_links`, i.e. the LegoGroth16
+/// proofs as in `verify_aggregate_proof` and also the CP_link proofs of all of them. The
+/// transcript must be the same as the one given to the prover.
+pub fn verify_aggregate_proof_incl_cp_links<E: Pairing, R: Rng, T: Transcript>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    vk: &VerifyingKeyWithLink<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &AggregateLegoProofWithLink<E>,
+    rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    // There is at least one link commitment besides `d`
+    if vk.link_pp.l < 2 || vk.link_vk.c.len() < vk.link_pp.l {
+        return Err(AggregationError::MalformedVerifyingKey);
+    }
+    proof.parsing_check(vk.link_pp.l)?;
+    verify(
+        ip_verifier_srs,
+        &vk.groth16_vk,
+        public_inputs,
+        &proof.proof,
+        Some((&proof.link, &vk.link_vk)),
+        rng,
+        transcript,
+        pairing_check,
+    )
+    .map(|_| ())
+}
+
+/// Verifies a proof created by `aggregate_proofs_incl_cp_links_safe`. Appends the fingerprint of
+/// `vk`, the size of the SRS and `public_inputs` to the transcript as the prover did.
+pub fn verify_aggregate_proof_incl_cp_links_safe<E: Pairing, R: Rng, T: Transcript>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    vk: &VerifyingKeyWithLink<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &AggregateLegoProofWithLink<E>,
+    rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<(), AggregationError> {
+    append_statement(
+        transcript,
+        &vk.fingerprint(),
+        ip_verifier_srs.n,
+        public_inputs,
+    );
+    verify_aggregate_proof_incl_cp_links(
+        ip_verifier_srs,
+        vk,
+        public_inputs,
+        proof,
+        rng,
+        transcript,
+        pairing_check,
+    )
+}
+
+/// Verifies an already parsed `proof` and, if `link` is given, the aggregated CP_link equation
+/// $\prod_i e(link\_d_i^r, c_i) \cdot e(D^r, c_l) \cdot e(link\_pi^r, -a) = 1$ with the same `r`.
+/// Returns `r`.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn verify<E: Pairing, R: Rng, T: Transcript>(
+    ip_verifier_srs: &VerifierSRS<E>,
+    vk: &VerifyingKey<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    proof: &AggregateLegoProof<E>,
+    link: Option<(&AggregateLinkProof<E>, &LinkVK<E::G2Affine>)>,
+    rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+) -> Result<E::ScalarField, AggregationError> {
+    let ver_srs_proj = ip_verifier_srs.to_projective();
+    verify_statement(
+        vk,
+        public_inputs,
+        &GipaInstance::from(proof),
+        link,
+        rng,
+        transcript,
+        pairing_check,
+        |r, transcript, checker| {
+            verify_tipp_mipp_incl_link(
+                &ver_srs_proj,
+                proof,
+                link.map(|(l, _)| l),
+                r, // we give the extra r as it's not part of the proof itself - it is simply used on top for the groth16 aggregation
+                transcript,
+                checker,
+            )
+        },
+    )
+}
+
+/// Same as `verify` but for any aggregate proof given by its `instance`, with
+/// `verify_tipp_mipp` verifying its TIPP and MIPP relations for the challenge `r`
+#[allow(clippy::too_many_arguments)]
+pub(super) fn verify_statement<E: Pairing, R: Rng, T: Transcript>(
+    vk: &VerifyingKey<E>,
+    public_inputs: &[Vec<E::ScalarField>],
+    instance: &GipaInstance<E>,
+    link: Option<(&AggregateLinkProof<E>, &LinkVK<E::G2Affine>)>,
+    mut rng: R,
+    transcript: &mut T,
+    pairing_check: Option<&mut RandomizedPairingChecker<E>>,
+    verify_tipp_mipp: impl FnOnce(
+        &E::ScalarField,
+        &mut T,
+        &mut RandomizedPairingChecker<E>,
+    ) -> Result<(), AggregationError>,
+) -> Result<E::ScalarField, AggregationError> {
     for pub_input in public_inputs {
-        if (pub_input.len() + 1) > pvk.vk.gamma_abc_g1.len() {
+        if (pub_input.len() + 1) > vk.gamma_abc_g1.len() {
             return Err(AggregationError::MalformedVerifyingKey);
         }
     }
-
-    if public_inputs.len() != proof.tmipp.gipa.nproofs as usize {
-        return Err(AggregationError::InvalidProof(format!(
-            "public inputs len {} != number of proofs {}",
-            public_inputs.len(),
-            proof.tmipp.gipa.nproofs
-        )));
+    if let Some((link, link_vk)) = link {
+        if link.z.len() < 2 || link_vk.c.len() < link.z.len() {
+            return Err(AggregationError::MalformedVerifyingKey);
+        }
     }
+    // As in `calculate_d`, a key without `eta`, like one converted from Groth16, cannot have
+    // commitments in the proofs. Otherwise `d` is unconstrained. All `d` are 0 only if their
+    // random linear combination is.
+    if vk.eta_gamma_inv_g1.is_zero() && !instance.z_d.is_zero() {
+        return Err(AggregationError::InvalidProof(
+            "proofs for a key without eta cannot have commitments".to_string(),
+        ));
+    }
+
+    // The prover padded the proofs to a power of two by repeating the last one
+    let public_inputs =
+        pad_for_verification(public_inputs, instance.gipa.nproofs, "public inputs")?;
+    let public_inputs = public_inputs.as_ref();
 
     // Random linear combination of proofs
-    transcript.append(b"AB-commitment", &proof.com_ab);
-    transcript.append(b"C-commitment", &proof.com_c);
-    transcript.append(b"D-commitment", &proof.com_d);
+    transcript.append(b"AB-commitment", instance.com_ab);
+    transcript.append(b"C-commitment", instance.com_c);
+    transcript.append(b"D-commitment", instance.com_d);
+    if let Some((link, _)) = link {
+        transcript.append(b"link-commitments", &link.com);
+    }
 
     let r = transcript.challenge_scalar::<E::ScalarField>(b"r-random-fiatshamir");
 
     let mut c = RandomizedPairingChecker::new_using_rng(&mut rng, true);
     let mut checker = pairing_check.unwrap_or_else(|| &mut c);
 
-    let ver_srs_proj = ip_verifier_srs.to_projective();
-    verify_tipp_mipp::<E, T>(
-        &ver_srs_proj,
-        proof,
-        &r, // we give the extra r as it's not part of the proof itself - it is simply used on top for the groth16 aggregation
-        &mut transcript,
-        &mut checker,
-    )?;
+    verify_tipp_mipp(&r, transcript, checker)?;
+
+    if let Some((link, link_vk)) = link {
+        // The last vector is of link_pi and the one before it would have been of D
+        let l = link.z.len() - 1;
+        let mut source1 = link.z[..l].to_vec();
+        source1.push(*instance.z_d);
+        source1.push(link.z[l]);
+        let mut source2 = link_vk.c[..l + 1].to_vec();
+        source2.push(link_vk.a.into_group().neg().into_affine());
+        checker.add_multiple_sources_and_target(&source1, source2, &PairingOutput::<E>::zero());
+    }
 
     let mut source1 = Vec::with_capacity(4);
     let mut source2 = Vec::with_capacity(4);
 
-    source1.push(proof.z_d);
-    source2.push(pvk.vk.gamma_g2);
+    source1.push(*instance.z_d);
+    source2.push(vk.gamma_g2);
 
     final_verification_check(
         source1,
         source2,
-        proof.z_c.clone(),
-        &proof.z_ab,
+        *instance.z_c,
+        instance.z_ab,
         &r,
         public_inputs,
-        &pvk.vk.alpha_g1,
-        pvk.vk.beta_g2,
-        pvk.vk.gamma_g2,
-        pvk.vk.delta_g2,
-        &pvk.vk.gamma_abc_g1,
+        &vk.alpha_g1,
+        vk.beta_g2,
+        vk.gamma_g2,
+        vk.delta_g2,
+        &vk.gamma_abc_g1,
         &mut checker,
-    )
+    )?;
+    Ok(r)
 }
 
 /// verify_tipp_mipp returns a pairing equation to check the tipp proof.  $r$ is
@@ -109,9 +285,22 @@ pub fn verify_tipp_mipp<E: Pairing, T: Transcript>(
     transcript: &mut T,
     pairing_checker: &mut RandomizedPairingChecker<E>,
 ) -> Result<(), AggregationError> {
+    verify_tipp_mipp_incl_link(v_srs, proof, None, r_shift, transcript, pairing_checker)
+}
+
+/// Same as `verify_tipp_mipp` but also checks the MIPP relations of the `link` vectors
+fn verify_tipp_mipp_incl_link<E: Pairing, T: Transcript>(
+    v_srs: &VerifierSRSProjective<E>,
+    proof: &AggregateLegoProof<E>,
+    link: Option<&AggregateLinkProof<E>>,
+    r_shift: &E::ScalarField,
+    transcript: &mut T,
+    pairing_checker: &mut RandomizedPairingChecker<E>,
+) -> Result<(), AggregationError> {
+    let instance = GipaInstance::from(proof);
     // (T,U), Z for TIPP and MIPP  and all challenges
-    let (final_res, final_r, challenges, challenges_inv) =
-        gipa_verify_tipp_mipp(&proof, r_shift, transcript);
+    let (final_res, final_r, challenges, challenges_inv, link_res) =
+        gipa_verify_tipp_mipp_incl_link(&instance, link, r_shift, transcript);
 
     // KZG challenge point
     transcript.append(b"kzg-challenge", &challenges[0]);
@@ -134,30 +323,51 @@ pub fn verify_tipp_mipp<E: Pairing, T: Transcript>(
         pairing_checker,
     );
 
+    check_gipa_finals(
+        instance.gipa,
+        link,
+        &final_res,
+        &final_r,
+        &link_res,
+        pairing_checker,
+    )
+}
+
+/// Checks the final values of the GIPA proof against the values `final_res`, `final_r` and
+/// `link_res` computed by the verifier. The final commitment keys in `gipa` must have been
+/// checked already.
+pub(super) fn check_gipa_finals<E: Pairing>(
+    gipa: &GipaProofLego<E>,
+    link: Option<&AggregateLinkProof<E>>,
+    final_res: &GipaTUZ<E>,
+    final_r: &E::ScalarField,
+    link_res: &[LinkTUZ<E>],
+    pairing_checker: &mut RandomizedPairingChecker<E>,
+) -> Result<(), AggregationError> {
     // We create a sequence of pairing tuple that we aggregate together at
     // the end to perform only once the final exponentiation.
 
-    let b_prep = E::G2Prepared::from(proof.tmipp.gipa.final_b);
-    let v_0_prep = E::G2Prepared::from(proof.tmipp.gipa.final_vkey.0);
-    let v_1_prep = E::G2Prepared::from(proof.tmipp.gipa.final_vkey.1);
+    let b_prep = E::G2Prepared::from(gipa.final_b);
+    let v_0_prep = E::G2Prepared::from(gipa.final_vkey.0);
+    let v_1_prep = E::G2Prepared::from(gipa.final_vkey.1);
 
     // TIPP
     // z = e(A,B)
     pairing_checker.add_multiple_sources_and_target(
-        &[proof.tmipp.gipa.final_a],
+        &[gipa.final_a],
         vec![b_prep.clone()],
         &final_res.zab,
     );
     //  final_aB.0 = T = e(A,v1)e(w1,B)
     pairing_checker.add_multiple_sources_and_target(
-        &[proof.tmipp.gipa.final_a, proof.tmipp.gipa.final_wkey.0],
+        &[gipa.final_a, gipa.final_wkey.0],
         vec![v_0_prep.clone(), b_prep.clone()],
         &final_res.tab,
     );
 
     //  final_aB.1 = U = e(A,v2)e(w2,B)
     pairing_checker.add_multiple_sources_and_target(
-        &[proof.tmipp.gipa.final_a, proof.tmipp.gipa.final_wkey.1],
+        &[gipa.final_a, gipa.final_wkey.1],
         vec![v_1_prep.clone(), b_prep],
         &final_res.uab,
     );
@@ -165,17 +375,17 @@ pub fn verify_tipp_mipp<E: Pairing, T: Transcript>(
     // MIPP for C
     // Verify base inner product commitment
     // Z ==  c ^ r
-    let final_zc = proof.tmipp.gipa.final_c.mul(final_r);
+    let final_zc = gipa.final_c.mul(final_r);
     // Check commitment correctness
     // T = e(C,v1)
     pairing_checker.add_multiple_sources_and_target(
-        &[proof.tmipp.gipa.final_c],
+        &[gipa.final_c],
         vec![v_0_prep.clone()],
         &final_res.tc,
     );
     // U = e(C,v2)
     pairing_checker.add_multiple_sources_and_target(
-        &[proof.tmipp.gipa.final_c],
+        &[gipa.final_c],
         vec![v_1_prep.clone()],
         &final_res.uc,
     );
@@ -183,21 +393,34 @@ pub fn verify_tipp_mipp<E: Pairing, T: Transcript>(
     // MIPP for D
     // Verify base inner product commitment
     // Z ==  D ^ r
-    let final_zd = proof.tmipp.gipa.final_d.mul(final_r);
+    let final_zd = gipa.final_d.mul(final_r);
     // Check commitment correctness
     // T = e(D,v1)
     pairing_checker.add_multiple_sources_and_target(
-        &[proof.tmipp.gipa.final_d],
-        vec![v_0_prep],
+        &[gipa.final_d],
+        vec![v_0_prep.clone()],
         &final_res.td,
     );
     // U = e(D,v2)
     pairing_checker.add_multiple_sources_and_target(
-        &[proof.tmipp.gipa.final_d],
-        vec![v_1_prep],
+        &[gipa.final_d],
+        vec![v_1_prep.clone()],
         &final_res.ud,
     );
 
+    // MIPP for link_d and link_pi, same as for D
+    if let Some(link) = link {
+        for (final_l, (t, u, z)) in link.finals.iter().zip(link_res.iter()) {
+            pairing_checker.add_multiple_sources_and_target(&[*final_l], vec![v_0_prep.clone()], t);
+            pairing_checker.add_multiple_sources_and_target(&[*final_l], vec![v_1_prep.clone()], u);
+            if final_l.mul(final_r) != *z {
+                return Err(AggregationError::InvalidProof(
+                    "tipp verify: INVALID final_z check for link".to_string(),
+                ));
+            }
+        }
+    }
+
     if final_zc != final_res.zc {
         return Err(AggregationError::InvalidProof(format!(
             "tipp verify: INVALID final_z check for C {} vs {}",
@@ -232,7 +455,55 @@ pub fn gipa_verify_tipp_mipp<E: Pairing, T: Transcript>(
     Vec<E::ScalarField>,
     Vec<E::ScalarField>,
 ) {
-    let gipa = &proof.tmipp.gipa;
+    let (final_res, final_r, challenges, challenges_inv, _) =
+        gipa_verify_tipp_mipp_incl_link(&GipaInstance::from(proof), None, r_shift, transcript);
+    (final_res, final_r, challenges, challenges_inv)
+}
+
+/// The parts of an aggregate proof that GIPA is verified against: the commitments to and the
+/// random linear combinations of the proofs, and the GIPA proof itself
+pub(super) struct GipaInstance<'a, E: Pairing> {
+    pub com_ab: &'a PairCommitment<E>,
+    pub com_c: &'a PairCommitment<E>,
+    pub com_d: &'a PairCommitment<E>,
+    pub z_ab: &'a PairingOutput<E>,
+    pub z_c: &'a E::G1Affine,
+    pub z_d: &'a E::G1Affine,
+    pub gipa: &'a GipaProofLego<E>,
+}
+
+impl<'a, E: Pairing> From<&'a AggregateLegoProof<E>> for GipaInstance<'a, E> {
+    fn from(proof: &'a AggregateLegoProof<E>) -> Self {
+        Self {
+            com_ab: &proof.com_ab,
+            com_c: &proof.com_c,
+            com_d: &proof.com_d,
+            z_ab: &proof.z_ab,
+            z_c: &proof.z_c,
+            z_d: &proof.z_d,
+            gipa: &proof.tmipp.gipa,
+        }
+    }
+}
+
+/// The final T, U and Z of a link vector
+pub(super) type LinkTUZ<E> = (PairingOutput<E>, PairingOutput<E>, <E as Pairing>::G1);
+
+/// Same as `gipa_verify_tipp_mipp` but also returns the final values for the `link` vectors
+#[allow(clippy::type_complexity)]
+pub(super) fn gipa_verify_tipp_mipp_incl_link<E: Pairing, T: Transcript>(
+    instance: &GipaInstance<E>,
+    link: Option<&AggregateLinkProof<E>>,
+    r_shift: &E::ScalarField,
+    transcript: &mut T,
+) -> (
+    GipaTUZ<E>,
+    E::ScalarField,
+    Vec<E::ScalarField>,
+    Vec<E::ScalarField>,
+    Vec<LinkTUZ<E>>,
+) {
+    let gipa = instance.gipa;
     // COM(A,B) = PROD e(A,B) given by prover
     let comms_ab = &gipa.comms_ab;
     // COM(C,r) = SUM C^r given by prover
@@ -246,9 +517,12 @@ pub fn gipa_verify_tipp_mipp<E: Pairing, T: Transcript>(
     let mut challenges = Vec::new();
     let mut challenges_inv = Vec::new();
 
-    transcript.append(b"inner-product-ab", &proof.z_ab);
-    transcript.append(b"comm-c", &proof.z_c);
-    transcript.append(b"comm-d", &proof.z_d);
+    transcript.append(b"inner-product-ab", instance.z_ab);
+    transcript.append(b"comm-c", instance.z_c);
+    transcript.append(b"comm-d", instance.z_d);
+    if let Some(link) = link {
+        transcript.append(b"comm-link", &link.z);
+    }
     let mut c_inv: E::ScalarField =
         transcript.challenge_scalar::<E::ScalarField>(b"first-challenge");
     let mut c = c_inv.inverse().unwrap();
@@ -287,6 +561,10 @@ pub fn gipa_verify_tipp_mipp<E: Pairing, T: Transcript>(
             transcript.append(b"tuc_r", tuc_r);
             transcript.append(b"tud_l", tud_l);
             transcript.append(b"tud_r", tud_r);
+            if let Some(link) = link {
+                transcript.append(b"zlink", &link.z_lr[i]);
+                transcript.append(b"tulink", &link.comms[i]);
+            }
             c_inv = transcript.challenge_scalar::<E::ScalarField>(b"challenge_i");
             c = c_inv.inverse().unwrap();
         }
@@ -295,16 +573,16 @@ pub fn gipa_verify_tipp_mipp<E: Pairing, T: Transcript>(
     }
 
     // output of the pair commitment T and U in TIPP -> COM((v,w),A,B)
-    let PairCommitment { t: t_ab, u: u_ab } = proof.com_ab.clone();
-    let z_ab = proof.z_ab; // in the end must be equal to Z = A^r * B
+    let PairCommitment { t: t_ab, u: u_ab } = instance.com_ab.clone();
+    let z_ab = *instance.z_ab; // in the end must be equal to Z = A^r * B
 
     // COM(v,C)
-    let PairCommitment { t: t_c, u: u_c } = proof.com_c.clone();
-    let z_c = proof.z_c.into_group(); // in the end must be equal to Z = C^r
+    let PairCommitment { t: t_c, u: u_c } = instance.com_c.clone();
+    let z_c = instance.z_c.into_group(); // in the end must be equal to Z = C^r
 
     // COM(v,D)
-    let PairCommitment { t: t_d, u: u_d } = proof.com_d.clone();
-    let z_d = proof.z_d.into_group(); // in the end must be equal to Z = D^r
+    let PairCommitment { t: t_d, u: u_d } = instance.com_d.clone();
+    let z_d = instance.z_d.into_group(); // in the end must be equal to Z = D^r
 
     let mut final_res = GipaTUZ {
         tab: t_ab,
@@ -462,6 +740,29 @@ pub fn gipa_verify_tipp_mipp<E: Pairing, T: Transcript>(
         res
     });
 
+    // Same as for D, for each link vector
+    let link_res = link
+        .map(|link| {
+            (0..link.com.len())
+                .map(|j| {
+                    let PairCommitment { mut t, mut u } = link.com[j].clone();
+                    let mut z_b = Vec::with_capacity(z_s.len());
+                    for (round, (c, c_inv)) in
+                        challenges.iter().zip(challenges_inv.iter()).enumerate()
+                    {
+                        let (l, r) = &link.comms[round][j];
+                        t += l.t * c + r.t * c_inv;
+                        u += l.u * c + r.u * c_inv;
+                        z_b.push(link.z_lr[round][j].0);
+                        z_b.push(link.z_lr[round][j].1);
+                    }
+                    let z = link.z[j].into_group() + E::G1::msm_bigint(&z_b, &z_s);
+                    (t, u, z)
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
     // we reverse the order because the polynomial evaluation routine expects
     // the challenges in reverse order.Doing it here allows us to compute the final_r
     // in log time. Challenges are used as well in the KZG verification checks.
@@ -478,7 +779,7 @@ pub fn gipa_verify_tipp_mipp<E: Pairing, T: Transcript>(
         &E::ScalarField::one(),
     );
 
-    (final_res, final_r, challenges, challenges_inv)
+    (final_res, final_r, challenges, challenges_inv, link_res)
 }
 
 /// Keeps track of the variables that have been sent by the prover and must
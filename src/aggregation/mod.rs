@@ -1,10 +1,16 @@
 pub mod commitment;
 pub mod error;
 pub mod groth16;
+pub mod heterogeneous;
 pub mod key;
 pub mod kzg;
 pub mod legogroth16;
+#[cfg(feature = "std")]
+pub mod ptau;
 pub mod srs;
+pub mod statement;
+pub mod torus;
+pub mod transparent;
 pub mod utils;
 
 #[cfg(test)]
@@ -0,0 +1,349 @@
+//! Importing powers of tau from the transcripts of real ceremonies to build a `GenericSRS`.
+//! The SRS needs the powers of 2 independent secrets, so `GenericSRS::from_powers_of_tau` takes
+//! the powers from 2 distinct ceremonies like `taupipp`'s [assemble](https://github.com/nikkolasg/taupipp/blob/baca1426266bf39416c45303e35c966d69f4f8b4/src/bin/assemble.rs).
+//!
+//! 2 formats are supported:
+//! - snarkjs `.ptau` files, <https://github.com/iden3/snarkjs/blob/master/src/powersoftau_new.js>
+//! - the challenge and response files of the Zcash and Filecoin ceremonies for BLS12-381,
+//!   <https://github.com/ebfull/powersoftau>. The points are in Zcash's encoding which is the
+//!   encoding `ark-bls12-381` uses.
+
+use ark_ec::pairing::Pairing;
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ec::{AffineRepr, CurveConfig, CurveGroup, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use ark_std::collections::BTreeMap;
+use ark_std::rand::Rng;
+use ark_std::{format, string::ToString, vec::Vec, UniformRand, Zero};
+use std::io::{Read, Seek, SeekFrom};
+
+use super::error::AggregationError;
+use super::srs::{GenericSRS, MAX_SRS_SIZE};
+
+/// Power of 2 of the number of powers in the Zcash ceremony (Powers of Tau for Sapling)
+pub const ZCASH_CEREMONY_POWER: u32 = 21;
+
+/// Power of 2 of the number of powers in the Filecoin ceremony
+pub const FILECOIN_CEREMONY_POWER: u32 = 27;
+
+/// Size of the hash at the start of the Zcash and Filecoin challenge and response files
+const POWERSOFTAU_HASH_SIZE: u64 = 64;
+
+const PTAU_HEADER_SECTION: u32 = 1;
+const PTAU_TAU_G1_SECTION: u32 = 2;
+const PTAU_TAU_G2_SECTION: u32 = 3;
+
+/// Prime field of the coordinates of points of curve `P`
+type BasePrimeField<P> = <<P as CurveConfig>::BaseField as Field>::BasePrimeField;
+
+/// $\{g^{\tau^i}\}_{i=0}^{N-1}$ and $\{h^{\tau^i}\}_{i=0}^{N-1}$ from a powers of tau ceremony
+#[derive(Clone, Debug, PartialEq)]
+pub struct PowersOfTau<E: Pairing> {
+    pub tau_g1: Vec<E::G1Affine>,
+    pub tau_g2: Vec<E::G2Affine>,
+}
+
+impl<E: Pairing> PowersOfTau<E> {
+    /// Reads the first `max_powers` powers of tau, or all if the file has fewer, from a snarkjs
+    /// `.ptau` file. The file's curve must be the curve of `E`.
+    pub fn read_ptau<P1, P2, R>(mut reader: R, max_powers: usize) -> Result<Self, AggregationError>
+    where
+        E: Pairing<G1Affine = Affine<P1>, G2Affine = Affine<P2>>,
+        P1: SWCurveConfig,
+        P2: SWCurveConfig,
+        P2::BaseField: Field<BasePrimeField = <P1::BaseField as Field>::BasePrimeField>,
+        R: Read + Seek,
+    {
+        let mut magic = [0u8; 4];
+        read_exact(&mut reader, &mut magic)?;
+        if &magic != b"ptau" {
+            return Err(AggregationError::InvalidSRS(
+                "Invalid magic number".to_string(),
+            ));
+        }
+        let version = read_u32(&mut reader)?;
+        if version != 1 {
+            return Err(AggregationError::InvalidSRS(format!(
+                "Unsupported ptau version {}",
+                version
+            )));
+        }
+
+        // section type -> (file offset, section size)
+        let mut sections = BTreeMap::<u32, (u64, u64)>::new();
+        let num_sections = read_u32(&mut reader)?;
+        for _ in 0..num_sections {
+            let sec_type = read_u32(&mut reader)?;
+            let sec_size = read_u64(&mut reader)?;
+            let offset = seek(&mut reader, SeekFrom::Current(0))?;
+            sections.insert(sec_type, (offset, sec_size));
+            let skip = i64::try_from(sec_size).map_err(|_| {
+                AggregationError::InvalidSRS(format!(
+                    "Section {} size {} too large in ptau file",
+                    sec_type, sec_size
+                ))
+            })?;
+            seek(&mut reader, SeekFrom::Current(skip))?;
+        }
+        let section = |sec_type: u32| {
+            sections.get(&sec_type).copied().ok_or_else(|| {
+                AggregationError::InvalidSRS(format!("No section {} in ptau file", sec_type))
+            })
+        };
+
+        let (header_offset, _) = section(PTAU_HEADER_SECTION)?;
+        seek(&mut reader, SeekFrom::Start(header_offset))?;
+        let n8 = read_u32(&mut reader)? as usize;
+        let mut q = ark_std::vec![0u8; n8];
+        read_exact(&mut reader, &mut q)?;
+        let mut modulus = BasePrimeField::<P1>::MODULUS.to_bytes_le();
+        modulus.resize(n8, 0);
+        if q != modulus || BasePrimeField::<P1>::zero().uncompressed_size() != n8 {
+            return Err(AggregationError::InvalidSRS(
+                "ptau file is for a different curve".to_string(),
+            ));
+        }
+        let power = read_u32(&mut reader)?;
+        if power >= 32 {
+            return Err(AggregationError::InvalidSRS(format!(
+                "Invalid ptau power {}",
+                power
+            )));
+        }
+        // There are 2^{power+1} - 1 powers in G1 and 2^power in G2
+        let num_g1 = (1usize << (power + 1)) - 1;
+        let num_g2 = 1usize << power;
+        let num = max_powers.min(num_g2);
+
+        // Coordinates are in Montgomery form with R = 2^{8 * n8}
+        let r_inv = BasePrimeField::<P1>::from(2u64)
+            .pow([8 * n8 as u64])
+            .inverse()
+            .unwrap();
+
+        let (tau_g1_offset, tau_g1_size) = section(PTAU_TAU_G1_SECTION)?;
+        if tau_g1_size != (num_g1 * 2 * n8) as u64 {
+            return Err(AggregationError::InvalidSRS(
+                "Invalid size of tau G1 section".to_string(),
+            ));
+        }
+        seek(&mut reader, SeekFrom::Start(tau_g1_offset))?;
+        let tau_g1 = (0..num)
+            .map(|_| read_ptau_point::<P1, _>(&mut reader, n8, &r_inv))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (tau_g2_offset, tau_g2_size) = section(PTAU_TAU_G2_SECTION)?;
+        if tau_g2_size != (num_g2 * 4 * n8) as u64 {
+            return Err(AggregationError::InvalidSRS(
+                "Invalid size of tau G2 section".to_string(),
+            ));
+        }
+        seek(&mut reader, SeekFrom::Start(tau_g2_offset))?;
+        let tau_g2 = (0..num)
+            .map(|_| read_ptau_point::<P2, _>(&mut reader, n8, &r_inv))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { tau_g1, tau_g2 })
+    }
+
+    /// Reads the first `max_powers` powers of tau, or all if the file has fewer, from a challenge
+    /// (uncompressed points) or response (compressed points) file of a ceremony with `2^power`
+    /// powers, like the Zcash and Filecoin ceremonies, see `ZCASH_CEREMONY_POWER` and
+    /// `FILECOIN_CEREMONY_POWER`.
+    pub fn read_powersoftau<R: Read + Seek>(
+        mut reader: R,
+        power: u32,
+        compress: Compress,
+        max_powers: usize,
+    ) -> Result<Self, AggregationError> {
+        if power >= 32 {
+            return Err(AggregationError::InvalidSRS(format!(
+                "Invalid ceremony power {}",
+                power
+            )));
+        }
+        let num_g1 = (1u64 << (power + 1)) - 1;
+        let num = max_powers.min(1 << power);
+        let g1_size = E::G1Affine::zero().serialized_size(compress) as u64;
+
+        seek(&mut reader, SeekFrom::Start(POWERSOFTAU_HASH_SIZE))?;
+        let tau_g1 = (0..num)
+            .map(|_| read_point::<E::G1Affine, _>(&mut reader, compress))
+            .collect::<Result<Vec<_>, _>>()?;
+        seek(
+            &mut reader,
+            SeekFrom::Start(POWERSOFTAU_HASH_SIZE + num_g1 * g1_size),
+        )?;
+        let tau_g2 = (0..num)
+            .map(|_| read_point::<E::G2Affine, _>(&mut reader, compress))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { tau_g1, tau_g2 })
+    }
+
+    /// Checks that there are at least 2 powers, that the first powers are the generators and
+    /// that each power is the previous one multiplied by the same tau, using random linear
+    /// combinations of the powers.
+    pub fn check<R: Rng>(&self, rng: &mut R) -> Result<(), AggregationError> {
+        let n = self.tau_g1.len();
+        if n < 2 || self.tau_g2.len() != n {
+            return Err(AggregationError::InvalidSRS(format!(
+                "Need the same number, at least 2, of powers in G1 and G2 but found {} and {}",
+                n,
+                self.tau_g2.len()
+            )));
+        }
+        if self.tau_g1[0] != E::G1Affine::generator() || self.tau_g2[0] != E::G2Affine::generator()
+        {
+            return Err(AggregationError::InvalidSRS(
+                "First powers of tau must be the generators".to_string(),
+            ));
+        }
+        let rho = (0..n - 1)
+            .map(|_| E::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+
+        // e(\sum_i rho_i * g^{tau^{i+1}}, h) = e(\sum_i rho_i * g^{tau^i}, h^tau)
+        let g_next = E::G1::msm_unchecked(&self.tau_g1[1..], &rho);
+        let g_prev = E::G1::msm_unchecked(&self.tau_g1[..n - 1], &rho);
+        if !E::multi_pairing(
+            [g_next.into_affine(), (-g_prev).into_affine()],
+            [self.tau_g2[0], self.tau_g2[1]],
+        )
+        .is_zero()
+        {
+            return Err(AggregationError::InvalidSRS(
+                "Powers of tau in G1 are inconsistent".to_string(),
+            ));
+        }
+
+        // e(g, \sum_i rho_i * h^{tau^{i+1}}) = e(g^tau, \sum_i rho_i * h^{tau^i})
+        let h_next = E::G2::msm_unchecked(&self.tau_g2[1..], &rho);
+        let h_prev = E::G2::msm_unchecked(&self.tau_g2[..n - 1], &rho);
+        if !E::multi_pairing(
+            [self.tau_g1[0], (-self.tau_g1[1].into_group()).into_affine()],
+            [h_next.into_affine(), h_prev.into_affine()],
+        )
+        .is_zero()
+        {
+            return Err(AggregationError::InvalidSRS(
+                "Powers of tau in G2 are inconsistent".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<E: Pairing> GenericSRS<E> {
+    /// Builds the SRS from the powers of tau of 2 distinct ceremonies, the first gives the
+    /// powers of `a` and the second the powers of `b`. The powers are checked with
+    /// `PowersOfTau::check` and the SRS has as many powers as the smaller of the two, up to
+    /// `MAX_SRS_SIZE`.
+    pub fn from_powers_of_tau<R: Rng>(
+        alpha: PowersOfTau<E>,
+        beta: PowersOfTau<E>,
+        rng: &mut R,
+    ) -> Result<Self, AggregationError> {
+        alpha.check(rng)?;
+        beta.check(rng)?;
+        // The SRS is only binding if a and b are independent
+        if alpha.tau_g1[1] == beta.tau_g1[1] {
+            return Err(AggregationError::InvalidSRS(
+                "Powers of tau are from the same ceremony".to_string(),
+            ));
+        }
+        let len = alpha.tau_g1.len().min(beta.tau_g1.len()).min(MAX_SRS_SIZE);
+        let PowersOfTau {
+            tau_g1: mut g_alpha_powers,
+            tau_g2: mut h_alpha_powers,
+        } = alpha;
+        let PowersOfTau {
+            tau_g1: mut g_beta_powers,
+            tau_g2: mut h_beta_powers,
+        } = beta;
+        g_alpha_powers.truncate(len);
+        h_alpha_powers.truncate(len);
+        g_beta_powers.truncate(len);
+        h_beta_powers.truncate(len);
+        Ok(Self {
+            g_alpha_powers,
+            h_alpha_powers,
+            g_beta_powers,
+            h_beta_powers,
+        })
+    }
+}
+
+/// Reads a point in snarkjs' encoding, i.e. coordinates in Montgomery form as little endian
+/// bytes. Points at infinity, all zero bytes, are rejected as no power of tau is one.
+fn read_ptau_point<P: SWCurveConfig, R: Read>(
+    mut reader: R,
+    n8: usize,
+    r_inv: &BasePrimeField<P>,
+) -> Result<Affine<P>, AggregationError> {
+    let mut read_coordinate = || {
+        let elems = (0..P::BaseField::extension_degree())
+            .map(|_| {
+                let mut bytes = ark_std::vec![0u8; n8];
+                read_exact(&mut reader, &mut bytes)?;
+                let e = BasePrimeField::<P>::deserialize_uncompressed(bytes.as_slice())?;
+                Ok(e * r_inv)
+            })
+            .collect::<Result<Vec<_>, AggregationError>>()?;
+        P::BaseField::from_base_prime_field_elems(&elems).ok_or_else(|| {
+            AggregationError::InvalidSRS("Invalid coordinate in ptau file".to_string())
+        })
+    };
+    let x = read_coordinate()?;
+    let y = read_coordinate()?;
+    let p = Affine::<P>::new_unchecked(x, y);
+    if x.is_zero() && y.is_zero() {
+        return Err(AggregationError::InvalidSRS(
+            "Point at infinity in powers of tau".to_string(),
+        ));
+    }
+    if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(AggregationError::InvalidSRS(
+            "Invalid point in ptau file".to_string(),
+        ));
+    }
+    Ok(p)
+}
+
+fn read_point<G: AffineRepr, R: Read>(
+    reader: R,
+    compress: Compress,
+) -> Result<G, AggregationError> {
+    let p = G::deserialize_with_mode(reader, compress, Validate::Yes)?;
+    if p.is_zero() {
+        return Err(AggregationError::InvalidSRS(
+            "Point at infinity in powers of tau".to_string(),
+        ));
+    }
+    Ok(p)
+}
+
+fn read_u32<R: Read>(reader: R) -> Result<u32, AggregationError> {
+    let mut buf = [0; 4];
+    read_exact(reader, &mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(reader: R) -> Result<u64, AggregationError> {
+    let mut buf = [0; 8];
+    read_exact(reader, &mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_exact<R: Read>(mut reader: R, buf: &mut [u8]) -> Result<(), AggregationError> {
+    reader
+        .read_exact(buf)
+        .map_err(|err| AggregationError::Serialization(err.to_string()))
+}
+
+fn seek<R: Seek>(mut reader: R, pos: SeekFrom) -> Result<u64, AggregationError> {
+    reader
+        .seek(pos)
+        .map_err(|err| AggregationError::Serialization(err.to_string()))
+}
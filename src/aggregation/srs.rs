@@ -2,13 +2,21 @@ use crate::aggregation::key::PreparedVKey;
 use ark_ec::scalar_mul::fixed_base::FixedBase;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, Group};
 use ark_ff::PrimeField;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
 use ark_std::io::{Read, Write};
-use ark_std::ops::MulAssign;
-use ark_std::{format, rand::Rng, string::ToString, vec::Vec, One, UniformRand};
+use ark_std::marker::PhantomData;
+use ark_std::ops::{MulAssign, Range};
+use ark_std::{cfg_chunks, format, rand::Rng, string::ToString, vec::Vec, One, UniformRand};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 use super::error::AggregationError;
 use super::key::{VKey, WKey};
+use super::utils::{check_points, deserialize_points, padded_proof_count};
+use crate::envelope::{envelope_payload, EnvelopeError};
 
 /// Maximum size of the generic SRS constructed from Filecoin and Zcash power of
 /// taus.
@@ -25,7 +33,7 @@ pub const MAX_SRS_SIZE: usize = (2 << 19) + 1;
 /// ,in other words from two distinct Groth16 CRS.
 /// See [there](https://github.com/nikkolasg/taupipp) a way on how to generate
 /// this GenesisSRS.
-#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Clone, Debug, CanonicalSerialize)]
 pub struct GenericSRS<E: Pairing> {
     /// $\{g^a^i\}_{i=0}^{N}$ where N is the smallest size of the two Groth16 CRS.
     pub g_alpha_powers: Vec<E::G1Affine>,
@@ -39,9 +47,9 @@ pub struct GenericSRS<E: Pairing> {
 
 /// ProverSRS is the specialized SRS version for the prover for a specific number of proofs to
 /// aggregate. It contains as well the commitment keys for this specific size.
-/// Note the size must be a power of two for the moment - if it is not, padding must be
-/// applied.
-#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+/// The size is always a power of two, the aggregation pads the proofs when there are fewer
+/// than that.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize)]
 pub struct ProverSRS<E: Pairing> {
     /// number of proofs to aggregate
     pub n: usize,
@@ -63,7 +71,9 @@ pub struct ProverSRS<E: Pairing> {
     pub wkey: WKey<E>,
 }
 
-#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+/// A `ProverSRS` with the `v` commitment key prepared for pairings. It can be persisted, like
+/// with `Enveloped::to_envelope_bytes`, so that it need not be specialized and prepared again.
+#[derive(Clone, Debug, CanonicalSerialize)]
 pub struct PreparedProverSRS<E: Pairing> {
     /// number of proofs to aggregate
     pub n: usize,
@@ -120,6 +130,168 @@ impl<E: Pairing> PartialEq for GenericSRS<E> {
     }
 }
 
+// The SRS are deserialized manually so that the points are decoded and checked in parallel. The
+// layout is the same as the derived `CanonicalDeserialize` would read.
+
+impl<E: Pairing> Valid for GenericSRS<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        check_points(&self.g_alpha_powers)?;
+        check_points(&self.h_alpha_powers)?;
+        check_points(&self.g_beta_powers)?;
+        check_points(&self.h_beta_powers)
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for GenericSRS<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let g_alpha_powers = deserialize_points(&mut reader, compress, validate)?;
+        let h_alpha_powers = deserialize_points(&mut reader, compress, validate)?;
+        let g_beta_powers = deserialize_points(&mut reader, compress, validate)?;
+        let h_beta_powers = deserialize_points(&mut reader, compress, validate)?;
+        Ok(Self {
+            g_alpha_powers,
+            h_alpha_powers,
+            g_beta_powers,
+            h_beta_powers,
+        })
+    }
+}
+
+impl<E: Pairing> Valid for ProverSRS<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        check_prover_srs_len::<E>(
+            self.n,
+            [&self.g_alpha_powers_table, &self.g_beta_powers_table],
+            [&self.h_alpha_powers_table, &self.h_beta_powers_table],
+            &self.vkey,
+            &self.wkey,
+        )?;
+        check_points(&self.g_alpha_powers_table)?;
+        check_points(&self.h_alpha_powers_table)?;
+        check_points(&self.g_beta_powers_table)?;
+        check_points(&self.h_beta_powers_table)?;
+        self.vkey.check()?;
+        self.wkey.check()
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for ProverSRS<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let n = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let g_alpha_powers_table = deserialize_points(&mut reader, compress, validate)?;
+        let h_alpha_powers_table = deserialize_points(&mut reader, compress, validate)?;
+        let g_beta_powers_table = deserialize_points(&mut reader, compress, validate)?;
+        let h_beta_powers_table = deserialize_points(&mut reader, compress, validate)?;
+        let vkey = VKey::<E>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let wkey = WKey::<E>::deserialize_with_mode(&mut reader, compress, validate)?;
+        // Lengths are always checked as the prover would panic otherwise
+        check_prover_srs_len::<E>(
+            n,
+            [&g_alpha_powers_table, &g_beta_powers_table],
+            [&h_alpha_powers_table, &h_beta_powers_table],
+            &vkey,
+            &wkey,
+        )?;
+        Ok(Self {
+            n,
+            g_alpha_powers_table,
+            h_alpha_powers_table,
+            g_beta_powers_table,
+            h_beta_powers_table,
+            vkey,
+            wkey,
+        })
+    }
+}
+
+impl<E: Pairing> Valid for PreparedProverSRS<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        check_prover_srs_len::<E>(
+            self.n,
+            [&self.g_alpha_powers_table, &self.g_beta_powers_table],
+            [&self.h_alpha_powers_table, &self.h_beta_powers_table],
+            &self.vkey,
+            &self.wkey,
+        )?;
+        if self.prepared_vkey.a.len() != self.n || self.prepared_vkey.b.len() != self.n {
+            return Err(SerializationError::InvalidData);
+        }
+        check_points(&self.g_alpha_powers_table)?;
+        check_points(&self.h_alpha_powers_table)?;
+        check_points(&self.g_beta_powers_table)?;
+        check_points(&self.h_beta_powers_table)?;
+        self.vkey.check()?;
+        self.prepared_vkey.check()?;
+        self.wkey.check()
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for PreparedProverSRS<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let n = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let g_alpha_powers_table = deserialize_points(&mut reader, compress, validate)?;
+        let h_alpha_powers_table = deserialize_points(&mut reader, compress, validate)?;
+        let g_beta_powers_table = deserialize_points(&mut reader, compress, validate)?;
+        let h_beta_powers_table = deserialize_points(&mut reader, compress, validate)?;
+        let vkey = VKey::<E>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let prepared_vkey =
+            PreparedVKey::<E>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let wkey = WKey::<E>::deserialize_with_mode(&mut reader, compress, validate)?;
+        check_prover_srs_len::<E>(
+            n,
+            [&g_alpha_powers_table, &g_beta_powers_table],
+            [&h_alpha_powers_table, &h_beta_powers_table],
+            &vkey,
+            &wkey,
+        )?;
+        if prepared_vkey.a.len() != n || prepared_vkey.b.len() != n {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(Self {
+            n,
+            g_alpha_powers_table,
+            h_alpha_powers_table,
+            g_beta_powers_table,
+            h_beta_powers_table,
+            vkey,
+            prepared_vkey,
+            wkey,
+        })
+    }
+}
+
+/// Checks the lengths of the tables and keys of a prover SRS for `n` proofs are as `specialize`
+/// creates them
+fn check_prover_srs_len<E: Pairing>(
+    n: usize,
+    g_tables: [&[E::G1Affine]; 2],
+    h_tables: [&[E::G2Affine]; 2],
+    vkey: &VKey<E>,
+    wkey: &WKey<E>,
+) -> Result<(), SerializationError> {
+    if n.checked_mul(2).is_none()
+        || g_tables.iter().any(|t| t.len() != 2 * n)
+        || h_tables.iter().any(|t| t.len() != n)
+        || !vkey.has_correct_len(n)
+        || !wkey.has_correct_len(n)
+    {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(())
+}
+
 impl<E: Pairing> ProverSRS<E> {
     /// Returns true if commitment keys have the exact required length.
     /// It is necessary for the IPP scheme to work that commitment
@@ -205,58 +377,36 @@ impl<E: Pairing> PreparedProverSRS<E> {
 
 impl<E: Pairing> GenericSRS<E> {
     /// specializes returns the prover and verifier SRS for a specific number of
-    /// proofs to aggregate. The number of proofs MUST BE a power of two, it
-    /// panics otherwise. The number of proofs must be inferior to half of the
-    /// size of the generic srs otherwise it panics.
-    pub fn specialize(&self, num_proofs: usize) -> (ProverSRS<E>, VerifierSRS<E>) {
-        assert!(num_proofs.is_power_of_two());
-        let tn = 2 * num_proofs; // size of the CRS we need
-        assert!(self.g_alpha_powers.len() >= tn);
-        assert!(self.h_alpha_powers.len() >= tn);
-        assert!(self.g_beta_powers.len() >= tn);
-        assert!(self.h_beta_powers.len() >= tn);
-        let n = num_proofs;
+    /// proofs to aggregate. The number of proofs need not be a power of two, the SRS
+    /// is specialized for `padded_proof_count(num_proofs)` proofs which must not be more than
+    /// half of the size of the generic srs.
+    pub fn specialize(
+        &self,
+        num_proofs: usize,
+    ) -> Result<(ProverSRS<E>, VerifierSRS<E>), AggregationError> {
+        if num_proofs == 0 {
+            return Err(AggregationError::InvalidSRS(
+                "cannot specialize for 0 proofs".to_string(),
+            ));
+        }
+        let n = padded_proof_count(num_proofs);
+        let tn = 2 * n; // size of the CRS we need
+        if self.g_alpha_powers.len() < tn
+            || self.h_alpha_powers.len() < tn
+            || self.g_beta_powers.len() < tn
+            || self.h_beta_powers.len() < tn
+        {
+            return Err(AggregationError::InsufficientKeyLength(tn));
+        }
         // when doing the KZG opening we need _all_ coefficients from 0
         // to 2n-1 because the polynomial is of degree 2n-1.
-        let g_low = 0;
-        let g_up = tn;
-        let h_low = 0;
-        let h_up = h_low + n;
-        // TODO  precompute window
-        let g_alpha_powers_table = self.g_alpha_powers[g_low..g_up].to_vec();
-        let g_beta_powers_table = self.g_beta_powers[g_low..g_up].to_vec();
-        let h_alpha_powers_table = self.h_alpha_powers[h_low..h_up].to_vec();
-        let h_beta_powers_table = self.h_beta_powers[h_low..h_up].to_vec();
-
-        let v1 = self.h_alpha_powers[h_low..h_up].to_vec();
-        let v2 = self.h_beta_powers[h_low..h_up].to_vec();
-        let vkey = VKey::<E> { a: v1, b: v2 };
-        assert!(vkey.has_correct_len(n));
-        // however, here we only need the "right" shifted bases for the
-        // commitment scheme.
-        let w1 = self.g_alpha_powers[n..g_up].to_vec();
-        let w2 = self.g_beta_powers[n..g_up].to_vec();
-        let wkey = WKey::<E> { a: w1, b: w2 };
-        assert!(wkey.has_correct_len(n));
-        let pk = ProverSRS::<E> {
-            g_alpha_powers_table,
-            g_beta_powers_table,
-            h_alpha_powers_table,
-            h_beta_powers_table,
-            vkey,
-            wkey,
-            n,
-        };
-        let vk = VerifierSRS::<E> {
+        Ok(specialized_srs(
             n,
-            g: self.g_alpha_powers[0],
-            h: self.h_alpha_powers[0],
-            g_alpha: self.g_alpha_powers[1],
-            g_beta: self.g_beta_powers[1],
-            h_alpha: self.h_alpha_powers[1],
-            h_beta: self.h_beta_powers[1],
-        };
-        (pk, vk)
+            self.g_alpha_powers[..tn].to_vec(),
+            self.g_beta_powers[..tn].to_vec(),
+            self.h_alpha_powers[..n].to_vec(),
+            self.h_beta_powers[..n].to_vec(),
+        ))
     }
 
     pub fn write<W: Write>(&self, mut writer: W) -> Result<(), AggregationError> {
@@ -296,6 +446,12 @@ impl<E: Pairing> GenericSRS<E> {
         Ok(())
     }
 
+    /// Reads an SRS written by `write`. The points are compressed and checked so this is slow for
+    /// a large SRS. An SRS written with `Enveloped::to_envelope_bytes` with `Compress::No` is
+    /// read much faster with `Enveloped::from_envelope_bytes`, and even faster with
+    /// `Validate::No` for a trusted file whose integrity the envelope's checksum ensures. To only
+    /// specialize such an SRS, use `GenericSRSView` over the bytes, which can be memory mapped by
+    /// the caller, so the whole SRS is neither decoded nor copied.
     pub fn read<R: Read>(mut reader: R) -> Result<Self, AggregationError> {
         let len = u32::deserialize_uncompressed(&mut reader)
             .map_err(|e| AggregationError::Serialization(e.to_string()))?;
@@ -323,6 +479,150 @@ impl<E: Pairing> GenericSRS<E> {
     }
 }
 
+/// A `GenericSRS` borrowed from its uncompressed serialization, like a memory mapped file written
+/// with `Enveloped::to_envelope_bytes` and `Compress::No`. Nothing is decoded when creating the
+/// view, `specialize` decodes only the points it needs straight from the bytes, so a server can
+/// specialize for a few proofs from an SRS for `MAX_SRS_SIZE` without reading all of it.
+#[derive(Clone, Copy, Debug)]
+pub struct GenericSRSView<'a, E: Pairing> {
+    g_alpha_powers: &'a [u8],
+    h_alpha_powers: &'a [u8],
+    g_beta_powers: &'a [u8],
+    h_beta_powers: &'a [u8],
+    _e: PhantomData<E>,
+}
+
+impl<'a, E: Pairing> GenericSRSView<'a, E> {
+    /// View over the payload of an envelope of an uncompressed `GenericSRS`. The checksum and the
+    /// header are checked but not the points.
+    pub fn from_envelope_bytes(bytes: &'a [u8]) -> Result<Self, EnvelopeError> {
+        let (header, payload) = envelope_payload::<GenericSRS<E>>(bytes)?;
+        if header.compressed {
+            return Err(EnvelopeError::Serialization(
+                "the SRS must be uncompressed to view it".to_string(),
+            ));
+        }
+        Ok(Self::from_uncompressed_bytes(payload)?)
+    }
+
+    /// View over `GenericSRS` serialized with `Compress::No`. Only the lengths are checked.
+    pub fn from_uncompressed_bytes(mut bytes: &'a [u8]) -> Result<Self, SerializationError> {
+        let g1_size = E::G1Affine::zero().uncompressed_size();
+        let g2_size = E::G2Affine::zero().uncompressed_size();
+        let g_alpha_powers = split_points(&mut bytes, g1_size)?;
+        let h_alpha_powers = split_points(&mut bytes, g2_size)?;
+        let g_beta_powers = split_points(&mut bytes, g1_size)?;
+        let h_beta_powers = split_points(&mut bytes, g2_size)?;
+        if !bytes.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(Self {
+            g_alpha_powers,
+            h_alpha_powers,
+            g_beta_powers,
+            h_beta_powers,
+            _e: PhantomData,
+        })
+    }
+
+    /// Same as `GenericSRS::specialize`. The points are decoded with `validate`, which can be
+    /// `Validate::No` when the bytes are trusted like when the envelope's checksum was checked.
+    pub fn specialize(
+        &self,
+        num_proofs: usize,
+        validate: Validate,
+    ) -> Result<(ProverSRS<E>, VerifierSRS<E>), AggregationError> {
+        if num_proofs == 0 {
+            return Err(AggregationError::InvalidSRS(
+                "cannot specialize for 0 proofs".to_string(),
+            ));
+        }
+        let n = padded_proof_count(num_proofs);
+        let tn = 2 * n;
+        let g1_size = E::G1Affine::zero().uncompressed_size();
+        let g2_size = E::G2Affine::zero().uncompressed_size();
+        if self.g_alpha_powers.len() < tn * g1_size
+            || self.g_beta_powers.len() < tn * g1_size
+            || self.h_alpha_powers.len() < tn * g2_size
+            || self.h_beta_powers.len() < tn * g2_size
+        {
+            return Err(AggregationError::InsufficientKeyLength(tn));
+        }
+        Ok(specialized_srs(
+            n,
+            decode_points(self.g_alpha_powers, 0..tn, validate)?,
+            decode_points(self.g_beta_powers, 0..tn, validate)?,
+            decode_points(self.h_alpha_powers, 0..n, validate)?,
+            decode_points(self.h_beta_powers, 0..n, validate)?,
+        ))
+    }
+}
+
+/// Splits the bytes of a vector of uncompressed points of `size` bytes each off `bytes`
+fn split_points<'a>(bytes: &mut &'a [u8], size: usize) -> Result<&'a [u8], SerializationError> {
+    let len = u64::deserialize_uncompressed(&mut *bytes)?;
+    let len = usize::try_from(len)
+        .ok()
+        .and_then(|len| len.checked_mul(size))
+        .filter(|len| *len <= bytes.len())
+        .ok_or(SerializationError::InvalidData)?;
+    let (points, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(points)
+}
+
+/// Decodes the uncompressed points at indices `range`
+fn decode_points<G: AffineRepr>(
+    bytes: &[u8],
+    range: Range<usize>,
+    validate: Validate,
+) -> Result<Vec<G>, SerializationError> {
+    let size = G::zero().uncompressed_size();
+    cfg_chunks!(bytes[range.start * size..range.end * size], size)
+        .map(|b| G::deserialize_with_mode(b, Compress::No, validate))
+        .collect()
+}
+
+/// The prover and verifier SRS for `n` proofs from the first `2n` powers in $G_1$ and the first
+/// `n` powers in $G_2$
+fn specialized_srs<E: Pairing>(
+    n: usize,
+    g_alpha_powers_table: Vec<E::G1Affine>,
+    g_beta_powers_table: Vec<E::G1Affine>,
+    h_alpha_powers_table: Vec<E::G2Affine>,
+    h_beta_powers_table: Vec<E::G2Affine>,
+) -> (ProverSRS<E>, VerifierSRS<E>) {
+    let vk = VerifierSRS::<E> {
+        n,
+        g: g_alpha_powers_table[0],
+        h: h_alpha_powers_table[0],
+        g_alpha: g_alpha_powers_table[1],
+        g_beta: g_beta_powers_table[1],
+        h_alpha: h_alpha_powers_table[1],
+        h_beta: h_beta_powers_table[1],
+    };
+    let vkey = VKey::<E> {
+        a: h_alpha_powers_table.clone(),
+        b: h_beta_powers_table.clone(),
+    };
+    // however, here we only need the "right" shifted bases for the
+    // commitment scheme.
+    let wkey = WKey::<E> {
+        a: g_alpha_powers_table[n..].to_vec(),
+        b: g_beta_powers_table[n..].to_vec(),
+    };
+    let pk = ProverSRS::<E> {
+        g_alpha_powers_table,
+        g_beta_powers_table,
+        h_alpha_powers_table,
+        h_beta_powers_table,
+        vkey,
+        wkey,
+        n,
+    };
+    (pk, vk)
+}
+
 /// Generates a SRS of the given size. It must NOT be used in production, only
 /// in testing, as this is insecure given we know the secret exponent of the SRS.
 pub fn setup_fake_srs<E: Pairing, R: Rng>(rng: &mut R, size: usize) -> GenericSRS<E> {
@@ -0,0 +1,116 @@
+//! Binding the statement of the aggregated proofs to the transcript. The challenges of the
+//! aggregation must depend on everything the verifier checks the proofs against: the verifying
+//! key, the number of proofs through the size of the SRS, and all the public inputs. Otherwise a
+//! prover could pick public inputs after seeing the challenges. The `*_safe` aggregate and verify
+//! functions append these with `append_statement` so callers need not.
+//!
+//! The public inputs are hashed rather than appended one by one as appending the inputs of many
+//! proofs to a transcript is slow. They are hashed in chunks of `INPUTS_PER_CHUNK` vectors, in
+//! parallel with the `parallel` feature, and the hashes of the chunks are hashed again.
+//!
+//! Aggregation modes where the verifier gets each proof's commitment `d` in the clear also append
+//! the commitments with `append_commitments`, and those with a verifying key per proof append the
+//! fingerprint of all the keys from `verifying_keys_fingerprint`.
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::{cfg_chunks, string::ToString, vec::Vec};
+use dock_crypto_utils::transcript::Transcript;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::aggregation::error::AggregationError;
+use crate::fingerprint::{Fingerprint, Hasher};
+use crate::VerifyingKey;
+
+const PUBLIC_INPUTS_LABEL: &[u8] = b"LegoGroth16: aggregated public inputs";
+const PUBLIC_INPUTS_CHUNK_LABEL: &[u8] = b"LegoGroth16: aggregated public inputs chunk";
+const COMMITMENTS_LABEL: &[u8] = b"LegoGroth16: aggregated commitments";
+const VERIFYING_KEYS_LABEL: &[u8] = b"LegoGroth16: aggregated verifying keys";
+/// Number of public input vectors hashed together by `hash_public_inputs`
+pub const INPUTS_PER_CHUNK: usize = 256;
+
+/// Appends the fingerprint of the verifying key, the size of the SRS, i.e. the padded number of
+/// proofs, and the hash of `public_inputs` to the transcript. The prover and the verifier must
+/// call this with the same arguments before aggregating and verifying.
+pub fn append_statement<F: PrimeField, I: AsRef<[F]> + Sync, T: Transcript>(
+    transcript: &mut T,
+    vk_fingerprint: &Fingerprint,
+    srs_size: usize,
+    public_inputs: &[I],
+) {
+    vk_fingerprint.append_to_transcript(transcript);
+    transcript.append(b"srs-size", &(srs_size as u64));
+    transcript.append(b"public-inputs-hash", &hash_public_inputs(public_inputs));
+}
+
+/// Hash of all the public input vectors. The number of vectors and the length of each vector are
+/// hashed as well so moving an input from one vector to another changes the hash.
+pub fn hash_public_inputs<F: PrimeField, I: AsRef<[F]> + Sync>(public_inputs: &[I]) -> [u8; 32] {
+    let chunk_hashes = cfg_chunks!(public_inputs, INPUTS_PER_CHUNK)
+        .map(|chunk| {
+            let mut hasher = Hasher::new(PUBLIC_INPUTS_CHUNK_LABEL);
+            for inputs in chunk {
+                hasher.absorb(&inputs.as_ref());
+            }
+            hasher.finish().0
+        })
+        .collect::<Vec<_>>();
+    let mut hasher = Hasher::new(PUBLIC_INPUTS_LABEL);
+    hasher.absorb(&(public_inputs.len() as u64));
+    for h in chunk_hashes {
+        hasher.update(&h);
+    }
+    hasher.finish().0
+}
+
+/// Appends the hash of the commitments `d` of all the proofs to the transcript. The prover must
+/// call this before the challenge `r` is derived and the verifier the same.
+pub fn append_commitments<G: CanonicalSerialize, T: Transcript>(transcript: &mut T, d: &[G]) {
+    let mut hasher = Hasher::new(COMMITMENTS_LABEL);
+    hasher.absorb(&d);
+    transcript.append(b"commitments-hash", &hasher.finish().0);
+}
+
+/// Fingerprint of the verifying keys of all the proofs, in order, to be given to
+/// `append_statement` when the proofs have different keys. Each key is fingerprinted once for all
+/// the references to it, the rest is hashing 32 bytes per proof.
+pub fn verifying_keys_fingerprint<'a, E: Pairing>(
+    vks: impl ExactSizeIterator<Item = &'a VerifyingKey<E>>,
+) -> Fingerprint {
+    let mut hasher = Hasher::new(VERIFYING_KEYS_LABEL);
+    hasher.absorb(&(vks.len() as u64));
+    let mut distinct: Vec<(&VerifyingKey<E>, Fingerprint)> = Vec::new();
+    for vk in vks {
+        let fingerprint = match distinct.iter().find(|(d, _)| core::ptr::eq(*d, vk)) {
+            Some((_, f)) => *f,
+            None => {
+                let f = vk.fingerprint();
+                distinct.push((vk, f));
+                f
+            }
+        };
+        hasher.update(fingerprint.as_bytes());
+    }
+    hasher.finish()
+}
+
+/// Checks that there are public inputs for each of the `num_proofs` proofs and appends the
+/// statement to the transcript with `append_statement`
+pub(crate) fn bind_statement<F: PrimeField, I: AsRef<[F]> + Sync, T: Transcript>(
+    transcript: &mut T,
+    vk_fingerprint: &Fingerprint,
+    srs_size: usize,
+    num_proofs: usize,
+    public_inputs: &[I],
+) -> Result<(), AggregationError> {
+    if public_inputs.len() != num_proofs {
+        return Err(AggregationError::InvalidProof(
+            "need the public inputs of each proof".to_string(),
+        ));
+    }
+    append_statement(transcript, vk_fingerprint, srs_size, public_inputs);
+    Ok(())
+}
@@ -1,18 +1,34 @@
+use crate::aggregation::error::AggregationError;
+use crate::aggregation::legogroth16::AggregateLegoProofWithLink;
 use crate::aggregation::srs::PreparedProverSRS;
-use crate::aggregation::{groth16, legogroth16, srs};
-use crate::{create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof};
-use ark_bls12_381::{Bls12_381, Fr};
-use ark_ff::{Field, One};
+use crate::aggregation::transparent::TransparentSRS;
+use crate::aggregation::utils::padded_proof_count;
+use crate::aggregation::{groth16, heterogeneous, legogroth16, ptau, srs, statement, torus};
+use crate::envelope::{EnvelopeError, Enveloped};
+use crate::fingerprint::new_merlin_transcript_with_fingerprint;
+use crate::progress::{Event, Phase};
+use crate::{
+    create_random_proof, create_random_proof_incl_cp_links, generate_random_parameters,
+    generate_random_parameters_incl_cp_links, prepare_verifying_key, verify_link_proof,
+    verify_proof, LinkPublicGenerators, PreparedVerifyingKey, ProofWithLink,
+};
+use ark_bls12_381::{Bls12_381, Fr, G1Projective, G2Projective};
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_ff::{Field, One, PrimeField, Zero};
 use ark_relations::lc;
 use ark_relations::r1cs::{
     ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable,
 };
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
 use ark_snark::SNARK;
 use ark_std::rand::prelude::StdRng;
 use ark_std::rand::SeedableRng;
 use ark_std::UniformRand;
 use dock_crypto_utils::transcript::{new_merlin_transcript, Transcript};
+use std::cell::RefCell;
 use std::marker::PhantomData;
+use std::ops::ControlFlow;
 use std::time::Instant;
 
 pub struct Benchmark<F: Field> {
@@ -124,7 +140,7 @@ fn legogroth16_aggregation() {
     // prepare the SRS needed for snarkpack - specialize after to the right
     // number of proofs
     let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, nproofs);
-    let (prover_srs, ver_srs) = srs.specialize(nproofs);
+    let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
     let prepared_srs = PreparedProverSRS::from(prover_srs.clone());
 
     // create all the proofs
@@ -175,7 +191,70 @@ fn legogroth16_aggregation() {
     );
     assert_eq!(aggregate_proof, aggregate_proof_);
 
+    // Observing does not change the aggregate proof and the observer can cancel the aggregation
+    let events = RefCell::new(vec![]);
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    prover_transcript.append(b"public-inputs", &all_inputs);
+    let observed_aggregate_proof = legogroth16::aggregate_proofs_with_observer(
+        prepared_srs.clone(),
+        &mut prover_transcript,
+        &proofs,
+        &|event: Event| {
+            events.borrow_mut().push(event);
+            ControlFlow::Continue(())
+        },
+    )
+    .expect("error in aggregation");
+    assert_eq!(aggregate_proof, observed_aggregate_proof);
+    let events = events.take();
+    assert_eq!(events.len(), 4);
+    assert_eq!(
+        events[0],
+        Event::Started {
+            phase: Phase::AggregationCommitments,
+            size: nproofs
+        }
+    );
+    assert_eq!(
+        events[2],
+        Event::Started {
+            phase: Phase::AggregationInnerProducts,
+            size: nproofs
+        }
+    );
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    assert_eq!(
+        legogroth16::aggregate_proofs_with_observer(
+            prepared_srs.clone(),
+            &mut prover_transcript,
+            &proofs,
+            &|event: Event| match event {
+                Event::Started {
+                    phase: Phase::AggregationInnerProducts,
+                    ..
+                } => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            },
+        )
+        .unwrap_err(),
+        AggregationError::Cancelled
+    );
+
     let start = Instant::now();
+    // Stored in an envelope
+    let bytes = aggregate_proof.to_envelope_bytes(Compress::Yes).unwrap();
+    assert_eq!(
+        legogroth16::AggregateLegoProof::<Bls12_381>::from_envelope_bytes(&bytes, Validate::Yes)
+            .unwrap(),
+        aggregate_proof
+    );
+    println!(
+        "Size of aggregate proof of {} LegoGroth16 proofs: {} bytes compressed, {} bytes uncompressed",
+        nproofs,
+        aggregate_proof.compressed_size(),
+        aggregate_proof.uncompressed_size()
+    );
+
     let mut ver_transcript = new_merlin_transcript(b"test aggregation");
     ver_transcript.append(b"public-inputs", &all_inputs);
     legogroth16::verify_aggregate_proof(
@@ -245,6 +324,46 @@ fn legogroth16_aggregation() {
         nproofs,
         start.elapsed()
     );
+
+    // Transcripts bound to the verifying key's fingerprint only verify with the same key
+    let fingerprint = params.vk.fingerprint();
+    let mut prover_transcript =
+        new_merlin_transcript_with_fingerprint(b"test aggregation", &fingerprint);
+    prover_transcript.append(b"public-inputs", &all_inputs);
+    let aggregate_proof =
+        legogroth16::aggregate_proofs(prepared_srs, &mut prover_transcript, &proofs)
+            .expect("error in aggregation");
+    let mut ver_transcript =
+        new_merlin_transcript_with_fingerprint(b"test aggregation", &fingerprint);
+    ver_transcript.append(b"public-inputs", &all_inputs);
+    legogroth16::verify_aggregate_proof(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut ver_transcript,
+        None,
+    )
+    .expect("error in verification");
+
+    let other_params = {
+        let c = Benchmark::<Fr>::new(num_constraints);
+        generate_random_parameters::<Bls12_381, _, _>(c, 10, &mut rng).unwrap()
+    };
+    let mut ver_transcript =
+        new_merlin_transcript_with_fingerprint(b"test aggregation", &other_params.vk.fingerprint());
+    ver_transcript.append(b"public-inputs", &all_inputs);
+    assert!(legogroth16::verify_aggregate_proof(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut ver_transcript,
+        None,
+    )
+    .is_err());
 }
 
 #[test]
@@ -265,7 +384,7 @@ fn legogroth16_aggregation_multiply() {
     // prepare the SRS needed for snarkpack - specialize after to the right
     // number of proofs
     let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, nproofs);
-    let (prover_srs, ver_srs) = srs.specialize(nproofs);
+    let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
     let prepared_srs = PreparedProverSRS::from(prover_srs.clone());
 
     // create all the proofs
@@ -412,7 +531,7 @@ fn groth16_aggregation() {
     // prepare the SRS needed for snarkpack - specialize after to the right
     // number of proofs
     let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, nproofs);
-    let (prover_srs, ver_srs) = srs.specialize(nproofs);
+    let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
     let prepared_srs = PreparedProverSRS::from(prover_srs.clone());
 
     // create all the proofs
@@ -449,6 +568,12 @@ fn groth16_aggregation() {
         nproofs,
         start.elapsed()
     );
+    println!(
+        "Size of aggregate proof of {} Groth16 proofs: {} bytes compressed, {} bytes uncompressed",
+        nproofs,
+        aggregate_proof.compressed_size(),
+        aggregate_proof.uncompressed_size()
+    );
 
     let start = Instant::now();
     let mut ver_transcript = new_merlin_transcript(b"test aggregation");
@@ -488,7 +613,7 @@ fn groth16_aggregation_multiply() {
     // prepare the SRS needed for snarkpack - specialize after to the right
     // number of proofs
     let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, nproofs);
-    let (prover_srs, ver_srs) = srs.specialize(nproofs);
+    let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
     let prepared_srs = PreparedProverSRS::from(prover_srs.clone());
 
     // create all the proofs
@@ -553,3 +678,1852 @@ fn groth16_aggregation_multiply() {
         start.elapsed()
     );
 }
+
+#[test]
+fn aggregation_non_power_of_two() {
+    let num_constraints = 10;
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let params = {
+        let c = Multiply {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        generate_random_parameters::<Bls12_381, _, _>(c, 2, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+    let (g16_pk, g16_vk) = {
+        let c = Multiply {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        ark_groth16::Groth16::<Bls12_381>::circuit_specific_setup(c, &mut rng).unwrap()
+    };
+    let g16_pvk = ark_groth16::prepare_verifying_key(&g16_vk);
+
+    let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, 8);
+    assert!(srs.specialize(0).is_err());
+    assert_eq!(
+        srs.specialize(9).unwrap_err(),
+        AggregationError::InsufficientKeyLength(32)
+    );
+
+    for nproofs in [1, 3, 5, 7] {
+        let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
+        assert_eq!(prover_srs.n, padded_proof_count(nproofs));
+        assert_eq!(ver_srs.n, padded_proof_count(nproofs));
+
+        let mut all_inputs = vec![];
+        let mut proofs = vec![];
+        let mut g16_proofs = vec![];
+        for i in 1..=nproofs {
+            let a = Fr::from(10 * i as u64);
+            let b = Fr::from(20 * i as u64);
+            all_inputs.push(vec![a * b]);
+            let c = Multiply {
+                num_constraints,
+                a: Some(a),
+                b: Some(b),
+            };
+            proofs.push(create_random_proof(c, Fr::rand(&mut rng), &params, &mut rng).unwrap());
+            let c = Multiply {
+                num_constraints,
+                a: Some(a),
+                b: Some(b),
+            };
+            g16_proofs
+                .push(ark_groth16::Groth16::<Bls12_381>::prove(&g16_pk, c, &mut rng).unwrap());
+        }
+
+        let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+        prover_transcript.append(b"public-inputs", &all_inputs);
+        let aggregate_proof =
+            legogroth16::aggregate_proofs(prover_srs.clone(), &mut prover_transcript, &proofs)
+                .unwrap();
+        assert_eq!(
+            aggregate_proof.tmipp.gipa.nproofs as usize,
+            padded_proof_count(nproofs)
+        );
+        let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+        ver_transcript.append(b"public-inputs", &all_inputs);
+        legogroth16::verify_aggregate_proof(
+            &ver_srs,
+            &pvk,
+            &all_inputs,
+            &aggregate_proof,
+            &mut rng,
+            &mut ver_transcript,
+            None,
+        )
+        .unwrap();
+
+        // Public inputs for a different number of proofs are rejected, as is changing the
+        // input that the padding repeats
+        let mut more_inputs = all_inputs.clone();
+        more_inputs.resize(padded_proof_count(nproofs) + 1, all_inputs[0].clone());
+        let mut wrong_last = all_inputs.clone();
+        wrong_last.last_mut().unwrap()[0] += Fr::one();
+        for inputs in [vec![], more_inputs, wrong_last] {
+            let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+            ver_transcript.append(b"public-inputs", &all_inputs);
+            assert!(legogroth16::verify_aggregate_proof(
+                &ver_srs,
+                &pvk,
+                &inputs,
+                &aggregate_proof,
+                &mut rng,
+                &mut ver_transcript,
+                None,
+            )
+            .is_err());
+        }
+
+        let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+        prover_transcript.append(b"public-inputs", &all_inputs);
+        let (aggregate_proof, d) = legogroth16::using_groth16::aggregate_proofs(
+            prover_srs.clone(),
+            &mut prover_transcript,
+            &proofs,
+        )
+        .unwrap();
+        assert_eq!(d.len(), nproofs);
+        let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+        ver_transcript.append(b"public-inputs", &all_inputs);
+        legogroth16::using_groth16::verify_aggregate_proof(
+            &ver_srs,
+            &pvk,
+            &all_inputs,
+            &aggregate_proof,
+            &d,
+            &mut rng,
+            &mut ver_transcript,
+            None,
+        )
+        .unwrap();
+
+        let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+        prover_transcript.append(b"public-inputs", &all_inputs);
+        let aggregate_proof =
+            groth16::aggregate_proofs(prover_srs, &mut prover_transcript, &g16_proofs).unwrap();
+        let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+        ver_transcript.append(b"public-inputs", &all_inputs);
+        groth16::verify_aggregate_proof(
+            &ver_srs,
+            &g16_pvk,
+            &all_inputs,
+            &aggregate_proof,
+            &mut rng,
+            &mut ver_transcript,
+            None,
+        )
+        .unwrap();
+    }
+
+    let (prover_srs, _) = srs.specialize(4).unwrap();
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    assert!(
+        legogroth16::aggregate_proofs::<Bls12_381, _>(prover_srs, &mut prover_transcript, &[])
+            .is_err()
+    );
+}
+
+#[test]
+fn legogroth16_aggregation_incl_cp_links() {
+    let num_constraints = 10;
+    let nproofs = 5;
+    let mut rng = StdRng::seed_from_u64(0u64);
+    // All link commitments share the generators of the subspace SNARK
+    let g1 = G1Projective::rand(&mut rng).into_affine();
+    let g2 = G2Projective::rand(&mut rng).into_affine();
+    let mut link_gens = |count: usize| LinkPublicGenerators::<Bls12_381> {
+        pedersen_gens: (0..count)
+            .map(|_| G1Projective::rand(&mut rng).into_affine())
+            .collect(),
+        g1,
+        g2,
+    };
+    // 2 link commitments, to `a` and to `a, b`
+    let gens = vec![(link_gens(2), 0..1), (link_gens(3), 0..2)];
+    let params = {
+        let c = Multiply {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        generate_random_parameters_incl_cp_links::<Bls12_381, _, _>(c, gens, 2, &mut rng).unwrap()
+    };
+    let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, padded_proof_count(nproofs));
+    let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
+
+    let mut all_inputs = vec![];
+    let mut proofs = vec![];
+    for i in 1..=nproofs {
+        let a = Fr::from(10 * i as u64);
+        let b = Fr::from(20 * i as u64);
+        all_inputs.push(vec![a * b]);
+        let c = Multiply {
+            num_constraints,
+            a: Some(a),
+            b: Some(b),
+        };
+        let link_v = [Fr::rand(&mut rng), Fr::rand(&mut rng)];
+        let proof =
+            create_random_proof_incl_cp_links(c, Fr::rand(&mut rng), &link_v, &params, &mut rng)
+                .unwrap();
+        verify_link_proof(&params.vk, &proof).unwrap();
+        proofs.push(proof);
+    }
+
+    let aggregate = |proofs: &[ProofWithLink<Bls12_381>]| {
+        let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+        prover_transcript.append(b"public-inputs", &all_inputs);
+        legogroth16::aggregate_proofs_incl_cp_links(
+            prover_srs.clone(),
+            &mut prover_transcript,
+            proofs,
+        )
+        .unwrap()
+    };
+    let verify = |proof: &AggregateLegoProofWithLink<Bls12_381>| {
+        let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+        ver_transcript.append(b"public-inputs", &all_inputs);
+        legogroth16::verify_aggregate_proof_incl_cp_links(
+            &ver_srs,
+            &params.vk,
+            &all_inputs,
+            proof,
+            StdRng::seed_from_u64(1u64),
+            &mut ver_transcript,
+            None,
+        )
+    };
+
+    let aggregate_proof = aggregate(&proofs);
+    verify(&aggregate_proof).unwrap();
+
+    // Check serialization
+    let mut bytes = vec![];
+    aggregate_proof.serialize_compressed(&mut bytes).unwrap();
+    let deserialized =
+        AggregateLegoProofWithLink::<Bls12_381>::deserialize_compressed(&bytes[..]).unwrap();
+    assert_eq!(deserialized, aggregate_proof);
+
+    // The transcript covers the link elements so the LegoGroth16 part does not verify by itself
+    let pvk = prepare_verifying_key(&params.vk.groth16_vk);
+    let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+    ver_transcript.append(b"public-inputs", &all_inputs);
+    legogroth16::verify_aggregate_proof(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof.proof,
+        &mut rng,
+        &mut ver_transcript,
+        None,
+    )
+    .unwrap_err();
+
+    // An invalid link commitment or link proof in any proof fails the aggregate
+    let mut bad_proofs = proofs.clone();
+    bad_proofs[1].link_d[0] = G1Projective::rand(&mut rng).into_affine();
+    assert!(verify(&aggregate(&bad_proofs)).is_err());
+    let mut bad_proofs = proofs.clone();
+    bad_proofs[0].link_pi = proofs[2].link_pi;
+    assert!(verify(&aggregate(&bad_proofs)).is_err());
+
+    // Tampering with the link elements of the aggregate fails too
+    let mut bad = aggregate_proof.clone();
+    bad.link.z.swap(0, 1);
+    assert!(verify(&bad).is_err());
+    let mut bad = aggregate_proof.clone();
+    bad.link.finals.pop();
+    assert!(verify(&bad).is_err());
+
+    // A key without any link commitment is rejected before the link proof is looked at
+    for l in [0, 1] {
+        let mut bad_vk = params.vk.clone();
+        bad_vk.link_pp.l = l;
+        let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+        assert_eq!(
+            legogroth16::verify_aggregate_proof_incl_cp_links(
+                &ver_srs,
+                &bad_vk,
+                &all_inputs,
+                &aggregate_proof,
+                &mut rng,
+                &mut ver_transcript,
+                None,
+            )
+            .unwrap_err(),
+            AggregationError::MalformedVerifyingKey
+        );
+    }
+
+    // All proofs must have the same number of link commitments
+    let mut bad_proofs = proofs.clone();
+    bad_proofs[3].link_d.pop();
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    assert!(legogroth16::aggregate_proofs_incl_cp_links(
+        prover_srs.clone(),
+        &mut prover_transcript,
+        &bad_proofs,
+    )
+    .is_err());
+}
+
+#[test]
+fn heterogeneous_aggregation() {
+    let mut rng = StdRng::seed_from_u64(0u64);
+    // 3 circuits, of different sizes and committing to different number of witnesses
+    let params = [(10, 2), (20, 0), (10, 1)]
+        .into_iter()
+        .map(|(num_constraints, commit_witness_count)| {
+            let c = Multiply {
+                num_constraints,
+                a: None,
+                b: None,
+            };
+            let params =
+                generate_random_parameters::<Bls12_381, _, _>(c, commit_witness_count, &mut rng)
+                    .unwrap();
+            (num_constraints, params)
+        })
+        .collect::<Vec<_>>();
+    let pvks = params
+        .iter()
+        .map(|(_, p)| prepare_verifying_key(&p.vk))
+        .collect::<Vec<_>>();
+
+    // Interleaved proofs of the circuits
+    let nproofs = 7;
+    let circuit_of = [0, 1, 2, 0, 0, 2, 1];
+    let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, padded_proof_count(nproofs));
+    let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
+
+    let mut all_inputs = vec![];
+    let mut proofs = vec![];
+    for (i, k) in circuit_of.iter().enumerate() {
+        let a = Fr::from(10 * i as u64 + 1);
+        let b = Fr::from(20 * i as u64 + 1);
+        all_inputs.push(vec![a * b]);
+        let (num_constraints, params) = &params[*k];
+        let c = Multiply {
+            num_constraints: *num_constraints,
+            a: Some(a),
+            b: Some(b),
+        };
+        proofs.push(create_random_proof(c, Fr::rand(&mut rng), params, &mut rng).unwrap());
+    }
+    let vks = circuit_of
+        .iter()
+        .map(|k| &params[*k].1.vk)
+        .collect::<Vec<_>>();
+    let vks_com = heterogeneous::VerifyingKeysCommitment::new(&prover_srs.wkey, &vks).unwrap();
+
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    prover_transcript.append(b"public-inputs", &all_inputs);
+    let aggregate_proof =
+        heterogeneous::aggregate_proofs(prover_srs.clone(), &mut prover_transcript, &proofs, &vks)
+            .unwrap();
+    assert_eq!(aggregate_proof.tipp.nproofs, 8);
+
+    let verify = |statements: &[(&PreparedVerifyingKey<Bls12_381>, &[Fr])],
+                  vks_com: &heterogeneous::VerifyingKeysCommitment<Bls12_381>| {
+        let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+        ver_transcript.append(b"public-inputs", &all_inputs);
+        heterogeneous::verify_aggregate_proof(
+            &ver_srs,
+            vks_com,
+            statements,
+            &aggregate_proof,
+            StdRng::seed_from_u64(1u64),
+            &mut ver_transcript,
+            None,
+        )
+    };
+    let statements = circuit_of
+        .iter()
+        .zip(all_inputs.iter())
+        .map(|(k, inputs)| (&pvks[*k], inputs.as_slice()))
+        .collect::<Vec<_>>();
+    verify(&statements, &vks_com).unwrap();
+
+    // Wrong verifying key for a proof
+    let mut bad_statements = statements.clone();
+    bad_statements[1].0 = &pvks[0];
+    assert!(matches!(
+        verify(&bad_statements, &vks_com),
+        Err(AggregationError::InvalidProof(e)) if e.contains("verifying keys commitment")
+    ));
+    let mut bad_vks = vks.clone();
+    bad_vks[1] = &params[0].1.vk;
+    let bad_vks_com =
+        heterogeneous::VerifyingKeysCommitment::new(&prover_srs.wkey, &bad_vks).unwrap();
+    assert!(verify(&bad_statements, &bad_vks_com).is_err());
+
+    // Wrong public input
+    let wrong_input = [Fr::one()];
+    let mut bad_statements = statements.clone();
+    bad_statements[4].1 = &wrong_input;
+    assert!(verify(&bad_statements, &vks_com).is_err());
+
+    // Wrong number of statements
+    assert!(verify(&statements[..3], &vks_com).is_err());
+
+    // Mismatched proofs and keys
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    assert!(heterogeneous::aggregate_proofs(
+        prover_srs,
+        &mut prover_transcript,
+        &proofs,
+        &vks[..nproofs - 1]
+    )
+    .is_err());
+}
+
+#[test]
+fn legogroth16_incremental_aggregation() {
+    let num_constraints = 10;
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let params = {
+        let c = Multiply {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        generate_random_parameters::<Bls12_381, _, _>(c, 2, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+    let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, 8);
+
+    let mut aggregator = legogroth16::IncrementalAggregator::new(&srs);
+    assert!(aggregator.is_empty());
+    assert!(aggregator
+        .aggregate(&mut new_merlin_transcript(b"test aggregation"))
+        .is_err());
+
+    let mut all_inputs = vec![];
+    let mut proofs = vec![];
+    for i in 1..=8 {
+        let a = Fr::from(10 * i as u64);
+        let b = Fr::from(20 * i as u64);
+        all_inputs.push(vec![a * b]);
+        let c = Multiply {
+            num_constraints,
+            a: Some(a),
+            b: Some(b),
+        };
+        let proof = create_random_proof(c, Fr::rand(&mut rng), &params, &mut rng).unwrap();
+        proofs.push(proof.clone());
+        aggregator.append(proof).unwrap();
+        assert_eq!(aggregator.len(), i);
+
+        if ![1, 2, 3, 5, 8].contains(&i) {
+            continue;
+        }
+        let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+        prover_transcript.append(b"public-inputs", &all_inputs);
+        let aggregate_proof = aggregator.aggregate(&mut prover_transcript).unwrap();
+
+        // Same as aggregating all proofs at once
+        let (prover_srs, ver_srs) = srs.specialize(i).unwrap();
+        let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+        prover_transcript.append(b"public-inputs", &all_inputs);
+        let expected =
+            legogroth16::aggregate_proofs(prover_srs, &mut prover_transcript, aggregator.proofs())
+                .unwrap();
+        let mut bytes = vec![];
+        aggregate_proof.serialize_compressed(&mut bytes).unwrap();
+        let mut expected_bytes = vec![];
+        expected.serialize_compressed(&mut expected_bytes).unwrap();
+        assert_eq!(bytes, expected_bytes);
+
+        let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+        ver_transcript.append(b"public-inputs", &all_inputs);
+        legogroth16::verify_aggregate_proof(
+            &ver_srs,
+            &pvk,
+            &all_inputs,
+            &aggregate_proof,
+            &mut rng,
+            &mut ver_transcript,
+            None,
+        )
+        .unwrap();
+    }
+
+    // The SRS supports at most 8 proofs
+    assert_eq!(
+        aggregator.append(proofs[0].clone()).unwrap_err(),
+        AggregationError::InsufficientKeyLength(32)
+    );
+    assert_eq!(aggregator.len(), 8);
+
+    // Appending an invalid proof makes the aggregate proof invalid
+    let mut aggregator = legogroth16::IncrementalAggregator::new(&srs);
+    for p in &proofs[..3] {
+        aggregator.append(p.clone()).unwrap();
+    }
+    let mut bad_proof = proofs[3].clone();
+    bad_proof.c = (bad_proof.c + bad_proof.c).into_affine();
+    aggregator.append(bad_proof).unwrap();
+    let inputs = all_inputs[..4].to_vec();
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    prover_transcript.append(b"public-inputs", &inputs);
+    let aggregate_proof = aggregator.aggregate(&mut prover_transcript).unwrap();
+    let (_, ver_srs) = srs.specialize(4).unwrap();
+    let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+    ver_transcript.append(b"public-inputs", &inputs);
+    assert!(legogroth16::verify_aggregate_proof(
+        &ver_srs,
+        &pvk,
+        &inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut ver_transcript,
+        None,
+    )
+    .is_err());
+}
+
+/// The powers of `tau` as a snarkjs ptau file of a ceremony with `2^power` powers
+fn write_ptau(tau: ark_bn254::Fr, power: u32) -> Vec<u8> {
+    use ark_ff::BigInteger;
+    let n8 = 32u32;
+    let tau_g1 = srs::structured_generators_scalar_power(
+        (1 << (power + 1)) - 1,
+        &ark_bn254::G1Projective::generator(),
+        &tau,
+    );
+    let tau_g2 = srs::structured_generators_scalar_power(
+        1 << power,
+        &ark_bn254::G2Projective::generator(),
+        &tau,
+    );
+    // Coordinates are in Montgomery form which is how arkworks represents them
+    let mut header = n8.to_le_bytes().to_vec();
+    header.extend(<ark_bn254::Fq as PrimeField>::MODULUS.to_bytes_le());
+    header.extend(power.to_le_bytes());
+    header.extend(power.to_le_bytes());
+    let mut g1 = vec![];
+    for p in &tau_g1 {
+        g1.extend(p.x.0.to_bytes_le());
+        g1.extend(p.y.0.to_bytes_le());
+    }
+    let mut g2 = vec![];
+    for p in &tau_g2 {
+        for c in [p.x.c0, p.x.c1, p.y.c0, p.y.c1] {
+            g2.extend(c.0.to_bytes_le());
+        }
+    }
+    let mut bytes = b"ptau".to_vec();
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(3u32.to_le_bytes());
+    // The sections need not be in order
+    for (sec_type, section) in [(3u32, g2), (1, header), (2, g1)] {
+        bytes.extend(sec_type.to_le_bytes());
+        bytes.extend((section.len() as u64).to_le_bytes());
+        bytes.extend(section);
+    }
+    bytes
+}
+
+/// The powers of `tau` as a challenge or response file of a Zcash like ceremony with `2^power`
+/// powers. Only the hash and the powers of tau are written.
+fn write_powersoftau(tau: Fr, power: u32, compress: Compress) -> Vec<u8> {
+    let tau_g1 = srs::structured_generators_scalar_power(
+        (1 << (power + 1)) - 1,
+        &G1Projective::generator(),
+        &tau,
+    );
+    let tau_g2 =
+        srs::structured_generators_scalar_power(1 << power, &G2Projective::generator(), &tau);
+    let mut bytes = vec![7u8; 64];
+    for p in &tau_g1 {
+        p.serialize_with_mode(&mut bytes, compress).unwrap();
+    }
+    for p in &tau_g2 {
+        p.serialize_with_mode(&mut bytes, compress).unwrap();
+    }
+    bytes
+}
+
+#[test]
+fn srs_from_powers_of_tau() {
+    use ark_bn254::Bn254;
+    use std::io::Cursor;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let power = 4;
+
+    // snarkjs ptau
+    let tau_1 = ark_bn254::Fr::rand(&mut rng);
+    let tau_2 = ark_bn254::Fr::rand(&mut rng);
+    let ptau_1 = write_ptau(tau_1, power);
+    let ptau_2 = write_ptau(tau_2, power);
+    let powers_1 = ptau::PowersOfTau::<Bn254>::read_ptau(Cursor::new(&ptau_1), 1000).unwrap();
+    assert_eq!(powers_1.tau_g1.len(), 1 << power);
+    assert_eq!(powers_1.tau_g2.len(), 1 << power);
+    assert_eq!(
+        powers_1.tau_g1[1],
+        (ark_bn254::G1Affine::generator() * tau_1).into_affine()
+    );
+    assert_eq!(
+        powers_1.tau_g2[3],
+        (ark_bn254::G2Affine::generator() * tau_1.pow([3])).into_affine()
+    );
+    powers_1.check(&mut rng).unwrap();
+    let fewer = ptau::PowersOfTau::<Bn254>::read_ptau(Cursor::new(&ptau_1), 5).unwrap();
+    assert_eq!(fewer.tau_g1, powers_1.tau_g1[..5]);
+    assert_eq!(fewer.tau_g2, powers_1.tau_g2[..5]);
+    let powers_2 = ptau::PowersOfTau::<Bn254>::read_ptau(Cursor::new(&ptau_2), 1000).unwrap();
+
+    // The same ceremony can't give both a and b
+    assert!(
+        srs::GenericSRS::from_powers_of_tau(powers_1.clone(), powers_1.clone(), &mut rng).is_err()
+    );
+
+    // Inconsistent powers
+    let mut bad = powers_1.clone();
+    bad.tau_g1[2] = (bad.tau_g1[2] + bad.tau_g1[1]).into_affine();
+    assert!(bad.check(&mut rng).is_err());
+    assert!(srs::GenericSRS::from_powers_of_tau(bad, powers_2.clone(), &mut rng).is_err());
+    let mut bad = powers_1.clone();
+    bad.tau_g2.swap(4, 5);
+    assert!(bad.check(&mut rng).is_err());
+    let mut bad = powers_1.clone();
+    bad.tau_g1[0] = bad.tau_g1[1];
+    assert!(bad.check(&mut rng).is_err());
+
+    // Malformed files
+    let mut bad_ptau = ptau_1.clone();
+    bad_ptau[0] = b'x';
+    assert!(ptau::PowersOfTau::<Bn254>::read_ptau(Cursor::new(&bad_ptau), 1000).is_err());
+    assert!(ptau::PowersOfTau::<Bn254>::read_ptau(Cursor::new(&ptau_1[..200]), 1000).is_err());
+    // A section size that does not fit in a seek offset
+    let mut bad_ptau = ptau_1.clone();
+    bad_ptau[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(matches!(
+        ptau::PowersOfTau::<Bn254>::read_ptau(Cursor::new(&bad_ptau), 1000),
+        Err(AggregationError::InvalidSRS(_))
+    ));
+    // A coordinate of the last read point in G2 is changed so it's no longer on the curve
+    let g2_start = 4 + 4 + 4 + 4 + 8;
+    let mut bad_ptau = ptau_1.clone();
+    bad_ptau[g2_start + 10] ^= 1;
+    assert!(ptau::PowersOfTau::<Bn254>::read_ptau(Cursor::new(&bad_ptau), 1000).is_err());
+    // Ptau for BN254 can't be read for BLS12-381
+    assert!(ptau::PowersOfTau::<Bls12_381>::read_ptau(Cursor::new(&ptau_1), 1000).is_err());
+
+    // The SRS works for aggregation
+    let generic_srs = srs::GenericSRS::from_powers_of_tau(powers_1, powers_2, &mut rng).unwrap();
+    assert_eq!(generic_srs.g_alpha_powers.len(), 1 << power);
+    let params = {
+        let c = Multiply::<ark_bn254::Fr> {
+            num_constraints: 10,
+            a: None,
+            b: None,
+        };
+        generate_random_parameters::<Bn254, _, _>(c, 2, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+    let nproofs = 8;
+    let (prover_srs, ver_srs) = generic_srs.specialize(nproofs).unwrap();
+    assert!(generic_srs.specialize(nproofs + 1).is_err());
+    let mut all_inputs = vec![];
+    let mut proofs = vec![];
+    for i in 1..=nproofs {
+        let a = ark_bn254::Fr::from(i as u64);
+        let b = ark_bn254::Fr::from(2 * i as u64);
+        all_inputs.push(vec![a * b]);
+        let c = Multiply {
+            num_constraints: 10,
+            a: Some(a),
+            b: Some(b),
+        };
+        proofs.push(
+            create_random_proof(c, ark_bn254::Fr::rand(&mut rng), &params, &mut rng).unwrap(),
+        );
+    }
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    let aggregate_proof =
+        legogroth16::aggregate_proofs(prover_srs, &mut prover_transcript, &proofs).unwrap();
+    let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+    legogroth16::verify_aggregate_proof(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut ver_transcript,
+        None,
+    )
+    .unwrap();
+
+    // Zcash and Filecoin format
+    let tau_1 = Fr::rand(&mut rng);
+    let tau_2 = Fr::rand(&mut rng);
+    for compress in [Compress::Yes, Compress::No] {
+        let file_1 = write_powersoftau(tau_1, power, compress);
+        let file_2 = write_powersoftau(tau_2, power, compress);
+        let powers_1 = ptau::PowersOfTau::<Bls12_381>::read_powersoftau(
+            Cursor::new(&file_1),
+            power,
+            compress,
+            10,
+        )
+        .unwrap();
+        assert_eq!(powers_1.tau_g1.len(), 10);
+        assert_eq!(
+            powers_1.tau_g2[9],
+            (ark_bls12_381::G2Affine::generator() * tau_1.pow([9])).into_affine()
+        );
+        let powers_2 = ptau::PowersOfTau::<Bls12_381>::read_powersoftau(
+            Cursor::new(&file_2),
+            power,
+            compress,
+            1000,
+        )
+        .unwrap();
+        assert_eq!(powers_2.tau_g1.len(), 1 << power);
+        let generic_srs =
+            srs::GenericSRS::from_powers_of_tau(powers_1, powers_2, &mut rng).unwrap();
+        assert_eq!(generic_srs.g_beta_powers.len(), 10);
+        generic_srs.specialize(4).unwrap();
+
+        // Reading with the wrong ceremony size reads the powers of tau in G2 from the wrong place
+        assert!(ptau::PowersOfTau::<Bls12_381>::read_powersoftau(
+            Cursor::new(&file_1),
+            power - 1,
+            compress,
+            1000,
+        )
+        .and_then(|p| p.check(&mut rng))
+        .is_err());
+    }
+}
+
+#[test]
+fn srs_serialization() {
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let generic_srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, 8);
+
+    for compress in [Compress::Yes, Compress::No] {
+        let bytes = generic_srs.to_envelope_bytes(compress).unwrap();
+        for validate in [Validate::Yes, Validate::No] {
+            assert_eq!(
+                srs::GenericSRS::<Bls12_381>::from_envelope_bytes(&bytes, validate).unwrap(),
+                generic_srs
+            );
+        }
+    }
+    let mut bytes = vec![];
+    generic_srs.write(&mut bytes).unwrap();
+    assert_eq!(
+        srs::GenericSRS::<Bls12_381>::read(bytes.as_slice()).unwrap(),
+        generic_srs
+    );
+
+    // Specializing from a view over the uncompressed bytes is the same as from the decoded SRS
+    let bytes = generic_srs.to_envelope_bytes(Compress::No).unwrap();
+    let view = srs::GenericSRSView::<Bls12_381>::from_envelope_bytes(&bytes).unwrap();
+    for num_proofs in [1, 3, 8] {
+        for validate in [Validate::Yes, Validate::No] {
+            assert_eq!(
+                view.specialize(num_proofs, validate).unwrap(),
+                generic_srs.specialize(num_proofs).unwrap()
+            );
+        }
+    }
+    assert!(matches!(
+        view.specialize(9, Validate::No).unwrap_err(),
+        AggregationError::InsufficientKeyLength(32)
+    ));
+    let compressed_bytes = generic_srs.to_envelope_bytes(Compress::Yes).unwrap();
+    assert!(srs::GenericSRSView::<Bls12_381>::from_envelope_bytes(&compressed_bytes).is_err());
+    let mut payload = vec![];
+    generic_srs.serialize_uncompressed(&mut payload).unwrap();
+    assert!(srs::GenericSRSView::<Bls12_381>::from_uncompressed_bytes(&payload).is_ok());
+    assert!(srs::GenericSRSView::<Bls12_381>::from_uncompressed_bytes(
+        &payload[..payload.len() - 1]
+    )
+    .is_err());
+
+    // A persisted prover SRS, with the prepared key, can be used without specializing again
+    let (prover_srs, ver_srs) = generic_srs.specialize(4).unwrap();
+    let bytes = prover_srs.to_envelope_bytes(Compress::No).unwrap();
+    assert_eq!(
+        srs::ProverSRS::<Bls12_381>::from_envelope_bytes(&bytes, Validate::Yes).unwrap(),
+        prover_srs
+    );
+    let prepared_srs = PreparedProverSRS::from(prover_srs.clone());
+    let mut expected = vec![];
+    prepared_srs.serialize_uncompressed(&mut expected).unwrap();
+    let bytes = prepared_srs.to_envelope_bytes(Compress::No).unwrap();
+    let mut loaded_srs = None;
+    for validate in [Validate::Yes, Validate::No] {
+        let loaded = PreparedProverSRS::<Bls12_381>::from_envelope_bytes(&bytes, validate).unwrap();
+        let mut loaded_bytes = vec![];
+        loaded.serialize_uncompressed(&mut loaded_bytes).unwrap();
+        assert_eq!(loaded_bytes, expected);
+        loaded_srs = Some(loaded);
+    }
+
+    let num_constraints = 10;
+    let params = {
+        let c = Multiply {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        generate_random_parameters::<Bls12_381, _, _>(c, 2, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+    let mut all_inputs = vec![];
+    let mut proofs = vec![];
+    for i in 1..=4 {
+        let a = Fr::from(i as u64);
+        let b = Fr::from(3 * i as u64);
+        all_inputs.push(vec![a * b]);
+        let c = Multiply {
+            num_constraints,
+            a: Some(a),
+            b: Some(b),
+        };
+        proofs.push(create_random_proof(c, Fr::rand(&mut rng), &params, &mut rng).unwrap());
+    }
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    let aggregate_proof =
+        legogroth16::aggregate_proofs(loaded_srs.unwrap(), &mut prover_transcript, &proofs)
+            .unwrap();
+    let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+    legogroth16::verify_aggregate_proof(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut ver_transcript,
+        None,
+    )
+    .unwrap();
+
+    // Corrupted bytes and objects of another type are rejected
+    let mut bad_bytes = bytes.clone();
+    let mid = bad_bytes.len() / 2;
+    bad_bytes[mid] ^= 1;
+    assert_eq!(
+        PreparedProverSRS::<Bls12_381>::from_envelope_bytes(&bad_bytes, Validate::No).unwrap_err(),
+        EnvelopeError::InvalidChecksum
+    );
+    assert!(matches!(
+        srs::ProverSRS::<Bls12_381>::from_envelope_bytes(&bytes, Validate::No).unwrap_err(),
+        EnvelopeError::ObjectTypeMismatch(..)
+    ));
+
+    // Keys of the wrong length are rejected even when not validating the points
+    let mut bad_srs = prover_srs;
+    bad_srs.wkey.a.pop();
+    let mut bytes = vec![];
+    bad_srs.serialize_uncompressed(&mut bytes).unwrap();
+    assert!(srs::ProverSRS::<Bls12_381>::deserialize_with_mode(
+        bytes.as_slice(),
+        Compress::No,
+        Validate::No
+    )
+    .is_err());
+}
+
+#[test]
+fn legogroth16_aggregation_with_common_witnesses() {
+    let num_constraints = 10;
+    let mut rng = StdRng::seed_from_u64(0u64);
+    // Both witnesses a and b are committed in d
+    let params = {
+        let c = Multiply {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        generate_random_parameters::<Bls12_381, _, _>(c, 2, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+    let nproofs = 5;
+    let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, 8);
+    let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
+
+    // a is the same in all proofs
+    let a = Fr::from(7u64);
+    let mut all_inputs = vec![];
+    let mut proofs = vec![];
+    let mut witnesses = vec![];
+    let mut v = vec![];
+    for i in 1..=nproofs {
+        let b = Fr::from(i as u64);
+        all_inputs.push(vec![a * b]);
+        witnesses.push(vec![a, b]);
+        v.push(Fr::rand(&mut rng));
+        let c = Multiply {
+            num_constraints,
+            a: Some(a),
+            b: Some(b),
+        };
+        proofs.push(create_random_proof(c, v[i - 1], &params, &mut rng).unwrap());
+    }
+
+    let blinding = Fr::rand(&mut rng);
+    let aggregate = |transcript: &mut _, witnesses: &[Vec<Fr>], common: &[usize], rng: &mut _| {
+        legogroth16::aggregate_proofs_with_common_witnesses(
+            prover_srs.clone(),
+            transcript,
+            &proofs,
+            &params.vk,
+            witnesses,
+            &v,
+            common,
+            blinding,
+            rng,
+        )
+    };
+    let verify = |aggregate_proof: &legogroth16::AggregateLegoProof<Bls12_381>,
+                  cw_proof: &legogroth16::CommonWitnessesProof<Bls12_381>,
+                  common: &[usize],
+                  rng: &mut StdRng| {
+        let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+        ver_transcript.append(b"public-inputs", &all_inputs);
+        legogroth16::verify_aggregate_proof_with_common_witnesses(
+            &ver_srs,
+            &pvk,
+            &all_inputs,
+            aggregate_proof,
+            cw_proof,
+            common,
+            rng,
+            &mut ver_transcript,
+            None,
+        )
+    };
+
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    prover_transcript.append(b"public-inputs", &all_inputs);
+    let (aggregate_proof, cw_proof) =
+        aggregate(&mut prover_transcript, &witnesses, &[0], &mut rng).unwrap();
+    verify(&aggregate_proof, &cw_proof, &[0], &mut rng).unwrap();
+
+    // The commitment to the common witness has the bases of d so it can be opened like d
+    let start = params.vk.gamma_abc_g1.len() - params.vk.commit_witness_count;
+    assert_eq!(
+        cw_proof.commitment,
+        (params.vk.gamma_abc_g1[start] * a + params.vk.eta_gamma_inv_g1 * blinding).into_affine()
+    );
+
+    // No common witnesses is a proof of knowledge of the opening of the aggregated commitment
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    prover_transcript.append(b"public-inputs", &all_inputs);
+    let (aggregate_proof_1, cw_proof_1) =
+        aggregate(&mut prover_transcript, &witnesses, &[], &mut rng).unwrap();
+    verify(&aggregate_proof_1, &cw_proof_1, &[], &mut rng).unwrap();
+
+    // The aggregate proof can't be verified without the proof of common witnesses
+    let mut ver_transcript = new_merlin_transcript(b"test aggregation");
+    ver_transcript.append(b"public-inputs", &all_inputs);
+    assert!(legogroth16::verify_aggregate_proof(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut ver_transcript,
+        None,
+    )
+    .is_err());
+
+    // b differs between proofs
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    assert!(aggregate(&mut prover_transcript, &witnesses, &[1], &mut rng).is_err());
+    // and claiming it's the same fails verification
+    let mut fake_witnesses = witnesses.clone();
+    for w in fake_witnesses.iter_mut() {
+        w[1] = Fr::one();
+    }
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    prover_transcript.append(b"public-inputs", &all_inputs);
+    let (aggregate_proof_2, cw_proof_2) =
+        aggregate(&mut prover_transcript, &fake_witnesses, &[1], &mut rng).unwrap();
+    assert!(verify(&aggregate_proof_2, &cw_proof_2, &[1], &mut rng).is_err());
+
+    // Invalid indices and tampered proofs
+    let mut prover_transcript = new_merlin_transcript(b"test aggregation");
+    assert!(aggregate(&mut prover_transcript, &witnesses, &[2], &mut rng).is_err());
+    assert!(aggregate(&mut prover_transcript, &witnesses, &[0, 0], &mut rng).is_err());
+    assert!(verify(&aggregate_proof, &cw_proof, &[1], &mut rng).is_err());
+    assert!(verify(&aggregate_proof, &cw_proof, &[0, 1], &mut rng).is_err());
+    assert!(verify(&aggregate_proof, &cw_proof_1, &[0], &mut rng).is_err());
+    let mut bad_proof = cw_proof.clone();
+    bad_proof.commitment = (bad_proof.commitment + params.vk.eta_gamma_inv_g1).into_affine();
+    assert!(verify(&aggregate_proof, &bad_proof, &[0], &mut rng).is_err());
+    let mut bad_proof = cw_proof.clone();
+    bad_proof.responses[0] += Fr::one();
+    assert!(verify(&aggregate_proof, &bad_proof, &[0], &mut rng).is_err());
+}
+
+#[test]
+fn aggregation_with_statement_binding() {
+    let num_constraints = 10;
+    let nproofs = 5;
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let params = {
+        let c = Multiply {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        generate_random_parameters::<Bls12_381, _, _>(c, 2, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+    let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, 16);
+    let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
+
+    let mut all_inputs = vec![];
+    let mut proofs = vec![];
+    for i in 1..=nproofs {
+        let a = Fr::from(10 * i as u64);
+        let b = Fr::from(20 * i as u64);
+        all_inputs.push(vec![a * b]);
+        let c = Multiply {
+            num_constraints,
+            a: Some(a),
+            b: Some(b),
+        };
+        proofs.push(create_random_proof(c, Fr::rand(&mut rng), &params, &mut rng).unwrap());
+    }
+
+    let aggregate_proof = legogroth16::aggregate_proofs_safe(
+        prover_srs.clone(),
+        &mut new_merlin_transcript(b"test aggregation"),
+        &params.vk,
+        &proofs,
+        &all_inputs,
+    )
+    .unwrap();
+    legogroth16::verify_aggregate_proof_safe(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .unwrap();
+
+    // The statement isn't in the transcript of the plain functions
+    assert!(legogroth16::verify_aggregate_proof(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .is_err());
+    let unbound_proof = legogroth16::aggregate_proofs(
+        prover_srs.clone(),
+        &mut new_merlin_transcript(b"test aggregation"),
+        &proofs,
+    )
+    .unwrap();
+    assert!(legogroth16::verify_aggregate_proof_safe(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &unbound_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .is_err());
+
+    // The public inputs of each proof are needed
+    assert!(legogroth16::aggregate_proofs_safe(
+        prover_srs.clone(),
+        &mut new_merlin_transcript(b"test aggregation"),
+        &params.vk,
+        &proofs,
+        &all_inputs[1..],
+    )
+    .is_err());
+
+    // The hash covers how the inputs are split in vectors, and inputs beyond the first chunk
+    let a = Fr::from(1u64);
+    let b = Fr::from(2u64);
+    assert_ne!(
+        statement::hash_public_inputs(&[vec![a, b]]),
+        statement::hash_public_inputs(&[vec![a], vec![b]])
+    );
+    let mut many_inputs = vec![vec![a, b]; 3 * statement::INPUTS_PER_CHUNK + 1];
+    let hash = statement::hash_public_inputs(&many_inputs);
+    assert_eq!(hash, statement::hash_public_inputs(&many_inputs));
+    many_inputs[2 * statement::INPUTS_PER_CHUNK + 5][1] = a;
+    assert_ne!(hash, statement::hash_public_inputs(&many_inputs));
+
+    // Groth16
+    let (pk, vk) = {
+        let c = Benchmark::<Fr>::new(num_constraints);
+        ark_groth16::Groth16::<Bls12_381>::circuit_specific_setup(c, &mut rng).unwrap()
+    };
+    let pvk = ark_groth16::prepare_verifying_key(&vk);
+    let proofs = (0..nproofs)
+        .map(|_| {
+            let c = Benchmark::new(num_constraints);
+            ark_groth16::Groth16::<Bls12_381>::prove(&pk, c, &mut rng).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let all_inputs = vec![vec![Fr::one(); 2]; nproofs];
+    let aggregate_proof = groth16::aggregate_proofs_safe(
+        prover_srs,
+        &mut new_merlin_transcript(b"test aggregation"),
+        &vk,
+        &proofs,
+        &all_inputs,
+    )
+    .unwrap();
+    groth16::verify_aggregate_proof_safe(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .unwrap();
+    assert!(groth16::verify_aggregate_proof(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .is_err());
+}
+
+#[test]
+fn aggregation_with_statement_binding_in_other_modes() {
+    let num_constraints = 10;
+    let nproofs = 5;
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let circuit = |a, b| Multiply {
+        num_constraints,
+        a,
+        b,
+    };
+    let params =
+        generate_random_parameters::<Bls12_381, _, _>(circuit(None, None), 2, &mut rng).unwrap();
+    let params_1 =
+        generate_random_parameters::<Bls12_381, _, _>(circuit(None, None), 1, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+    let pvk_1 = prepare_verifying_key(&params_1.vk);
+    let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, 16);
+    let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
+    let transcript = || new_merlin_transcript(b"test aggregation");
+
+    // a is the same in all proofs
+    let a = Fr::from(7u64);
+    let mut all_inputs = vec![];
+    let mut proofs = vec![];
+    let mut witnesses = vec![];
+    let mut v = vec![];
+    for i in 1..=nproofs {
+        let b = Fr::from(i as u64);
+        all_inputs.push(vec![a * b]);
+        witnesses.push(vec![a, b]);
+        v.push(Fr::rand(&mut rng));
+        proofs.push(
+            create_random_proof(circuit(Some(a), Some(b)), v[i - 1], &params, &mut rng).unwrap(),
+        );
+    }
+    let mut other_inputs = all_inputs.clone();
+    other_inputs[nproofs - 1][0] += Fr::one();
+
+    // Aggregation using Groth16 binds the commitments as well
+    let (aggregate_proof, d) = legogroth16::using_groth16::aggregate_proofs_safe(
+        prover_srs.clone(),
+        &mut transcript(),
+        &params.vk,
+        &proofs,
+        &all_inputs,
+    )
+    .unwrap();
+    legogroth16::using_groth16::verify_aggregate_proof_safe(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &d,
+        &mut rng,
+        &mut transcript(),
+        None,
+    )
+    .unwrap();
+    let (unbound_proof, _) = legogroth16::using_groth16::aggregate_proofs(
+        prover_srs.clone(),
+        &mut transcript(),
+        &proofs,
+    )
+    .unwrap();
+    assert!(legogroth16::using_groth16::verify_aggregate_proof_safe(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &unbound_proof,
+        &d,
+        &mut rng,
+        &mut transcript(),
+        None,
+    )
+    .is_err());
+    assert!(legogroth16::using_groth16::aggregate_proofs_safe(
+        prover_srs.clone(),
+        &mut transcript(),
+        &params.vk,
+        &proofs,
+        &all_inputs[1..],
+    )
+    .is_err());
+
+    // Common witnesses, where the indices are bound as well
+    let blinding = Fr::rand(&mut rng);
+    let (aggregate_proof, cw_proof) = legogroth16::aggregate_proofs_with_common_witnesses_safe(
+        prover_srs.clone(),
+        &mut transcript(),
+        &proofs,
+        &all_inputs,
+        &params.vk,
+        &witnesses,
+        &v,
+        &[0],
+        blinding,
+        &mut rng,
+    )
+    .unwrap();
+    let verify_cw = |inputs: &[Vec<Fr>], rng: &mut StdRng| {
+        legogroth16::verify_aggregate_proof_with_common_witnesses_safe(
+            &ver_srs,
+            &pvk,
+            inputs,
+            &aggregate_proof,
+            &cw_proof,
+            &[0],
+            rng,
+            &mut transcript(),
+            None,
+        )
+    };
+    verify_cw(&all_inputs, &mut rng).unwrap();
+    assert!(verify_cw(&other_inputs, &mut rng).is_err());
+    assert!(legogroth16::verify_aggregate_proof_with_common_witnesses(
+        &ver_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &cw_proof,
+        &[0],
+        &mut rng,
+        &mut transcript(),
+        None,
+    )
+    .is_err());
+
+    // Heterogeneous, where all the verifying keys are bound
+    let proof_1 = create_random_proof(
+        circuit(Some(a), Some(a)),
+        Fr::rand(&mut rng),
+        &params_1,
+        &mut rng,
+    )
+    .unwrap();
+    let mut het_proofs = proofs.clone();
+    het_proofs.push(proof_1);
+    let mut het_inputs = all_inputs.clone();
+    het_inputs.push(vec![a * a]);
+    let mut vks = vec![&params.vk; nproofs];
+    vks.push(&params_1.vk);
+    let (het_prover_srs, het_ver_srs) = srs.specialize(nproofs + 1).unwrap();
+    let vks_com = heterogeneous::VerifyingKeysCommitment::new(&het_prover_srs.wkey, &vks).unwrap();
+    let aggregate_proof = heterogeneous::aggregate_proofs_safe(
+        het_prover_srs.clone(),
+        &mut transcript(),
+        &het_proofs,
+        &vks,
+        &het_inputs,
+    )
+    .unwrap();
+    let verify_het = |inputs: &[Vec<Fr>], proof: &heterogeneous::HeterogeneousAggregateProof<_>| {
+        let mut statements = vec![];
+        for (i, inputs) in inputs.iter().enumerate() {
+            statements.push((if i < nproofs { &pvk } else { &pvk_1 }, inputs.as_slice()));
+        }
+        heterogeneous::verify_aggregate_proof_safe(
+            &het_ver_srs,
+            &vks_com,
+            &statements,
+            proof,
+            StdRng::seed_from_u64(1u64),
+            &mut transcript(),
+            None,
+        )
+    };
+    verify_het(&het_inputs, &aggregate_proof).unwrap();
+    let mut other_het_inputs = het_inputs.clone();
+    other_het_inputs[0][0] += Fr::one();
+    assert!(verify_het(&other_het_inputs, &aggregate_proof).is_err());
+    let unbound_proof =
+        heterogeneous::aggregate_proofs(het_prover_srs, &mut transcript(), &het_proofs, &vks)
+            .unwrap();
+    assert!(verify_het(&het_inputs, &unbound_proof).is_err());
+
+    // Transparent
+    let transparent_srs = TransparentSRS::<Bls12_381>::new(b"test", nproofs).unwrap();
+    let aggregate_proof = legogroth16::transparent::aggregate_proofs_safe(
+        &transparent_srs,
+        &mut transcript(),
+        &params.vk,
+        &proofs,
+        &all_inputs,
+    )
+    .unwrap();
+    let verify_transparent = |inputs: &[Vec<Fr>], rng: &mut StdRng| {
+        legogroth16::transparent::verify_aggregate_proof_safe(
+            &transparent_srs,
+            &pvk,
+            inputs,
+            &aggregate_proof,
+            rng,
+            &mut transcript(),
+            None,
+        )
+    };
+    verify_transparent(&all_inputs, &mut rng).unwrap();
+    assert!(verify_transparent(&other_inputs, &mut rng).is_err());
+    assert!(legogroth16::transparent::verify_aggregate_proof(
+        &transparent_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut transcript(),
+        None,
+    )
+    .is_err());
+}
+
+/// Serialization of `proof` with compressed group elements but uncompressed GT elements, as before
+/// GT elements were compressed
+fn serialize_without_gt_compression(proof: &legogroth16::AggregateLegoProof<Bls12_381>) -> Vec<u8> {
+    let mut bytes = vec![];
+    for com in [&proof.com_ab, &proof.com_c, &proof.com_d] {
+        com.serialize_uncompressed(&mut bytes).unwrap();
+    }
+    proof.z_ab.serialize_uncompressed(&mut bytes).unwrap();
+    proof.z_c.serialize_compressed(&mut bytes).unwrap();
+    proof.z_d.serialize_compressed(&mut bytes).unwrap();
+    let gipa = &proof.tmipp.gipa;
+    gipa.nproofs.serialize_compressed(&mut bytes).unwrap();
+    for comms in [&gipa.comms_ab, &gipa.comms_c, &gipa.comms_d] {
+        comms.serialize_uncompressed(&mut bytes).unwrap();
+    }
+    gipa.z_ab.serialize_uncompressed(&mut bytes).unwrap();
+    gipa.z_c.serialize_compressed(&mut bytes).unwrap();
+    gipa.z_d.serialize_compressed(&mut bytes).unwrap();
+    gipa.final_a.serialize_compressed(&mut bytes).unwrap();
+    gipa.final_b.serialize_compressed(&mut bytes).unwrap();
+    gipa.final_c.serialize_compressed(&mut bytes).unwrap();
+    gipa.final_d.serialize_compressed(&mut bytes).unwrap();
+    gipa.final_vkey.serialize_compressed(&mut bytes).unwrap();
+    gipa.final_wkey.serialize_compressed(&mut bytes).unwrap();
+    proof
+        .tmipp
+        .vkey_opening
+        .serialize_compressed(&mut bytes)
+        .unwrap();
+    proof
+        .tmipp
+        .wkey_opening
+        .serialize_compressed(&mut bytes)
+        .unwrap();
+    bytes
+}
+
+fn check_gt_compression<E: Pairing>(rng: &mut StdRng) {
+    let size = torus::compressed_gt_size::<E>();
+    assert_eq!(2 * size, E::TargetField::one().uncompressed_size());
+
+    let x = E::pairing(E::G1::rand(rng), E::G2::rand(rng));
+    let minus_one = PairingOutput::<E>(-E::TargetField::one());
+    for y in [x, x + x, -x, PairingOutput::zero(), minus_one] {
+        let bytes = torus::compress_gt(&y).unwrap();
+        assert_eq!(bytes.len(), size);
+        assert_eq!(torus::decompress_gt::<E>(&bytes).unwrap(), y);
+
+        let mut bytes = vec![];
+        torus::serialize_gt(&y, &mut bytes, Compress::Yes).unwrap();
+        assert_eq!(bytes.len(), torus::gt_serialized_size::<E>(Compress::Yes));
+        let result = torus::deserialize_gt::<E, _>(bytes.as_slice(), Compress::Yes, Validate::Yes);
+        // -1 is in the torus but not in GT
+        if y == minus_one {
+            assert!(result.is_err());
+        } else {
+            assert_eq!(result.unwrap(), y);
+        }
+    }
+
+    // Elements not in the torus can't be compressed
+    assert!(torus::compress_gt(&PairingOutput::<E>(E::TargetField::rand(rng))).is_err());
+    assert!(torus::decompress_gt::<E>(&vec![0; size - 1]).is_err());
+    // An invalid field element
+    let mut bytes = torus::compress_gt(&x).unwrap();
+    bytes[..size / 2].fill(0xff);
+    assert!(torus::decompress_gt::<E>(&bytes).is_err());
+}
+
+#[test]
+fn gt_compression() {
+    let mut rng = StdRng::seed_from_u64(0u64);
+    check_gt_compression::<Bls12_381>(&mut rng);
+    check_gt_compression::<ark_bn254::Bn254>(&mut rng);
+
+    let num_constraints = 10;
+    let nproofs = 8;
+    let params = {
+        let c = Multiply {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        generate_random_parameters::<Bls12_381, _, _>(c, 2, &mut rng).unwrap()
+    };
+    let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, nproofs);
+    let (prover_srs, _) = srs.specialize(nproofs).unwrap();
+    let proofs = (1..=nproofs)
+        .map(|i| {
+            let c = Multiply {
+                num_constraints,
+                a: Some(Fr::from(i as u64)),
+                b: Some(Fr::from(2 * i as u64)),
+            };
+            create_random_proof(c, Fr::rand(&mut rng), &params, &mut rng).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let aggregate_proof = legogroth16::aggregate_proofs(
+        prover_srs,
+        &mut new_merlin_transcript(b"test aggregation"),
+        &proofs,
+    )
+    .unwrap();
+
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = vec![];
+        aggregate_proof
+            .serialize_with_mode(&mut bytes, compress)
+            .unwrap();
+        assert_eq!(bytes.len(), aggregate_proof.serialized_size(compress));
+        let deserialized = legogroth16::AggregateLegoProof::<Bls12_381>::deserialize_with_mode(
+            bytes.as_slice(),
+            compress,
+            Validate::Yes,
+        )
+        .unwrap();
+        assert_eq!(deserialized, aggregate_proof);
+    }
+
+    // Half of each GT element is saved: 3 PairCommitments and z_ab, and for each of the 3 GIPA
+    // rounds 3 pairs of PairCommitments and a pair for z_ab
+    let legacy_bytes = serialize_without_gt_compression(&aggregate_proof);
+    let gt_elements = 3 * 2 + 1 + 3 * (3 * 2 * 2 + 2);
+    assert_eq!(
+        legacy_bytes.len() - aggregate_proof.compressed_size(),
+        gt_elements * torus::compressed_gt_size::<Bls12_381>()
+    );
+    println!(
+        "Size of compressed aggregate proof of {} LegoGroth16 proofs: {} bytes with compressed GT elements, {} bytes without",
+        nproofs,
+        aggregate_proof.compressed_size(),
+        legacy_bytes.len()
+    );
+
+    // Proofs written before GT elements were compressed can still be read
+    assert_eq!(
+        legogroth16::AggregateLegoProof::<Bls12_381>::from_envelope_or_legacy_bytes(
+            &legacy_bytes,
+            Compress::Yes,
+            Validate::Yes
+        )
+        .unwrap(),
+        aggregate_proof
+    );
+}
+
+#[test]
+fn transparent_aggregation() {
+    let num_constraints = 10;
+    let nproofs = 5;
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let params = {
+        let c = Multiply {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        generate_random_parameters::<Bls12_381, _, _>(c, 2, &mut rng).unwrap()
+    };
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let start = Instant::now();
+    let generic_srs =
+        TransparentSRS::<Bls12_381>::new(b"test transparent aggregation", 16).unwrap();
+    println!(
+        "Time to derive transparent SRS for 16 proofs: {:?}",
+        start.elapsed()
+    );
+    let srs = generic_srs.specialize(nproofs).unwrap();
+    assert_eq!(
+        srs,
+        TransparentSRS::new(b"test transparent aggregation", nproofs).unwrap()
+    );
+    assert!(srs.has_correct_len(padded_proof_count(nproofs)));
+    assert!(TransparentSRS::<Bls12_381>::new(b"test transparent aggregation", 0).is_err());
+    assert!(srs.specialize(2 * nproofs).is_err());
+
+    let mut all_inputs = vec![];
+    let mut proofs = vec![];
+    for i in 1..=nproofs {
+        let a = Fr::from(10 * i as u64);
+        let b = Fr::from(20 * i as u64);
+        all_inputs.push(vec![a * b]);
+        let c = Multiply {
+            num_constraints,
+            a: Some(a),
+            b: Some(b),
+        };
+        proofs.push(create_random_proof(c, Fr::rand(&mut rng), &params, &mut rng).unwrap());
+    }
+
+    let start = Instant::now();
+    let aggregate_proof = legogroth16::transparent::aggregate_proofs(
+        &srs,
+        &mut new_merlin_transcript(b"test aggregation"),
+        &proofs,
+    )
+    .unwrap();
+    println!(
+        "Time to aggregate {} proofs with transparent SRS: {:?}",
+        nproofs,
+        start.elapsed()
+    );
+    let start = Instant::now();
+    legogroth16::transparent::verify_aggregate_proof(
+        &srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .unwrap();
+    println!(
+        "Time to verify aggregate proof with transparent SRS: {:?}",
+        start.elapsed()
+    );
+    println!(
+        "Transparent aggregate proof size: {} bytes compressed",
+        aggregate_proof.compressed_size()
+    );
+
+    // The keys derived from another label aren't the ones of the commitments
+    let other_srs = TransparentSRS::<Bls12_381>::new(b"other label", nproofs).unwrap();
+    assert!(legogroth16::transparent::verify_aggregate_proof(
+        &other_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .is_err());
+    assert!(legogroth16::transparent::verify_aggregate_proof(
+        &generic_srs,
+        &pvk,
+        &all_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .is_err());
+    assert!(legogroth16::transparent::aggregate_proofs(
+        &generic_srs,
+        &mut new_merlin_transcript(b"test aggregation"),
+        &proofs,
+    )
+    .is_err());
+
+    let mut wrong_inputs = all_inputs.clone();
+    wrong_inputs[2][0] += Fr::one();
+    assert!(legogroth16::transparent::verify_aggregate_proof(
+        &srs,
+        &pvk,
+        &wrong_inputs,
+        &aggregate_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .is_err());
+
+    // Final commitment keys other than the folded keys are rejected
+    let mut bad_proof = aggregate_proof.clone();
+    bad_proof.gipa.final_vkey.0 = bad_proof.gipa.final_vkey.1;
+    assert!(legogroth16::transparent::verify_aggregate_proof(
+        &srs,
+        &pvk,
+        &all_inputs,
+        &bad_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .is_err());
+    let mut bad_proof = aggregate_proof.clone();
+    bad_proof.gipa.final_wkey.1 =
+        (bad_proof.gipa.final_wkey.1 + G1Projective::generator()).into_affine();
+    assert!(legogroth16::transparent::verify_aggregate_proof(
+        &srs,
+        &pvk,
+        &all_inputs,
+        &bad_proof,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .is_err());
+
+    // An invalid proof among the aggregated proofs
+    let mut bad_proofs = proofs.clone();
+    bad_proofs[1].c = (bad_proofs[1].c + G1Projective::generator()).into_affine();
+    let bad_aggregate = legogroth16::transparent::aggregate_proofs(
+        &srs,
+        &mut new_merlin_transcript(b"test aggregation"),
+        &bad_proofs,
+    )
+    .unwrap();
+    assert!(legogroth16::transparent::verify_aggregate_proof(
+        &srs,
+        &pvk,
+        &all_inputs,
+        &bad_aggregate,
+        &mut rng,
+        &mut new_merlin_transcript(b"test aggregation"),
+        None,
+    )
+    .is_err());
+
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = vec![];
+        aggregate_proof
+            .serialize_with_mode(&mut bytes, compress)
+            .unwrap();
+        assert_eq!(bytes.len(), aggregate_proof.serialized_size(compress));
+        let deserialized =
+            legogroth16::TransparentAggregateLegoProof::<Bls12_381>::deserialize_with_mode(
+                bytes.as_slice(),
+                compress,
+                Validate::Yes,
+            )
+            .unwrap();
+        assert_eq!(deserialized, aggregate_proof);
+    }
+    let bytes = aggregate_proof.to_envelope_bytes(Compress::Yes).unwrap();
+    assert_eq!(
+        legogroth16::TransparentAggregateLegoProof::<Bls12_381>::from_envelope_bytes(
+            &bytes,
+            Validate::Yes
+        )
+        .unwrap(),
+        aggregate_proof
+    );
+    assert!(matches!(
+        legogroth16::AggregateLegoProof::<Bls12_381>::from_envelope_bytes(&bytes, Validate::Yes),
+        Err(EnvelopeError::ObjectTypeMismatch(..))
+    ));
+    let bytes = srs.to_envelope_bytes(Compress::Yes).unwrap();
+    assert_eq!(
+        TransparentSRS::<Bls12_381>::from_envelope_bytes(&bytes, Validate::Yes).unwrap(),
+        srs
+    );
+}
+
+#[test]
+fn aggregation_with_converted_keys() {
+    let num_constraints = 10;
+    let nproofs = 4;
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let (g16_pk, g16_vk) = {
+        let c = Multiply::<Fr> {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        ark_groth16::Groth16::<Bls12_381>::circuit_specific_setup(c, &mut rng).unwrap()
+    };
+    let vk = crate::VerifyingKey::from(g16_vk);
+    let pvk = prepare_verifying_key(&vk);
+    let srs = srs::setup_fake_srs::<Bls12_381, _>(&mut rng, 16);
+    let (prover_srs, ver_srs) = srs.specialize(nproofs).unwrap();
+
+    let mut all_inputs = vec![];
+    let mut proofs = vec![];
+    for i in 1..=nproofs {
+        let a = Fr::from(10 * i as u64);
+        let b = Fr::from(20 * i as u64);
+        all_inputs.push(vec![a * b]);
+        let c = Multiply {
+            num_constraints,
+            a: Some(a),
+            b: Some(b),
+        };
+        let proof = ark_groth16::Groth16::<Bls12_381>::prove(&g16_pk, c, &mut rng).unwrap();
+        proofs.push(crate::Proof::from(proof));
+    }
+
+    // A proof for any statement as d is unconstrained by the equation when eta is 0:
+    // e(alpha, beta) = e(alpha, beta) * e(IC + d, gamma) * e(0, delta) with d = -IC
+    let forged_input = Fr::from(12345u64);
+    let ic = vk.gamma_abc_g1[0] + vk.gamma_abc_g1[1] * forged_input;
+    let forged = crate::Proof {
+        a: vk.alpha_g1,
+        b: vk.beta_g2,
+        c: <Bls12_381 as Pairing>::G1Affine::zero(),
+        d: (-ic).into_affine(),
+    };
+    assert!(verify_proof(&pvk, &forged, &[forged_input]).is_err());
+    let mut forged_proofs = proofs.clone();
+    forged_proofs[1] = forged.clone();
+    let mut forged_inputs = all_inputs.clone();
+    forged_inputs[1] = vec![forged_input];
+
+    // LegoGroth16 aggregation
+    let verify = |proofs: &[crate::Proof<Bls12_381>], inputs: &[Vec<Fr>]| {
+        let aggregate_proof = legogroth16::aggregate_proofs(
+            prover_srs.clone(),
+            &mut new_merlin_transcript(b"test aggregation"),
+            proofs,
+        )
+        .unwrap();
+        legogroth16::verify_aggregate_proof(
+            &ver_srs,
+            &pvk,
+            inputs,
+            &aggregate_proof,
+            StdRng::seed_from_u64(1u64),
+            &mut new_merlin_transcript(b"test aggregation"),
+            None,
+        )
+    };
+    verify(&proofs, &all_inputs).unwrap();
+    assert!(verify(&forged_proofs, &forged_inputs).is_err());
+
+    // Aggregation with each d given to the verifier
+    let verify = |proofs: &[crate::Proof<Bls12_381>], inputs: &[Vec<Fr>]| {
+        let (aggregate_proof, d) = legogroth16::using_groth16::aggregate_proofs(
+            prover_srs.clone(),
+            &mut new_merlin_transcript(b"test aggregation"),
+            proofs,
+        )
+        .unwrap();
+        legogroth16::using_groth16::verify_aggregate_proof(
+            &ver_srs,
+            &pvk,
+            inputs,
+            &aggregate_proof,
+            &d,
+            &mut StdRng::seed_from_u64(1u64),
+            &mut new_merlin_transcript(b"test aggregation"),
+            None,
+        )
+    };
+    verify(&proofs, &all_inputs).unwrap();
+    assert!(verify(&forged_proofs, &forged_inputs).is_err());
+
+    // Heterogeneous aggregation
+    let vks = vec![&vk; nproofs];
+    let vks_com = heterogeneous::VerifyingKeysCommitment::new(&prover_srs.wkey, &vks).unwrap();
+    let verify = |proofs: &[crate::Proof<Bls12_381>],
+                  statements: &[(&PreparedVerifyingKey<Bls12_381>, &[Fr])],
+                  vks: &[&crate::VerifyingKey<Bls12_381>],
+                  vks_com: &heterogeneous::VerifyingKeysCommitment<Bls12_381>| {
+        let aggregate_proof = heterogeneous::aggregate_proofs(
+            prover_srs.clone(),
+            &mut new_merlin_transcript(b"test aggregation"),
+            proofs,
+            vks,
+        )
+        .unwrap();
+        heterogeneous::verify_aggregate_proof(
+            &ver_srs,
+            vks_com,
+            statements,
+            &aggregate_proof,
+            StdRng::seed_from_u64(1u64),
+            &mut new_merlin_transcript(b"test aggregation"),
+            None,
+        )
+    };
+    let statements = all_inputs
+        .iter()
+        .map(|inputs| (&pvk, inputs.as_slice()))
+        .collect::<Vec<_>>();
+    verify(&proofs, &statements, &vks, &vks_com).unwrap();
+    let forged_statements = forged_inputs
+        .iter()
+        .map(|inputs| (&pvk, inputs.as_slice()))
+        .collect::<Vec<_>>();
+    assert!(verify(&forged_proofs, &forged_statements, &vks, &vks_com).is_err());
+
+    // Keys without eta cannot be mixed with keys with eta
+    let lego_params = {
+        let c = Multiply {
+            num_constraints,
+            a: None,
+            b: None,
+        };
+        generate_random_parameters::<Bls12_381, _, _>(c, 0, &mut rng).unwrap()
+    };
+    let lego_pvk = prepare_verifying_key(&lego_params.vk);
+    let a = Fr::from(3u64);
+    let b = Fr::from(4u64);
+    let c = Multiply {
+        num_constraints,
+        a: Some(a),
+        b: Some(b),
+    };
+    let mut mixed_proofs = proofs.clone();
+    mixed_proofs[3] = create_random_proof(c, Fr::rand(&mut rng), &lego_params, &mut rng).unwrap();
+    let mut mixed_vks = vks.clone();
+    mixed_vks[3] = &lego_params.vk;
+    let mixed_vks_com =
+        heterogeneous::VerifyingKeysCommitment::new(&prover_srs.wkey, &mixed_vks).unwrap();
+    let mixed_input = [a * b];
+    let mut mixed_statements = statements.clone();
+    mixed_statements[3] = (&lego_pvk, &mixed_input);
+    assert!(matches!(
+        verify(&mixed_proofs, &mixed_statements, &mixed_vks, &mixed_vks_com),
+        Err(AggregationError::InvalidProof(_))
+    ));
+}
@@ -0,0 +1,150 @@
+//! Compression of pairing outputs, i.e. elements of the target group GT, to half their size using
+//! the algebraic torus T2. The target field is a quadratic extension $F_{q^k} = F_{q^{k/2}}[w]$ and
+//! GT lies in its elements $x$ with $x \bar{x} = 1$ where $\bar{x}$ is the conjugate over
+//! $F_{q^{k/2}}$. For such $x \ne 1$, $\gamma = (x + 1) / (x - 1)$ satisfies $\bar{\gamma} = -\gamma$
+//! so $\gamma = c w$ for some $c \in F_{q^{k/2}}$, which is the compressed form, and
+//! $x = (\gamma + 1) / (\gamma - 1)$. The identity is encoded with all bytes `0xff` which is not the
+//! encoding of any field element.
+//!
+//! This only needs the arithmetic of the target field and its canonical serialization, where the
+//! coefficient of $w$ comes second, so it works for any `Pairing`. T6 would compress to a third
+//! but needs the subfield tower of the target field which `Pairing` does not expose.
+//!
+//! The aggregate proofs, their GIPA proofs and `PairCommitment` serialize their GT elements this
+//! way with `Compress::Yes`. With `Compress::No` GT elements are serialized as before.
+
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ff::{Field, One, Zero};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+use ark_std::{vec, vec::Vec};
+
+type GTPairs<E> = Vec<(PairingOutput<E>, PairingOutput<E>)>;
+
+/// Every byte of the encoding of the identity
+const IDENTITY_BYTE: u8 = 0xff;
+
+/// Size of a compressed GT element, half the size of an uncompressed one
+pub fn compressed_gt_size<E: Pairing>() -> usize {
+    E::TargetField::zero().uncompressed_size() / 2
+}
+
+/// Compresses `x` to `compressed_gt_size` bytes. Fails if `x` is not in the torus, which all
+/// pairing outputs are.
+pub fn compress_gt<E: Pairing>(x: &PairingOutput<E>) -> Result<Vec<u8>, SerializationError> {
+    let size = compressed_gt_size::<E>();
+    if x.0.is_one() {
+        return Ok(vec![IDENTITY_BYTE; size]);
+    }
+    let one = E::TargetField::one();
+    let gamma = (x.0 + one)
+        * (x.0 - one)
+            .inverse()
+            .ok_or(SerializationError::InvalidData)?;
+    let mut bytes = Vec::with_capacity(2 * size);
+    gamma.serialize_uncompressed(&mut bytes)?;
+    // The coefficient of 1 is 0 only if x is in the torus
+    if bytes.len() != 2 * size || bytes[..size].iter().any(|b| *b != 0) {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(bytes.split_off(size))
+}
+
+/// Decompresses the bytes written by `compress_gt`. The result is in the torus but not necessarily
+/// in GT, which is checked by `PairingOutput::check`.
+pub fn decompress_gt<E: Pairing>(bytes: &[u8]) -> Result<PairingOutput<E>, SerializationError> {
+    let size = compressed_gt_size::<E>();
+    if bytes.len() != size {
+        return Err(SerializationError::InvalidData);
+    }
+    if bytes.iter().all(|b| *b == IDENTITY_BYTE) {
+        return Ok(PairingOutput(E::TargetField::one()));
+    }
+    let mut gamma_bytes = vec![0; size];
+    gamma_bytes.extend_from_slice(bytes);
+    let gamma = E::TargetField::deserialize_uncompressed_unchecked(gamma_bytes.as_slice())?;
+    let one = E::TargetField::one();
+    // gamma - 1 is not 0 as the coefficient of 1 is -1
+    let x = (gamma + one)
+        * (gamma - one)
+            .inverse()
+            .ok_or(SerializationError::InvalidData)?;
+    Ok(PairingOutput(x))
+}
+
+/// Serializes `x` compressed with `compress_gt` for `Compress::Yes` and as `PairingOutput` otherwise
+pub fn serialize_gt<E: Pairing, W: Write>(
+    x: &PairingOutput<E>,
+    mut writer: W,
+    compress: Compress,
+) -> Result<(), SerializationError> {
+    match compress {
+        Compress::Yes => Ok(writer.write_all(&compress_gt(x)?)?),
+        Compress::No => x.serialize_uncompressed(writer),
+    }
+}
+
+pub fn gt_serialized_size<E: Pairing>(compress: Compress) -> usize {
+    match compress {
+        Compress::Yes => compressed_gt_size::<E>(),
+        Compress::No => E::TargetField::zero().uncompressed_size(),
+    }
+}
+
+/// Deserializes a GT element written by `serialize_gt`
+pub fn deserialize_gt<E: Pairing, R: Read>(
+    mut reader: R,
+    compress: Compress,
+    validate: Validate,
+) -> Result<PairingOutput<E>, SerializationError> {
+    match compress {
+        Compress::Yes => {
+            let mut bytes = vec![0; compressed_gt_size::<E>()];
+            reader.read_exact(&mut bytes)?;
+            let x = decompress_gt(&bytes)?;
+            if validate == Validate::Yes {
+                x.check()?;
+            }
+            Ok(x)
+        }
+        Compress::No => PairingOutput::deserialize_with_mode(reader, Compress::No, validate),
+    }
+}
+
+/// Serializes pairs of GT elements like `Vec<(PairingOutput, PairingOutput)>` but with `serialize_gt`
+pub(crate) fn serialize_gt_pairs<E: Pairing, W: Write>(
+    pairs: &[(PairingOutput<E>, PairingOutput<E>)],
+    mut writer: W,
+    compress: Compress,
+) -> Result<(), SerializationError> {
+    (pairs.len() as u64).serialize_with_mode(&mut writer, compress)?;
+    for (l, r) in pairs {
+        serialize_gt(l, &mut writer, compress)?;
+        serialize_gt(r, &mut writer, compress)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn gt_pairs_serialized_size<E: Pairing>(
+    pairs: &[(PairingOutput<E>, PairingOutput<E>)],
+    compress: Compress,
+) -> usize {
+    8 + 2 * pairs.len() * gt_serialized_size::<E>(compress)
+}
+
+pub(crate) fn deserialize_gt_pairs<E: Pairing, R: Read>(
+    mut reader: R,
+    compress: Compress,
+    validate: Validate,
+) -> Result<GTPairs<E>, SerializationError> {
+    let len = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+    let mut pairs = Vec::new();
+    for _ in 0..len {
+        let l = deserialize_gt(&mut reader, compress, validate)?;
+        let r = deserialize_gt(&mut reader, compress, validate)?;
+        pairs.push((l, r));
+    }
+    Ok(pairs)
+}
@@ -0,0 +1,161 @@
+//! Commitment keys for aggregation without a second trusted setup.
+//!
+//! The keys $v$ and $w$ of `srs::ProverSRS` are powers of two secret exponents so that the
+//! prover can show with KZG openings that the commitment keys, folded by GIPA to a single element
+//! each, are well formed, which lets the verifier take logarithmic time. `TransparentSRS` instead
+//! derives every key element by hashing to the curve so nobody knows discrete logarithms between
+//! them, and the verifier folds the keys itself like the generators in Bulletproofs. The folded
+//! key is $\sum_i s_i v_i$ where $s_i$ is the product of the challenges of the GIPA rounds in
+//! which index $i$ was on the right half, so the verifier does a multi-scalar multiplication of
+//! size `n` in each group instead of checking KZG openings.
+//!
+//! Key elements only depend on the label and their index so the keys for fewer proofs are a
+//! prefix of the keys for more proofs, see `TransparentSRS::specialize`.
+
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{cfg_into_iter, cfg_iter, cfg_iter_mut, string::ToString, vec, vec::Vec};
+use blake2::Blake2b512;
+use dock_crypto_utils::{ff::powers, hashing_utils::affine_group_elem_from_try_and_incr};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::error::AggregationError;
+use super::key::{Key, VKey, WKey};
+use super::srs::MAX_SRS_SIZE;
+use super::utils::padded_proof_count;
+
+/// The final $v$ and $w$ as in `GipaProofLego`
+type FinalKeys<E> = (
+    (<E as Pairing>::G2Affine, <E as Pairing>::G2Affine),
+    (<E as Pairing>::G1Affine, <E as Pairing>::G1Affine),
+);
+
+/// Commitment keys derived from a public label
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct TransparentSRS<E: Pairing> {
+    pub vkey: VKey<E>,
+    pub wkey: WKey<E>,
+}
+
+impl<E: Pairing> TransparentSRS<E> {
+    /// Derives the commitment keys for `padded_proof_count(num_proofs)` proofs from `label`. The
+    /// prover and verifier must use the same label.
+    pub fn new(label: &[u8], num_proofs: usize) -> Result<Self, AggregationError> {
+        if num_proofs == 0 {
+            return Err(AggregationError::InvalidSRS(
+                "cannot create SRS for 0 proofs".to_string(),
+            ));
+        }
+        let n = padded_proof_count(num_proofs);
+        if n > MAX_SRS_SIZE {
+            return Err(AggregationError::InvalidSRS(
+                "too many proofs for the SRS".to_string(),
+            ));
+        }
+        Ok(Self {
+            vkey: Key {
+                a: hash_to_points(label, b"v1", n),
+                b: hash_to_points(label, b"v2", n),
+            },
+            wkey: Key {
+                a: hash_to_points(label, b"w1", n),
+                b: hash_to_points(label, b"w2", n),
+            },
+        })
+    }
+
+    /// Returns the SRS for `padded_proof_count(num_proofs)` proofs, which is the same as
+    /// `TransparentSRS::new` with the label of this SRS.
+    pub fn specialize(&self, num_proofs: usize) -> Result<Self, AggregationError> {
+        if num_proofs == 0 {
+            return Err(AggregationError::InvalidSRS(
+                "cannot specialize for 0 proofs".to_string(),
+            ));
+        }
+        let n = padded_proof_count(num_proofs);
+        if self.vkey.len() < n || self.wkey.len() < n {
+            return Err(AggregationError::InsufficientKeyLength(n));
+        }
+        Ok(Self {
+            vkey: Key {
+                a: self.vkey.a[..n].to_vec(),
+                b: self.vkey.b[..n].to_vec(),
+            },
+            wkey: Key {
+                a: self.wkey.a[..n].to_vec(),
+                b: self.wkey.b[..n].to_vec(),
+            },
+        })
+    }
+
+    /// Returns true if commitment keys have the exact required length.
+    /// It is necessary for the IPP scheme to work that commitment
+    /// key have the exact same number of arguments as the number of proofs to
+    /// aggregate.
+    pub fn has_correct_len(&self, n: usize) -> bool {
+        self.vkey.has_correct_len(n) && self.wkey.has_correct_len(n)
+    }
+
+    /// Folds the commitment keys as GIPA does to get the final $v$ and $w$. `challenges` and
+    /// `challenges_inv` are those of each GIPA round starting from the last one, `v` is folded
+    /// with the inverses and `w`, scaled by the powers of $r^{-1}$, with the challenges. The keys
+    /// must have the length of the number of challenges.
+    pub fn final_keys(
+        &self,
+        challenges: &[E::ScalarField],
+        challenges_inv: &[E::ScalarField],
+        r_shift: &E::ScalarField,
+    ) -> Result<FinalKeys<E>, AggregationError> {
+        let n = 1 << challenges.len();
+        if challenges_inv.len() != challenges.len() || !self.has_correct_len(n) {
+            return Err(AggregationError::InvalidKeyLength);
+        }
+        let s_v = fold_coefficients(challenges_inv);
+        let r_inv = r_shift
+            .inverse()
+            .ok_or(AggregationError::InvalidKeyLength)?;
+        let r_inv_powers = powers(&r_inv, n);
+        let mut s_w = fold_coefficients(challenges);
+        cfg_iter_mut!(s_w)
+            .zip(cfg_iter!(r_inv_powers))
+            .for_each(|(s, r)| *s *= r);
+        let v1 = E::G2::msm_unchecked(&self.vkey.a, &s_v);
+        let v2 = E::G2::msm_unchecked(&self.vkey.b, &s_v);
+        let w1 = E::G1::msm_unchecked(&self.wkey.a, &s_w);
+        let w2 = E::G1::msm_unchecked(&self.wkey.b, &s_w);
+        Ok((
+            (v1.into_affine(), v2.into_affine()),
+            (w1.into_affine(), w2.into_affine()),
+        ))
+    }
+}
+
+/// The `n` points hashed from `label`, `tag` and their index
+fn hash_to_points<G: AffineRepr>(label: &[u8], tag: &[u8], n: usize) -> Vec<G> {
+    cfg_into_iter!(0..n)
+        .map(|i| {
+            let bytes = [
+                &(label.len() as u64).to_le_bytes()[..],
+                label,
+                tag,
+                &(i as u64).to_le_bytes(),
+            ]
+            .concat();
+            affine_group_elem_from_try_and_incr::<G, Blake2b512>(&bytes)
+        })
+        .collect()
+}
+
+/// The coefficient of each key element in the key folded with `challenges`, given from the last
+/// GIPA round. The last round decides on the lowest bit of the index and the first on the highest.
+fn fold_coefficients<F: Field>(challenges: &[F]) -> Vec<F> {
+    let mut s = vec![F::one()];
+    for c in challenges {
+        let right = s.iter().map(|s_i| *s_i * c).collect::<Vec<_>>();
+        s.extend(right);
+    }
+    s
+}
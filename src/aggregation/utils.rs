@@ -1,8 +1,11 @@
 use ark_ec::pairing::PairingOutput;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, Compress, SerializationError, Validate};
 use ark_std::{
-    cfg_into_iter, cfg_iter, cfg_iter_mut,
+    borrow::Cow,
+    cfg_chunks, cfg_into_iter, cfg_iter, cfg_iter_mut, format,
+    io::Read,
     ops::{AddAssign, Mul, MulAssign},
     string::ToString,
     vec::Vec,
@@ -24,6 +27,77 @@ use crate::aggregation::srs::VerifierSRSProjective;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+/// Number of points `deserialize_points` reads at a time
+const POINTS_PER_BATCH: usize = 1 << 16;
+
+/// Deserializes a `Vec<G>` serialized by `CanonicalSerialize` but decodes and checks the points in
+/// parallel with the `parallel` feature, which makes loading a large SRS much faster. The points
+/// are read in batches so a corrupted length can't make it allocate much more than the input has.
+pub(crate) fn deserialize_points<G: AffineRepr, R: Read>(
+    mut reader: R,
+    compress: Compress,
+    validate: Validate,
+) -> Result<Vec<G>, SerializationError> {
+    let len = u64::deserialize_with_mode(&mut reader, compress, validate)? as usize;
+    let size = G::zero().serialized_size(compress);
+    let mut points = Vec::with_capacity(len.min(POINTS_PER_BATCH));
+    let mut bytes = Vec::new();
+    while points.len() < len {
+        let batch = (len - points.len()).min(POINTS_PER_BATCH);
+        bytes.resize(batch * size, 0);
+        reader.read_exact(&mut bytes)?;
+        let batch = cfg_chunks!(bytes, size)
+            .map(|b| G::deserialize_with_mode(b, compress, validate))
+            .collect::<Result<Vec<_>, _>>()?;
+        points.extend(batch);
+    }
+    Ok(points)
+}
+
+/// Checks the points in parallel with the `parallel` feature
+pub(crate) fn check_points<G: AffineRepr>(points: &[G]) -> Result<(), SerializationError> {
+    cfg_iter!(points).try_for_each(|p| p.check())
+}
+
+/// Returns the number of proofs that are actually aggregated when aggregating `n` proofs. The
+/// inner product arguments need a power of two number of at least 2 proofs so `n` is rounded
+/// up to that and the extra slots are filled by repeating the last proof, which the verifier
+/// mirrors by repeating the last public input. `GenericSRS::specialize` rounds up the same way.
+pub fn padded_proof_count(n: usize) -> usize {
+    n.max(2).next_power_of_two()
+}
+
+/// Pads `items` to `padded_proof_count(items.len())` by repeating the last item. Borrows when
+/// no padding is needed.
+pub(crate) fn pad_by_repeating_last<T: Clone>(items: &[T]) -> Cow<'_, [T]> {
+    let padded = padded_proof_count(items.len());
+    if items.is_empty() || items.len() == padded {
+        return Cow::Borrowed(items);
+    }
+    let mut v = Vec::with_capacity(padded);
+    v.extend_from_slice(items);
+    v.resize(padded, items[items.len() - 1].clone());
+    Cow::Owned(v)
+}
+
+/// Checks that an aggregate proof of `nproofs` proofs can be for `items.len()` proofs and
+/// pads `items` the way the prover padded the proofs.
+pub(crate) fn pad_for_verification<'a, T: Clone>(
+    items: &'a [T],
+    nproofs: u32,
+    what: &str,
+) -> Result<Cow<'a, [T]>, AggregationError> {
+    if items.is_empty() || padded_proof_count(items.len()) != nproofs as usize {
+        return Err(AggregationError::InvalidProof(format!(
+            "{} len {} does not match number of proofs {}",
+            what,
+            items.len(),
+            nproofs
+        )));
+    }
+    Ok(pad_by_repeating_last(items))
+}
+
 /// compress is similar to commit::{V,W}KEY::compress: it modifies the `vec`
 /// vector by setting the value at index $i:0 -> split$  $vec[i] = vec[i] +
 /// vec[i+split]^scaler$. The `vec` vector is half of its size after this call.
@@ -9,6 +9,7 @@ use ark_relations::r1cs::{
 use ark_std::rand::RngCore;
 use ark_std::string::String;
 use ark_std::vec::Vec;
+use zeroize::Zeroizing;
 
 use crate::circom::WitnessCalculator;
 
@@ -17,7 +18,8 @@ pub struct CircomCircuit<E: Pairing> {
     pub r1cs: R1CS<E>,
     /// All wires of the circuit, including the public and private input wires as well as the intermediate wires.
     /// The 1st is always "1", followed by public input wires followed by the private (witness) wires.
-    pub wires: Option<Vec<E::ScalarField>>,
+    /// They contain the witnesses so they are zeroized when dropped.
+    pub wires: Option<Zeroizing<Vec<E::ScalarField>>>,
 }
 
 impl<E: Pairing> CircomCircuit<E> {
@@ -57,9 +59,10 @@ impl<'a, E: Pairing> CircomCircuit<E> {
         }
     }
 
-    /// Set values for the circuit wires.
-    pub fn set_wires(&mut self, wires: Vec<E::ScalarField>) {
-        self.wires = Some(wires);
+    /// Set values for the circuit wires. Pass the `Zeroizing` wires returned by
+    /// `WitnessCalculator::calculate_witnesses` to not leave an unzeroized copy of them.
+    pub fn set_wires(&mut self, wires: impl Into<Zeroizing<Vec<E::ScalarField>>>) {
+        self.wires = Some(wires.into());
     }
 
     pub fn set_wires_using_witness_calculator<
@@ -70,8 +73,7 @@ impl<'a, E: Pairing> CircomCircuit<E> {
         inputs: I,
         sanity_check: bool,
     ) -> Result<(), CircomError> {
-        let all_wires = wit_calc.calculate_witnesses::<_>(inputs, sanity_check)?;
-        self.wires = Some(all_wires);
+        self.wires = Some(wit_calc.calculate_witnesses::<_>(inputs, sanity_check)?);
         Ok(())
     }
 }
@@ -147,7 +149,7 @@ pub mod tests {
     pub fn generate_params_and_test_circuit<E: Pairing>(
         r1cs_file_path: &str,
         commit_witness_count: usize,
-        wires: Option<Vec<E::ScalarField>>,
+        wires: Option<Zeroizing<Vec<E::ScalarField>>>,
     ) -> ConstraintSystemRef<E::ScalarField> {
         let mut circuit = CircomCircuit::<E>::from_r1cs_file(abs_path(r1cs_file_path)).unwrap();
 
@@ -195,8 +197,11 @@ pub mod tests {
             commit_witness_count: usize,
             wits: Option<Vec<E::ScalarField>>,
         ) {
-            let cs =
-                generate_params_and_test_circuit::<E>(r1cs_file_path, commit_witness_count, wits);
+            let cs = generate_params_and_test_circuit::<E>(
+                r1cs_file_path,
+                commit_witness_count,
+                wits.map(Zeroizing::new),
+            );
             assert_eq!(cs.num_instance_variables(), 2);
             assert_eq!(cs.num_witness_variables(), 2);
             assert_eq!(cs.num_constraints(), 1);
@@ -229,8 +234,11 @@ pub mod tests {
             commit_witness_count: usize,
             wits: Option<Vec<E::ScalarField>>,
         ) {
-            let cs =
-                generate_params_and_test_circuit::<E>(r1cs_file_path, commit_witness_count, wits);
+            let cs = generate_params_and_test_circuit::<E>(
+                r1cs_file_path,
+                commit_witness_count,
+                wits.map(Zeroizing::new),
+            );
             assert_eq!(cs.num_instance_variables(), 2);
             assert_eq!(cs.num_witness_variables(), 2);
             assert_eq!(cs.num_constraints(), 2);
@@ -262,8 +270,11 @@ pub mod tests {
             commit_witness_count: usize,
             wits: Option<Vec<E::ScalarField>>,
         ) {
-            let cs =
-                generate_params_and_test_circuit::<E>(r1cs_file_path, commit_witness_count, wits);
+            let cs = generate_params_and_test_circuit::<E>(
+                r1cs_file_path,
+                commit_witness_count,
+                wits.map(Zeroizing::new),
+            );
             assert_eq!(cs.num_instance_variables(), 2);
             assert_eq!(cs.num_witness_variables(), 4);
             assert_eq!(cs.num_constraints(), 3);
@@ -296,8 +307,11 @@ pub mod tests {
             commit_witness_count: usize,
             wits: Option<Vec<E::ScalarField>>,
         ) {
-            let cs =
-                generate_params_and_test_circuit::<E>(r1cs_file_path, commit_witness_count, wits);
+            let cs = generate_params_and_test_circuit::<E>(
+                r1cs_file_path,
+                commit_witness_count,
+                wits.map(Zeroizing::new),
+            );
             assert_eq!(cs.num_instance_variables(), 5); // 1 + 2 outputs + 2 public inputs
             assert_eq!(cs.num_witness_variables(), 7);
             assert_eq!(cs.num_constraints(), 5);
@@ -322,8 +336,11 @@ pub mod tests {
             commit_witness_count: usize,
             wits: Option<Vec<E::ScalarField>>,
         ) {
-            let cs =
-                generate_params_and_test_circuit::<E>(r1cs_file_path, commit_witness_count, wits);
+            let cs = generate_params_and_test_circuit::<E>(
+                r1cs_file_path,
+                commit_witness_count,
+                wits.map(Zeroizing::new),
+            );
             assert_eq!(cs.num_instance_variables(), 7); // 1 + 2 outputs + 4 public inputs
         }
 
@@ -456,7 +473,7 @@ pub mod tests {
                 inputs.insert("in2".to_string(), vec![E::ScalarField::from(b)]);
                 set_circuit_wires(&mut circuit, wasm_file_path, inputs.clone());
 
-                let wires = circuit.wires.clone().unwrap();
+                let wires = circuit.wires.take().unwrap();
                 assert!(wires[0].is_one());
                 assert_eq!(wires[1], E::ScalarField::from(q));
                 assert_eq!(wires[2], E::ScalarField::from(r));
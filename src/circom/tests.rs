@@ -57,9 +57,10 @@ pub fn prove_and_verify_circuit<E: Pairing>(
     let public_inputs = circuit.get_public_inputs().unwrap();
     let committed_witnesses = circuit
         .wires
-        .clone()
+        .as_ref()
         .unwrap()
-        .into_iter()
+        .iter()
+        .copied()
         .skip(1 + public_inputs.len())
         .take(commit_witness_count)
         .collect::<Vec<_>>();
@@ -586,7 +587,7 @@ fn average_n_less_than_public<E: Pairing>(r1cs_file_path: &str, wasm_file_path:
     inputs.insert("max".to_string(), vec![E::ScalarField::from(max)]);
 
     set_circuit_wires(&mut circuit, wasm_file_path, inputs.clone());
-    for w in circuit.wires.clone().unwrap().into_iter().take(10) {
+    for w in circuit.wires.as_ref().unwrap().iter().take(10) {
         println!("{:?}", w.into_bigint());
     }
 
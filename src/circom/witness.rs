@@ -11,6 +11,7 @@ use core::hash::Hasher;
 use fnv::FnvHasher;
 use num_bigint::BigUint;
 use wasmer::{imports, Instance, Module, Store};
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::circom::{BLS12_381_ORDER, BN128_ORDER};
 
@@ -116,12 +117,13 @@ impl<E: Pairing> WitnessCalculator<E> {
     /// values of all wires of the circuit. The input wires are a map from the signal name to its
     /// value (values if the signal is an array). The returned wire list will always have 1st wire
     /// with value "1", followed by values of output wires, then the input wires. The order of input
-    /// wires in this list is the same in which the got created in the circuit.
+    /// wires in this list is the same in which the got created in the circuit. The wires are zeroized
+    /// when dropped.
     pub fn calculate_witnesses<I: IntoIterator<Item = (String, Vec<E::ScalarField>)>>(
         &mut self,
         inputs: I,
         sanity_check: bool,
-    ) -> Result<Vec<E::ScalarField>, CircomError> {
+    ) -> Result<Zeroizing<Vec<E::ScalarField>>, CircomError> {
         self.instance.init(sanity_check)?;
         // Field element size in 32-byte chunks
         let field_element_size = self.instance.get_field_num_len32()?;
@@ -132,8 +134,9 @@ impl<E: Pairing> WitnessCalculator<E> {
             let (msb, lsb) = fnv(&name);
 
             let mut seen_signals = 0;
-            for (i, value) in values.into_iter().enumerate() {
-                let f_arr = to_array32::<E>(&value, field_element_size as usize);
+            for (i, mut value) in values.into_iter().enumerate() {
+                let f_arr = Zeroizing::new(to_array32::<E>(&value, field_element_size as usize));
+                value.zeroize();
                 for j in 0..field_element_size {
                     self.instance
                         .write_shared_rw_memory(j as u32, f_arr[j as usize])?;
@@ -161,17 +164,17 @@ impl<E: Pairing> WitnessCalculator<E> {
             ));
         }
 
-        let mut wires = Vec::new();
+        let mut wires = Zeroizing::new(Vec::new());
 
         let witness_size = self.instance.get_witness_count()?;
+        let mut arr = Zeroizing::new(vec![0; field_element_size as usize]);
         for i in 0..witness_size {
             self.instance.get_witness(i)?;
-            let mut arr = vec![0; field_element_size as usize];
             for j in 0..field_element_size {
                 // Reading in little endian with read_shared_rw_memory
                 arr[j as usize] = self.instance.read_shared_rw_memory(j)?;
             }
-            wires.push(from_array32::<E>(arr));
+            wires.push(from_array32::<E>(&arr));
         }
 
         Ok(wires)
@@ -209,12 +212,12 @@ mod runtime {
 }
 
 /// Read a base-{2^32} number given in little-endian format
-fn from_array32<E: Pairing>(arr: Vec<u32>) -> E::ScalarField {
+fn from_array32<E: Pairing>(arr: &[u32]) -> E::ScalarField {
     let mut res = E::ScalarField::from(0 as u64);
     let mut current_multiple = E::ScalarField::from(1 as u64);
     let base = E::ScalarField::from(u32::MAX as u64 + 1);
     for val in arr {
-        res += current_multiple * E::ScalarField::from(val as u64);
+        res += current_multiple * E::ScalarField::from(*val as u64);
         current_multiple.mul_assign(&base);
     }
     res
@@ -224,7 +227,7 @@ fn from_array32<E: Pairing>(arr: Vec<u32>) -> E::ScalarField {
 /// chunk of the input
 fn to_array32<E: Pairing>(s: &E::ScalarField, size: usize) -> Vec<u32> {
     let mut res = vec![0; size as usize];
-    let bytes = s.into_bigint().to_bytes_le();
+    let bytes = Zeroizing::new(s.into_bigint().to_bytes_le());
     let l = bytes.len();
     let mut k = 0;
     for i in (0..l).step_by(4) {
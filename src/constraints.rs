@@ -1,7 +1,11 @@
-use crate::{Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+use crate::{
+    r1cs_to_qap::R1CStoQAP, snark::LegoGroth16, LibsnarkReduction, PreparedVerifyingKey, Proof,
+    VerifyingKey,
+};
 use ark_crypto_primitives::snark::constraints::{CircuitSpecificSetupSNARKGadget, SNARKGadget};
 use ark_crypto_primitives::snark::{BooleanInputVar, SNARK};
-use ark_ec::{AffineRepr, PairingEngine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::Field;
 use ark_r1cs_std::groups::CurveVar;
 use ark_r1cs_std::{
     alloc::{AllocVar, AllocationMode},
@@ -14,25 +18,29 @@ use ark_r1cs_std::{
 use ark_relations::r1cs::{Namespace, SynthesisError};
 use ark_std::{borrow::Borrow, marker::PhantomData, vec::Vec};
 
-/// The proof variable for the Groth16 construction
+type BasePrimeField<E> = <<<E as Pairing>::G1 as CurveGroup>::BaseField as Field>::BasePrimeField;
+
+/// The proof variable for the LegoGroth16 construction
 #[derive(Derivative)]
 #[derivative(Clone(bound = "P::G1Var: Clone, P::G2Var: Clone"))]
-pub struct ProofVar<E: PairingEngine, P: PairingVar<E>> {
+pub struct ProofVar<E: Pairing, P: PairingVar<E, BasePrimeField<E>>> {
     /// The `A` element in `G1`.
     pub a: P::G1Var,
     /// The `B` element in `G2`.
     pub b: P::G2Var,
     /// The `C` element in `G1`.
     pub c: P::G1Var,
+    /// The `D` element in `G1`, the commitment to the committed witnesses.
+    pub d: P::G1Var,
 }
 
-/// A variable representing the Groth16 verifying key in the constraint system.
+/// A variable representing the LegoGroth16 verifying key in the constraint system.
 #[derive(Derivative)]
 #[derivative(
     Clone(bound = "P::G1Var: Clone, P::GTVar: Clone, P::G1PreparedVar: Clone, \
     P::G2PreparedVar: Clone, ")
 )]
-pub struct VerifyingKeyVar<E: PairingEngine, P: PairingVar<E>> {
+pub struct VerifyingKeyVar<E: Pairing, P: PairingVar<E, BasePrimeField<E>>> {
     #[doc(hidden)]
     pub alpha_g1: P::G1Var,
     #[doc(hidden)]
@@ -43,9 +51,13 @@ pub struct VerifyingKeyVar<E: PairingEngine, P: PairingVar<E>> {
     pub delta_g2: P::G2Var,
     #[doc(hidden)]
     pub gamma_abc_g1: Vec<P::G1Var>,
+    #[doc(hidden)]
+    pub eta_gamma_inv_g1: P::G1Var,
+    /// Not a variable as it determines the shape of the verifier's constraints
+    pub commit_witness_count: usize,
 }
 
-impl<E: PairingEngine, P: PairingVar<E>> VerifyingKeyVar<E, P> {
+impl<E: Pairing, P: PairingVar<E, BasePrimeField<E>>> VerifyingKeyVar<E, P> {
     /// Prepare `self` for use in proof verification.
     pub fn prepare(&self) -> Result<PreparedVerifyingKeyVar<E, P>, SynthesisError> {
         let alpha_g1_pc = P::prepare_g1(&self.alpha_g1)?;
@@ -60,17 +72,19 @@ impl<E: PairingEngine, P: PairingVar<E>> VerifyingKeyVar<E, P> {
             gamma_g2_neg_pc,
             delta_g2_neg_pc,
             gamma_abc_g1: self.gamma_abc_g1.clone(),
+            eta_gamma_inv_g1: self.eta_gamma_inv_g1.clone(),
+            commit_witness_count: self.commit_witness_count,
         })
     }
 }
 
-/// Preprocessed verification key parameters variable for the Groth16 construction
+/// Preprocessed verification key parameters variable for the LegoGroth16 construction
 #[derive(Derivative)]
 #[derivative(
     Clone(bound = "P::G1Var: Clone, P::GTVar: Clone, P::G1PreparedVar: Clone, \
     P::G2PreparedVar: Clone, ")
 )]
-pub struct PreparedVerifyingKeyVar<E: PairingEngine, P: PairingVar<E>> {
+pub struct PreparedVerifyingKeyVar<E: Pairing, P: PairingVar<E, BasePrimeField<E>>> {
     #[doc(hidden)]
     pub alpha_g1_beta_g2: P::GTVar,
     #[doc(hidden)]
@@ -79,30 +93,45 @@ pub struct PreparedVerifyingKeyVar<E: PairingEngine, P: PairingVar<E>> {
     pub delta_g2_neg_pc: P::G2PreparedVar,
     #[doc(hidden)]
     pub gamma_abc_g1: Vec<P::G1Var>,
+    #[doc(hidden)]
+    pub eta_gamma_inv_g1: P::G1Var,
+    pub commit_witness_count: usize,
 }
 
-/// Constraints for the verifier of the SNARK of [[Groth16]](https://eprint.iacr.org/2016/260.pdf).
-pub struct Groth16VerifierGadget<E, P>
-where
-    E: PairingEngine,
-    P: PairingVar<E>,
+/// Constraints for the verifier of [`LegoGroth16`], i.e. for verifying a proof over the pairing `E`
+/// in a circuit over the base field of `E`.
+pub struct LegoGroth16VerifierGadget<
+    E,
+    P,
+    QAP = LibsnarkReduction,
+    const COMMIT_WITNESS_COUNT: usize = 0,
+> where
+    E: Pairing,
+    P: PairingVar<E, BasePrimeField<E>>,
+    QAP: R1CStoQAP,
 {
     _pairing_engine: PhantomData<E>,
     _pairing_gadget: PhantomData<P>,
+    _qap: PhantomData<QAP>,
 }
 
-impl<E: PairingEngine, P: PairingVar<E, E::Fq>> SNARKGadget<E::ScalarField, E::Fq, Groth16<E>>
-    for Groth16VerifierGadget<E, P>
+impl<E, P, QAP, const COMMIT_WITNESS_COUNT: usize>
+    SNARKGadget<E::ScalarField, BasePrimeField<E>, LegoGroth16<E, QAP, COMMIT_WITNESS_COUNT>>
+    for LegoGroth16VerifierGadget<E, P, QAP, COMMIT_WITNESS_COUNT>
+where
+    E: Pairing,
+    P: PairingVar<E, BasePrimeField<E>>,
+    QAP: R1CStoQAP,
 {
     type ProcessedVerifyingKeyVar = PreparedVerifyingKeyVar<E, P>;
     type VerifyingKeyVar = VerifyingKeyVar<E, P>;
-    type InputVar = BooleanInputVar<E::ScalarField, E::Fq>;
+    type InputVar = BooleanInputVar<E::ScalarField, BasePrimeField<E>>;
     type ProofVar = ProofVar<E, P>;
 
     type VerifierSize = usize;
 
     fn verifier_size(
-        circuit_vk: &<Groth16<E> as SNARK<E::ScalarField>>::VerifyingKey,
+        circuit_vk: &<LegoGroth16<E, QAP, COMMIT_WITNESS_COUNT> as SNARK<E::ScalarField>>::VerifyingKey,
     ) -> Self::VerifierSize {
         circuit_vk.gamma_abc_g1.len()
     }
@@ -111,7 +140,7 @@ impl<E: PairingEngine, P: PairingVar<E, E::Fq>> SNARKGadget<E::ScalarField, E::F
     /// subgroup checks.
     #[tracing::instrument(target = "r1cs", skip(cs, f))]
     fn new_proof_unchecked<T: Borrow<Proof<E>>>(
-        cs: impl Into<Namespace<E::Fq>>,
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
         f: impl FnOnce() -> Result<T, SynthesisError>,
         mode: AllocationMode,
     ) -> Result<Self::ProofVar, SynthesisError> {
@@ -134,7 +163,12 @@ impl<E: PairingEngine, P: PairingVar<E, E::Fq>> SNARKGadget<E::ScalarField, E::F
                 || Ok(proof.c.into_group()),
                 mode,
             )?;
-            Ok(ProofVar { a, b, c })
+            let d = CurveVar::new_variable_omit_prime_order_check(
+                ark_relations::ns!(cs, "Proof.d"),
+                || Ok(proof.d.into_group()),
+                mode,
+            )?;
+            Ok(ProofVar { a, b, c, d })
         })
     }
 
@@ -142,7 +176,7 @@ impl<E: PairingEngine, P: PairingVar<E, E::Fq>> SNARKGadget<E::ScalarField, E::F
     /// subgroup checks.
     #[tracing::instrument(target = "r1cs", skip(cs, f))]
     fn new_verification_key_unchecked<T: Borrow<VerifyingKey<E>>>(
-        cs: impl Into<Namespace<E::Fq>>,
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
         f: impl FnOnce() -> Result<T, SynthesisError>,
         mode: AllocationMode,
     ) -> Result<Self::VerifyingKeyVar, SynthesisError> {
@@ -181,6 +215,11 @@ impl<E: PairingEngine, P: PairingVar<E, E::Fq>> SNARKGadget<E::ScalarField, E::F
                     )
                 })
                 .collect::<Result<Vec<_>, _>>()?;
+            let eta_gamma_inv_g1 = P::G1Var::new_variable_omit_prime_order_check(
+                ark_relations::ns!(cs, "eta_gamma_inv_g1"),
+                || Ok(vk.eta_gamma_inv_g1.into_group()),
+                mode,
+            )?;
 
             Ok(VerifyingKeyVar {
                 alpha_g1,
@@ -188,34 +227,42 @@ impl<E: PairingEngine, P: PairingVar<E, E::Fq>> SNARKGadget<E::ScalarField, E::F
                 gamma_g2,
                 delta_g2,
                 gamma_abc_g1,
+                eta_gamma_inv_g1,
+                commit_witness_count: vk.commit_witness_count,
             })
         })
     }
 
+    /// Same as `verify_proof`. The last `commit_witness_count` elements of `gamma_abc_g1` are not used
+    /// as the committed witnesses are in `proof.d`.
     #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof))]
     fn verify_with_processed_vk(
         circuit_pvk: &Self::ProcessedVerifyingKeyVar,
         x: &Self::InputVar,
         proof: &Self::ProofVar,
-    ) -> Result<Boolean<E::Fq>, SynthesisError> {
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
         let circuit_pvk = circuit_pvk.clone();
 
+        let num_public_inputs = circuit_pvk
+            .gamma_abc_g1
+            .len()
+            .checked_sub(1 + circuit_pvk.commit_witness_count)
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+        let public_inputs = x.clone().into_iter().collect::<Vec<_>>();
+        if public_inputs.len() != num_public_inputs {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
         let g_ic = {
             let mut g_ic: P::G1Var = circuit_pvk.gamma_abc_g1[0].clone();
-            let mut input_len = 1;
-            let mut public_inputs = x.clone().into_iter();
             for (input, b) in public_inputs
-                .by_ref()
+                .iter()
                 .zip(circuit_pvk.gamma_abc_g1.iter().skip(1))
             {
                 let encoded_input_i: P::G1Var = b.scalar_mul_le(input.to_bits_le()?.iter())?;
                 g_ic += encoded_input_i;
-                input_len += 1;
             }
-            // Check that the input and the query in the verification are of the
-            // same length.
-            assert!(input_len == circuit_pvk.gamma_abc_g1.len() && public_inputs.next().is_none());
-            g_ic
+            g_ic + &proof.d
         };
 
         let test_exp = {
@@ -226,17 +273,21 @@ impl<E: PairingEngine, P: PairingVar<E, E::Fq>> SNARKGadget<E::ScalarField, E::F
             let g_ic_prep = P::prepare_g1(&g_ic)?;
 
             P::miller_loop(
-                &[proof_a_prep, g_ic_prep, proof_c_prep],
+                &[proof_a_prep, proof_c_prep, g_ic_prep],
                 &[
                     proof_b_prep,
-                    circuit_pvk.gamma_g2_neg_pc.clone(),
                     circuit_pvk.delta_g2_neg_pc.clone(),
+                    circuit_pvk.gamma_g2_neg_pc.clone(),
                 ],
             )?
         };
 
         let test = P::final_exponentiation(&test_exp)?;
-        test.is_eq(&circuit_pvk.alpha_g1_beta_g2)
+        let valid = test.is_eq(&circuit_pvk.alpha_g1_beta_g2)?;
+
+        // A key without `eta`, like one converted from Groth16, cannot have a commitment in the proof
+        let has_eta = circuit_pvk.eta_gamma_inv_g1.is_zero()?.not();
+        valid.and(&has_eta.or(&proof.d.is_zero()?)?)
     }
 
     #[tracing::instrument(target = "r1cs", skip(circuit_vk, x, proof))]
@@ -244,27 +295,33 @@ impl<E: PairingEngine, P: PairingVar<E, E::Fq>> SNARKGadget<E::ScalarField, E::F
         circuit_vk: &Self::VerifyingKeyVar,
         x: &Self::InputVar,
         proof: &Self::ProofVar,
-    ) -> Result<Boolean<E::Fq>, SynthesisError> {
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
         let pvk = circuit_vk.prepare()?;
         Self::verify_with_processed_vk(&pvk, x, proof)
     }
 }
 
-impl<E, P> CircuitSpecificSetupSNARKGadget<E::ScalarField, E::Fq, Groth16<E>> for Groth16VerifierGadget<E, P>
+impl<E, P, QAP, const COMMIT_WITNESS_COUNT: usize>
+    CircuitSpecificSetupSNARKGadget<
+        E::ScalarField,
+        BasePrimeField<E>,
+        LegoGroth16<E, QAP, COMMIT_WITNESS_COUNT>,
+    > for LegoGroth16VerifierGadget<E, P, QAP, COMMIT_WITNESS_COUNT>
 where
-    E: PairingEngine,
-    P: PairingVar<E, E::Fq>,
+    E: Pairing,
+    P: PairingVar<E, BasePrimeField<E>>,
+    QAP: R1CStoQAP,
 {
 }
 
-impl<E, P> AllocVar<PreparedVerifyingKey<E>, E::Fq> for PreparedVerifyingKeyVar<E, P>
+impl<E, P> AllocVar<PreparedVerifyingKey<E>, BasePrimeField<E>> for PreparedVerifyingKeyVar<E, P>
 where
-    E: PairingEngine,
-    P: PairingVar<E>,
+    E: Pairing,
+    P: PairingVar<E, BasePrimeField<E>>,
 {
     #[tracing::instrument(target = "r1cs", skip(cs, f))]
     fn new_variable<T: Borrow<PreparedVerifyingKey<E>>>(
-        cs: impl Into<Namespace<E::Fq>>,
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
         f: impl FnOnce() -> Result<T, SynthesisError>,
         mode: AllocationMode,
     ) -> Result<Self, SynthesisError> {
@@ -275,7 +332,7 @@ where
             let pvk = pvk.borrow();
             let alpha_g1_beta_g2 = P::GTVar::new_variable(
                 ark_relations::ns!(cs, "alpha_g1_beta_g2"),
-                || Ok(pvk.alpha_g1_beta_g2.clone()),
+                || Ok(pvk.alpha_g1_beta_g2.0),
                 mode,
             )?;
 
@@ -293,7 +350,20 @@ where
 
             let gamma_abc_g1 = Vec::new_variable(
                 ark_relations::ns!(cs, "gamma_abc_g1"),
-                || Ok(pvk.vk.gamma_abc_g1.clone()),
+                || {
+                    Ok(pvk
+                        .vk
+                        .gamma_abc_g1
+                        .iter()
+                        .map(|g| g.into_group())
+                        .collect::<Vec<_>>())
+                },
+                mode,
+            )?;
+
+            let eta_gamma_inv_g1 = P::G1Var::new_variable(
+                ark_relations::ns!(cs, "eta_gamma_inv_g1"),
+                || Ok(pvk.vk.eta_gamma_inv_g1.into_group()),
                 mode,
             )?;
 
@@ -302,19 +372,21 @@ where
                 gamma_g2_neg_pc,
                 delta_g2_neg_pc,
                 gamma_abc_g1,
+                eta_gamma_inv_g1,
+                commit_witness_count: pvk.vk.commit_witness_count,
             })
         })
     }
 }
 
-impl<E, P> AllocVar<VerifyingKey<E>, E::Fq> for VerifyingKeyVar<E, P>
+impl<E, P> AllocVar<VerifyingKey<E>, BasePrimeField<E>> for VerifyingKeyVar<E, P>
 where
-    E: PairingEngine,
-    P: PairingVar<E>,
+    E: Pairing,
+    P: PairingVar<E, BasePrimeField<E>>,
 {
     #[tracing::instrument(target = "r1cs", skip(cs, f))]
     fn new_variable<T: Borrow<VerifyingKey<E>>>(
-        cs: impl Into<Namespace<E::Fq>>,
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
         f: impl FnOnce() -> Result<T, SynthesisError>,
         mode: AllocationMode,
     ) -> Result<Self, SynthesisError> {
@@ -328,36 +400,66 @@ where
                 gamma_g2,
                 delta_g2,
                 gamma_abc_g1,
+                eta_gamma_inv_g1,
+                commit_witness_count,
             } = vk.borrow().clone();
-            let alpha_g1 =
-                P::G1Var::new_variable(ark_relations::ns!(cs, "alpha_g1"), || Ok(alpha_g1), mode)?;
-            let beta_g2 =
-                P::G2Var::new_variable(ark_relations::ns!(cs, "beta_g2"), || Ok(beta_g2), mode)?;
-            let gamma_g2 =
-                P::G2Var::new_variable(ark_relations::ns!(cs, "gamma_g2"), || Ok(gamma_g2), mode)?;
-            let delta_g2 =
-                P::G2Var::new_variable(ark_relations::ns!(cs, "delta_g2"), || Ok(delta_g2), mode)?;
-
-            let gamma_abc_g1 = Vec::new_variable(cs.clone(), || Ok(gamma_abc_g1), mode)?;
+            let alpha_g1 = P::G1Var::new_variable(
+                ark_relations::ns!(cs, "alpha_g1"),
+                || Ok(alpha_g1.into_group()),
+                mode,
+            )?;
+            let beta_g2 = P::G2Var::new_variable(
+                ark_relations::ns!(cs, "beta_g2"),
+                || Ok(beta_g2.into_group()),
+                mode,
+            )?;
+            let gamma_g2 = P::G2Var::new_variable(
+                ark_relations::ns!(cs, "gamma_g2"),
+                || Ok(gamma_g2.into_group()),
+                mode,
+            )?;
+            let delta_g2 = P::G2Var::new_variable(
+                ark_relations::ns!(cs, "delta_g2"),
+                || Ok(delta_g2.into_group()),
+                mode,
+            )?;
+
+            let gamma_abc_g1 = Vec::new_variable(
+                cs.clone(),
+                || {
+                    Ok(gamma_abc_g1
+                        .iter()
+                        .map(|g| g.into_group())
+                        .collect::<Vec<_>>())
+                },
+                mode,
+            )?;
+            let eta_gamma_inv_g1 = P::G1Var::new_variable(
+                ark_relations::ns!(cs, "eta_gamma_inv_g1"),
+                || Ok(eta_gamma_inv_g1.into_group()),
+                mode,
+            )?;
             Ok(Self {
                 alpha_g1,
                 beta_g2,
                 gamma_g2,
                 delta_g2,
                 gamma_abc_g1,
+                eta_gamma_inv_g1,
+                commit_witness_count,
             })
         })
     }
 }
 
-impl<E, P> AllocVar<Proof<E>, E::Fq> for ProofVar<E, P>
+impl<E, P> AllocVar<Proof<E>, BasePrimeField<E>> for ProofVar<E, P>
 where
-    E: PairingEngine,
-    P: PairingVar<E>,
+    E: Pairing,
+    P: PairingVar<E, BasePrimeField<E>>,
 {
     #[tracing::instrument(target = "r1cs", skip(cs, f))]
     fn new_variable<T: Borrow<Proof<E>>>(
-        cs: impl Into<Namespace<E::Fq>>,
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
         f: impl FnOnce() -> Result<T, SynthesisError>,
         mode: AllocationMode,
     ) -> Result<Self, SynthesisError> {
@@ -365,23 +467,28 @@ where
         let cs = ns.cs();
 
         f().and_then(|proof| {
-            let Proof { a, b, c } = proof.borrow().clone();
-            let a = P::G1Var::new_variable(ark_relations::ns!(cs, "a"), || Ok(a), mode)?;
-            let b = P::G2Var::new_variable(ark_relations::ns!(cs, "b"), || Ok(b), mode)?;
-            let c = P::G1Var::new_variable(ark_relations::ns!(cs, "c"), || Ok(c), mode)?;
-            Ok(Self { a, b, c })
+            let Proof { a, b, c, d } = proof.borrow().clone();
+            let a =
+                P::G1Var::new_variable(ark_relations::ns!(cs, "a"), || Ok(a.into_group()), mode)?;
+            let b =
+                P::G2Var::new_variable(ark_relations::ns!(cs, "b"), || Ok(b.into_group()), mode)?;
+            let c =
+                P::G1Var::new_variable(ark_relations::ns!(cs, "c"), || Ok(c.into_group()), mode)?;
+            let d =
+                P::G1Var::new_variable(ark_relations::ns!(cs, "d"), || Ok(d.into_group()), mode)?;
+            Ok(Self { a, b, c, d })
         })
     }
 }
 
-impl<E, P> ToBytesGadget<E::Fq> for VerifyingKeyVar<E, P>
+impl<E, P> ToBytesGadget<BasePrimeField<E>> for VerifyingKeyVar<E, P>
 where
-    E: PairingEngine,
-    P: PairingVar<E>,
+    E: Pairing,
+    P: PairingVar<E, BasePrimeField<E>>,
 {
     #[inline]
     #[tracing::instrument(target = "r1cs", skip(self))]
-    fn to_bytes(&self) -> Result<Vec<UInt8<E::Fq>>, SynthesisError> {
+    fn to_bytes(&self) -> Result<Vec<UInt8<BasePrimeField<E>>>, SynthesisError> {
         let mut bytes = Vec::new();
         bytes.extend_from_slice(&self.alpha_g1.to_bytes()?);
         bytes.extend_from_slice(&self.beta_g2.to_bytes()?);
@@ -390,23 +497,45 @@ where
         for g in &self.gamma_abc_g1 {
             bytes.extend_from_slice(&g.to_bytes()?);
         }
+        bytes.extend_from_slice(&self.eta_gamma_inv_g1.to_bytes()?);
+        bytes.extend_from_slice(&UInt8::constant_vec(
+            &(self.commit_witness_count as u64).to_le_bytes(),
+        ));
+        Ok(bytes)
+    }
+}
+
+impl<E, P> ToBytesGadget<BasePrimeField<E>> for ProofVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E, BasePrimeField<E>>,
+{
+    #[inline]
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn to_bytes(&self) -> Result<Vec<UInt8<BasePrimeField<E>>>, SynthesisError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.a.to_bytes()?);
+        bytes.extend_from_slice(&self.b.to_bytes()?);
+        bytes.extend_from_slice(&self.c.to_bytes()?);
+        bytes.extend_from_slice(&self.d.to_bytes()?);
         Ok(bytes)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{constraints::Groth16VerifierGadget, Groth16};
+    use super::{BasePrimeField, LegoGroth16VerifierGadget};
+    use crate::snark::LegoGroth16;
+    use crate::LibsnarkReduction;
     use ark_crypto_primitives::snark::constraints::SNARKGadget;
     use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
-    use ark_ec::pairing::Pairing;
+    use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
     use ark_ff::{Field, UniformRand};
-    use ark_mnt4_298::{
-        constraints::PairingVar as MNT4PairingVar, Fr as MNT4Fr, MNT4_298 as MNT4PairingEngine,
-    };
-    use ark_mnt6_298::Fr as MNT6Fr;
+    use ark_mnt4_298::{constraints::PairingVar as MNT4PairingVar, Fr as MNT4Fr, MNT4_298};
+    use ark_mnt6_298::{Fr as MNT6Fr, MNT6_298};
     use ark_r1cs_std::bits::boolean::Boolean;
-    use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget};
+    use ark_r1cs_std::pairing::PairingVar;
+    use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, R1CSVar, ToBytesGadget};
     use ark_relations::{
         lc, ns,
         r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError},
@@ -449,62 +578,61 @@ mod test {
         }
     }
 
-    type TestSNARK = Groth16<MNT4PairingEngine>;
-    type TestSNARKGadget = Groth16VerifierGadget<MNT4PairingEngine, MNT4PairingVar>;
+    /// Commits to `a` and `b` in the proof
+    type TestSNARK<E> = LegoGroth16<E, LibsnarkReduction, 2>;
+    type TestSNARKGadget<E, P> = LegoGroth16VerifierGadget<E, P, LibsnarkReduction, 2>;
 
-    #[test]
-    fn groth16_snark_test() {
+    fn legogroth16_snark_test<E, P>()
+    where
+        E: Pairing,
+        P: PairingVar<E, BasePrimeField<E>>,
+    {
         let mut rng = StdRng::seed_from_u64(0u64);
-        let a = MNT4Fr::rand(&mut rng);
-        let b = MNT4Fr::rand(&mut rng);
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
         let mut c = a;
         c.mul_assign(&b);
 
         let circ = Circuit {
-            a: Some(a.clone()),
-            b: Some(b.clone()),
+            a: Some(a),
+            b: Some(b),
             num_constraints: 100,
             num_variables: 25,
         };
 
-        let (pk, vk) = TestSNARK::setup(circ, &mut rng).unwrap();
+        let (pk, vk) = TestSNARK::<E>::setup(circ, &mut rng).unwrap();
 
-        let proof = TestSNARK::prove(&pk, circ.clone(), &mut rng).unwrap();
+        let proof = TestSNARK::<E>::prove(&pk, circ, &mut rng).unwrap();
 
         assert!(
-            TestSNARK::verify(&vk, &vec![c], &proof).unwrap(),
+            TestSNARK::<E>::verify(&vk, &[c], &proof).unwrap(),
             "The native verification check fails."
         );
 
-        let cs_sys = ConstraintSystem::<MNT6Fr>::new();
-        let cs = ConstraintSystemRef::new(cs_sys);
+        let cs = ConstraintSystem::<BasePrimeField<E>>::new_ref();
 
-        let input_gadget = <TestSNARKGadget as SNARKGadget<
-            <MNT4PairingEngine as PairingEngine>::Fr,
-            <MNT4PairingEngine as PairingEngine>::Fq,
-            TestSNARK,
-        >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![c]))
-        .unwrap();
-        let proof_gadget = <TestSNARKGadget as SNARKGadget<
-            <MNT4PairingEngine as PairingEngine>::Fr,
-            <MNT4PairingEngine as PairingEngine>::Fq,
-            TestSNARK,
-        >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof))
-        .unwrap();
-        let vk_gadget = <TestSNARKGadget as SNARKGadget<
-            <MNT4PairingEngine as PairingEngine>::Fr,
-            <MNT4PairingEngine as PairingEngine>::Fq,
-            TestSNARK,
-        >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), vk.clone())
-        .unwrap();
-        <TestSNARKGadget as SNARKGadget<
-            <MNT4PairingEngine as PairingEngine>::Fr,
-            <MNT4PairingEngine as PairingEngine>::Fq,
-            TestSNARK,
-        >>::verify(&vk_gadget, &input_gadget, &proof_gadget)
-        .unwrap()
-        .enforce_equal(&Boolean::constant(true))
-        .unwrap();
+        let input_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::InputVar::new_input(
+                ns!(cs, "new_input"),
+                || Ok(vec![c]),
+            )
+            .unwrap();
+        let proof_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::ProofVar::new_witness(
+                ns!(cs, "alloc_proof"),
+                || Ok(proof.clone()),
+            )
+            .unwrap();
+        let vk_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::VerifyingKeyVar::new_constant(
+                ns!(cs, "alloc_vk"),
+                vk.clone(),
+            )
+            .unwrap();
+        TestSNARKGadget::<E, P>::verify(&vk_gadget, &input_gadget, &proof_gadget)
+            .unwrap()
+            .enforce_equal(&Boolean::constant(true))
+            .unwrap();
 
         assert!(
             cs.is_satisfied().unwrap(),
@@ -512,24 +640,203 @@ mod test {
             cs.which_is_unsatisfied().unwrap().unwrap_or_default()
         );
 
-        let pvk = TestSNARK::process_vk(&vk).unwrap();
-        let pvk_gadget = <TestSNARKGadget as SNARKGadget<
-            <MNT4PairingEngine as PairingEngine>::Fr,
-            <MNT4PairingEngine as PairingEngine>::Fq,
-            TestSNARK,
-        >>::ProcessedVerifyingKeyVar::new_constant(
-            ns!(cs, "alloc_pvk"), pvk.clone()
+        let pvk = TestSNARK::<E>::process_vk(&vk).unwrap();
+        let pvk_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::ProcessedVerifyingKeyVar::new_constant(
+                ns!(cs, "alloc_pvk"),
+                pvk,
+            )
+            .unwrap();
+        TestSNARKGadget::<E, P>::verify_with_processed_vk(
+            &pvk_gadget,
+            &input_gadget,
+            &proof_gadget,
         )
+        .unwrap()
+        .enforce_equal(&Boolean::constant(true))
         .unwrap();
-        TestSNARKGadget::verify_with_processed_vk(&pvk_gadget, &input_gadget, &proof_gadget)
-            .unwrap()
-            .enforce_equal(&Boolean::constant(true))
-            .unwrap();
 
         assert!(
             cs.is_satisfied().unwrap(),
             "Constraints not satisfied: {}",
             cs.which_is_unsatisfied().unwrap().unwrap_or_default()
         );
+
+        // The proof's bytes in the circuit are its elements' bytes
+        let vk_witness =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::VerifyingKeyVar::new_witness(
+                ns!(cs, "alloc_vk_witness"),
+                || Ok(vk.clone()),
+            )
+            .unwrap();
+        assert_eq!(
+            vk_witness.to_bytes().unwrap().value().unwrap(),
+            vk_gadget.to_bytes().unwrap().value().unwrap()
+        );
+        assert!(!proof_gadget.to_bytes().unwrap().is_empty());
+
+        // Wrong public input
+        let cs = ConstraintSystem::<BasePrimeField<E>>::new_ref();
+        let input_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::InputVar::new_input(
+                ns!(cs, "new_input"),
+                || Ok(vec![c + E::ScalarField::from(1u64)]),
+            )
+            .unwrap();
+        let proof_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::ProofVar::new_witness(
+                ns!(cs, "alloc_proof"),
+                || Ok(proof.clone()),
+            )
+            .unwrap();
+        let vk_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::VerifyingKeyVar::new_constant(
+                ns!(cs, "alloc_vk"),
+                vk.clone(),
+            )
+            .unwrap();
+        assert!(
+            !TestSNARKGadget::<E, P>::verify(&vk_gadget, &input_gadget, &proof_gadget)
+                .unwrap()
+                .value()
+                .unwrap()
+        );
+
+        // Commitment to other witnesses
+        let mut tampered_proof = proof.clone();
+        tampered_proof.d = (tampered_proof.d + E::G1Affine::generator()).into_affine();
+        let cs = ConstraintSystem::<BasePrimeField<E>>::new_ref();
+        let input_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::InputVar::new_input(
+                ns!(cs, "new_input"),
+                || Ok(vec![c]),
+            )
+            .unwrap();
+        let proof_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::ProofVar::new_witness(
+                ns!(cs, "alloc_proof"),
+                || Ok(tampered_proof),
+            )
+            .unwrap();
+        let vk_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::VerifyingKeyVar::new_constant(
+                ns!(cs, "alloc_vk"),
+                vk.clone(),
+            )
+            .unwrap();
+        assert!(
+            !TestSNARKGadget::<E, P>::verify(&vk_gadget, &input_gadget, &proof_gadget)
+                .unwrap()
+                .value()
+                .unwrap()
+        );
+
+        // The number of public inputs must match the key
+        let input_gadget =
+            <TestSNARKGadget<E, P> as SNARKGadget<_, _, TestSNARK<E>>>::InputVar::new_input(
+                ns!(cs, "new_input"),
+                || Ok(vec![c, c]),
+            )
+            .unwrap();
+        assert_eq!(
+            TestSNARKGadget::<E, P>::verify(&vk_gadget, &input_gadget, &proof_gadget).unwrap_err(),
+            SynthesisError::MalformedVerifyingKey
+        );
+    }
+
+    #[test]
+    fn legogroth16_snark_test_mnt4_298() {
+        legogroth16_snark_test::<MNT4_298, MNT4PairingVar>();
+    }
+
+    #[test]
+    fn legogroth16_snark_test_bls12_377() {
+        legogroth16_snark_test::<ark_bls12_377::Bls12_377, ark_bls12_377::constraints::PairingVar>(
+        );
+    }
+
+    /// Proves over MNT6-298 that a proof over MNT4-298 verifies for the public input `c`
+    #[derive(Clone)]
+    struct VerifierCircuit {
+        vk: <TestSNARK<MNT4_298> as SNARK<MNT4Fr>>::VerifyingKey,
+        proof: <TestSNARK<MNT4_298> as SNARK<MNT4Fr>>::Proof,
+        c: MNT4Fr,
+    }
+
+    impl ConstraintSynthesizer<MNT6Fr> for VerifierCircuit {
+        fn generate_constraints(
+            self,
+            cs: ConstraintSystemRef<MNT6Fr>,
+        ) -> Result<(), SynthesisError> {
+            let input_gadget =
+                <TestSNARKGadget<MNT4_298, MNT4PairingVar> as SNARKGadget<
+                    _,
+                    _,
+                    TestSNARK<MNT4_298>,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![self.c]))?;
+            let proof_gadget = <TestSNARKGadget<MNT4_298, MNT4PairingVar> as SNARKGadget<
+                _,
+                _,
+                TestSNARK<MNT4_298>,
+            >>::ProofVar::new_witness(
+                ns!(cs, "alloc_proof"), || Ok(self.proof)
+            )?;
+            let vk_gadget =
+                <TestSNARKGadget<MNT4_298, MNT4PairingVar> as SNARKGadget<
+                    _,
+                    _,
+                    TestSNARK<MNT4_298>,
+                >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), self.vk)?;
+            TestSNARKGadget::<MNT4_298, MNT4PairingVar>::verify(
+                &vk_gadget,
+                &input_gadget,
+                &proof_gadget,
+            )?
+            .enforce_equal(&Boolean::constant(true))
+        }
+    }
+
+    /// The public inputs of the outer proof, i.e. the inner public inputs packed in the outer field
+    fn outer_public_inputs(circuit: VerifierCircuit) -> Vec<MNT6Fr> {
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        let inputs = cs.borrow().unwrap().instance_assignment[1..].to_vec();
+        inputs
+    }
+
+    #[test]
+    fn recursion_mnt4_298_in_mnt6_298() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let c = a * b;
+
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 10,
+            num_variables: 5,
+        };
+        let (inner_pk, inner_vk) = TestSNARK::<MNT4_298>::setup(circ, &mut rng).unwrap();
+        let inner_proof = TestSNARK::<MNT4_298>::prove(&inner_pk, circ, &mut rng).unwrap();
+
+        let verifier_circuit = VerifierCircuit {
+            vk: inner_vk,
+            proof: inner_proof,
+            c,
+        };
+        let (outer_pk, outer_vk) =
+            LegoGroth16::<MNT6_298>::setup(verifier_circuit.clone(), &mut rng).unwrap();
+        let outer_proof =
+            LegoGroth16::<MNT6_298>::prove(&outer_pk, verifier_circuit.clone(), &mut rng).unwrap();
+
+        let outer_inputs = outer_public_inputs(verifier_circuit);
+        assert!(LegoGroth16::<MNT6_298>::verify(&outer_vk, &outer_inputs, &outer_proof).unwrap());
+
+        // The outer proof is for the inner public input `c` only
+        let mut other_inputs = outer_inputs;
+        other_inputs[0] += MNT6Fr::from(1u64);
+        assert!(!LegoGroth16::<MNT6_298>::verify(&outer_vk, &other_inputs, &outer_proof).unwrap());
     }
 }
@@ -1,4 +1,4 @@
-use crate::link::{EK, PP, VK};
+use crate::link::{LinkSnark, PESubspaceSnark, PP};
 use ark_ec::pairing::{Pairing, PairingOutput};
 use ark_serialize::*;
 use ark_std::vec::Vec;
@@ -16,14 +16,15 @@ pub struct Proof<E: Pairing> {
     pub d: E::G1Affine,
 }
 
-/// A proof in the Groth16 SNARK with CP_link proof
+/// A proof in the Groth16 SNARK with CP_link proof. `L` is the Subspace Snark used for proving the
+/// equality of openings of `cp_{link}` and `d`
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct ProofWithLink<E: Pairing> {
+pub struct ProofWithLink<E: Pairing, L: LinkSnark<E> = PESubspaceSnark<E>> {
     pub groth16_proof: Proof<E>,
-    /// cp_{link}
-    pub link_d: E::G1Affine,
-    /// proof of commitment opening equality between `cp_{link}` and `d`
-    pub link_pi: E::G1Affine,
+    /// cp_{link}s, 1 for each link commitment key in the verifying key
+    pub link_d: Vec<E::G1Affine>,
+    /// proof of commitment opening equality between all `cp_{link}`s and `d`
+    pub link_pi: L::Proof,
 }
 
 impl<E: Pairing> Default for Proof<E> {
@@ -37,12 +38,12 @@ impl<E: Pairing> Default for Proof<E> {
     }
 }
 
-impl<E: Pairing> Default for ProofWithLink<E> {
+impl<E: Pairing, L: LinkSnark<E>> Default for ProofWithLink<E, L> {
     fn default() -> Self {
         Self {
             groth16_proof: Proof::default(),
-            link_pi: E::G1Affine::default(),
-            link_d: E::G1Affine::default(),
+            link_pi: L::Proof::default(),
+            link_d: Vec::new(),
         }
     }
 }
@@ -71,14 +72,18 @@ pub struct VerifyingKey<E: Pairing> {
 
 /// A verification key in the Groth16 SNARK with CP_link verification parameters
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct VerifyingKeyWithLink<E: Pairing> {
+pub struct VerifyingKeyWithLink<E: Pairing, L: LinkSnark<E> = PESubspaceSnark<E>> {
     pub groth16_vk: VerifyingKey<E>,
     /// Public parameters of the Subspace Snark
     pub link_pp: PP<E::G1Affine, E::G2Affine>,
-    /// Commitment key of the link commitment cp_link
-    pub link_bases: Vec<E::G1Affine>,
+    /// Commitment keys of the link commitments cp_link, 1 per link commitment. The last base of
+    /// each key is for the randomness.
+    pub link_bases: Vec<Vec<E::G1Affine>>,
+    /// Range of the committed witnesses as `(start, end)`, exclusive of `end`, committed in each link
+    /// commitment
+    pub link_witness_ranges: Vec<(usize, usize)>,
     /// Verification key of the Subspace Snark
-    pub link_vk: VK<E::G2Affine>,
+    pub link_vk: L::VK,
 }
 
 impl<E: Pairing> Default for VerifyingKey<E> {
@@ -95,13 +100,14 @@ impl<E: Pairing> Default for VerifyingKey<E> {
     }
 }
 
-impl<E: Pairing> Default for VerifyingKeyWithLink<E> {
+impl<E: Pairing, L: LinkSnark<E>> Default for VerifyingKeyWithLink<E, L> {
     fn default() -> Self {
         Self {
             groth16_vk: VerifyingKey::default(),
             link_pp: PP::<E::G1Affine, E::G2Affine>::default(),
             link_bases: Vec::new(),
-            link_vk: VK::<E::G2Affine>::default(),
+            link_witness_ranges: Vec::new(),
+            link_vk: L::VK::default(),
         }
     }
 }
@@ -177,12 +183,12 @@ pub struct ProvingKey<E: Pairing> {
 
 /// The prover key for for the Groth16 zkSNARK with CP_link parameters
 #[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct ProvingKeyWithLink<E: Pairing> {
+pub struct ProvingKeyWithLink<E: Pairing, L: LinkSnark<E> = PESubspaceSnark<E>> {
     /// The underlying verification key.
-    pub vk: VerifyingKeyWithLink<E>,
+    pub vk: VerifyingKeyWithLink<E, L>,
     pub common: ProvingKeyCommon<E>,
     /// Evaluation key of cp_{link}
-    pub link_ek: EK<E::G1Affine>,
+    pub link_ek: L::EK,
 }
 
 /// Public parameters for CP link
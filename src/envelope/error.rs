@@ -0,0 +1,29 @@
+use ark_serialize::SerializationError;
+use ark_std::string::{String, ToString};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EnvelopeError {
+    /// The bytes do not start with `MAGIC`
+    InvalidMagic,
+    UnsupportedFormatVersion(u8),
+    /// The payload was written with a layout this version of the crate cannot read
+    UnsupportedLayoutVersion(u16),
+    /// The object was written for another curve, i.e. (found, expected) curve ids
+    CurveMismatch([u8; 8], [u8; 8]),
+    /// The object has another type than the one being read, i.e. (found, expected) object types. For
+    /// keys and proofs with CP_link of another Subspace Snark, these are the (found, expected)
+    /// `SubspaceSnark::ID`s, 0 if none is expected.
+    ObjectTypeMismatch(u8, u8),
+    /// The bytes are shorter or longer than the header says
+    InvalidLength,
+    InvalidChecksum,
+    /// The metadata in the header does not match the object
+    MetadataMismatch,
+    Serialization(String),
+}
+
+impl From<SerializationError> for EnvelopeError {
+    fn from(e: SerializationError) -> Self {
+        Self::Serialization(e.to_string())
+    }
+}
@@ -0,0 +1,77 @@
+//! Layouts of objects written by version 0.6 of this crate, before keys and proofs could have several link
+//! commitments. The keys and proofs with CP_link had a single link commitment over all committed witnesses,
+//! the other objects have the same layout as now. The link matrix of such a key is the one now created for
+//! a single link commitment over `0..commit_witness_count`, so the converted keys verify the old proofs.
+//!
+//! `test-vectors/legacy/v0.6` has keys and proofs written by 0.6 which the tests read with these layouts.
+//! Reading the layouts of 0.5 is not implemented. No 0.5 of this crate was released, crates.io has 0.4.0
+//! and then 0.6.0, so there are no files written by 0.5 to generate test vectors from, and a reader that
+//! has not been checked against real files could misread a key without any error. The same holds for
+//! the versions before. Objects written before 0.6 have to be read with the version that wrote them and
+//! converted from there.
+
+use crate::{
+    link::{LinkSnark, PESubspaceSnark, PP},
+    Proof, ProofWithLink, ProvingKeyCommon, ProvingKeyWithLink, VerifyingKey, VerifyingKeyWithLink,
+};
+use ark_ec::pairing::Pairing;
+use ark_serialize::*;
+use ark_std::{vec, vec::Vec};
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofWithLinkV0_6<E: Pairing, L: LinkSnark<E> = PESubspaceSnark<E>> {
+    pub groth16_proof: Proof<E>,
+    pub link_d: E::G1Affine,
+    pub link_pi: L::Proof,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifyingKeyWithLinkV0_6<E: Pairing, L: LinkSnark<E> = PESubspaceSnark<E>> {
+    pub groth16_vk: VerifyingKey<E>,
+    pub link_pp: PP<E::G1Affine, E::G2Affine>,
+    /// Commitment key of the link commitment, the last base is for the randomness
+    pub link_bases: Vec<E::G1Affine>,
+    pub link_vk: L::VK,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProvingKeyWithLinkV0_6<E: Pairing, L: LinkSnark<E> = PESubspaceSnark<E>> {
+    pub vk: VerifyingKeyWithLinkV0_6<E, L>,
+    pub common: ProvingKeyCommon<E>,
+    pub link_ek: L::EK,
+}
+
+impl<E: Pairing, L: LinkSnark<E>> From<ProofWithLinkV0_6<E, L>> for ProofWithLink<E, L> {
+    fn from(proof: ProofWithLinkV0_6<E, L>) -> Self {
+        Self {
+            groth16_proof: proof.groth16_proof,
+            link_d: vec![proof.link_d],
+            link_pi: proof.link_pi,
+        }
+    }
+}
+
+impl<E: Pairing, L: LinkSnark<E>> From<VerifyingKeyWithLinkV0_6<E, L>>
+    for VerifyingKeyWithLink<E, L>
+{
+    fn from(vk: VerifyingKeyWithLinkV0_6<E, L>) -> Self {
+        let commit_witness_count = vk.groth16_vk.commit_witness_count;
+        Self {
+            groth16_vk: vk.groth16_vk,
+            link_pp: vk.link_pp,
+            link_bases: vec![vk.link_bases],
+            link_witness_ranges: vec![(0, commit_witness_count)],
+            link_vk: vk.link_vk,
+        }
+    }
+}
+
+impl<E: Pairing, L: LinkSnark<E>> From<ProvingKeyWithLinkV0_6<E, L>> for ProvingKeyWithLink<E, L> {
+    fn from(pk: ProvingKeyWithLinkV0_6<E, L>) -> Self {
+        Self {
+            vk: pk.vk.into(),
+            common: pk.common,
+            link_ek: pk.link_ek,
+        }
+    }
+}
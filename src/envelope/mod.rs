@@ -0,0 +1,434 @@
+//! A self-describing format for storing keys, proofs and SRS. The object's canonical serialization is
+//! wrapped as
+//!
+//! `MAGIC || format version (1 byte) || header || payload || checksum (32 bytes)`
+//!
+//! where the header (see `EnvelopeHeader`) identifies the curve, the type of the object, the Subspace
+//! Snark of keys and proofs with CP_link and the layout of the payload, and the checksum is a hash of
+//! all the preceding bytes. So reading an object of another type or for another curve fails with a
+//! specific error rather than a confusing deserialization error.
+//! Objects written without an envelope by version 0.6 of this crate can be read with
+//! `Enveloped::from_envelope_or_legacy_bytes`.
+
+pub mod error;
+pub mod legacy;
+
+pub use error::EnvelopeError;
+
+use crate::{
+    fingerprint::Hasher, link::LinkSnark, Proof, ProofWithLink, ProvingKey, ProvingKeyWithLink,
+    VerifyingKey, VerifyingKeyWithLink,
+};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::PrimeField;
+use ark_serialize::*;
+use ark_std::vec::Vec;
+
+pub const MAGIC: [u8; 4] = *b"LG16";
+/// Version 1 is the header without `link_snark`
+pub const FORMAT_VERSION: u8 = 2;
+/// Layout of the payload. Version 1 is the layout of 0.6, written without envelopes, version 2 allows
+/// several link commitments, version 3 compresses the GT elements of aggregate proofs.
+pub const LAYOUT_VERSION: u16 = 3;
+/// Layout of the first envelopes
+const FIRST_ENVELOPE_LAYOUT_VERSION: u16 = 2;
+
+const CURVE_ID_LABEL: &[u8] = b"LegoGroth16: curve id";
+const CHECKSUM_LABEL: &[u8] = b"LegoGroth16: envelope checksum";
+const CHECKSUM_SIZE: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ObjectType {
+    ProvingKey = 1,
+    ProvingKeyWithLink = 2,
+    VerifyingKey = 3,
+    VerifyingKeyWithLink = 4,
+    Proof = 5,
+    ProofWithLink = 6,
+    GenericSRS = 7,
+    AggregateProof = 8,
+    AggregateLegoProof = 9,
+    ProverSRS = 10,
+    PreparedProverSRS = 11,
+    TransparentSRS = 12,
+    TransparentAggregateLegoProof = 13,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EnvelopeHeader {
+    pub layout_version: u16,
+    /// `(major, minor, patch)` version of the crate that wrote the object
+    pub crate_version: [u16; 3],
+    /// See `curve_id`
+    pub curve_id: [u8; 8],
+    /// An `ObjectType`
+    pub object_type: u8,
+    /// Whether the payload is serialized with compressed group elements
+    pub compressed: bool,
+    /// Number of committed witnesses for keys
+    pub commit_witness_count: Option<u64>,
+    /// `SubspaceSnark::ID` of the Subspace Snark for keys and proofs with CP_link. It is `None` in
+    /// headers of format version 1.
+    pub link_snark: Option<u8>,
+    pub payload_len: u64,
+}
+
+/// Header of format version 1
+#[derive(CanonicalDeserialize)]
+struct EnvelopeHeaderV1 {
+    layout_version: u16,
+    crate_version: [u16; 3],
+    curve_id: [u8; 8],
+    object_type: u8,
+    compressed: bool,
+    commit_witness_count: Option<u64>,
+    payload_len: u64,
+}
+
+impl From<EnvelopeHeaderV1> for EnvelopeHeader {
+    fn from(header: EnvelopeHeaderV1) -> Self {
+        Self {
+            layout_version: header.layout_version,
+            crate_version: header.crate_version,
+            curve_id: header.curve_id,
+            object_type: header.object_type,
+            compressed: header.compressed,
+            commit_witness_count: header.commit_witness_count,
+            link_snark: None,
+            payload_len: header.payload_len,
+        }
+    }
+}
+
+/// Identifies the pairing by a hash of the scalar field's modulus and the generators of both groups
+pub fn curve_id<E: Pairing>() -> [u8; 8] {
+    let mut hasher = Hasher::new(CURVE_ID_LABEL);
+    hasher.absorb(&E::ScalarField::MODULUS);
+    hasher.absorb(&E::G1Affine::generator());
+    hasher.absorb(&E::G2Affine::generator());
+    let mut id = [0; 8];
+    id.copy_from_slice(&hasher.finish().as_bytes()[..8]);
+    id
+}
+
+/// Read the header of an enveloped object without deserializing the object, like to find out what a
+/// file contains. The checksum is not checked.
+pub fn read_envelope_header(bytes: &[u8]) -> Result<EnvelopeHeader, EnvelopeError> {
+    let (header, _) = split_envelope(bytes)?;
+    Ok(header)
+}
+
+/// Returns the header and the offset of the payload
+fn split_envelope(bytes: &[u8]) -> Result<(EnvelopeHeader, usize), EnvelopeError> {
+    if !bytes.starts_with(&MAGIC) {
+        return Err(EnvelopeError::InvalidMagic);
+    }
+    let format_version = *bytes.get(MAGIC.len()).ok_or(EnvelopeError::InvalidLength)?;
+    let mut reader = &bytes[MAGIC.len() + 1..];
+    let header = match format_version {
+        FORMAT_VERSION => EnvelopeHeader::deserialize_compressed(&mut reader)?,
+        1 => EnvelopeHeaderV1::deserialize_compressed(&mut reader)?.into(),
+        _ => return Err(EnvelopeError::UnsupportedFormatVersion(format_version)),
+    };
+    let payload_start = bytes.len() - reader.len();
+    if (reader.len() as u64) < header.payload_len {
+        return Err(EnvelopeError::InvalidLength);
+    }
+    Ok((header, payload_start))
+}
+
+/// Checks the checksum and the header of the envelope of a `T` and returns the header and the
+/// payload, without deserializing it
+pub(crate) fn envelope_payload<T: Enveloped>(
+    bytes: &[u8],
+) -> Result<(EnvelopeHeader, &[u8]), EnvelopeError> {
+    let (header, payload_start) = split_envelope(bytes)?;
+    // The checksum follows the payload and must be the last bytes
+    let checksum_start = payload_start + header.payload_len as usize;
+    if bytes.len() != checksum_start + CHECKSUM_SIZE
+        || checksum(&bytes[..checksum_start]) != bytes[checksum_start..]
+    {
+        return Err(EnvelopeError::InvalidChecksum);
+    }
+    let expected_curve_id = curve_id::<T::E>();
+    if header.curve_id != expected_curve_id {
+        return Err(EnvelopeError::CurveMismatch(
+            header.curve_id,
+            expected_curve_id,
+        ));
+    }
+    if header.object_type != T::OBJECT_TYPE as u8 {
+        return Err(EnvelopeError::ObjectTypeMismatch(
+            header.object_type,
+            T::OBJECT_TYPE as u8,
+        ));
+    }
+    // Headers of format version 1 do not say which Subspace Snark the object is for
+    if let Some(link_snark) = header.link_snark {
+        if Some(link_snark) != T::LINK_SNARK {
+            return Err(EnvelopeError::ObjectTypeMismatch(
+                link_snark,
+                T::LINK_SNARK.unwrap_or(0),
+            ));
+        }
+    }
+    if !(FIRST_ENVELOPE_LAYOUT_VERSION..=LAYOUT_VERSION).contains(&header.layout_version) {
+        return Err(EnvelopeError::UnsupportedLayoutVersion(
+            header.layout_version,
+        ));
+    }
+    Ok((header, &bytes[payload_start..checksum_start]))
+}
+
+/// Objects that can be wrapped in an envelope. The default methods should not be overridden.
+pub trait Enveloped: CanonicalSerialize + CanonicalDeserialize {
+    type E: Pairing;
+    const OBJECT_TYPE: ObjectType;
+    /// `SubspaceSnark::ID` of the Subspace Snark for keys and proofs with CP_link
+    const LINK_SNARK: Option<u8> = None;
+
+    fn commit_witness_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// Deserialize from the bytes written by version 0.6 of this crate, which are the same as the
+    /// canonical serialization unless the layout changed since
+    fn deserialize_legacy<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_mode(reader, compress, validate)
+    }
+
+    /// Deserialize the payload of an envelope with an older `layout_version`, which is the same as
+    /// the canonical serialization unless the layout of the object changed since
+    fn deserialize_layout<R: Read>(
+        reader: R,
+        _layout_version: u16,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_mode(reader, compress, validate)
+    }
+
+    fn to_envelope_bytes(&self, compress: Compress) -> Result<Vec<u8>, EnvelopeError> {
+        let header = EnvelopeHeader {
+            layout_version: LAYOUT_VERSION,
+            crate_version: crate_version(),
+            curve_id: curve_id::<Self::E>(),
+            object_type: Self::OBJECT_TYPE as u8,
+            compressed: compress == Compress::Yes,
+            commit_witness_count: self.commit_witness_count().map(|c| c as u64),
+            link_snark: Self::LINK_SNARK,
+            payload_len: self.serialized_size(compress) as u64,
+        };
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(FORMAT_VERSION);
+        header.serialize_compressed(&mut bytes)?;
+        self.serialize_with_mode(&mut bytes, compress)?;
+        let checksum = checksum(&bytes);
+        bytes.extend_from_slice(&checksum);
+        Ok(bytes)
+    }
+
+    fn from_envelope_bytes(bytes: &[u8], validate: Validate) -> Result<Self, EnvelopeError> {
+        let (header, payload) = envelope_payload::<Self>(bytes)?;
+        let compress = if header.compressed {
+            Compress::Yes
+        } else {
+            Compress::No
+        };
+        let object = if header.layout_version == LAYOUT_VERSION {
+            Self::deserialize_with_mode(payload, compress, validate)?
+        } else {
+            Self::deserialize_layout(payload, header.layout_version, compress, validate)?
+        };
+        if object.commit_witness_count().map(|c| c as u64) != header.commit_witness_count {
+            return Err(EnvelopeError::MetadataMismatch);
+        }
+        Ok(object)
+    }
+
+    /// Read an enveloped object, or if `bytes` do not start with `MAGIC`, an object written without
+    /// envelope by version 0.6 of this crate with the given `compress` mode
+    fn from_envelope_or_legacy_bytes(
+        bytes: &[u8],
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, EnvelopeError> {
+        if bytes.starts_with(&MAGIC) {
+            Self::from_envelope_bytes(bytes, validate)
+        } else {
+            Ok(Self::deserialize_legacy(bytes, compress, validate)?)
+        }
+    }
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let mut hasher = Hasher::new(CHECKSUM_LABEL);
+    hasher.update(bytes);
+    *hasher.finish().as_bytes()
+}
+
+fn crate_version() -> [u16; 3] {
+    [
+        env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap_or(0),
+        env!("CARGO_PKG_VERSION_MINOR").parse().unwrap_or(0),
+        env!("CARGO_PKG_VERSION_PATCH").parse().unwrap_or(0),
+    ]
+}
+
+impl<E: Pairing> Enveloped for ProvingKey<E> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::ProvingKey;
+
+    fn commit_witness_count(&self) -> Option<usize> {
+        Some(self.vk.commit_witness_count)
+    }
+}
+
+impl<E: Pairing, L: LinkSnark<E>> Enveloped for ProvingKeyWithLink<E, L> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::ProvingKeyWithLink;
+    const LINK_SNARK: Option<u8> = Some(L::ID);
+
+    fn commit_witness_count(&self) -> Option<usize> {
+        Some(self.vk.groth16_vk.commit_witness_count)
+    }
+
+    fn deserialize_legacy<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        legacy::ProvingKeyWithLinkV0_6::deserialize_with_mode(reader, compress, validate)
+            .map(Into::into)
+    }
+}
+
+impl<E: Pairing> Enveloped for VerifyingKey<E> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::VerifyingKey;
+
+    fn commit_witness_count(&self) -> Option<usize> {
+        Some(self.commit_witness_count)
+    }
+}
+
+impl<E: Pairing, L: LinkSnark<E>> Enveloped for VerifyingKeyWithLink<E, L> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::VerifyingKeyWithLink;
+    const LINK_SNARK: Option<u8> = Some(L::ID);
+
+    fn commit_witness_count(&self) -> Option<usize> {
+        Some(self.groth16_vk.commit_witness_count)
+    }
+
+    fn deserialize_legacy<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        legacy::VerifyingKeyWithLinkV0_6::deserialize_with_mode(reader, compress, validate)
+            .map(Into::into)
+    }
+}
+
+impl<E: Pairing> Enveloped for Proof<E> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::Proof;
+}
+
+impl<E: Pairing, L: LinkSnark<E>> Enveloped for ProofWithLink<E, L> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::ProofWithLink;
+    const LINK_SNARK: Option<u8> = Some(L::ID);
+
+    fn deserialize_legacy<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        legacy::ProofWithLinkV0_6::deserialize_with_mode(reader, compress, validate).map(Into::into)
+    }
+}
+
+#[cfg(feature = "aggregation")]
+impl<E: Pairing> Enveloped for crate::aggregation::srs::GenericSRS<E> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::GenericSRS;
+}
+
+#[cfg(feature = "aggregation")]
+impl<E: Pairing> Enveloped for crate::aggregation::srs::ProverSRS<E> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::ProverSRS;
+}
+
+#[cfg(feature = "aggregation")]
+impl<E: Pairing> Enveloped for crate::aggregation::srs::PreparedProverSRS<E> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::PreparedProverSRS;
+}
+
+#[cfg(feature = "aggregation")]
+impl<E: Pairing> Enveloped for crate::aggregation::groth16::AggregateProof<E> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::AggregateProof;
+
+    /// GT elements were not compressed before layout 3
+    fn deserialize_legacy<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_gt_mode(reader, compress, Compress::No, validate)
+    }
+
+    fn deserialize_layout<R: Read>(
+        reader: R,
+        _layout_version: u16,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_legacy(reader, compress, validate)
+    }
+}
+
+#[cfg(feature = "aggregation")]
+impl<E: Pairing> Enveloped for crate::aggregation::legogroth16::AggregateLegoProof<E> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::AggregateLegoProof;
+
+    /// GT elements were not compressed before layout 3
+    fn deserialize_legacy<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_gt_mode(reader, compress, Compress::No, validate)
+    }
+
+    fn deserialize_layout<R: Read>(
+        reader: R,
+        _layout_version: u16,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_legacy(reader, compress, validate)
+    }
+}
+
+#[cfg(feature = "aggregation")]
+impl<E: Pairing> Enveloped for crate::aggregation::transparent::TransparentSRS<E> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::TransparentSRS;
+}
+
+#[cfg(feature = "aggregation")]
+impl<E: Pairing> Enveloped for crate::aggregation::legogroth16::TransparentAggregateLegoProof<E> {
+    type E = E;
+    const OBJECT_TYPE: ObjectType = ObjectType::TransparentAggregateLegoProof;
+}
@@ -3,8 +3,12 @@ use crate::aggregation::error::AggregationError;
 #[cfg(feature = "circom")]
 use crate::circom::error::CircomError;
 
+use crate::envelope::EnvelopeError;
 use crate::link::error::LinkError;
+use crate::mpc::error::MpcError;
+use crate::progress::Cancelled;
 use ark_relations::r1cs::SynthesisError;
+use ark_std::fmt;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Error {
@@ -15,10 +19,23 @@ pub enum Error {
     InvalidLinkCommitment,
     InvalidWitnessCommitment,
     InsufficientWitnessesForCommitment(usize, usize),
+    MpcError(MpcError),
+    /// The circuit given to `ProverContext` does not have the size of the one the proving key was generated for
+    CircuitAndProvingKeyMismatch,
+    /// `ProverContext` was not created with a `ProvingKeyWithLink`
+    MissingLinkProvingKey,
+    /// The parts of a proving key do not agree with each other
+    InvalidProvingKey,
+    EnvelopeError(EnvelopeError),
+    /// A `ProgressObserver` cancelled the operation
+    Cancelled,
     #[cfg(feature = "circom")]
     CircomError(CircomError),
     #[cfg(feature = "aggregation")]
     AggregationError(AggregationError),
+    /// Keys with committed witnesses or proofs with a non-identity `d` have no Groth16 counterpart
+    #[cfg(feature = "ark-groth16")]
+    CannotConvertToGroth16,
 }
 
 impl From<SynthesisError> for Error {
@@ -33,6 +50,24 @@ impl From<LinkError> for Error {
     }
 }
 
+impl From<MpcError> for Error {
+    fn from(e: MpcError) -> Self {
+        Self::MpcError(e)
+    }
+}
+
+impl From<Cancelled> for Error {
+    fn from(_: Cancelled) -> Self {
+        Self::Cancelled
+    }
+}
+
+impl From<EnvelopeError> for Error {
+    fn from(e: EnvelopeError) -> Self {
+        Self::EnvelopeError(e)
+    }
+}
+
 #[cfg(feature = "circom")]
 impl From<CircomError> for Error {
     fn from(e: CircomError) -> Self {
@@ -46,3 +81,14 @@ impl From<AggregationError> for Error {
         Self::AggregationError(e)
     }
 }
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SynthesisError(e) => write!(f, "{}", e),
+            e => write!(f, "{:?}", e),
+        }
+    }
+}
+
+impl ark_std::error::Error for Error {}
@@ -0,0 +1,168 @@
+//! Fingerprints identify keys by a hash of their canonical compressed encoding, like to reference a
+//! circuit in logs, registries or transcripts. Each kind of key is hashed under its own label so keys of
+//! different kinds never share a fingerprint. A proving key's fingerprint is computed over the fingerprint
+//! of its verifying key and the rest of the proving key, so given `pk`, `pk.vk_fingerprint()` is the
+//! fingerprint a verifier holding only `pk.vk` computes. Similarly, `VerifyingKeyWithLink` is hashed over
+//! the fingerprint of its `groth16_vk`.
+
+use crate::{
+    link::{LinkSnark, SigmaSubspaceSnark},
+    ProvingKey, ProvingKeyWithLink, VerifyingKey, VerifyingKeyWithLink,
+};
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{fmt, io::Write, vec::Vec};
+use blake2::{Blake2s256, Digest};
+use dock_crypto_utils::transcript::{new_merlin_transcript, Transcript};
+
+const VK_LABEL: &[u8] = b"LegoGroth16: VerifyingKey fingerprint";
+const VK_WITH_LINK_LABEL: &[u8] = b"LegoGroth16: VerifyingKeyWithLink fingerprint";
+const PK_LABEL: &[u8] = b"LegoGroth16: ProvingKey fingerprint";
+const PK_WITH_LINK_LABEL: &[u8] = b"LegoGroth16: ProvingKeyWithLink fingerprint";
+#[cfg(feature = "ark-groth16")]
+const GROTH16_VK_LABEL: &[u8] = b"LegoGroth16: ark_groth16 VerifyingKey fingerprint";
+/// Label under which a fingerprint is appended to a transcript
+pub const FINGERPRINT_TRANSCRIPT_LABEL: &[u8] = b"key-fingerprint";
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize,
+)]
+pub struct Fingerprint(pub [u8; 32]);
+
+impl Fingerprint {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Bind the challenges subsequently derived from `transcript` to the key with this fingerprint
+    pub fn append_to_transcript<T: Transcript>(&self, transcript: &mut T) {
+        transcript.append(FINGERPRINT_TRANSCRIPT_LABEL, self);
+    }
+}
+
+/// Lowercase hex
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.0 {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// A new transcript which already has `fingerprint` appended, so all challenges derived from it, like
+/// during aggregation, are bound to the key. The prover and the verifier must both create their
+/// transcript with this.
+pub fn new_merlin_transcript_with_fingerprint(
+    label: &'static [u8],
+    fingerprint: &Fingerprint,
+) -> impl Transcript {
+    let mut transcript = new_merlin_transcript(label);
+    fingerprint.append_to_transcript(&mut transcript);
+    transcript
+}
+
+impl<E: Pairing> VerifyingKey<E> {
+    pub fn fingerprint(&self) -> Fingerprint {
+        let mut hasher = Hasher::new(VK_LABEL);
+        hasher.absorb(self);
+        hasher.finish()
+    }
+}
+
+/// Fingerprint of a verifying key of `ark_groth16`, like for aggregating Groth16 proofs
+#[cfg(feature = "ark-groth16")]
+pub fn groth16_vk_fingerprint<E: Pairing>(vk: &ark_groth16::VerifyingKey<E>) -> Fingerprint {
+    let mut hasher = Hasher::new(GROTH16_VK_LABEL);
+    hasher.absorb(vk);
+    hasher.finish()
+}
+
+impl<E: Pairing, L: LinkSnark<E>> VerifyingKeyWithLink<E, L> {
+    pub fn fingerprint(&self) -> Fingerprint {
+        let mut hasher = Hasher::new(VK_WITH_LINK_LABEL);
+        hasher.absorb(&self.groth16_vk.fingerprint());
+        hasher.absorb(&self.link_pp);
+        hasher.absorb(&self.link_bases);
+        hasher.absorb(&self.link_witness_ranges);
+        hasher.absorb(&self.link_vk);
+        hasher.finish()
+    }
+}
+
+impl<E: Pairing> ProvingKey<E> {
+    pub fn fingerprint(&self) -> Fingerprint {
+        let mut hasher = Hasher::new(PK_LABEL);
+        hasher.absorb(&self.vk_fingerprint());
+        hasher.absorb(&self.common);
+        hasher.finish()
+    }
+
+    /// Fingerprint of the embedded verifying key
+    pub fn vk_fingerprint(&self) -> Fingerprint {
+        self.vk.fingerprint()
+    }
+}
+
+impl<E: Pairing, L: LinkSnark<E>> ProvingKeyWithLink<E, L> {
+    pub fn fingerprint(&self) -> Fingerprint {
+        let mut hasher = Hasher::new(PK_WITH_LINK_LABEL);
+        hasher.absorb(&self.vk_fingerprint());
+        hasher.absorb(&self.common);
+        hasher.absorb(&self.link_ek);
+        hasher.finish()
+    }
+
+    /// Fingerprint of the embedded verifying key
+    pub fn vk_fingerprint(&self) -> Fingerprint {
+        self.vk.fingerprint()
+    }
+}
+
+impl<E: Pairing> ProvingKeyWithLink<E, SigmaSubspaceSnark<E>> {
+    /// Absorb the fingerprint of the Groth16 verifying key in the challenge of the CP_link proofs. This
+    /// changes the link keys so it must be done before the verifying key is given out, as the generator
+    /// of keys with `SigmaSubspaceSnark` does.
+    pub fn bind_link_proofs_to_fingerprint(&mut self) {
+        let fingerprint = Some(self.vk.groth16_vk.fingerprint());
+        self.link_ek.context = fingerprint;
+        self.vk.link_vk.context = fingerprint;
+    }
+}
+
+/// Hashes the label and then the compressed encodings, which are written directly into the hash rather
+/// than first collected in a buffer as proving keys can be large.
+pub(crate) struct Hasher(Blake2s256);
+
+impl Hasher {
+    pub(crate) fn new(label: &[u8]) -> Self {
+        let mut h = Blake2s256::new();
+        h.update([label.len() as u8]);
+        h.update(label);
+        Self(h)
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    pub(crate) fn absorb<S: CanonicalSerialize>(&mut self, s: &S) {
+        s.serialize_compressed(&mut *self)
+            .expect("writing to a hash does not fail");
+    }
+
+    pub(crate) fn finish(self) -> Fingerprint {
+        Fingerprint(self.0.finalize().into())
+    }
+}
+
+impl Write for Hasher {
+    fn write(&mut self, buf: &[u8]) -> ark_std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> ark_std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    link::{PESubspaceSnark, SparseMatrix, SubspaceSnark, PP},
+    link::{error::LinkError, LinkSnark, PESubspaceSnark, SigmaSubspaceSnark, SparseMatrix, PP},
+    progress::{observe, observe_phase, NoObserver, Phase, ProgressObserver, Query},
     r1cs_to_qap::LibsnarkReduction,
     LinkPublicGenerators, ProvingKey, ProvingKeyCommon, ProvingKeyWithLink, Vec, VerifyingKey,
     VerifyingKeyWithLink,
@@ -10,7 +11,8 @@ use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
 use ark_relations::r1cs::{
     ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisError, SynthesisMode,
 };
-use ark_std::{cfg_into_iter, cfg_iter, end_timer, rand::Rng, start_timer, vec};
+use ark_std::{cfg_into_iter, cfg_iter, end_timer, ops::Range, rand::Rng, start_timer, vec};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use crate::r1cs_to_qap::R1CStoQAP;
 
@@ -42,6 +44,84 @@ where
     )
 }
 
+#[inline]
+/// Same as `generate_random_parameters_incl_cp_link` but CP_link uses `SigmaSubspaceSnark` which does
+/// not need a trusted setup for the link keys, i.e. no trapdoor is created for them. The link proofs
+/// are bound to the fingerprint of the Groth16 verifying key.
+pub fn generate_random_parameters_incl_transparent_cp_link<E, C, R>(
+    circuit: C,
+    link_gens: LinkPublicGenerators<E>,
+    commit_witness_count: usize,
+    rng: &mut R,
+) -> crate::Result<ProvingKeyWithLink<E, SigmaSubspaceSnark<E>>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+{
+    let mut pk = generate_random_parameters_incl_cp_link_with_link_snark::<
+        E,
+        C,
+        R,
+        LibsnarkReduction,
+        SigmaSubspaceSnark<E>,
+    >(circuit, link_gens, commit_witness_count, rng)?;
+    pk.bind_link_proofs_to_fingerprint();
+    Ok(pk)
+}
+
+/// Same as `generate_random_parameters_incl_transparent_cp_link` but tells `observer` about the
+/// progress, and returns `Error::Cancelled` if it cancels
+pub fn generate_random_parameters_incl_transparent_cp_link_with_observer<E, C, R, O>(
+    circuit: C,
+    link_gens: LinkPublicGenerators<E>,
+    commit_witness_count: usize,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProvingKeyWithLink<E, SigmaSubspaceSnark<E>>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    O: ProgressObserver + ?Sized,
+{
+    let mut pk = generate_random_parameters_incl_cp_link_with_link_snark_and_observer::<
+        E,
+        C,
+        R,
+        LibsnarkReduction,
+        SigmaSubspaceSnark<E>,
+        O,
+    >(circuit, link_gens, commit_witness_count, rng, observer)?;
+    pk.bind_link_proofs_to_fingerprint();
+    Ok(pk)
+}
+
+#[inline]
+/// Same as `generate_random_parameters_incl_cp_link` but the committed witnesses can be split among
+/// several link commitments. Each item of `link_gens` has the bases for a link commitment and the range
+/// of committed witnesses it commits to. The number of bases must be 1 more than the number of
+/// witnesses in the range, the last base being for the randomness. The ranges can overlap.
+pub fn generate_random_parameters_incl_cp_links<E, C, R>(
+    circuit: C,
+    link_gens: Vec<(LinkPublicGenerators<E>, Range<usize>)>,
+    commit_witness_count: usize,
+    rng: &mut R,
+) -> crate::Result<ProvingKeyWithLink<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+{
+    generate_random_parameters_incl_cp_links_with_link_snark::<
+        E,
+        C,
+        R,
+        LibsnarkReduction,
+        PESubspaceSnark<E>,
+    >(circuit, link_gens, commit_witness_count, rng)
+}
+
 #[inline]
 /// Generates a random common reference string for a circuit.
 /// `commit_witness_count` is the number of witnesses committed in proof
@@ -62,6 +142,78 @@ where
     )
 }
 
+/// Same as `generate_random_parameters` but tells `observer` about the progress, and returns
+/// `Error::Cancelled` if it cancels
+pub fn generate_random_parameters_with_observer<E, C, R, O>(
+    circuit: C,
+    commit_witness_count: usize,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProvingKey<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    O: ProgressObserver + ?Sized,
+{
+    generate_random_parameters_with_reduction_and_observer::<E, C, R, LibsnarkReduction, O>(
+        circuit,
+        commit_witness_count,
+        rng,
+        observer,
+    )
+}
+
+/// Same as `generate_random_parameters_incl_cp_link` but tells `observer` about the progress, and
+/// returns `Error::Cancelled` if it cancels
+pub fn generate_random_parameters_incl_cp_link_with_observer<E, C, R, O>(
+    circuit: C,
+    link_gens: LinkPublicGenerators<E>,
+    commit_witness_count: usize,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProvingKeyWithLink<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    O: ProgressObserver + ?Sized,
+{
+    generate_random_parameters_incl_cp_link_with_link_snark_and_observer::<
+        E,
+        C,
+        R,
+        LibsnarkReduction,
+        PESubspaceSnark<E>,
+        O,
+    >(circuit, link_gens, commit_witness_count, rng, observer)
+}
+
+/// Same as `generate_random_parameters_incl_cp_links` but tells `observer` about the progress, and
+/// returns `Error::Cancelled` if it cancels
+pub fn generate_random_parameters_incl_cp_links_with_observer<E, C, R, O>(
+    circuit: C,
+    link_gens: Vec<(LinkPublicGenerators<E>, Range<usize>)>,
+    commit_witness_count: usize,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProvingKeyWithLink<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    O: ProgressObserver + ?Sized,
+{
+    generate_random_parameters_incl_cp_links_with_link_snark_and_observer::<
+        E,
+        C,
+        R,
+        LibsnarkReduction,
+        PESubspaceSnark<E>,
+        O,
+    >(circuit, link_gens, commit_witness_count, rng, observer)
+}
+
 /// Generates a random common reference string for
 /// a circuit.
 /// `commit_witness_count` is the number of witnesses committed in proof
@@ -77,20 +229,43 @@ where
     R: Rng,
     QAP: R1CStoQAP,
 {
-    let (alpha, beta, gamma, delta, eta, g1_generator, g2_generator) =
-        generate_randomness::<E, R>(rng);
+    generate_random_parameters_with_reduction_and_observer::<E, C, R, QAP, _>(
+        circuit,
+        commit_witness_count,
+        rng,
+        &NoObserver,
+    )
+}
 
-    generate_parameters_with_qap::<E, C, R, QAP>(
+/// Same as `generate_random_parameters_with_reduction` but tells `observer` about the progress,
+/// and returns `Error::Cancelled` if it cancels
+pub fn generate_random_parameters_with_reduction_and_observer<E, C, R, QAP, O>(
+    circuit: C,
+    commit_witness_count: usize,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProvingKey<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    QAP: R1CStoQAP,
+    O: ProgressObserver + ?Sized,
+{
+    let (toxic_waste, g1_generator, g2_generator) = generate_randomness::<E, R>(rng);
+
+    generate_parameters_with_qap_and_observer::<E, C, R, QAP, O>(
         circuit,
-        alpha,
-        beta,
-        gamma,
-        delta,
-        eta,
+        toxic_waste.alpha,
+        toxic_waste.beta,
+        toxic_waste.gamma,
+        toxic_waste.delta,
+        toxic_waste.eta,
         g1_generator,
         g2_generator,
         commit_witness_count,
         rng,
+        observer,
     )
 }
 
@@ -110,10 +285,147 @@ where
     R: Rng,
     QAP: R1CStoQAP,
 {
-    let (alpha, beta, gamma, delta, eta, g1_generator, g2_generator) =
-        generate_randomness::<E, R>(rng);
+    generate_random_parameters_incl_cp_link_with_link_snark::<E, C, R, QAP, PESubspaceSnark<E>>(
+        circuit,
+        link_gens,
+        commit_witness_count,
+        rng,
+    )
+}
+
+/// Same as `generate_random_parameters_incl_cp_link_with_reduction` but the Subspace Snark `L` used
+/// for CP_link can be chosen.
+#[inline]
+pub fn generate_random_parameters_incl_cp_link_with_link_snark<E, C, R, QAP, L>(
+    circuit: C,
+    link_gens: LinkPublicGenerators<E>,
+    commit_witness_count: usize,
+    rng: &mut R,
+) -> crate::Result<ProvingKeyWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+{
+    generate_random_parameters_incl_cp_link_with_link_snark_and_observer::<E, C, R, QAP, L, _>(
+        circuit,
+        link_gens,
+        commit_witness_count,
+        rng,
+        &NoObserver,
+    )
+}
+
+/// Same as `generate_random_parameters_incl_cp_link_with_link_snark` but tells `observer` about the
+/// progress, and returns `Error::Cancelled` if it cancels
+pub fn generate_random_parameters_incl_cp_link_with_link_snark_and_observer<E, C, R, QAP, L, O>(
+    circuit: C,
+    link_gens: LinkPublicGenerators<E>,
+    commit_witness_count: usize,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProvingKeyWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+    O: ProgressObserver + ?Sized,
+{
+    generate_random_parameters_incl_cp_links_with_link_snark_and_observer::<E, C, R, QAP, L, O>(
+        circuit,
+        vec![(link_gens, 0..commit_witness_count)],
+        commit_witness_count,
+        rng,
+        observer,
+    )
+}
+
+/// Same as `generate_random_parameters_incl_cp_link_with_link_snark` but with several link
+/// commitments, see `generate_random_parameters_incl_cp_links`
+#[inline]
+pub fn generate_random_parameters_incl_cp_links_with_link_snark<E, C, R, QAP, L>(
+    circuit: C,
+    link_gens: Vec<(LinkPublicGenerators<E>, Range<usize>)>,
+    commit_witness_count: usize,
+    rng: &mut R,
+) -> crate::Result<ProvingKeyWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+{
+    generate_random_parameters_incl_cp_links_with_link_snark_and_observer::<E, C, R, QAP, L, _>(
+        circuit,
+        link_gens,
+        commit_witness_count,
+        rng,
+        &NoObserver,
+    )
+}
+
+/// Same as `generate_random_parameters_incl_cp_links_with_link_snark` but tells `observer` about
+/// the progress, and returns `Error::Cancelled` if it cancels
+pub fn generate_random_parameters_incl_cp_links_with_link_snark_and_observer<E, C, R, QAP, L, O>(
+    circuit: C,
+    link_gens: Vec<(LinkPublicGenerators<E>, Range<usize>)>,
+    commit_witness_count: usize,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProvingKeyWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+    O: ProgressObserver + ?Sized,
+{
+    let (toxic_waste, g1_generator, g2_generator) = generate_randomness::<E, R>(rng);
+
+    generate_parameters_incl_cp_links_with_qap_and_observer::<E, C, R, QAP, L, O>(
+        circuit,
+        toxic_waste.alpha,
+        toxic_waste.beta,
+        toxic_waste.gamma,
+        toxic_waste.delta,
+        toxic_waste.eta,
+        g1_generator,
+        g2_generator,
+        link_gens,
+        commit_witness_count,
+        rng,
+        observer,
+    )
+}
 
-    generate_parameters_incl_cp_link_with_qap::<E, C, R, QAP>(
+/// Create parameters for a circuit, given some toxic waste, R1CS to QAP calculator and group generators
+#[inline]
+pub fn generate_parameters_incl_cp_link_with_qap<E, C, R, QAP>(
+    circuit: C,
+    alpha: E::ScalarField,
+    beta: E::ScalarField,
+    gamma: E::ScalarField,
+    delta: E::ScalarField,
+    eta: E::ScalarField,
+    g1_generator: E::G1,
+    g2_generator: E::G2,
+    link_gens: LinkPublicGenerators<E>,
+    commit_witness_count: usize,
+    rng: &mut R,
+) -> crate::Result<ProvingKeyWithLink<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    QAP: R1CStoQAP,
+{
+    generate_parameters_incl_cp_link_with_qap_and_link_snark::<E, C, R, QAP, PESubspaceSnark<E>>(
         circuit,
         alpha,
         beta,
@@ -128,9 +440,10 @@ where
     )
 }
 
-/// Create parameters for a circuit, given some toxic waste, R1CS to QAP calculator and group generators
+/// Create parameters for a circuit, given some toxic waste, R1CS to QAP calculator, group generators
+/// and the Subspace Snark `L` used for CP_link
 #[inline]
-pub fn generate_parameters_incl_cp_link_with_qap<E, C, R, QAP>(
+pub fn generate_parameters_incl_cp_link_with_qap_and_link_snark<E, C, R, QAP, L>(
     circuit: C,
     alpha: E::ScalarField,
     beta: E::ScalarField,
@@ -142,15 +455,137 @@ pub fn generate_parameters_incl_cp_link_with_qap<E, C, R, QAP>(
     link_gens: LinkPublicGenerators<E>,
     commit_witness_count: usize,
     rng: &mut R,
-) -> crate::Result<ProvingKeyWithLink<E>>
+) -> crate::Result<ProvingKeyWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+{
+    generate_parameters_incl_cp_link_with_qap_and_observer::<E, C, R, QAP, L, _>(
+        circuit,
+        alpha,
+        beta,
+        gamma,
+        delta,
+        eta,
+        g1_generator,
+        g2_generator,
+        link_gens,
+        commit_witness_count,
+        rng,
+        &NoObserver,
+    )
+}
+
+/// Same as `generate_parameters_incl_cp_link_with_qap_and_link_snark` but tells `observer` about
+/// the progress, and returns `Error::Cancelled` if it cancels
+#[allow(clippy::too_many_arguments)]
+pub fn generate_parameters_incl_cp_link_with_qap_and_observer<E, C, R, QAP, L, O>(
+    circuit: C,
+    alpha: E::ScalarField,
+    beta: E::ScalarField,
+    gamma: E::ScalarField,
+    delta: E::ScalarField,
+    eta: E::ScalarField,
+    g1_generator: E::G1,
+    g2_generator: E::G2,
+    link_gens: LinkPublicGenerators<E>,
+    commit_witness_count: usize,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProvingKeyWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+    O: ProgressObserver + ?Sized,
+{
+    generate_parameters_incl_cp_links_with_qap_and_observer::<E, C, R, QAP, L, O>(
+        circuit,
+        alpha,
+        beta,
+        gamma,
+        delta,
+        eta,
+        g1_generator,
+        g2_generator,
+        vec![(link_gens, 0..commit_witness_count)],
+        commit_witness_count,
+        rng,
+        observer,
+    )
+}
+
+/// Create parameters for a circuit with several link commitments, given some toxic waste, R1CS to QAP
+/// calculator, group generators and the Subspace Snark `L` used for CP_link. The public params of the
+/// Subspace Snark use `g1` and `g2` of the first item of `link_gens`.
+#[inline]
+pub fn generate_parameters_incl_cp_links_with_qap_and_link_snark<E, C, R, QAP, L>(
+    circuit: C,
+    alpha: E::ScalarField,
+    beta: E::ScalarField,
+    gamma: E::ScalarField,
+    delta: E::ScalarField,
+    eta: E::ScalarField,
+    g1_generator: E::G1,
+    g2_generator: E::G2,
+    link_gens: Vec<(LinkPublicGenerators<E>, Range<usize>)>,
+    commit_witness_count: usize,
+    rng: &mut R,
+) -> crate::Result<ProvingKeyWithLink<E, L>>
 where
     E: Pairing,
     C: ConstraintSynthesizer<E::ScalarField>,
     R: Rng,
     QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+{
+    generate_parameters_incl_cp_links_with_qap_and_observer::<E, C, R, QAP, L, _>(
+        circuit,
+        alpha,
+        beta,
+        gamma,
+        delta,
+        eta,
+        g1_generator,
+        g2_generator,
+        link_gens,
+        commit_witness_count,
+        rng,
+        &NoObserver,
+    )
+}
+
+/// Same as `generate_parameters_incl_cp_links_with_qap_and_link_snark` but tells `observer` about
+/// the progress, and returns `Error::Cancelled` if it cancels
+pub fn generate_parameters_incl_cp_links_with_qap_and_observer<E, C, R, QAP, L, O>(
+    circuit: C,
+    alpha: E::ScalarField,
+    beta: E::ScalarField,
+    gamma: E::ScalarField,
+    delta: E::ScalarField,
+    eta: E::ScalarField,
+    g1_generator: E::G1,
+    g2_generator: E::G2,
+    link_gens: Vec<(LinkPublicGenerators<E>, Range<usize>)>,
+    commit_witness_count: usize,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProvingKeyWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+    O: ProgressObserver + ?Sized,
 {
     let (groth16_pk, num_instance_variables) =
-        generate_parameters_and_extra_info_with_qap::<E, C, R, QAP>(
+        generate_parameters_and_extra_info_with_qap::<E, C, R, QAP, O>(
             circuit,
             alpha,
             beta,
@@ -161,39 +596,81 @@ where
             g2_generator,
             commit_witness_count,
             rng,
+            observer,
         )?;
 
+    if link_gens.is_empty() {
+        return Err(LinkError::VectorWithUnexpectedLength(0, 1).into());
+    }
+    // A single subspace SNARK proves all links so it has the generators of all of them
+    let (g1, g2) = (link_gens[0].0.g1, link_gens[0].0.g2);
+    if link_gens
+        .iter()
+        .any(|(gens, _)| gens.g1 != g1 || gens.g2 != g2)
+    {
+        return Err(LinkError::DifferentLinkGenerators.into());
+    }
+    for (gens, range) in &link_gens {
+        if range.start > range.end || range.end > commit_witness_count {
+            return Err(LinkError::InvalidIndex(range.end, commit_witness_count).into());
+        }
+        if gens.pedersen_gens.len() != range.len() + 1 {
+            return Err(LinkError::VectorWithUnexpectedLength(
+                gens.pedersen_gens.len(),
+                range.len() + 1,
+            )
+            .into());
+        }
+    }
+
     // Setup public params for the Subspace Snark
-    let link_rows = 2; // we're comparing two commitments, proof.d and proof.link_d
-    let link_cols = commit_witness_count + 2; // we have `commit_witness_count` witnesses and 1 hiding factor per row
+    let num_links = link_gens.len();
+    let link_rows = num_links + 1; // we're comparing the commitments proof.link_d with proof.d
+    let link_cols = commit_witness_count + num_links + 1; // we have `commit_witness_count` witnesses and 1 hiding factor per row
     let link_pp = PP::<E::G1Affine, E::G2Affine> {
         l: link_rows,
         t: link_cols,
-        g1: link_gens.g1,
-        g2: link_gens.g2,
+        g1,
+        g2,
     };
 
+    // Row `i` is for the `i`-th link commitment which has the witnesses in its range and the hiding
+    // factor in column `commit_witness_count + i`. The last row is for proof.d
     let mut link_m = SparseMatrix::<E::G1Affine>::new(link_rows, link_cols);
-    link_m.insert_row_slice(0, 0, link_gens.pedersen_gens.clone())?;
+    let mut link_bases = Vec::with_capacity(num_links);
+    let mut link_witness_ranges = Vec::with_capacity(num_links);
+    for (i, (gens, range)) in link_gens.into_iter().enumerate() {
+        link_m.insert_row_slice(i, range.start, gens.pedersen_gens[..range.len()].to_vec())?;
+        link_m.insert_val(i, commit_witness_count + i, gens.pedersen_gens[range.len()])?;
+        link_bases.push(gens.pedersen_gens);
+        link_witness_ranges.push((range.start, range.end));
+    }
     link_m.insert_row_slice(
-        1,
+        num_links,
         0,
         groth16_pk.vk.gamma_abc_g1
             [num_instance_variables..num_instance_variables + commit_witness_count]
             .to_vec(),
     )?;
     link_m.insert_row_slice(
-        1,
-        commit_witness_count + 1,
+        num_links,
+        commit_witness_count + num_links,
         vec![groth16_pk.vk.eta_gamma_inv_g1],
     )?;
 
-    let (link_ek, link_vk) = PESubspaceSnark::<E>::keygen(rng, &link_pp, &link_m)?;
+    let (link_ek, link_vk) = observe_phase(
+        observer,
+        Phase::LinkKeygen,
+        num_links,
+        || L::keygen(rng, &link_pp, &link_m).map_err(crate::error::Error::from),
+        |_| num_links,
+    )?;
 
-    let vk = VerifyingKeyWithLink::<E> {
+    let vk = VerifyingKeyWithLink::<E, L> {
         groth16_vk: groth16_pk.vk,
         link_pp,
-        link_bases: link_gens.pedersen_gens,
+        link_bases,
+        link_witness_ranges,
         link_vk,
     };
 
@@ -224,7 +701,7 @@ where
     R: Rng,
     QAP: R1CStoQAP,
 {
-    let (pk, _) = generate_parameters_and_extra_info_with_qap::<E, C, R, QAP>(
+    generate_parameters_with_qap_and_observer::<E, C, R, QAP, _>(
         circuit,
         alpha,
         beta,
@@ -235,6 +712,45 @@ where
         g2_generator,
         commit_witness_count,
         rng,
+        &NoObserver,
+    )
+}
+
+/// Same as `generate_parameters_with_qap` but tells `observer` about the progress, and returns
+/// `Error::Cancelled` if it cancels
+#[allow(clippy::too_many_arguments)]
+pub fn generate_parameters_with_qap_and_observer<E, C, R, QAP, O>(
+    circuit: C,
+    alpha: E::ScalarField,
+    beta: E::ScalarField,
+    gamma: E::ScalarField,
+    delta: E::ScalarField,
+    eta: E::ScalarField,
+    g1_generator: E::G1,
+    g2_generator: E::G2,
+    commit_witness_count: usize,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProvingKey<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    QAP: R1CStoQAP,
+    O: ProgressObserver + ?Sized,
+{
+    let (pk, _) = generate_parameters_and_extra_info_with_qap::<E, C, R, QAP, O>(
+        circuit,
+        alpha,
+        beta,
+        gamma,
+        delta,
+        eta,
+        g1_generator,
+        g2_generator,
+        commit_witness_count,
+        rng,
+        observer,
     )?;
     Ok(pk)
 }
@@ -242,7 +758,7 @@ where
 /// Create parameters for a circuit, given some toxic waste, R1CS to QAP calculator and group generators.
 /// Returns the proving key and the number of public inputs.
 #[inline]
-fn generate_parameters_and_extra_info_with_qap<E, C, R, QAP>(
+fn generate_parameters_and_extra_info_with_qap<E, C, R, QAP, O>(
     circuit: C,
     alpha: E::ScalarField,
     beta: E::ScalarField,
@@ -253,35 +769,55 @@ fn generate_parameters_and_extra_info_with_qap<E, C, R, QAP>(
     g2_generator: E::G2,
     commit_witness_count: usize,
     rng: &mut R,
+    observer: &O,
 ) -> crate::Result<(ProvingKey<E>, usize)>
 where
     E: Pairing,
     C: ConstraintSynthesizer<E::ScalarField>,
     R: Rng,
     QAP: R1CStoQAP,
+    O: ProgressObserver + ?Sized,
 {
     type D<F> = GeneralEvaluationDomain<F>;
 
+    // The toxic waste and all values derived from it are zeroized when dropped
+    let (alpha, beta, gamma, delta, eta) = (
+        Zeroizing::new(alpha),
+        Zeroizing::new(beta),
+        Zeroizing::new(gamma),
+        Zeroizing::new(delta),
+        Zeroizing::new(eta),
+    );
+
     let setup_time = start_timer!(|| "Groth16::Generator");
     let cs = ConstraintSystem::new_ref();
     cs.set_optimization_goal(OptimizationGoal::Constraints);
     cs.set_mode(SynthesisMode::Setup);
 
     // Synthesize the circuit.
-    let synthesis_time = start_timer!(|| "Constraint synthesis");
-    circuit.generate_constraints(cs.clone())?;
-    end_timer!(synthesis_time);
+    observe_phase(
+        observer,
+        Phase::Synthesis,
+        0,
+        || {
+            let synthesis_time = start_timer!(|| "Constraint synthesis");
+            circuit.generate_constraints(cs.clone())?;
+            end_timer!(synthesis_time);
 
-    let lc_time = start_timer!(|| "Inlining LCs");
-    cs.finalize();
-    end_timer!(lc_time);
+            let lc_time = start_timer!(|| "Inlining LCs");
+            cs.finalize();
+            end_timer!(lc_time);
+            Ok::<_, crate::error::Error>(())
+        },
+        |_| cs.num_constraints(),
+    )?;
 
     ///////////////////////////////////////////////////////////////////////////
     let domain_time = start_timer!(|| "Constructing evaluation domain");
 
     let domain_size = cs.num_constraints() + cs.num_instance_variables();
     let domain = D::new(domain_size).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
-    let t = domain.sample_element_outside_domain(rng);
+    let t = Zeroizing::new(domain.sample_element_outside_domain(rng));
 
     end_timer!(domain_time);
     ///////////////////////////////////////////////////////////////////////////
@@ -297,10 +833,24 @@ where
     let n = num_instance_variables + commit_witness_count;
 
     let reduction_time = start_timer!(|| "R1CS to QAP Instance Map with Evaluation");
-    let (a, b, c, zt, qap_num_variables, m_raw) = LibsnarkReduction::instance_map_with_evaluation::<
-        E::ScalarField,
-        D<E::ScalarField>,
-    >(cs, &t)?;
+    let (a, b, c, zt, qap_num_variables, m_raw) = observe_phase(
+        observer,
+        Phase::QapInstanceMap,
+        domain.size(),
+        || {
+            LibsnarkReduction::instance_map_with_evaluation::<E::ScalarField, D<E::ScalarField>>(
+                cs, &t,
+            )
+            .map_err(crate::error::Error::from)
+        },
+        |_| domain.size(),
+    )?;
+    let (a, b, c, zt) = (
+        Zeroizing::new(a),
+        Zeroizing::new(b),
+        Zeroizing::new(c),
+        Zeroizing::new(zt),
+    );
     end_timer!(reduction_time);
 
     // Compute query densities
@@ -314,20 +864,24 @@ where
 
     let scalar_bits = E::ScalarField::MODULUS_BIT_SIZE as usize;
 
-    let gamma_inverse = gamma.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
-    let delta_inverse = delta.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
+    let gamma_inverse = Zeroizing::new(gamma.inverse().ok_or(SynthesisError::UnexpectedIdentity)?);
+    let delta_inverse = Zeroizing::new(delta.inverse().ok_or(SynthesisError::UnexpectedIdentity)?);
 
-    let gamma_abc = cfg_iter!(a[..n])
-        .zip(&b[..n])
-        .zip(&c[..n])
-        .map(|((a, b), c)| (beta * a + &(alpha * b) + c) * &gamma_inverse)
-        .collect::<Vec<_>>();
+    let gamma_abc = Zeroizing::new(
+        cfg_iter!(a[..n])
+            .zip(&b[..n])
+            .zip(&c[..n])
+            .map(|((a, b), c)| (*beta * a + &(*alpha * b) + c) * *gamma_inverse)
+            .collect::<Vec<_>>(),
+    );
 
-    let l = cfg_iter!(a)
-        .zip(&b)
-        .zip(&c)
-        .map(|((a, b), c)| (beta * a + &(alpha * b) + c) * &delta_inverse)
-        .collect::<Vec<_>>();
+    let l = Zeroizing::new(
+        cfg_iter!(a)
+            .zip(&*b)
+            .zip(&*c)
+            .map(|((a, b), c)| (*beta * a + &(*alpha * b) + c) * *delta_inverse)
+            .collect::<Vec<_>>(),
+    );
 
     drop(c);
 
@@ -339,7 +893,12 @@ where
 
     // Compute the B-query in G2
     let b_g2_time = start_timer!(|| "Calculate B G2");
-    let b_g2_query = FixedBase::msm::<E::G2>(scalar_bits, g2_window, &g2_table, &b);
+    let b_g2_query = observe::<_, _, crate::error::Error>(
+        observer,
+        Phase::FixedBaseMsm(Query::BG2),
+        b.len(),
+        || FixedBase::msm::<E::G2>(scalar_bits, g2_window, &g2_table, &b),
+    )?;
     drop(g2_table);
     end_timer!(b_g2_time);
 
@@ -353,41 +912,63 @@ where
     // Generate the R1CS proving key
     let proving_key_time = start_timer!(|| "Generate the R1CS proving key");
 
-    let beta_repr = beta.into_bigint();
-    let delta_repr = delta.into_bigint();
+    let beta_repr = Zeroizing::new(beta.into_bigint());
+    let delta_repr = Zeroizing::new(delta.into_bigint());
 
     let alpha_g1 = g1_generator.mul_bigint(alpha.into_bigint());
-    let beta_g1 = g1_generator.mul_bigint(beta_repr);
-    let beta_g2 = g2_generator.mul_bigint(beta_repr);
-    let delta_g1 = g1_generator.mul_bigint(delta_repr);
-    let delta_g2 = g2_generator.mul_bigint(delta_repr);
+    let beta_g1 = g1_generator.mul_bigint(*beta_repr);
+    let beta_g2 = g2_generator.mul_bigint(*beta_repr);
+    let delta_g1 = g1_generator.mul_bigint(*delta_repr);
+    let delta_g2 = g2_generator.mul_bigint(*delta_repr);
 
     // Compute the A-query
     let a_time = start_timer!(|| "Calculate A");
-    let a_query = FixedBase::msm::<E::G1>(scalar_bits, g1_window, &g1_table, &a);
+    let a_query = observe::<_, _, crate::error::Error>(
+        observer,
+        Phase::FixedBaseMsm(Query::A),
+        a.len(),
+        || FixedBase::msm::<E::G1>(scalar_bits, g1_window, &g1_table, &a),
+    )?;
     drop(a);
     end_timer!(a_time);
 
     // Compute the B-query in G1
     let b_g1_time = start_timer!(|| "Calculate B G1");
-    let b_g1_query = FixedBase::msm::<E::G1>(scalar_bits, g1_window, &g1_table, &b);
+    let b_g1_query = observe::<_, _, crate::error::Error>(
+        observer,
+        Phase::FixedBaseMsm(Query::BG1),
+        b.len(),
+        || FixedBase::msm::<E::G1>(scalar_bits, g1_window, &g1_table, &b),
+    )?;
     drop(b);
     end_timer!(b_g1_time);
 
     // Compute the H-query
     let h_time = start_timer!(|| "Calculate H");
-    let h_query = FixedBase::msm::<E::G1>(
-        scalar_bits,
-        g1_window,
-        &g1_table,
-        &QAP::h_query_scalars::<_, D<E::ScalarField>>(m_raw - 1, t, zt, delta_inverse)?,
-    );
+    let h_scalars = Zeroizing::new(QAP::h_query_scalars::<_, D<E::ScalarField>>(
+        m_raw - 1,
+        *t,
+        *zt,
+        *delta_inverse,
+    )?);
+    let h_query = observe::<_, _, crate::error::Error>(
+        observer,
+        Phase::FixedBaseMsm(Query::H),
+        h_scalars.len(),
+        || FixedBase::msm::<E::G1>(scalar_bits, g1_window, &g1_table, &h_scalars),
+    )?;
+    drop(h_scalars);
 
     end_timer!(h_time);
 
     // Compute the L-query
     let l_time = start_timer!(|| "Calculate L");
-    let l_query = FixedBase::msm::<E::G1>(scalar_bits, g1_window, &g1_table, &l[n..]);
+    let l_query = observe::<_, _, crate::error::Error>(
+        observer,
+        Phase::FixedBaseMsm(Query::L),
+        l.len() - n,
+        || FixedBase::msm::<E::G1>(scalar_bits, g1_window, &g1_table, &l[n..]),
+    )?;
     drop(l);
     end_timer!(l_time);
 
@@ -396,13 +977,19 @@ where
     // Generate R1CS verification key
     let verifying_key_time = start_timer!(|| "Generate the R1CS verification key");
     let gamma_g2 = g2_generator.mul_bigint(gamma.into_bigint());
-    let gamma_abc_g1 = FixedBase::msm::<E::G1>(scalar_bits, g1_window, &g1_table, &gamma_abc);
+    let gamma_abc_g1 = observe::<_, _, crate::error::Error>(
+        observer,
+        Phase::FixedBaseMsm(Query::GammaAbc),
+        gamma_abc.len(),
+        || FixedBase::msm::<E::G1>(scalar_bits, g1_window, &g1_table, &gamma_abc),
+    )?;
 
     drop(g1_table);
 
     end_timer!(verifying_key_time);
 
-    let eta_gamma_inv_g1 = g1_generator.mul_bigint((eta * &gamma_inverse).into_bigint());
+    let eta_gamma_inv_g1 =
+        g1_generator.mul_bigint(Zeroizing::new(*eta * *gamma_inverse).into_bigint());
 
     let gamma_abc_g1_affine = E::G1::normalize_batch(&gamma_abc_g1);
     let eta_gamma_inv_g1_affine = eta_gamma_inv_g1.into_affine();
@@ -426,7 +1013,8 @@ where
     end_timer!(batch_normalization_time);
     end_timer!(setup_time);
 
-    let eta_delta_inv_g1 = g1_generator.mul_bigint((eta * &delta_inverse).into_bigint());
+    let eta_delta_inv_g1 =
+        g1_generator.mul_bigint(Zeroizing::new(*eta * *delta_inverse).into_bigint());
 
     let common = ProvingKeyCommon {
         beta_g1: beta_g1.into_affine(),
@@ -441,29 +1029,31 @@ where
     Ok((ProvingKey { vk, common }, num_instance_variables))
 }
 
+/// The secret randomness of the setup, zeroized when dropped
+#[derive(Zeroize, ZeroizeOnDrop)]
+struct ToxicWaste<F: PrimeField> {
+    alpha: F,
+    beta: F,
+    gamma: F,
+    delta: F,
+    eta: F,
+}
+
 #[inline]
-fn generate_randomness<E, R>(
-    rng: &mut R,
-) -> (
-    E::ScalarField,
-    E::ScalarField,
-    E::ScalarField,
-    E::ScalarField,
-    E::ScalarField,
-    E::G1,
-    E::G2,
-)
+fn generate_randomness<E, R>(rng: &mut R) -> (ToxicWaste<E::ScalarField>, E::G1, E::G2)
 where
     E: Pairing,
     R: Rng,
 {
-    let alpha = E::ScalarField::rand(rng);
-    let beta = E::ScalarField::rand(rng);
-    let gamma = E::ScalarField::rand(rng);
-    let delta = E::ScalarField::rand(rng);
-    let eta = E::ScalarField::rand(rng);
+    let toxic_waste = ToxicWaste {
+        alpha: E::ScalarField::rand(rng),
+        beta: E::ScalarField::rand(rng),
+        gamma: E::ScalarField::rand(rng),
+        delta: E::ScalarField::rand(rng),
+        eta: E::ScalarField::rand(rng),
+    };
 
     let g1_generator = E::G1::rand(rng);
     let g2_generator = E::G2::rand(rng);
-    (alpha, beta, gamma, delta, eta, g1_generator, g2_generator)
+    (toxic_waste, g1_generator, g2_generator)
 }
@@ -0,0 +1,107 @@
+//! Conversions between the keys and proofs of this crate and those of `ark_groth16`. This is only possible
+//! when no witnesses are committed, i.e. `commit_witness_count == 0`, as then LegoGroth16 is Groth16
+//! with the commitment `d` in the proof being `v * eta_gamma_inv_g1`.
+//!
+//! A Groth16 key has no `eta` so the converted key has `eta_gamma_inv_g1` and `eta_delta_inv_g1` as
+//! the identity, the proofs created or verified with it have `d` as the identity. The verifier
+//! rejects proofs with a non-identity `d` for such keys. Thus converting from `ark_groth16` and back
+//! is lossless.
+//!
+//! Going the other way, `eta` is dropped so a LegoGroth16 proof is convertible only if `d` is the
+//! identity, which is the case when the proof was created with `v = 0`. An existing proof can be
+//! changed to have `v = 0` with `rerandomize_proof_1`.
+
+use crate::{error::Error, Proof, ProvingKey, ProvingKeyCommon, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr};
+
+impl<E: Pairing> From<ark_groth16::Proof<E>> for Proof<E> {
+    fn from(proof: ark_groth16::Proof<E>) -> Self {
+        Self {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+            d: E::G1Affine::zero(),
+        }
+    }
+}
+
+impl<E: Pairing> TryFrom<Proof<E>> for ark_groth16::Proof<E> {
+    type Error = Error;
+
+    fn try_from(proof: Proof<E>) -> Result<Self, Self::Error> {
+        if !proof.d.is_zero() {
+            return Err(Error::CannotConvertToGroth16);
+        }
+        Ok(Self {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        })
+    }
+}
+
+impl<E: Pairing> From<ark_groth16::VerifyingKey<E>> for VerifyingKey<E> {
+    fn from(vk: ark_groth16::VerifyingKey<E>) -> Self {
+        Self {
+            alpha_g1: vk.alpha_g1,
+            beta_g2: vk.beta_g2,
+            gamma_g2: vk.gamma_g2,
+            delta_g2: vk.delta_g2,
+            gamma_abc_g1: vk.gamma_abc_g1,
+            eta_gamma_inv_g1: E::G1Affine::zero(),
+            commit_witness_count: 0,
+        }
+    }
+}
+
+impl<E: Pairing> TryFrom<VerifyingKey<E>> for ark_groth16::VerifyingKey<E> {
+    type Error = Error;
+
+    fn try_from(vk: VerifyingKey<E>) -> Result<Self, Self::Error> {
+        if vk.commit_witness_count != 0 {
+            return Err(Error::CannotConvertToGroth16);
+        }
+        Ok(Self {
+            alpha_g1: vk.alpha_g1,
+            beta_g2: vk.beta_g2,
+            gamma_g2: vk.gamma_g2,
+            delta_g2: vk.delta_g2,
+            gamma_abc_g1: vk.gamma_abc_g1,
+        })
+    }
+}
+
+impl<E: Pairing> From<ark_groth16::ProvingKey<E>> for ProvingKey<E> {
+    fn from(pk: ark_groth16::ProvingKey<E>) -> Self {
+        Self {
+            vk: pk.vk.into(),
+            common: ProvingKeyCommon {
+                beta_g1: pk.beta_g1,
+                delta_g1: pk.delta_g1,
+                eta_delta_inv_g1: E::G1Affine::zero(),
+                a_query: pk.a_query,
+                b_g1_query: pk.b_g1_query,
+                b_g2_query: pk.b_g2_query,
+                h_query: pk.h_query,
+                l_query: pk.l_query,
+            },
+        }
+    }
+}
+
+impl<E: Pairing> TryFrom<ProvingKey<E>> for ark_groth16::ProvingKey<E> {
+    type Error = Error;
+
+    fn try_from(pk: ProvingKey<E>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            vk: pk.vk.try_into()?,
+            beta_g1: pk.common.beta_g1,
+            delta_g1: pk.common.delta_g1,
+            a_query: pk.common.a_query,
+            b_g1_query: pk.common.b_g1_query,
+            b_g2_query: pk.common.b_g2_query,
+            h_query: pk.common.h_query,
+            l_query: pk.common.l_query,
+        })
+    }
+}
@@ -0,0 +1,190 @@
+//! Checks that the parts of a proving key agree with each other and with the circuit, like after loading
+//! the key from storage. The group elements are checked with randomized pairing checks, the elements
+//! depending on the QAP, like `a_query`, `h_query` and `l_query`, can only be checked for their length.
+
+use crate::{
+    error::Error,
+    link::{LinkSnark, PP},
+    prover::constraint_matrices,
+    LinkPublicGenerators, ProvingKeyCommon, ProvingKeyWithLink, VerifyingKey,
+};
+use crate::{ProvingKey, Vec};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_std::{cfg_iter, rand::Rng};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Check that `pk` is consistent and was generated for `circuit`, which does not need an assignment
+pub fn check_proving_key<E, C, R>(pk: &ProvingKey<E>, circuit: C, rng: &mut R) -> crate::Result<()>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+{
+    check_lengths(&pk.common, &pk.vk, circuit)?;
+    check_pairings(&pk.common, &pk.vk, rng)
+}
+
+/// Same as `check_proving_key` but also checks the CP_link parameters. `link_gens` are the generators
+/// of each link commitment, as given to the generator.
+pub fn check_proving_key_incl_cp_link<E, C, R, L>(
+    pk: &ProvingKeyWithLink<E, L>,
+    circuit: C,
+    link_gens: &[LinkPublicGenerators<E>],
+    rng: &mut R,
+) -> crate::Result<()>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    L: LinkSnark<E>,
+{
+    let vk = &pk.vk;
+    let groth16_vk = &vk.groth16_vk;
+    check_lengths(&pk.common, groth16_vk, circuit)?;
+    check_pairings(&pk.common, groth16_vk, rng)?;
+
+    let commit_witness_count = groth16_vk.commit_witness_count;
+    let num_links = vk.link_bases.len();
+    if link_gens.len() != num_links || vk.link_witness_ranges.len() != num_links {
+        return Err(Error::InvalidProvingKey);
+    }
+    if num_links == 0
+        || vk.link_pp
+            != (PP {
+                l: num_links + 1,
+                t: commit_witness_count + num_links + 1,
+                g1: link_gens[0].g1,
+                g2: link_gens[0].g2,
+            })
+    {
+        return Err(Error::InvalidProvingKey);
+    }
+    for ((bases, (start, end)), gens) in vk
+        .link_bases
+        .iter()
+        .zip(vk.link_witness_ranges.iter())
+        .zip(link_gens)
+    {
+        if *bases != gens.pedersen_gens
+            || gens.g1 != vk.link_pp.g1
+            || gens.g2 != vk.link_pp.g2
+            || start > end
+            || *end > commit_witness_count
+            || bases.len() != end - start + 1
+        {
+            return Err(Error::InvalidProvingKey);
+        }
+    }
+
+    // Commit to random witnesses in the link commitments and in `d` as the prover does, the proof of
+    // the link SNARK verifies only if the evaluation and verification keys were created together for
+    // these commitment keys
+    let witnesses = (0..commit_witness_count)
+        .map(|_| E::ScalarField::rand(rng))
+        .collect::<Vec<_>>();
+    let link_v = (0..num_links)
+        .map(|_| E::ScalarField::rand(rng))
+        .collect::<Vec<_>>();
+    let v = E::ScalarField::rand(rng);
+
+    let num_inputs = groth16_vk.gamma_abc_g1.len() - commit_witness_count;
+    let mut commitments = vk
+        .link_bases
+        .iter()
+        .zip(vk.link_witness_ranges.iter())
+        .zip(link_v.iter())
+        .map(|((bases, (start, end)), link_v)| {
+            let mut scalars = witnesses[*start..*end].to_vec();
+            scalars.push(*link_v);
+            msm::<E::G1>(bases, &scalars)
+        })
+        .collect::<Vec<_>>();
+    let mut d = msm::<E::G1>(&groth16_vk.gamma_abc_g1[num_inputs..], &witnesses);
+    d += groth16_vk.eta_gamma_inv_g1 * v;
+    commitments.push(d);
+    let commitments = E::G1::normalize_batch(&commitments);
+
+    let mut link_witness = witnesses;
+    link_witness.extend_from_slice(&link_v);
+    link_witness.push(v);
+    let link_pi =
+        L::prove(&vk.link_pp, &pk.link_ek, &link_witness).map_err(|_| Error::InvalidProvingKey)?;
+    L::verify(&vk.link_pp, &vk.link_vk, &commitments, &link_pi)
+        .map_err(|_| Error::InvalidProvingKey)
+}
+
+/// Check the lengths of the vectors in the key against the shape of the circuit
+fn check_lengths<E, C>(
+    pk_common: &ProvingKeyCommon<E>,
+    vk: &VerifyingKey<E>,
+    circuit: C,
+) -> crate::Result<()>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+{
+    let matrices = constraint_matrices::<E, C>(circuit)?;
+    let domain = GeneralEvaluationDomain::<E::ScalarField>::new(
+        matrices.num_constraints + matrices.num_instance_variables,
+    )
+    .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+    let num_variables = matrices.num_instance_variables + matrices.num_witness_variables;
+    if vk.commit_witness_count > matrices.num_witness_variables
+        || vk.gamma_abc_g1.len() != matrices.num_instance_variables + vk.commit_witness_count
+        || pk_common.a_query.len() != num_variables
+        || pk_common.b_g1_query.len() != num_variables
+        || pk_common.b_g2_query.len() != num_variables
+        || pk_common.h_query.len() + 1 != domain.size()
+        || pk_common.l_query.len() != matrices.num_witness_variables - vk.commit_witness_count
+    {
+        return Err(Error::CircuitAndProvingKeyMismatch);
+    }
+    Ok(())
+}
+
+/// Check with random `r_i` that
+/// - `e(beta_g1, delta_g2) = e(delta_g1, beta_g2)`
+/// - `e(eta_delta_inv_g1, delta_g2) = e(eta_gamma_inv_g1, gamma_g2)`
+/// - `e(sum(r_i * b_g1_query[i]), delta_g2) = e(delta_g1, sum(r_i * b_g2_query[i]))`
+///
+/// in a single multi-pairing after combining them with random coefficients
+fn check_pairings<E: Pairing, R: Rng>(
+    pk_common: &ProvingKeyCommon<E>,
+    vk: &VerifyingKey<E>,
+    rng: &mut R,
+) -> crate::Result<()> {
+    if pk_common.delta_g1.is_zero() || vk.delta_g2.is_zero() {
+        return Err(Error::InvalidProvingKey);
+    }
+    let r = (0..pk_common.b_g1_query.len())
+        .map(|_| E::ScalarField::rand(rng))
+        .collect::<Vec<_>>();
+    let b_g1 = msm::<E::G1>(&pk_common.b_g1_query, &r);
+    let b_g2 = msm::<E::G2>(&pk_common.b_g2_query, &r);
+
+    let c1 = E::ScalarField::rand(rng);
+    let c2 = E::ScalarField::rand(rng);
+    // e(c1 * beta_g1 + c2 * eta_delta_inv_g1 + b_g1, delta_g2) * e(-delta_g1, c1 * beta_g2 + b_g2)
+    //  * e(-c2 * eta_gamma_inv_g1, gamma_g2) = 1
+    let lhs = pk_common.beta_g1 * c1 + pk_common.eta_delta_inv_g1 * c2 + b_g1;
+    let rhs = vk.beta_g2 * c1 + b_g2;
+    let eta_gamma_inv = vk.eta_gamma_inv_g1 * c2;
+    let g1 = E::G1::normalize_batch(&[lhs, -pk_common.delta_g1.into_group(), -eta_gamma_inv]);
+    let g2 = E::G2::normalize_batch(&[vk.delta_g2.into_group(), rhs, vk.gamma_g2.into_group()]);
+    if !E::multi_pairing(g1, g2).is_zero() {
+        return Err(Error::InvalidProvingKey);
+    }
+    Ok(())
+}
+
+fn msm<G: CurveGroup>(bases: &[G::Affine], scalars: &[G::ScalarField]) -> G {
+    let scalars = cfg_iter!(scalars)
+        .map(|s| s.into_bigint())
+        .collect::<Vec<_>>();
+    G::msm_bigint(bases, &scalars)
+}
@@ -34,21 +34,47 @@ pub mod link;
 
 pub mod error;
 
+/// Check the consistency of proving keys, like after loading them.
+pub mod key_check;
+
+/// Hashes identifying keys.
+pub mod fingerprint;
+
+/// Self-describing format for storing keys, proofs and SRS.
+pub mod envelope;
+
+/// Progress reporting and cancellation of long-running operations.
+pub mod progress;
+
+/// Create proofs collaboratively from secret shared witnesses.
+pub mod mpc;
+
 #[cfg(feature = "circom")]
 pub mod circom;
 
 #[cfg(feature = "aggregation")]
 pub mod aggregation;
 
-/// Constraints for the Groth16 verifier.
-// Cannot yet create a LegoGroth16 gadget (for recursive proof) so commenting it out.
-// #[cfg(feature = "r1cs")]
-// pub mod constraints;
+/// Implementation of the `ark_snark` traits.
+#[cfg(feature = "ark-snark")]
+pub mod snark;
+
+/// Conversions to and from the keys and proofs of `ark_groth16` when no witnesses are committed.
+#[cfg(feature = "ark-groth16")]
+pub mod groth16_conversion;
+
+/// Constraints for the LegoGroth16 verifier, to verify proofs in another circuit.
+#[cfg(feature = "r1cs")]
+pub mod constraints;
 
 pub type Result<T> = core::result::Result<T, error::Error>;
 
-pub use self::{data_structures::*, generator::*, prover::*, r1cs_to_qap::*, verifier::*};
+pub use self::{
+    data_structures::*, generator::*, key_check::*, prover::*, r1cs_to_qap::*, verifier::*,
+};
 use ark_std::vec::Vec;
+#[cfg(feature = "ark-snark")]
+pub use snark::{CommitAndProveSNARK, LegoGroth16};
 
 #[cfg(test)]
 pub mod tests;
@@ -1,7 +1,16 @@
+use ark_std::string::String;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum LinkError {
     InvalidIndex(usize, usize),
     VectorLongerThanExpected(usize, usize),
     VectorWithUnexpectedLength(usize, usize),
     InvalidProof,
+    /// A variable or commitment with this name was already added
+    DuplicateName(String),
+    /// No variable or commitment with this name was added
+    UnknownName(String),
+    /// The generators of the link commitments have different `g1` or `g2`, which must be the same
+    /// as they are the generators of the subspace SNARK proving all links
+    DifferentLinkGenerators,
 }
@@ -1,13 +1,21 @@
 pub mod error;
+pub mod relation;
+pub mod sigma;
 pub mod snark;
 mod utils;
 
+pub use relation::*;
+pub use sigma::*;
 pub use snark::*;
 pub use utils::*;
 
 #[cfg(test)]
 mod test {
-    use super::{PESubspaceSnark, SparseMatrix, SubspaceSnark, PP};
+    use super::error::LinkError;
+    use super::{
+        LinearRelationBuilder, LinkSnark, PESubspaceSnark, SigmaSubspaceSnark, SparseMatrix,
+        SubspaceSnark, PP,
+    };
     use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
     use ark_ec::{AffineRepr, CurveGroup, Group};
     use ark_ff::{One, PrimeField, UniformRand, Zero};
@@ -181,4 +189,152 @@ mod test {
 
         PESubspaceSnark::<Bls12_381>::verify(&pp, &vk, &x, &pi).unwrap();
     }
+
+    #[test]
+    fn test_sigma_some_vals_equal() {
+        // Same relation as `test_some_vals_equal` but proved with the transparent `SigmaSubspaceSnark`
+
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let g1 = G1Projective::rand(&mut rng).into_affine();
+        let g2 = G2Projective::rand(&mut rng).into_affine();
+
+        let l = 2;
+        let t = 4;
+        let pp = PP::<G1Affine, G2Affine> { l, t, g1, g2 };
+
+        let bases1 = (0..3)
+            .map(|_| G1Projective::rand(&mut rng).into_affine())
+            .collect::<Vec<_>>();
+        let bases2 = (0..3)
+            .map(|_| G1Projective::rand(&mut rng).into_affine())
+            .collect::<Vec<_>>();
+
+        let mut m = SparseMatrix::new(l, t);
+        m.insert_row_slice(0, 0, bases1.clone()).unwrap();
+        m.insert_row_slice(1, 0, bases2[0..2].to_vec()).unwrap();
+        m.insert_row_slice(1, 3, bases2[2..].to_vec()).unwrap();
+
+        let w: Vec<Fr> = (0..t).map(|_| Fr::rand(&mut rng)).collect();
+        let mut w_bad = w.clone();
+        w_bad[3] = Fr::rand(&mut rng);
+
+        let x: Vec<G1Affine> = vec![
+            bases1[0].into_group().mul_bigint(w[0].into_bigint())
+                + bases1[1].mul_bigint(w[1].into_bigint())
+                + bases1[2].mul_bigint(w[2].into_bigint()),
+            bases2[0].into_group().mul_bigint(w[0].into_bigint())
+                + bases2[1].mul_bigint(w[1].into_bigint())
+                + bases2[2].mul_bigint(w[3].into_bigint()),
+        ]
+        .into_iter()
+        .map(|p| p.into_affine())
+        .collect::<Vec<_>>();
+
+        let (ek, vk) = SigmaSubspaceSnark::<Bls12_381>::keygen(&mut rng, &pp, &m).unwrap();
+
+        let pi = SigmaSubspaceSnark::<Bls12_381>::prove(&pp, &ek, &w).unwrap();
+        let pi_bad = SigmaSubspaceSnark::<Bls12_381>::prove(&pp, &ek, &w_bad).unwrap();
+
+        SigmaSubspaceSnark::<Bls12_381>::verify(&pp, &vk, &x, &pi).unwrap();
+        assert!(SigmaSubspaceSnark::<Bls12_381>::verify(&pp, &vk, &x, &pi_bad).is_err());
+
+        // Proof does not verify for a different statement
+        let mut x_bad = x.clone();
+        x_bad.swap(0, 1);
+        assert!(SigmaSubspaceSnark::<Bls12_381>::verify(&pp, &vk, &x_bad, &pi).is_err());
+
+        // Matrix not matching the public params is rejected
+        assert!(SigmaSubspaceSnark::<Bls12_381>::keygen(
+            &mut rng,
+            &pp,
+            &SparseMatrix::new(l, t + 1)
+        )
+        .is_err());
+    }
+
+    fn check_linear_relations<L: LinkSnark<Bls12_381>>() {
+        // Given commitments `C1 = g1 * a + g2 * b + h * r1`, `C2 = g3 * c + h * r2` and `C3 = g4 * d + h * r3`,
+        // prove that `c = a + b` and `d = a - b`
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let g1 = G1Projective::rand(&mut rng).into_affine();
+        let g2 = G2Projective::rand(&mut rng).into_affine();
+        let bases = (0..5)
+            .map(|_| G1Projective::rand(&mut rng).into_affine())
+            .collect::<Vec<_>>();
+        let h = bases[4];
+        let one = Fr::one();
+
+        let mut builder = LinearRelationBuilder::<Bls12_381, L>::new();
+        for v in ["a", "b", "r1", "r2", "r3"] {
+            builder.add_variable(v).unwrap();
+        }
+        assert_eq!(
+            builder.add_variable("a").unwrap_err(),
+            LinkError::DuplicateName("a".into())
+        );
+        builder
+            .add_commitment_to_variables("C1", &[bases[0], bases[1], h], &["a", "b", "r1"])
+            .unwrap();
+        builder
+            .add_commitment(
+                "C2",
+                &[bases[2], h],
+                &[&[("a", one), ("b", one)], &[("r2", one)]],
+            )
+            .unwrap();
+        builder
+            .add_commitment(
+                "C3",
+                &[bases[3], h],
+                &[&[("a", one), ("b", -one)], &[("r3", one)]],
+            )
+            .unwrap();
+        assert_eq!(
+            builder
+                .add_commitment_to_variables("C4", &[bases[0]], &["x"])
+                .unwrap_err(),
+            LinkError::UnknownName("x".into())
+        );
+        assert_eq!(
+            builder
+                .add_commitment_to_variables("C4", &[bases[0]], &["a", "b"])
+                .unwrap_err(),
+            LinkError::VectorWithUnexpectedLength(2, 1)
+        );
+        assert_eq!(builder.commitment_index("C3").unwrap(), 2);
+
+        let pk = builder.build(&mut rng, g1, g2).unwrap();
+
+        let w = (0..5).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let (a, b) = (w[0], w[1]);
+        let comms = vec![
+            (bases[0] * a + bases[1] * b + h * w[2]).into_affine(),
+            (bases[2] * (a + b) + h * w[3]).into_affine(),
+            (bases[3] * (a - b) + h * w[4]).into_affine(),
+        ];
+        assert_eq!(pk.commit(&w).unwrap(), comms);
+
+        let proof = pk.prove(&w).unwrap();
+        pk.vk.verify(&comms, &proof).unwrap();
+
+        // `C2` commits to `a + b + 1`
+        let mut bad_comms = comms.clone();
+        bad_comms[1] = (bad_comms[1] + bases[2]).into_affine();
+        assert!(pk.vk.verify(&bad_comms, &proof).is_err());
+
+        assert_eq!(
+            pk.prove(&w[..4]).unwrap_err(),
+            LinkError::VectorWithUnexpectedLength(4, 5)
+        );
+        assert_eq!(
+            pk.vk.verify(&comms[..2], &proof).unwrap_err(),
+            LinkError::VectorWithUnexpectedLength(2, 3)
+        );
+    }
+
+    #[test]
+    fn test_linear_relations() {
+        check_linear_relations::<PESubspaceSnark<Bls12_381>>();
+        check_linear_relations::<SigmaSubspaceSnark<Bls12_381>>();
+    }
 }
@@ -0,0 +1,216 @@
+//! Builder for proving that values committed in several Pedersen commitments satisfy linear relations,
+//! using a Subspace Snark. Each commitment is a row of the matrix `M` and each witness variable a column
+//! so that the commitments are `y = Mx` where `x` are the witness variables.
+//!
+//! A commitment is described by its bases and, for each base, the linear combination of variables it
+//! commits to. This lets the relations be expressed by sharing variables among commitments:
+//! - Equality: committing to the same variable in 2 commitments (like an external commitment and `link_d`)
+//!   proves that they open to the same value.
+//! - Sums and differences: if `c = a + b`, then the commitment to `c` is a commitment to the linear
+//!   combination `a + b` and thus `c` need not be a variable. Similarly `c = a - b` is the linear
+//!   combination with coefficients `1` and `-1`.
+//!
+//! The blindings of the commitments are variables as well.
+
+use crate::link::error::LinkError;
+use crate::link::snark::{LinkSnark, PESubspaceSnark, PP};
+use crate::link::utils::*;
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{One, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{marker::PhantomData, rand::Rng, string::String, vec, vec::Vec};
+
+/// A term of a linear combination, i.e. a variable's name and its coefficient
+pub type Term<'a, F> = (&'a str, F);
+
+/// Builds the matrix for the Subspace Snark `L` from named variables and commitments
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearRelationBuilder<E: Pairing, L: LinkSnark<E> = PESubspaceSnark<E>> {
+    variables: Vec<String>,
+    commitments: Vec<String>,
+    /// The matrix entries as (row, column, value)
+    entries: Vec<(usize, usize, E::G1Affine)>,
+    _phantom: PhantomData<L>,
+}
+
+/// Key used by the verifier. The commitments passed to `verify` must be in the order in which they were
+/// added to the builder, as given by `commitment_names`
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LinearRelationVerifyingKey<E: Pairing, L: LinkSnark<E> = PESubspaceSnark<E>> {
+    pub pp: PP<E::G1Affine, E::G2Affine>,
+    pub vk: L::VK,
+    pub commitment_names: Vec<String>,
+}
+
+/// Key used by the prover. The witness passed to `prove` must be in the order in which the variables
+/// were added to the builder, as given by `variable_names`
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LinearRelationProvingKey<E: Pairing, L: LinkSnark<E> = PESubspaceSnark<E>> {
+    pub vk: LinearRelationVerifyingKey<E, L>,
+    pub ek: L::EK,
+    pub m: SparseMatrix<E::G1Affine>,
+    pub variable_names: Vec<String>,
+}
+
+impl<E: Pairing, L: LinkSnark<E>> Default for LinearRelationBuilder<E, L> {
+    fn default() -> Self {
+        Self {
+            variables: Vec::new(),
+            commitments: Vec::new(),
+            entries: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: Pairing, L: LinkSnark<E>> LinearRelationBuilder<E, L> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a witness variable and return its index in the witness
+    pub fn add_variable(&mut self, name: &str) -> Result<usize, LinkError> {
+        if self.variables.iter().any(|v| v == name) {
+            return Err(LinkError::DuplicateName(name.into()));
+        }
+        self.variables.push(name.into());
+        Ok(self.variables.len() - 1)
+    }
+
+    /// Add a commitment `bases[0] * openings[0] + bases[1] * openings[1] + ...` where each opening
+    /// is a linear combination of already added variables. Returns the index of the commitment.
+    pub fn add_commitment(
+        &mut self,
+        name: &str,
+        bases: &[E::G1Affine],
+        openings: &[&[Term<E::ScalarField>]],
+    ) -> Result<usize, LinkError> {
+        if self.commitments.iter().any(|c| c == name) {
+            return Err(LinkError::DuplicateName(name.into()));
+        }
+        if bases.len() != openings.len() {
+            return Err(LinkError::VectorWithUnexpectedLength(
+                openings.len(),
+                bases.len(),
+            ));
+        }
+        // The entry for a variable is the sum of bases weighted by the variable's coefficients
+        let mut row = vec![E::G1::zero(); self.variables.len()];
+        for (base, opening) in bases.iter().zip(openings.iter()) {
+            for (var, coeff) in opening.iter() {
+                let j = self.variable_index(var)?;
+                row[j] += base.mul_bigint(coeff.into_bigint());
+            }
+        }
+        let r = self.commitments.len();
+        for (j, entry) in E::G1::normalize_batch(&row).into_iter().enumerate() {
+            if !entry.is_zero() {
+                self.entries.push((r, j, entry));
+            }
+        }
+        self.commitments.push(name.into());
+        Ok(r)
+    }
+
+    /// Add a commitment `bases[0] * variables[0] + bases[1] * variables[1] + ...`
+    pub fn add_commitment_to_variables(
+        &mut self,
+        name: &str,
+        bases: &[E::G1Affine],
+        variables: &[&str],
+    ) -> Result<usize, LinkError> {
+        let one = E::ScalarField::one();
+        let terms = variables.iter().map(|v| [(*v, one)]).collect::<Vec<_>>();
+        let openings = terms.iter().map(|t| t.as_slice()).collect::<Vec<_>>();
+        self.add_commitment(name, bases, &openings)
+    }
+
+    pub fn variable_index(&self, name: &str) -> Result<usize, LinkError> {
+        self.variables
+            .iter()
+            .position(|v| v == name)
+            .ok_or_else(|| LinkError::UnknownName(name.into()))
+    }
+
+    pub fn commitment_index(&self, name: &str) -> Result<usize, LinkError> {
+        self.commitments
+            .iter()
+            .position(|c| c == name)
+            .ok_or_else(|| LinkError::UnknownName(name.into()))
+    }
+
+    /// Create the matrix and run the key generation of the Subspace Snark. `g1` and `g2` are the
+    /// generators for its public params.
+    pub fn build<R: Rng>(
+        self,
+        rng: &mut R,
+        g1: E::G1Affine,
+        g2: E::G2Affine,
+    ) -> Result<LinearRelationProvingKey<E, L>, LinkError> {
+        let pp = PP::new(self.commitments.len(), self.variables.len(), g1, g2);
+        let mut m = SparseMatrix::new(pp.l, pp.t);
+        for (r, c, v) in self.entries {
+            m.insert_val(r, c, v)?;
+        }
+        let (ek, vk) = L::keygen(rng, &pp, &m)?;
+        Ok(LinearRelationProvingKey {
+            vk: LinearRelationVerifyingKey {
+                pp,
+                vk,
+                commitment_names: self.commitments,
+            },
+            ek,
+            m,
+            variable_names: self.variables,
+        })
+    }
+}
+
+impl<E: Pairing, L: LinkSnark<E>> LinearRelationProvingKey<E, L> {
+    /// Compute the commitments for the given witness. Useful when the commitments are not created elsewhere.
+    pub fn commit(&self, witness: &[E::ScalarField]) -> Result<Vec<E::G1Affine>, LinkError> {
+        self.check_witness_length(witness)?;
+        SparseLinAlgebra::<E>::sparse_matrix_vector_mult(&self.m, witness)
+    }
+
+    pub fn prove(&self, witness: &[E::ScalarField]) -> Result<L::Proof, LinkError> {
+        self.check_witness_length(witness)?;
+        L::prove(&self.vk.pp, &self.ek, witness)
+    }
+
+    pub fn variable_index(&self, name: &str) -> Result<usize, LinkError> {
+        self.variable_names
+            .iter()
+            .position(|v| v == name)
+            .ok_or_else(|| LinkError::UnknownName(name.into()))
+    }
+
+    fn check_witness_length(&self, witness: &[E::ScalarField]) -> Result<(), LinkError> {
+        if witness.len() != self.variable_names.len() {
+            return Err(LinkError::VectorWithUnexpectedLength(
+                witness.len(),
+                self.variable_names.len(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<E: Pairing, L: LinkSnark<E>> LinearRelationVerifyingKey<E, L> {
+    pub fn verify(&self, commitments: &[E::G1Affine], proof: &L::Proof) -> Result<(), LinkError> {
+        if commitments.len() != self.commitment_names.len() {
+            return Err(LinkError::VectorWithUnexpectedLength(
+                commitments.len(),
+                self.commitment_names.len(),
+            ));
+        }
+        L::verify(&self.pp, &self.vk, commitments, proof)
+    }
+
+    pub fn commitment_index(&self, name: &str) -> Result<usize, LinkError> {
+        self.commitment_names
+            .iter()
+            .position(|c| c == name)
+            .ok_or_else(|| LinkError::UnknownName(name.into()))
+    }
+}
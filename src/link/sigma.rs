@@ -0,0 +1,169 @@
+//! A transparent alternative to the zkSNARK for Linear Subspaces in `snark.rs`. This is a Fiat-Shamir
+//! transformed sigma protocol for proving knowledge of `x` such that `y = Mx` where `M` is a (sparse)
+//! matrix of group elements, like the bases of multiple Pedersen commitments. Unlike `PESubspaceSnark`,
+//! key generation does not create any trapdoor so no trusted setup is needed. The cost is that the
+//! proof is not succinct, it contains 1 response per column of `M`, and the verifier does a multi-exp
+//! per row of `M` rather than a constant number of pairings.
+
+use crate::fingerprint::Fingerprint;
+use crate::link::error::LinkError;
+use crate::link::snark::{SubspaceSnark, PP};
+use crate::link::utils::*;
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{cfg_iter, marker::PhantomData, rand::Rng, vec::Vec};
+use dock_crypto_utils::transcript::{new_merlin_transcript, Transcript};
+use zeroize::Zeroizing;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Label for the transcript from which the challenge is derived
+const CHALLENGE_LABEL: &[u8] = b"LegoGroth16: SigmaSubspaceSnark challenge";
+/// Label for the transcript from which the prover's blindings are derived
+const NONCE_LABEL: &[u8] = b"LegoGroth16: SigmaSubspaceSnark nonces";
+
+/// Proof of the sigma protocol. Contains the challenge and 1 response per column of the matrix.
+#[derive(Clone, Default, PartialEq, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SigmaProof<F: PrimeField> {
+    pub challenge: F,
+    pub responses: Vec<F>,
+}
+
+/// Evaluation and verification key of the sigma protocol. `context` is absorbed in the challenge when set,
+/// like the fingerprint of the verifying key the link proof is made for, so that the proof cannot be
+/// used with another key having the same matrix.
+#[derive(Clone, Default, PartialEq, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SigmaKey<G: AffineRepr> {
+    pub matrix: SparseMatrix<G>,
+    pub context: Option<Fingerprint>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SigmaSubspaceSnark<PE: Pairing> {
+    pairing_engine_type: PhantomData<PE>,
+}
+
+// NB: Same as `PESubspaceSnark`, the system is for y = Mx
+impl<PE: Pairing> SubspaceSnark for SigmaSubspaceSnark<PE> {
+    const ID: u8 = 2;
+
+    type KMtx = SparseMatrix<PE::G1Affine>;
+    type InVec = PE::ScalarField;
+    type OutVec = PE::G1Affine;
+
+    type PP = PP<PE::G1Affine, PE::G2Affine>;
+
+    /// Both the prover and verifier only need the matrix as there is no trapdoor
+    type EK = SigmaKey<PE::G1Affine>;
+    type VK = SigmaKey<PE::G1Affine>;
+
+    type Proof = SigmaProof<PE::ScalarField>;
+
+    /// No randomness is needed, the keys are the matrix itself. Unlike `PESubspaceSnark`, a column can have
+    /// any number of non-zero items.
+    fn keygen<R: Rng>(
+        _rng: &mut R,
+        pp: &Self::PP,
+        m: &Self::KMtx,
+    ) -> Result<(Self::EK, Self::VK), LinkError> {
+        if m.nr != pp.l {
+            return Err(LinkError::VectorWithUnexpectedLength(m.nr, pp.l));
+        }
+        if m.nc != pp.t {
+            return Err(LinkError::VectorWithUnexpectedLength(m.nc, pp.t));
+        }
+        let key = SigmaKey {
+            matrix: m.clone(),
+            context: None,
+        };
+        Ok((key.clone(), key))
+    }
+
+    fn prove(pp: &Self::PP, ek: &Self::EK, w: &[Self::InVec]) -> Result<Self::Proof, LinkError> {
+        if pp.t < w.len() {
+            return Err(LinkError::VectorLongerThanExpected(w.len(), pp.t));
+        }
+        if ek.matrix.nr != pp.l {
+            return Err(LinkError::VectorWithUnexpectedLength(ek.matrix.nr, pp.l));
+        }
+        let y = SparseLinAlgebra::<PE>::sparse_matrix_vector_mult(&ek.matrix, w)?;
+
+        // The trait does not provide an RNG to the prover so the blindings are derived from the
+        // witness and the key, similar to deterministic signatures. As the witness contains the
+        // commitment randomness, the blindings are unpredictable to the verifier.
+        let mut nonce_transcript = new_merlin_transcript(NONCE_LABEL);
+        nonce_transcript.append(b"key", ek);
+        nonce_transcript.append(b"witness", &w);
+        let blindings = Zeroizing::new(
+            (0..w.len())
+                .map(|_| nonce_transcript.challenge_scalar::<PE::ScalarField>(b"blinding"))
+                .collect::<Vec<_>>(),
+        );
+
+        let t = SparseLinAlgebra::<PE>::sparse_matrix_vector_mult(&ek.matrix, &blindings)?;
+        let challenge = compute_challenge::<PE>(pp, ek, &y, &t);
+
+        let responses = cfg_iter!(blindings)
+            .zip(cfg_iter!(w))
+            .map(|(b, w)| *b + challenge * w)
+            .collect::<Vec<_>>();
+        Ok(SigmaProof {
+            challenge,
+            responses,
+        })
+    }
+
+    fn verify(
+        pp: &Self::PP,
+        vk: &Self::VK,
+        y: &[Self::OutVec],
+        pi: &Self::Proof,
+    ) -> Result<(), LinkError> {
+        if pp.l != y.len() {
+            return Err(LinkError::VectorWithUnexpectedLength(y.len(), pp.l));
+        }
+        if vk.matrix.nr != y.len() {
+            return Err(LinkError::VectorWithUnexpectedLength(y.len(), vk.matrix.nr));
+        }
+        if pp.t < pi.responses.len() {
+            return Err(LinkError::VectorLongerThanExpected(
+                pi.responses.len(),
+                pp.t,
+            ));
+        }
+
+        // Recompute the prover's commitments as `t = M * responses - challenge * y`
+        let m_z = SparseLinAlgebra::<PE>::sparse_matrix_vector_mult(&vk.matrix, &pi.responses)?;
+        let challenge_repr = pi.challenge.into_bigint();
+        let t = cfg_iter!(m_z)
+            .zip(cfg_iter!(y))
+            .map(|(m_z, y)| m_z.into_group() - y.mul_bigint(challenge_repr))
+            .collect::<Vec<_>>();
+        let t = PE::G1::normalize_batch(&t);
+
+        if compute_challenge::<PE>(pp, vk, y, &t) != pi.challenge {
+            return Err(LinkError::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+/// The challenge binds the public params, the matrix and context, the statement and the prover's commitments
+fn compute_challenge<PE: Pairing>(
+    pp: &PP<PE::G1Affine, PE::G2Affine>,
+    key: &SigmaKey<PE::G1Affine>,
+    y: &[PE::G1Affine],
+    t: &[PE::G1Affine],
+) -> PE::ScalarField {
+    let mut transcript = new_merlin_transcript(CHALLENGE_LABEL);
+    transcript.append(b"pp", pp);
+    transcript.append(b"matrix", &key.matrix);
+    if let Some(context) = &key.context {
+        context.append_to_transcript(&mut transcript);
+    }
+    transcript.append(b"y", &y);
+    transcript.append(b"t", &t);
+    transcript.challenge_scalar::<PE::ScalarField>(b"challenge")
+}
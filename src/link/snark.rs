@@ -10,11 +10,13 @@ use ark_ff::{UniformRand, Zero};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{
     cfg_iter,
+    fmt::Debug,
     marker::PhantomData,
     ops::{Mul, Neg},
     rand::Rng,
     vec::Vec,
 };
+use zeroize::Zeroizing;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
@@ -55,16 +57,20 @@ pub struct VK<G2: Clone + Default + CanonicalSerialize + CanonicalDeserialize> {
 }
 
 pub trait SubspaceSnark {
+    /// Identifies the Subspace Snark in the header of enveloped keys and proofs, so that they are not
+    /// read as keys and proofs of another Subspace Snark
+    const ID: u8;
+
     type KMtx;
     type InVec;
     type OutVec;
 
     type PP;
 
-    type EK;
-    type VK;
+    type EK: Clone + Default + PartialEq + Debug + CanonicalSerialize + CanonicalDeserialize;
+    type VK: Clone + Default + PartialEq + Debug + CanonicalSerialize + CanonicalDeserialize;
 
-    type Proof;
+    type Proof: Clone + Default + PartialEq + Debug + CanonicalSerialize + CanonicalDeserialize;
 
     fn keygen<R: Rng>(
         rng: &mut R,
@@ -80,12 +86,37 @@ pub trait SubspaceSnark {
     ) -> Result<(), LinkError>;
 }
 
+/// A `SubspaceSnark` over the groups of the pairing `E` that can be used for CP_link, i.e. to prove that
+/// multiple Pedersen commitments in `E::G1` (like `proof.d` and `link_d`) open to the same values.
+pub trait LinkSnark<E: Pairing>:
+    SubspaceSnark<
+    KMtx = SparseMatrix<E::G1Affine>,
+    InVec = E::ScalarField,
+    OutVec = E::G1Affine,
+    PP = PP<E::G1Affine, E::G2Affine>,
+>
+{
+}
+
+impl<E: Pairing, S> LinkSnark<E> for S where
+    S: SubspaceSnark<
+        KMtx = SparseMatrix<E::G1Affine>,
+        InVec = E::ScalarField,
+        OutVec = E::G1Affine,
+        PP = PP<E::G1Affine, E::G2Affine>,
+    >
+{
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct PESubspaceSnark<PE: Pairing> {
     pairing_engine_type: PhantomData<PE>,
 }
 
 // NB: Now the system is for y = Mx
 impl<PE: Pairing> SubspaceSnark for PESubspaceSnark<PE> {
+    const ID: u8 = 1;
+
     type KMtx = SparseMatrix<PE::G1Affine>;
     type InVec = PE::ScalarField;
     type OutVec = PE::G1Affine;
@@ -107,21 +138,21 @@ impl<PE: Pairing> SubspaceSnark for PESubspaceSnark<PE> {
         pp: &Self::PP,
         m: &Self::KMtx,
     ) -> Result<(Self::EK, Self::VK), LinkError> {
-        // `k` is the trapdoor
-        let mut k: Vec<PE::ScalarField> = Vec::with_capacity(pp.l);
+        // `k` is the trapdoor, it and `a` are zeroized when dropped
+        let mut k = Zeroizing::new(Vec::with_capacity(pp.l));
         for _ in 0..pp.l {
             k.push(PE::ScalarField::rand(rng));
         }
 
-        let a = PE::ScalarField::rand(rng);
+        let a = Zeroizing::new(PE::ScalarField::rand(rng));
 
         let p = SparseLinAlgebra::<PE>::sparse_vector_matrix_mult(&k, m)?;
 
-        let c = scale_vector::<PE>(&a, &k);
+        let c = Zeroizing::new(scale_vector::<PE>(&a, &k));
         let ek = EK::<PE::G1Affine> { p };
         let vk = VK::<PE::G2Affine> {
             c: multiples_of_g::<PE::G2Affine>(&pp.g2, &c),
-            a: pp.g2.mul(a).into_affine(),
+            a: pp.g2.mul(*a).into_affine(),
         };
         Ok((ek, vk))
     }
@@ -3,6 +3,11 @@
 use ark_ec::scalar_mul::fixed_base::FixedBase;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::{PrimeField, Zero};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+use ark_std::cfg_into_iter;
+use ark_std::io::{Read, Write};
 use ark_std::marker::PhantomData;
 use ark_std::ops::{AddAssign, Mul};
 use ark_std::vec;
@@ -10,8 +15,11 @@ use ark_std::vec::Vec;
 
 use crate::link::error::LinkError;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 /// CoeffPos: A struct to help build sparse matrices.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CoeffPos<T> {
     val: T,
     pos: usize,
@@ -23,7 +31,7 @@ type Col<T> = Vec<CoeffPos<T>>;
 /* TODO: One could consider a cache-friendlier implementation for the 2-row case*/
 
 /// Column-Major Sparse Matrix
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct SparseMatrix<T> {
     cols: Vec<Col<T>>, // a vector of columns
     pub nr: usize,     // no. of rows
@@ -72,6 +80,80 @@ impl<T: Copy> SparseMatrix<T> {
     }
 }
 
+impl<T: CanonicalSerialize> CanonicalSerialize for CoeffPos<T> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.pos.serialize_with_mode(&mut writer, compress)?;
+        self.val.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.pos.serialized_size(compress) + self.val.serialized_size(compress)
+    }
+}
+
+impl<T: Valid> Valid for CoeffPos<T> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.val.check()
+    }
+}
+
+impl<T: CanonicalDeserialize> CanonicalDeserialize for CoeffPos<T> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let pos = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let val = T::deserialize_with_mode(&mut reader, compress, validate)?;
+        Ok(Self { pos, val })
+    }
+}
+
+impl<T: CanonicalSerialize> CanonicalSerialize for SparseMatrix<T> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.nr.serialize_with_mode(&mut writer, compress)?;
+        self.nc.serialize_with_mode(&mut writer, compress)?;
+        self.cols.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.nr.serialized_size(compress)
+            + self.nc.serialized_size(compress)
+            + self.cols.serialized_size(compress)
+    }
+}
+
+impl<T: Valid> Valid for SparseMatrix<T> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.cols.check()
+    }
+}
+
+impl<T: CanonicalDeserialize> CanonicalDeserialize for SparseMatrix<T> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let nr = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let nc = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let cols = Vec::<Col<T>>::deserialize_with_mode(&mut reader, compress, validate)?;
+        // The dimensions must agree with the entries, otherwise later indexing will fail
+        if cols.len() != nc || cols.iter().flatten().any(|c| c.pos >= nr) {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(Self { cols, nr, nc })
+    }
+}
+
 pub struct SparseLinAlgebra<PE: Pairing> {
     pairing_engine_type: PhantomData<PE>,
 }
@@ -114,6 +196,34 @@ impl<PE: Pairing> SparseLinAlgebra<PE> {
         }
         Ok(res)
     }
+
+    /// Multiply the sparse matrix `m` with the column vector `v` to compute `m \dot v`. `v` has
+    /// dimensions `v.len() x 1` and m has dimensions `nr x nc`. Returns a matrix of dimension `nr x 1`.
+    /// `v` can be shorter than the number of columns in which case the missing entries are considered 0.
+    pub fn sparse_matrix_vector_mult(
+        m: &SparseMatrix<PE::G1Affine>,
+        v: &[PE::ScalarField],
+    ) -> Result<Vec<PE::G1Affine>, LinkError> {
+        if v.len() > m.nc {
+            return Err(LinkError::VectorLongerThanExpected(v.len(), m.nc));
+        }
+        // Collect the bases and scalars of each row so that each output is a single multi-exp
+        let mut bases = vec![Vec::new(); m.nr];
+        let mut scalars = vec![Vec::new(); m.nr];
+        for (c, s) in v.iter().enumerate() {
+            for coeffpos in m.get_col(c)? {
+                if coeffpos.pos >= m.nr {
+                    return Err(LinkError::InvalidIndex(coeffpos.pos, m.nr));
+                }
+                bases[coeffpos.pos].push(coeffpos.val);
+                scalars[coeffpos.pos].push(s.into_bigint());
+            }
+        }
+        let res = cfg_into_iter!(0..m.nr)
+            .map(|r| PE::G1::msm_bigint(&bases[r], &scalars[r]))
+            .collect::<Vec<_>>();
+        Ok(PE::G1::normalize_batch(&res))
+    }
 }
 
 /// MSM between a scalar vector and a G1 vector
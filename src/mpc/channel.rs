@@ -0,0 +1,129 @@
+//! Point-to-point channels between the parties. The prover only needs to send a message to a party and
+//! receive the next message from a party, messages between 2 parties must arrive in order. A network
+//! transport implements `Channel`, `InProcessChannel` connects parties running in the same process.
+
+use crate::mpc::error::MpcError;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::vec::Vec;
+
+pub trait Channel {
+    /// Id of this party, in `0..num_parties`
+    fn party_id(&self) -> usize;
+
+    fn num_parties(&self) -> usize;
+
+    fn send(&mut self, to: usize, message: Vec<u8>) -> Result<(), MpcError>;
+
+    /// Blocks until the next message from party `from` is available
+    fn recv(&mut self, from: usize) -> Result<Vec<u8>, MpcError>;
+}
+
+pub fn send_value<C: Channel, T: CanonicalSerialize>(
+    channel: &mut C,
+    to: usize,
+    value: &T,
+) -> Result<(), MpcError> {
+    let mut bytes = Vec::new();
+    value.serialize_compressed(&mut bytes)?;
+    channel.send(to, bytes)
+}
+
+pub fn recv_value<C: Channel, T: CanonicalDeserialize>(
+    channel: &mut C,
+    from: usize,
+) -> Result<T, MpcError> {
+    let bytes = channel.recv(from)?;
+    Ok(T::deserialize_compressed(bytes.as_slice())?)
+}
+
+/// Send `value` to all other parties and return the values of all parties indexed by party id, including
+/// this party's `value`
+pub fn broadcast<C: Channel, T: Clone + CanonicalSerialize + CanonicalDeserialize>(
+    channel: &mut C,
+    value: &T,
+) -> Result<Vec<T>, MpcError> {
+    let me = channel.party_id();
+    let n = channel.num_parties();
+    for j in (0..n).filter(|j| *j != me) {
+        send_value(channel, j, value)?;
+    }
+    let mut values = Vec::with_capacity(n);
+    for j in 0..n {
+        if j == me {
+            values.push(value.clone());
+        } else {
+            values.push(recv_value(channel, j)?);
+        }
+    }
+    Ok(values)
+}
+
+#[cfg(feature = "std")]
+pub use in_process::InProcessChannel;
+
+#[cfg(feature = "std")]
+mod in_process {
+    use super::Channel;
+    use crate::mpc::error::MpcError;
+    use std::sync::mpsc::{channel, Receiver, Sender};
+
+    /// Channel between parties running in the same process, like in different threads
+    pub struct InProcessChannel {
+        party_id: usize,
+        /// `senders[j]` sends to party `j`
+        senders: Vec<Sender<Vec<u8>>>,
+        /// `receivers[j]` receives from party `j`
+        receivers: Vec<Receiver<Vec<u8>>>,
+    }
+
+    impl InProcessChannel {
+        /// Create connected channels for `num_parties` parties, the channel at index `i` is for party `i`
+        pub fn new_network(num_parties: usize) -> Vec<Self> {
+            let mut senders = (0..num_parties).map(|_| Vec::new()).collect::<Vec<_>>();
+            let mut receivers = (0..num_parties).map(|_| Vec::new()).collect::<Vec<_>>();
+            for senders_from in senders.iter_mut() {
+                for receivers_to in receivers.iter_mut() {
+                    let (s, r) = channel();
+                    senders_from.push(s);
+                    receivers_to.push(r);
+                }
+            }
+            senders
+                .into_iter()
+                .zip(receivers)
+                .enumerate()
+                .map(|(party_id, (senders, receivers))| Self {
+                    party_id,
+                    senders,
+                    receivers,
+                })
+                .collect()
+        }
+    }
+
+    impl Channel for InProcessChannel {
+        fn party_id(&self) -> usize {
+            self.party_id
+        }
+
+        fn num_parties(&self) -> usize {
+            self.senders.len()
+        }
+
+        fn send(&mut self, to: usize, message: Vec<u8>) -> Result<(), MpcError> {
+            self.senders
+                .get(to)
+                .ok_or(MpcError::UnknownParty(to, self.senders.len()))?
+                .send(message)
+                .map_err(|_| MpcError::ChannelClosed(to))
+        }
+
+        fn recv(&mut self, from: usize) -> Result<Vec<u8>, MpcError> {
+            self.receivers
+                .get(from)
+                .ok_or(MpcError::UnknownParty(from, self.receivers.len()))?
+                .recv()
+                .map_err(|_| MpcError::ChannelClosed(from))
+        }
+    }
+}
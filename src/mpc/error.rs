@@ -0,0 +1,26 @@
+use ark_serialize::SerializationError;
+use ark_std::string::{String, ToString};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MpcError {
+    /// The channel to or from the given party is closed
+    ChannelClosed(usize),
+    /// Message for or from a party id that is not in the network, i.e. (party id, number of parties)
+    UnknownParty(usize, usize),
+    Serialization(String),
+    /// A message had a different number of items than expected, i.e. (received, expected)
+    UnexpectedMessageLength(usize, usize),
+    /// Fewer Beaver triples than multiplications, i.e. (available, needed)
+    InsufficientTriples(usize, usize),
+    /// Shamir sharing with threshold `t` needs at least `2t + 1` parties to multiply, i.e.
+    /// (number of parties, threshold)
+    InsufficientParties(usize, usize),
+    /// The assignment shares do not match the constraint system, i.e. (given, expected)
+    InvalidAssignmentLength(usize, usize),
+}
+
+impl From<SerializationError> for MpcError {
+    fn from(e: SerializationError) -> Self {
+        Self::Serialization(e.to_string())
+    }
+}
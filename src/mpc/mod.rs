@@ -0,0 +1,24 @@
+//! Collaborative proving where the witnesses are secret shared among several parties, none of which learns
+//! the others' witnesses, based on [`Experimenting with Collaborative zk-SNARKs`].
+//!
+//! The parties agree on the proving key and the constraint matrices (see `constraint_matrices`), and hold
+//! shares of the witness assignment under a `SecretSharing` scheme. They communicate over a `Channel` and
+//! all get the same proof from `create_proof_with_shares`.
+//!
+//! [`Experimenting with Collaborative zk-SNARKs`]: https://eprint.iacr.org/2021/1530.pdf
+
+pub mod channel;
+pub mod error;
+pub mod prover;
+pub mod sharing;
+
+#[cfg(all(test, feature = "std"))]
+mod tests;
+
+pub use crate::prover::constraint_matrices;
+pub use channel::Channel;
+#[cfg(feature = "std")]
+pub use channel::InProcessChannel;
+pub use error::MpcError;
+pub use prover::{create_proof_and_committed_witnesses_with_shares, create_proof_with_shares};
+pub use sharing::{AdditiveSharing, BeaverTriple, SecretSharing, ShamirSharing};
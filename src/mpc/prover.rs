@@ -0,0 +1,241 @@
+//! Proof generation by parties holding shares of the witness, following "Experimenting with Collaborative
+//! zk-SNARKs" (Ozdemir and Boneh). Each party runs the same steps as `create_proof_and_committed_witnesses_with_assignment`
+//! on its shares:
+//! - The QAP witness map evaluates the constraints and does FFTs, all linear in the assignment, so the
+//!   parties get shares of `h` locally except for the product `a * b` of the evaluations, which is
+//!   computed with `SecretSharing::mul`.
+//! - `A`, `B`, `h_acc`, `l_aux_acc` and `D` are multi-scalar multiplications with public bases, so each party
+//!   gets shares of these group elements from its shares of the scalars.
+//! - `C` needs `s * A + r * B - r * s * delta`, so `A` and `B` are opened first which is fine as they are
+//!   part of the proof, and `r * s` is multiplied along with `a * b`.
+//!
+//! The parties open `A`, `B`, `C` and `D` and thus all get the same proof. The committed witnesses remain
+//! shared. Only `LibsnarkReduction` is supported as the witness map has to be split around the multiplication.
+
+use crate::{
+    mpc::{
+        error::MpcError,
+        sharing::{open, SecretSharing},
+        Channel,
+    },
+    r1cs_to_qap::evaluate_constraint,
+    Proof, ProvingKey, ProvingKeyCommon, VerifyingKey,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::PrimeField;
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::{ConstraintMatrices, SynthesisError};
+use ark_std::{cfg_iter, cfg_iter_mut, end_timer, rand::Rng, start_timer, vec, vec::Vec};
+use zeroize::Zeroizing;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Create a LegoGroth16 proof from shares of the witnesses. `r`, `s` and `v` are shares of the proof's
+/// randomness and of the randomness of the commitment `proof.d`, see `SecretSharing::random`.
+/// `input_assignment` is public and includes the leading 1.
+pub fn create_proof_with_shares<E, S, C, R>(
+    pk: &ProvingKey<E>,
+    matrices: &ConstraintMatrices<E::ScalarField>,
+    input_assignment: &[E::ScalarField],
+    witness_shares: &[E::ScalarField],
+    r: E::ScalarField,
+    s: E::ScalarField,
+    v: E::ScalarField,
+    scheme: &mut S,
+    channel: &mut C,
+    rng: &mut R,
+) -> crate::Result<Proof<E>>
+where
+    E: Pairing,
+    S: SecretSharing<E::ScalarField>,
+    C: Channel,
+    R: Rng,
+{
+    let (proof, _comm_wits) = create_proof_and_committed_witnesses_with_shares(
+        &pk.common,
+        &pk.vk,
+        matrices,
+        input_assignment,
+        witness_shares,
+        r,
+        s,
+        v,
+        scheme,
+        channel,
+        rng,
+    )?;
+    Ok(proof)
+}
+
+/// Returns the proof and this party's shares of the committed witnesses.
+pub fn create_proof_and_committed_witnesses_with_shares<E, S, C, R>(
+    pk_common: &ProvingKeyCommon<E>,
+    vk: &VerifyingKey<E>,
+    matrices: &ConstraintMatrices<E::ScalarField>,
+    input_assignment: &[E::ScalarField],
+    witness_shares: &[E::ScalarField],
+    r: E::ScalarField,
+    s: E::ScalarField,
+    v: E::ScalarField,
+    scheme: &mut S,
+    channel: &mut C,
+    rng: &mut R,
+) -> crate::Result<(Proof<E>, Vec<E::ScalarField>)>
+where
+    E: Pairing,
+    S: SecretSharing<E::ScalarField>,
+    C: Channel,
+    R: Rng,
+{
+    if input_assignment.len() != matrices.num_instance_variables {
+        return Err(MpcError::InvalidAssignmentLength(
+            input_assignment.len(),
+            matrices.num_instance_variables,
+        )
+        .into());
+    }
+    if witness_shares.len() != matrices.num_witness_variables {
+        return Err(MpcError::InvalidAssignmentLength(
+            witness_shares.len(),
+            matrices.num_witness_variables,
+        )
+        .into());
+    }
+
+    // The public inputs become shares of public values so that all parties use the same full assignment
+    let mut full_assignment = Zeroizing::new(
+        input_assignment
+            .iter()
+            .map(|i| scheme.share_of_public(*i))
+            .collect::<Vec<_>>(),
+    );
+    full_assignment.extend_from_slice(witness_shares);
+
+    let witness_map_time = start_timer!(|| "Shared R1CS to QAP witness map");
+    let (h, rs) = shared_witness_map(matrices, &full_assignment, r, s, scheme, channel, rng)?;
+    end_timer!(witness_map_time);
+
+    let assignment = Zeroizing::new(
+        cfg_iter!(full_assignment[1..])
+            .map(|a| a.into_bigint())
+            .collect::<Vec<_>>(),
+    );
+    let num_inputs = input_assignment.len();
+    let committed_witnesses = &assignment[num_inputs - 1..num_inputs - 1 + vk.commit_witness_count];
+    let uncommitted_witnesses = &assignment[num_inputs - 1 + vk.commit_witness_count..];
+
+    let delta_g1 = pk_common.delta_g1.into_group();
+
+    // Compute A and B
+    let a_time = start_timer!(|| "Compute shares of A and B");
+    let g_a = scheme.share_of_public(pk_common.a_query[0] + vk.alpha_g1)
+        + E::G1::msm_bigint(&pk_common.a_query[1..], &assignment)
+        + delta_g1 * r;
+    let g1_b = scheme.share_of_public(pk_common.b_g1_query[0] + pk_common.beta_g1)
+        + E::G1::msm_bigint(&pk_common.b_g1_query[1..], &assignment)
+        + delta_g1 * s;
+    let g2_b = scheme.share_of_public(pk_common.b_g2_query[0] + vk.beta_g2)
+        + E::G2::msm_bigint(&pk_common.b_g2_query[1..], &assignment)
+        + vk.delta_g2 * s;
+    end_timer!(a_time);
+
+    let opened = open(scheme, channel, &[g_a, g1_b])?;
+    let (g_a, g1_b) = (opened[0], opened[1]);
+    let g2_b = open(scheme, channel, &[g2_b])?[0];
+
+    // Compute C and D
+    let c_time = start_timer!(|| "Compute shares of C and D");
+    let h_assignment = Zeroizing::new(cfg_iter!(h).map(|s| s.into_bigint()).collect::<Vec<_>>());
+    let h_acc = E::G1::msm_bigint(&pk_common.h_query, &h_assignment);
+    let l_aux_acc = E::G1::msm_bigint(&pk_common.l_query, uncommitted_witnesses);
+
+    let mut g_c = g_a * s;
+    g_c += g1_b * r;
+    g_c -= delta_g1 * *rs;
+    g_c += l_aux_acc;
+    g_c += h_acc;
+    g_c -= pk_common.eta_delta_inv_g1 * v;
+
+    let gamma_abc_inputs_source =
+        &vk.gamma_abc_g1[num_inputs..num_inputs + vk.commit_witness_count];
+    let mut g_d = E::G1::msm_bigint(gamma_abc_inputs_source, committed_witnesses);
+    g_d += vk.eta_gamma_inv_g1 * v;
+    end_timer!(c_time);
+
+    let opened = open(scheme, channel, &[g_c, g_d])?;
+
+    Ok((
+        Proof {
+            a: g_a.into_affine(),
+            b: g2_b.into_affine(),
+            c: opened[0].into_affine(),
+            d: opened[1].into_affine(),
+        },
+        witness_shares[..vk.commit_witness_count].to_vec(),
+    ))
+}
+
+/// Same as `LibsnarkReduction::witness_map_from_matrices` but over shares. Returns the shares of `h` and
+/// of `r * s`, the latter being multiplied along with `a * b` to save a round.
+fn shared_witness_map<F, S, C, R>(
+    matrices: &ConstraintMatrices<F>,
+    full_assignment: &[F],
+    r: F,
+    s: F,
+    scheme: &mut S,
+    channel: &mut C,
+    rng: &mut R,
+) -> crate::Result<(Zeroizing<Vec<F>>, Zeroizing<F>)>
+where
+    F: PrimeField,
+    S: SecretSharing<F>,
+    C: Channel,
+    R: Rng,
+{
+    let num_inputs = matrices.num_instance_variables;
+    let num_constraints = matrices.num_constraints;
+    let domain = GeneralEvaluationDomain::<F>::new(num_constraints + num_inputs)
+        .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+    let domain_size = domain.size();
+
+    let mut a = Zeroizing::new(vec![F::zero(); domain_size]);
+    let mut b = Zeroizing::new(vec![F::zero(); domain_size]);
+    let mut c = Zeroizing::new(vec![F::zero(); domain_size]);
+    cfg_iter_mut!(a[..num_constraints])
+        .zip(cfg_iter_mut!(b[..num_constraints]))
+        .zip(cfg_iter_mut!(c[..num_constraints]))
+        .enumerate()
+        .for_each(|(i, ((a, b), c))| {
+            *a = evaluate_constraint(&matrices.a[i], full_assignment);
+            *b = evaluate_constraint(&matrices.b[i], full_assignment);
+            *c = evaluate_constraint(&matrices.c[i], full_assignment);
+        });
+    a[num_constraints..num_constraints + num_inputs]
+        .clone_from_slice(&full_assignment[..num_inputs]);
+
+    let coset_domain = domain.get_coset(F::GENERATOR).unwrap();
+    for x in [&mut a, &mut b, &mut c] {
+        domain.ifft_in_place(x);
+        coset_domain.fft_in_place(x);
+    }
+
+    a.push(r);
+    b.push(s);
+    let mut ab = Zeroizing::new(scheme.mul(channel, &a, &b, rng)?);
+    let rs = ab.pop().unwrap();
+    drop(a);
+    drop(b);
+
+    let vanishing_polynomial_over_coset = domain
+        .evaluate_vanishing_polynomial(F::GENERATOR)
+        .inverse()
+        .unwrap();
+    cfg_iter_mut!(ab).zip(&*c).for_each(|(ab_i, c_i)| {
+        *ab_i -= c_i;
+        *ab_i *= &vanishing_polynomial_over_coset;
+    });
+    coset_domain.ifft_in_place(&mut ab);
+
+    Ok((ab, Zeroizing::new(rs)))
+}
@@ -0,0 +1,370 @@
+//! Secret sharing schemes for the collaborative prover. Linear operations on shares, like FFTs and
+//! multi-scalar multiplications, are done locally by each party so a scheme only has to define how
+//! public values are shared, how secrets are reconstructed and how shares are multiplied.
+//! - `AdditiveSharing`: the secret is the sum of all shares and all parties are needed to reconstruct
+//!   it. Multiplication consumes Beaver triples which must be generated in a preprocessing phase.
+//! - `ShamirSharing`: the secret is the evaluation at 0 of a polynomial of degree `threshold` and the
+//!   shares are its evaluations at `1, 2, ..., n`. Multiplication reshares the product of shares which
+//!   needs `n >= 2 * threshold + 1` but no preprocessing.
+//!
+//! Both are secure against semi-honest parties only, i.e. parties that follow the protocol.
+
+use crate::mpc::{
+    channel::{broadcast, recv_value, send_value, Channel},
+    error::MpcError,
+};
+use ark_ff::{batch_inversion, PrimeField, Zero};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{cfg_iter, ops::Mul, rand::Rng, vec, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+pub trait SecretSharing<F: PrimeField> {
+    /// Id of this party, in `0..num_parties`
+    fn party_id(&self) -> usize;
+
+    fn num_parties(&self) -> usize;
+
+    /// This party's share of a public value. Works for field elements and group elements alike.
+    fn share_of_public<T: Zero>(&self, value: T) -> T;
+
+    /// The secret is the sum of the shares of all parties weighted by these, indexed by party id
+    fn reconstruction_weights(&self) -> Vec<F>;
+
+    /// Shares of the products `x[i] * y[i]`. Needs 1 round of communication for all products.
+    fn mul<C: Channel, R: Rng>(
+        &mut self,
+        channel: &mut C,
+        x: &[F],
+        y: &[F],
+        rng: &mut R,
+    ) -> Result<Vec<F>, MpcError>;
+
+    /// Shares of `count` random values unknown to any party
+    fn random<C: Channel, R: Rng>(
+        &mut self,
+        channel: &mut C,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<F>, MpcError>;
+}
+
+/// Reconstruct the secrets from this party's `shares` by exchanging them with all parties. `T` can be field
+/// or group elements.
+pub fn open<F, T, S, C>(scheme: &S, channel: &mut C, shares: &[T]) -> Result<Vec<T>, MpcError>
+where
+    F: PrimeField,
+    T: Copy + Zero + Mul<F, Output = T> + CanonicalSerialize + CanonicalDeserialize,
+    S: SecretSharing<F>,
+    C: Channel,
+{
+    let all_shares = broadcast(channel, &shares.to_vec())?;
+    let weights = scheme.reconstruction_weights();
+    let mut secrets = vec![T::zero(); shares.len()];
+    for (party_shares, weight) in all_shares.into_iter().zip(weights) {
+        if party_shares.len() != shares.len() {
+            return Err(MpcError::UnexpectedMessageLength(
+                party_shares.len(),
+                shares.len(),
+            ));
+        }
+        for (secret, share) in secrets.iter_mut().zip(party_shares) {
+            *secret = *secret + share * weight;
+        }
+    }
+    Ok(secrets)
+}
+
+/// Shares of `a`, `b` and `a * b` where `a` and `b` are random
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BeaverTriple<F: PrimeField> {
+    pub a: F,
+    pub b: F,
+    pub c: F,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdditiveSharing<F: PrimeField> {
+    pub party_id: usize,
+    pub num_parties: usize,
+    /// Unused Beaver triples, each multiplication consumes one
+    pub triples: Vec<BeaverTriple<F>>,
+}
+
+impl<F: PrimeField> AdditiveSharing<F> {
+    pub fn new(party_id: usize, num_parties: usize, triples: Vec<BeaverTriple<F>>) -> Self {
+        Self {
+            party_id,
+            num_parties,
+            triples,
+        }
+    }
+
+    /// Split `secret` into `num_parties` shares
+    pub fn share<R: Rng>(secret: F, num_parties: usize, rng: &mut R) -> Vec<F> {
+        let mut shares = (1..num_parties).map(|_| F::rand(rng)).collect::<Vec<_>>();
+        let sum = shares.iter().fold(F::zero(), |acc, s| acc + s);
+        shares.insert(0, secret - sum);
+        shares
+    }
+
+    /// Generate `count` Beaver triples as a trusted dealer and return the triples of each party, indexed
+    /// by party id. Without a trusted dealer, triples are generated with oblivious transfer or
+    /// homomorphic encryption which is out of scope here.
+    pub fn deal_triples<R: Rng>(
+        num_parties: usize,
+        count: usize,
+        rng: &mut R,
+    ) -> Vec<Vec<BeaverTriple<F>>> {
+        let mut triples = vec![Vec::with_capacity(count); num_parties];
+        for _ in 0..count {
+            let a = F::rand(rng);
+            let b = F::rand(rng);
+            let a_shares = Self::share(a, num_parties, rng);
+            let b_shares = Self::share(b, num_parties, rng);
+            let c_shares = Self::share(a * b, num_parties, rng);
+            for (i, ((a, b), c)) in a_shares.into_iter().zip(b_shares).zip(c_shares).enumerate() {
+                triples[i].push(BeaverTriple { a, b, c });
+            }
+        }
+        triples
+    }
+}
+
+impl<F: PrimeField> SecretSharing<F> for AdditiveSharing<F> {
+    fn party_id(&self) -> usize {
+        self.party_id
+    }
+
+    fn num_parties(&self) -> usize {
+        self.num_parties
+    }
+
+    /// The first party holds the public value, others hold 0
+    fn share_of_public<T: Zero>(&self, value: T) -> T {
+        if self.party_id == 0 {
+            value
+        } else {
+            T::zero()
+        }
+    }
+
+    fn reconstruction_weights(&self) -> Vec<F> {
+        vec![F::one(); self.num_parties]
+    }
+
+    /// Each product consumes a triple `(a, b, c)`, `x - a` and `y - b` are opened and then
+    /// `x * y = c + (x - a) * b + (y - b) * a + (x - a) * (y - b)`
+    fn mul<C: Channel, R: Rng>(
+        &mut self,
+        channel: &mut C,
+        x: &[F],
+        y: &[F],
+        _rng: &mut R,
+    ) -> Result<Vec<F>, MpcError> {
+        if x.len() != y.len() {
+            return Err(MpcError::UnexpectedMessageLength(y.len(), x.len()));
+        }
+        if self.triples.len() < x.len() {
+            return Err(MpcError::InsufficientTriples(self.triples.len(), x.len()));
+        }
+        let triples = self.triples.drain(..x.len()).collect::<Vec<_>>();
+        let mut masked = cfg_iter!(x)
+            .zip(cfg_iter!(triples))
+            .map(|(x, t)| *x - t.a)
+            .collect::<Vec<_>>();
+        masked.extend(
+            cfg_iter!(y)
+                .zip(cfg_iter!(triples))
+                .map(|(y, t)| *y - t.b)
+                .collect::<Vec<_>>(),
+        );
+        let opened = open(self, channel, &masked)?;
+        let (d, e) = opened.split_at(x.len());
+        Ok(cfg_iter!(triples)
+            .zip(cfg_iter!(d))
+            .zip(cfg_iter!(e))
+            .map(|((t, d), e)| t.c + *d * t.b + *e * t.a + self.share_of_public(*d * e))
+            .collect())
+    }
+
+    fn random<C: Channel, R: Rng>(
+        &mut self,
+        _channel: &mut C,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<F>, MpcError> {
+        Ok((0..count).map(|_| F::rand(rng)).collect())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShamirSharing<F: PrimeField> {
+    pub party_id: usize,
+    pub num_parties: usize,
+    /// Degree of the sharing polynomial, `threshold + 1` shares are needed to reconstruct
+    pub threshold: usize,
+    /// Lagrange coefficients for evaluating at 0 from the evaluations at `1, 2, ..., num_parties`
+    lagrange_at_zero: Vec<F>,
+}
+
+impl<F: PrimeField> ShamirSharing<F> {
+    pub fn new(party_id: usize, num_parties: usize, threshold: usize) -> Result<Self, MpcError> {
+        if num_parties < 2 * threshold + 1 {
+            return Err(MpcError::InsufficientParties(num_parties, threshold));
+        }
+        if party_id >= num_parties {
+            return Err(MpcError::UnknownParty(party_id, num_parties));
+        }
+        Ok(Self {
+            party_id,
+            num_parties,
+            threshold,
+            lagrange_at_zero: lagrange_coefficients_at_zero(num_parties),
+        })
+    }
+
+    /// Split `secret` into `num_parties` shares, any `threshold + 1` of which reconstruct it
+    pub fn share<R: Rng>(secret: F, num_parties: usize, threshold: usize, rng: &mut R) -> Vec<F> {
+        let mut coeffs = vec![secret];
+        coeffs.extend((0..threshold).map(|_| F::rand(rng)));
+        let poly = DensePolynomial::from_coefficients_vec(coeffs);
+        (1..=num_parties)
+            .map(|i| poly.evaluate(&F::from(i as u64)))
+            .collect()
+    }
+
+    /// Share each of `secrets` with all parties and return the shares received from each party, indexed
+    /// by party id
+    fn reshare<C: Channel, R: Rng>(
+        &self,
+        channel: &mut C,
+        secrets: &[F],
+        rng: &mut R,
+    ) -> Result<Vec<Vec<F>>, MpcError> {
+        let n = self.num_parties;
+        // `shares_for[j]` are the shares sent to party `j`
+        let mut shares_for = vec![Vec::with_capacity(secrets.len()); n];
+        for secret in secrets {
+            for (j, share) in Self::share(*secret, n, self.threshold, rng)
+                .into_iter()
+                .enumerate()
+            {
+                shares_for[j].push(share);
+            }
+        }
+        for j in (0..n).filter(|j| *j != self.party_id) {
+            send_value(channel, j, &shares_for[j])?;
+        }
+        let mut received = Vec::with_capacity(n);
+        for (j, own) in shares_for.into_iter().enumerate() {
+            let shares: Vec<F> = if j == self.party_id {
+                own
+            } else {
+                recv_value(channel, j)?
+            };
+            if shares.len() != secrets.len() {
+                return Err(MpcError::UnexpectedMessageLength(
+                    shares.len(),
+                    secrets.len(),
+                ));
+            }
+            received.push(shares);
+        }
+        Ok(received)
+    }
+}
+
+impl<F: PrimeField> SecretSharing<F> for ShamirSharing<F> {
+    fn party_id(&self) -> usize {
+        self.party_id
+    }
+
+    fn num_parties(&self) -> usize {
+        self.num_parties
+    }
+
+    /// A constant polynomial, so every party holds the public value
+    fn share_of_public<T: Zero>(&self, value: T) -> T {
+        value
+    }
+
+    fn reconstruction_weights(&self) -> Vec<F> {
+        self.lagrange_at_zero.clone()
+    }
+
+    /// Products of shares are evaluations of a polynomial of degree `2 * threshold`. Each party reshares its
+    /// product with degree `threshold` and the new shares are interpolated at 0 (degree reduction of BGW/GRR).
+    fn mul<C: Channel, R: Rng>(
+        &mut self,
+        channel: &mut C,
+        x: &[F],
+        y: &[F],
+        rng: &mut R,
+    ) -> Result<Vec<F>, MpcError> {
+        if x.len() != y.len() {
+            return Err(MpcError::UnexpectedMessageLength(y.len(), x.len()));
+        }
+        let products = cfg_iter!(x)
+            .zip(cfg_iter!(y))
+            .map(|(x, y)| *x * y)
+            .collect::<Vec<_>>();
+        let received = self.reshare(channel, &products, rng)?;
+        let mut result = vec![F::zero(); x.len()];
+        for (shares, lambda) in received.into_iter().zip(self.lagrange_at_zero.iter()) {
+            for (r, s) in result.iter_mut().zip(shares) {
+                *r += s * lambda;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Each party shares random values and the shares of all parties are added
+    fn random<C: Channel, R: Rng>(
+        &mut self,
+        channel: &mut C,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<F>, MpcError> {
+        let secrets = (0..count).map(|_| F::rand(rng)).collect::<Vec<_>>();
+        let received = self.reshare(channel, &secrets, rng)?;
+        let mut result = vec![F::zero(); count];
+        for shares in received {
+            for (r, s) in result.iter_mut().zip(shares) {
+                *r += s;
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// For points `x_j = j + 1`, `lambda_j = prod_{m != j} x_m / (x_m - x_j)`
+fn lagrange_coefficients_at_zero<F: PrimeField>(num_parties: usize) -> Vec<F> {
+    let points = (1..=num_parties as u64).map(F::from).collect::<Vec<_>>();
+    let mut denominators = points
+        .iter()
+        .enumerate()
+        .map(|(j, x_j)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|(m, _)| *m != j)
+                .fold(F::one(), |acc, (_, x_m)| acc * (*x_m - x_j))
+        })
+        .collect::<Vec<_>>();
+    batch_inversion(&mut denominators);
+    points
+        .iter()
+        .enumerate()
+        .zip(denominators)
+        .map(|((j, _), d)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|(m, _)| *m != j)
+                .fold(d, |acc, (_, x_m)| acc * x_m)
+        })
+        .collect()
+}
@@ -0,0 +1,280 @@
+use crate::mpc::{
+    constraint_matrices, create_proof_and_committed_witnesses_with_shares, AdditiveSharing,
+    InProcessChannel, MpcError, SecretSharing, ShamirSharing,
+};
+use crate::{
+    create_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    verify_witness_commitment, Proof, ProvingKey,
+};
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, PrimeField};
+use ark_relations::lc;
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef,
+    OptimizationGoal, SynthesisError,
+};
+use ark_std::{
+    rand::{rngs::StdRng, SeedableRng},
+    UniformRand,
+};
+use std::thread;
+
+/// Circuit for `(x * y + z) * x = out` where `x` and `y` are committed
+#[derive(Clone)]
+struct Circuit<F: Field> {
+    x: Option<F>,
+    y: Option<F>,
+    z: Option<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for Circuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let x = cs.new_witness_variable(|| self.x.ok_or(SynthesisError::AssignmentMissing))?;
+        let y = cs.new_witness_variable(|| self.y.ok_or(SynthesisError::AssignmentMissing))?;
+        let z = cs.new_witness_variable(|| self.z.ok_or(SynthesisError::AssignmentMissing))?;
+        let t_val = self.x.and_then(|x| self.y.map(|y| x * y));
+        let t = cs.new_witness_variable(|| t_val.ok_or(SynthesisError::AssignmentMissing))?;
+        let out = cs.new_input_variable(|| {
+            let t = t_val.ok_or(SynthesisError::AssignmentMissing)?;
+            let z = self.z.ok_or(SynthesisError::AssignmentMissing)?;
+            let x = self.x.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok((t + z) * x)
+        })?;
+        cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + t)?;
+        cs.enforce_constraint(lc!() + t + z, lc!() + x, lc!() + out)?;
+        Ok(())
+    }
+}
+
+/// Returns the public input assignment (with the leading 1) and the witness assignment
+fn assignments<F: PrimeField>(circuit: Circuit<F>) -> (Vec<F>, Vec<F>) {
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.finalize();
+    let cs = cs.into_inner().unwrap();
+    (cs.instance_assignment, cs.witness_assignment)
+}
+
+/// Run the collaborative prover with 1 thread per party, each with its scheme and shares of the witnesses,
+/// `r`, `s` and `v`. Returns each party's proof and shares of the committed witnesses.
+fn run_parties<E: Pairing, S: SecretSharing<E::ScalarField> + Send + 'static>(
+    pk: &ProvingKey<E>,
+    matrices: &ConstraintMatrices<E::ScalarField>,
+    input_assignment: Vec<E::ScalarField>,
+    parties: Vec<(S, Vec<E::ScalarField>, [E::ScalarField; 3])>,
+) -> Vec<(Proof<E>, Vec<E::ScalarField>)> {
+    let channels = InProcessChannel::new_network(parties.len());
+    let handles = parties
+        .into_iter()
+        .zip(channels)
+        .enumerate()
+        .map(
+            |(i, ((mut scheme, witness_shares, [r, s, v]), mut channel))| {
+                let pk = pk.clone();
+                let matrices = matrices.clone();
+                let input_assignment = input_assignment.clone();
+                thread::spawn(move || {
+                    let mut rng = StdRng::seed_from_u64(i as u64);
+                    create_proof_and_committed_witnesses_with_shares(
+                        &pk.common,
+                        &pk.vk,
+                        &matrices,
+                        &input_assignment,
+                        &witness_shares,
+                        r,
+                        s,
+                        v,
+                        &mut scheme,
+                        &mut channel,
+                        &mut rng,
+                    )
+                    .unwrap()
+                })
+            },
+        )
+        .collect::<Vec<_>>();
+    handles.into_iter().map(|h| h.join().unwrap()).collect()
+}
+
+fn circuit<F: PrimeField, R: ark_std::rand::Rng>(rng: &mut R) -> Circuit<F> {
+    Circuit {
+        x: Some(F::rand(rng)),
+        y: Some(F::rand(rng)),
+        z: Some(F::rand(rng)),
+    }
+}
+
+fn test_additive<E: Pairing>(num_parties: usize) {
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let empty = Circuit::<E::ScalarField> {
+        x: None,
+        y: None,
+        z: None,
+    };
+    let pk = generate_random_parameters::<E, _, _>(empty.clone(), 2, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&pk.vk);
+
+    let circuit = circuit::<E::ScalarField, _>(&mut rng);
+    let (input_assignment, witness_assignment) = assignments(circuit.clone());
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    let v = E::ScalarField::rand(&mut rng);
+
+    // Each value is split among the parties, party `i` gets the `i`-th share of each
+    let share = |x: E::ScalarField, rng: &mut StdRng| {
+        AdditiveSharing::<E::ScalarField>::share(x, num_parties, rng)
+    };
+    let witness_shares = witness_assignment
+        .iter()
+        .map(|w| share(*w, &mut rng))
+        .collect::<Vec<_>>();
+    let rsv_shares = [r, s, v].map(|x| share(x, &mut rng));
+    let matrices = constraint_matrices::<E, _>(empty).unwrap();
+    let domain_size =
+        (matrices.num_constraints + matrices.num_instance_variables).next_power_of_two();
+    let triples = AdditiveSharing::deal_triples(num_parties, domain_size + 1, &mut rng);
+    let parties = triples
+        .into_iter()
+        .enumerate()
+        .map(|(i, triples)| {
+            (
+                AdditiveSharing::new(i, num_parties, triples),
+                witness_shares.iter().map(|w| w[i]).collect(),
+                [rsv_shares[0][i], rsv_shares[1][i], rsv_shares[2][i]],
+            )
+        })
+        .collect();
+
+    let results = run_parties(&pk, &matrices, input_assignment.clone(), parties);
+
+    // All parties get the same proof as a single prover with the same randomness
+    let expected = create_proof(circuit, &pk, r, s, v).unwrap();
+    for (proof, _) in &results {
+        assert_eq!(*proof, expected);
+    }
+    let proof = &results[0].0;
+    verify_proof(&pvk, proof, &input_assignment[1..]).unwrap();
+
+    // Opening the shares of the committed witnesses gives the opening of `d`
+    let committed = (0..2)
+        .map(|j| results.iter().map(|(_, c)| c[j]).sum())
+        .collect::<Vec<E::ScalarField>>();
+    assert_eq!(committed, witness_assignment[..2].to_vec());
+    verify_witness_commitment(&pk.vk, proof, 1, &committed, &v).unwrap();
+}
+
+fn test_shamir<E: Pairing>(num_parties: usize, threshold: usize) {
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let empty = Circuit::<E::ScalarField> {
+        x: None,
+        y: None,
+        z: None,
+    };
+    let pk = generate_random_parameters::<E, _, _>(empty.clone(), 2, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&pk.vk);
+
+    let circuit = circuit::<E::ScalarField, _>(&mut rng);
+    let (input_assignment, witness_assignment) = assignments(circuit);
+    let v = E::ScalarField::rand(&mut rng);
+
+    let share = |x: E::ScalarField, rng: &mut StdRng| {
+        ShamirSharing::<E::ScalarField>::share(x, num_parties, threshold, rng)
+    };
+    let witness_shares = witness_assignment
+        .iter()
+        .map(|w| share(*w, &mut rng))
+        .collect::<Vec<_>>();
+    let v_shares = share(v, &mut rng);
+
+    // `r` and `s` are generated jointly so no party knows them
+    let matrices = constraint_matrices::<E, _>(empty.clone()).unwrap();
+    let channels = InProcessChannel::new_network(num_parties);
+    let handles = channels
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut channel)| {
+            let pk = pk.clone();
+            let matrices = matrices.clone();
+            let input_assignment = input_assignment.clone();
+            let witness_shares = witness_shares.iter().map(|w| w[i]).collect::<Vec<_>>();
+            let v = v_shares[i];
+            thread::spawn(move || {
+                let mut rng = StdRng::seed_from_u64(100 + i as u64);
+                let mut scheme = ShamirSharing::new(i, num_parties, threshold).unwrap();
+                let rs = scheme.random(&mut channel, 2, &mut rng).unwrap();
+                create_proof_and_committed_witnesses_with_shares(
+                    &pk.common,
+                    &pk.vk,
+                    &matrices,
+                    &input_assignment,
+                    &witness_shares,
+                    rs[0],
+                    rs[1],
+                    v,
+                    &mut scheme,
+                    &mut channel,
+                    &mut rng,
+                )
+                .unwrap()
+            })
+        })
+        .collect::<Vec<_>>();
+    let results = handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .collect::<Vec<_>>();
+
+    for (proof, _) in &results {
+        assert_eq!(*proof, results[0].0);
+    }
+    let proof = &results[0].0;
+    verify_proof(&pvk, proof, &input_assignment[1..]).unwrap();
+
+    let weights = ShamirSharing::<E::ScalarField>::new(0, num_parties, threshold)
+        .unwrap()
+        .reconstruction_weights();
+    let committed = (0..2)
+        .map(|j| {
+            results
+                .iter()
+                .zip(weights.iter())
+                .map(|((_, c), w)| c[j] * w)
+                .sum()
+        })
+        .collect::<Vec<E::ScalarField>>();
+    assert_eq!(committed, witness_assignment[..2].to_vec());
+    verify_witness_commitment(&pk.vk, proof, 1, &committed, &v).unwrap();
+}
+
+#[test]
+fn additive_sharing() {
+    test_additive::<Bls12_381>(2);
+    test_additive::<Bn254>(3);
+}
+
+#[test]
+fn shamir_sharing() {
+    test_shamir::<Bls12_381>(3, 1);
+    test_shamir::<Bn254>(5, 2);
+}
+
+#[test]
+fn insufficient_resources() {
+    assert_eq!(
+        ShamirSharing::<<Bn254 as Pairing>::ScalarField>::new(0, 4, 2),
+        Err(MpcError::InsufficientParties(4, 2))
+    );
+
+    // Fewer triples than multiplications
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let mut channels = InProcessChannel::new_network(1);
+    let mut scheme = AdditiveSharing::<<Bn254 as Pairing>::ScalarField>::new(0, 1, vec![]);
+    let x = vec![<Bn254 as Pairing>::ScalarField::rand(&mut rng)];
+    assert_eq!(
+        scheme.mul(&mut channels[0], &x, &x, &mut rng),
+        Err(MpcError::InsufficientTriples(0, 1))
+    );
+}
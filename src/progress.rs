@@ -0,0 +1,133 @@
+//! Progress reporting and cancellation for setup, proving and aggregation. A `ProgressObserver` is told
+//! when each phase starts and finishes and can cancel the operation at these points by returning
+//! `ControlFlow::Break`, in which case the operation returns `Error::Cancelled` (or
+//! `AggregationError::Cancelled`). A phase is not interrupted once started, so the delay until
+//! cancellation is the duration of the longest phase, like the largest MSM.
+
+use ark_std::ops::ControlFlow;
+use core::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Query {
+    A,
+    BG1,
+    BG2,
+    H,
+    L,
+    GammaAbc,
+    /// The commitment `proof.d` to the witnesses
+    D,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Generating the constraints, and the assignment when proving. The size is the number of constraints.
+    Synthesis,
+    /// Evaluating the QAP polynomials at the secret point during setup. The size is the domain size.
+    QapInstanceMap,
+    /// Computing the coefficients of `h` when proving. The size is the domain size.
+    WitnessMap,
+    /// Multiplying the generator by the scalars of a query during setup. The size is the number of scalars.
+    FixedBaseMsm(Query),
+    /// Multi-scalar multiplication with the bases of a query when proving. The size is the number of bases.
+    Msm(Query),
+    /// Key generation of the Subspace Snark for CP_link. The size is the number of link commitments.
+    LinkKeygen,
+    /// Computing the link commitments. The size is the number of link commitments.
+    LinkCommitments,
+    /// Proving the equality of the openings of the link commitments and `proof.d`. The size is the
+    /// number of witnesses of the Subspace Snark.
+    LinkProof,
+    /// Committing to the proofs being aggregated. The size is the number of proofs.
+    AggregationCommitments,
+    /// The inner product arguments of the aggregation. The size is the number of proofs.
+    AggregationInnerProducts,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// `size` is 0 when not known before the phase, like the number of constraints before synthesis
+    Started { phase: Phase, size: usize },
+    /// `elapsed` is only measured with the `std` feature
+    Finished {
+        phase: Phase,
+        size: usize,
+        elapsed: Option<Duration>,
+    },
+}
+
+pub trait ProgressObserver {
+    /// Return `ControlFlow::Break(())` to cancel
+    fn on_event(&self, event: Event) -> ControlFlow<()>;
+}
+
+/// Observer that ignores all events, used by the functions not taking an observer
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoObserver;
+
+impl ProgressObserver for NoObserver {
+    fn on_event(&self, _event: Event) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+impl<F: Fn(Event) -> ControlFlow<()>> ProgressObserver for F {
+    fn on_event(&self, event: Event) -> ControlFlow<()> {
+        self(event)
+    }
+}
+
+/// The observer asked to cancel, converted to the error of the operation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+/// Run `f` as `phase`, telling `observer` before and after. `size` is the size known before running `f`
+/// and `finished_size` gives the size from the result of `f`.
+pub(crate) fn observe_phase<O, T, Err>(
+    observer: &O,
+    phase: Phase,
+    size: usize,
+    f: impl FnOnce() -> Result<T, Err>,
+    finished_size: impl FnOnce(&T) -> usize,
+) -> Result<T, Err>
+where
+    O: ProgressObserver + ?Sized,
+    Err: From<Cancelled>,
+{
+    if observer.on_event(Event::Started { phase, size }).is_break() {
+        return Err(Cancelled.into());
+    }
+    #[cfg(feature = "std")]
+    let start = std::time::Instant::now();
+    let result = f()?;
+    #[cfg(feature = "std")]
+    let elapsed = Some(start.elapsed());
+    #[cfg(not(feature = "std"))]
+    let elapsed = None;
+    let size = finished_size(&result);
+    if observer
+        .on_event(Event::Finished {
+            phase,
+            size,
+            elapsed,
+        })
+        .is_break()
+    {
+        return Err(Cancelled.into());
+    }
+    Ok(result)
+}
+
+/// Same as `observe_phase` for a phase that cannot fail and whose size is known before
+pub(crate) fn observe<O, T, Err>(
+    observer: &O,
+    phase: Phase,
+    size: usize,
+    f: impl FnOnce() -> T,
+) -> Result<T, Err>
+where
+    O: ProgressObserver + ?Sized,
+    Err: From<Cancelled>,
+{
+    observe_phase(observer, phase, size, || Ok(f()), |_| size)
+}
@@ -1,6 +1,7 @@
 use crate::{
-    link::{PESubspaceSnark, SubspaceSnark},
-    r1cs_to_qap::LibsnarkReduction,
+    link::{error::LinkError, LinkSnark, PESubspaceSnark},
+    progress::{observe, observe_phase, NoObserver, Phase, ProgressObserver, Query},
+    r1cs_to_qap::{evaluate_constraint, LibsnarkReduction, QapDomain},
     Proof, ProofWithLink, ProvingKey, ProvingKeyCommon, ProvingKeyWithLink, VerifyingKey,
     VerifyingKeyWithLink,
 };
@@ -9,13 +10,15 @@ use ark_ec::{
     VariableBaseMSM,
 };
 use ark_ff::{Field, PrimeField, UniformRand, Zero};
-use ark_poly::GeneralEvaluationDomain;
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
 use ark_relations::r1cs::{
-    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal, SynthesisError,
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef,
+    OptimizationGoal, SynthesisError, SynthesisMode,
 };
 use ark_std::rand::Rng;
 use ark_std::{
     cfg_into_iter, cfg_iter, end_timer,
+    marker::PhantomData,
     ops::{AddAssign, Mul},
     start_timer, vec,
     vec::Vec,
@@ -23,6 +26,7 @@ use ark_std::{
 
 use crate::error::Error;
 use crate::r1cs_to_qap::R1CStoQAP;
+use zeroize::{Zeroize, Zeroizing};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
@@ -30,22 +34,64 @@ use rayon::prelude::*;
 /// Same as `create_random_proof` but returns the CP_link and its corresponding proof as well. `link_v`
 /// is the blinding in CP_link
 #[inline]
-pub fn create_random_proof_incl_cp_link<E, C, R>(
+pub fn create_random_proof_incl_cp_link<E, C, R, L>(
     circuit: C,
     v: E::ScalarField,
     link_v: E::ScalarField,
-    pk: &ProvingKeyWithLink<E>,
+    pk: &ProvingKeyWithLink<E, L>,
     rng: &mut R,
-) -> crate::Result<ProofWithLink<E>>
+) -> crate::Result<ProofWithLink<E, L>>
 where
     E: Pairing,
     C: ConstraintSynthesizer<E::ScalarField>,
     R: Rng,
+    L: LinkSnark<E>,
 {
-    let r = E::ScalarField::rand(rng);
-    let s = E::ScalarField::rand(rng);
+    create_random_proof_incl_cp_links::<E, C, R, L>(circuit, v, &[link_v], pk, rng)
+}
 
-    create_proof_incl_cp_link::<E, C>(circuit, pk, r, s, v, link_v)
+/// Same as `create_random_proof_incl_cp_link` but for several link commitments. `link_v` has the
+/// blinding for each link commitment.
+#[inline]
+pub fn create_random_proof_incl_cp_links<E, C, R, L>(
+    circuit: C,
+    v: E::ScalarField,
+    link_v: &[E::ScalarField],
+    pk: &ProvingKeyWithLink<E, L>,
+    rng: &mut R,
+) -> crate::Result<ProofWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    L: LinkSnark<E>,
+{
+    create_random_proof_incl_cp_links_with_observer(circuit, v, link_v, pk, rng, &NoObserver)
+}
+
+/// Same as `create_random_proof_incl_cp_links` but tells `observer` about the progress, and returns
+/// `Error::Cancelled` if it cancels
+pub fn create_random_proof_incl_cp_links_with_observer<E, C, R, L, O>(
+    circuit: C,
+    v: E::ScalarField,
+    link_v: &[E::ScalarField],
+    pk: &ProvingKeyWithLink<E, L>,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<ProofWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    L: LinkSnark<E>,
+    O: ProgressObserver + ?Sized,
+{
+    let r = Zeroizing::new(E::ScalarField::rand(rng));
+    let s = Zeroizing::new(E::ScalarField::rand(rng));
+
+    create_proof_incl_cp_links_with_reduction_and_observer::<E, C, LibsnarkReduction, L, O>(
+        circuit, pk, *r, *s, v, link_v, observer,
+    )
 }
 
 /// Create a LegoGroth16 proof that is zero-knowledge. `v` is the blinding used in the commitment to the witness.
@@ -62,28 +108,68 @@ where
     C: ConstraintSynthesizer<E::ScalarField>,
     R: Rng,
 {
-    let r = E::ScalarField::rand(rng);
-    let s = E::ScalarField::rand(rng);
+    create_random_proof_with_observer(circuit, v, pk, rng, &NoObserver)
+}
+
+/// Same as `create_random_proof` but tells `observer` about the progress, and returns `Error::Cancelled`
+/// if it cancels
+pub fn create_random_proof_with_observer<E, C, R, O>(
+    circuit: C,
+    v: E::ScalarField,
+    pk: &ProvingKey<E>,
+    rng: &mut R,
+    observer: &O,
+) -> crate::Result<Proof<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: Rng,
+    O: ProgressObserver + ?Sized,
+{
+    let r = Zeroizing::new(E::ScalarField::rand(rng));
+    let s = Zeroizing::new(E::ScalarField::rand(rng));
 
-    create_proof::<E, C>(circuit, pk, r, s, v)
+    create_proof_with_reduction_and_observer::<E, C, LibsnarkReduction, O>(
+        circuit, pk, *r, *s, v, observer,
+    )
 }
 
 #[inline]
 /// Create a LegoGroth16 proof using randomness `r`, `s`, `v` and `link_v` where `v` is the blinding in
 /// the witness commitment in proof and `link_v` is the blinding in the witness commitment in CP_link
-pub fn create_proof_incl_cp_link<E, C>(
+pub fn create_proof_incl_cp_link<E, C, L>(
     circuit: C,
-    pk: &ProvingKeyWithLink<E>,
+    pk: &ProvingKeyWithLink<E, L>,
     r: E::ScalarField,
     s: E::ScalarField,
     v: E::ScalarField,
     link_v: E::ScalarField,
-) -> crate::Result<ProofWithLink<E>>
+) -> crate::Result<ProofWithLink<E, L>>
 where
     E: Pairing,
     C: ConstraintSynthesizer<E::ScalarField>,
+    L: LinkSnark<E>,
 {
-    create_proof_incl_cp_link_with_reduction::<E, C, LibsnarkReduction>(
+    create_proof_incl_cp_links::<E, C, L>(circuit, pk, r, s, v, &[link_v])
+}
+
+#[inline]
+/// Same as `create_proof_incl_cp_link` but for several link commitments. `link_v` has the blinding
+/// for each link commitment.
+pub fn create_proof_incl_cp_links<E, C, L>(
+    circuit: C,
+    pk: &ProvingKeyWithLink<E, L>,
+    r: E::ScalarField,
+    s: E::ScalarField,
+    v: E::ScalarField,
+    link_v: &[E::ScalarField],
+) -> crate::Result<ProofWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    L: LinkSnark<E>,
+{
+    create_proof_incl_cp_links_with_reduction::<E, C, LibsnarkReduction, L>(
         circuit, pk, r, s, v, link_v,
     )
 }
@@ -108,25 +194,76 @@ where
 /// Create a LegoGroth16 proof using randomness `r` and `s`.
 /// `v` is the randomness of the commitment `proof.d` and `link_v` is the randomness to CP_link commitment
 #[inline]
-pub fn create_proof_incl_cp_link_with_reduction<E, C, QAP>(
+pub fn create_proof_incl_cp_link_with_reduction<E, C, QAP, L>(
     circuit: C,
-    pk: &ProvingKeyWithLink<E>,
+    pk: &ProvingKeyWithLink<E, L>,
     r: E::ScalarField,
     s: E::ScalarField,
     v: E::ScalarField,
     link_v: E::ScalarField,
-) -> crate::Result<ProofWithLink<E>>
+) -> crate::Result<ProofWithLink<E, L>>
 where
     E: Pairing,
     C: ConstraintSynthesizer<E::ScalarField>,
     QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+{
+    create_proof_incl_cp_links_with_reduction::<E, C, QAP, L>(circuit, pk, r, s, v, &[link_v])
+}
+
+/// Create a LegoGroth16 proof using randomness `r` and `s`.
+/// `v` is the randomness of the commitment `proof.d` and `link_v` has the randomness of each CP_link commitment
+#[inline]
+pub fn create_proof_incl_cp_links_with_reduction<E, C, QAP, L>(
+    circuit: C,
+    pk: &ProvingKeyWithLink<E, L>,
+    r: E::ScalarField,
+    s: E::ScalarField,
+    v: E::ScalarField,
+    link_v: &[E::ScalarField],
+) -> crate::Result<ProofWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+{
+    create_proof_incl_cp_links_with_reduction_and_observer::<E, C, QAP, L, _>(
+        circuit,
+        pk,
+        r,
+        s,
+        v,
+        link_v,
+        &NoObserver,
+    )
+}
+
+/// Same as `create_proof_incl_cp_links_with_reduction` but tells `observer` about the progress, and
+/// returns `Error::Cancelled` if it cancels
+pub fn create_proof_incl_cp_links_with_reduction_and_observer<E, C, QAP, L, O>(
+    circuit: C,
+    pk: &ProvingKeyWithLink<E, L>,
+    r: E::ScalarField,
+    s: E::ScalarField,
+    v: E::ScalarField,
+    link_v: &[E::ScalarField],
+    observer: &O,
+) -> crate::Result<ProofWithLink<E, L>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+    O: ProgressObserver + ?Sized,
 {
     let prover_time = start_timer!(|| "Groth16::Prover");
-    let (cs, h) = synthesize_circuit::<E, C, QAP>(circuit)?;
+    let (cs, h) = synthesize_circuit_with_observer::<E, C, QAP, O>(circuit, observer)?;
 
     let prover = cs.borrow().unwrap();
-    let proof = create_proof_incl_cp_link_with_assignment::<E, QAP>(
+    let proof = create_proof_incl_cp_link_with_assignment::<E, QAP, L, O>(
         pk,
+        &ProverBases::new(&pk.common, &pk.vk.groth16_vk),
         r,
         s,
         v,
@@ -134,14 +271,15 @@ where
         &h,
         &prover.instance_assignment,
         &prover.witness_assignment,
-    )?;
+        observer,
+    );
 
     drop(prover);
-    drop(cs);
+    zeroize_witness_assignment(cs);
 
     end_timer!(prover_time);
 
-    Ok(proof)
+    proof
 }
 
 /// Create a LegoGroth16 proof using randomness `r` and `s`.
@@ -158,106 +296,546 @@ where
     E: Pairing,
     C: ConstraintSynthesizer<E::ScalarField>,
     QAP: R1CStoQAP,
+{
+    create_proof_with_reduction_and_observer::<E, C, QAP, _>(circuit, pk, r, s, v, &NoObserver)
+}
+
+/// Same as `create_proof_with_reduction` but tells `observer` about the progress, and returns
+/// `Error::Cancelled` if it cancels
+pub fn create_proof_with_reduction_and_observer<E, C, QAP, O>(
+    circuit: C,
+    pk: &ProvingKey<E>,
+    r: E::ScalarField,
+    s: E::ScalarField,
+    v: E::ScalarField,
+    observer: &O,
+) -> crate::Result<Proof<E>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    QAP: R1CStoQAP,
+    O: ProgressObserver + ?Sized,
 {
     let prover_time = start_timer!(|| "Groth16::Prover");
-    let (cs, h) = synthesize_circuit::<E, C, QAP>(circuit)?;
+    let (cs, h) = synthesize_circuit_with_observer::<E, C, QAP, O>(circuit, observer)?;
 
     let prover = cs.borrow().unwrap();
-    let proof = create_proof_with_assignment::<E, QAP>(
+    let proof = create_proof_with_assignment::<E, QAP, O>(
         pk,
+        &ProverBases::new(&pk.common, &pk.vk),
         r,
         s,
         v,
         &h,
         &prover.instance_assignment,
         &prover.witness_assignment,
-    )?;
+        observer,
+    );
 
     drop(prover);
-    drop(cs);
+    zeroize_witness_assignment(cs);
 
     end_timer!(prover_time);
 
-    Ok(proof)
+    proof
+}
+
+/// Prover for a fixed circuit and proving key which computes once what every proof of the circuit
+/// needs: the constraint matrices, the evaluation domain with its coset and the fixed-base tables and
+/// constants from the proving key. Proofs are the same as the ones from `create_random_proof` and
+/// `create_random_proof_incl_cp_links` but the circuit is synthesized without building its matrices.
+/// The domain is given to `QAP::witness_map_from_matrices_with_domain` for each proof.
+pub struct ProverContext<
+    'a,
+    E: Pairing,
+    QAP: R1CStoQAP = LibsnarkReduction,
+    L: LinkSnark<E> = PESubspaceSnark<E>,
+> {
+    pk_common: &'a ProvingKeyCommon<E>,
+    vk: &'a VerifyingKey<E>,
+    /// Set when created from a `ProvingKeyWithLink`
+    pk_with_link: Option<&'a ProvingKeyWithLink<E, L>>,
+    matrices: ConstraintMatrices<E::ScalarField>,
+    domain: QapDomain<E::ScalarField>,
+    bases: ProverBases<E>,
+    _qap: PhantomData<QAP>,
+}
+
+impl<'a, E, QAP, L> ProverContext<'a, E, QAP, L>
+where
+    E: Pairing,
+    QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+{
+    /// Create the context for proofs of `circuit`, which does not need an assignment, with `pk`
+    pub fn new<C: ConstraintSynthesizer<E::ScalarField>>(
+        circuit: C,
+        pk: &'a ProvingKey<E>,
+    ) -> crate::Result<Self> {
+        Self::new_with_keys(circuit, &pk.common, &pk.vk, None)
+    }
+
+    /// Same as `new` but the context can also create proofs with CP_link
+    pub fn new_incl_cp_link<C: ConstraintSynthesizer<E::ScalarField>>(
+        circuit: C,
+        pk: &'a ProvingKeyWithLink<E, L>,
+    ) -> crate::Result<Self> {
+        Self::new_with_keys(circuit, &pk.common, &pk.vk.groth16_vk, Some(pk))
+    }
+
+    fn new_with_keys<C: ConstraintSynthesizer<E::ScalarField>>(
+        circuit: C,
+        pk_common: &'a ProvingKeyCommon<E>,
+        vk: &'a VerifyingKey<E>,
+        pk_with_link: Option<&'a ProvingKeyWithLink<E, L>>,
+    ) -> crate::Result<Self> {
+        let context_time = start_timer!(|| "Prover context");
+        let matrices = constraint_matrices::<E, C>(circuit)?;
+        let domain = QapDomain::new(matrices.num_constraints, matrices.num_instance_variables)?;
+        // The generator creates `a_query` for each variable and `h_query` for each power below the
+        // domain size
+        if pk_common.a_query.len()
+            != matrices.num_instance_variables + matrices.num_witness_variables
+            || pk_common.h_query.len() + 1 != domain.domain.size()
+        {
+            return Err(Error::CircuitAndProvingKeyMismatch);
+        }
+        let bases = ProverBases::new(pk_common, vk);
+        end_timer!(context_time);
+        Ok(Self {
+            pk_common,
+            vk,
+            pk_with_link,
+            matrices,
+            domain,
+            bases,
+            _qap: PhantomData,
+        })
+    }
+
+    /// Same as `create_random_proof`
+    pub fn prove<C, R>(&self, circuit: C, v: E::ScalarField, rng: &mut R) -> crate::Result<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        R: Rng,
+    {
+        let r = Zeroizing::new(E::ScalarField::rand(rng));
+        let s = Zeroizing::new(E::ScalarField::rand(rng));
+
+        self.prove_with_randomness(circuit, *r, *s, v)
+    }
+
+    /// Same as `create_proof`
+    fn prove_with_randomness<C: ConstraintSynthesizer<E::ScalarField>>(
+        &self,
+        circuit: C,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        v: E::ScalarField,
+    ) -> crate::Result<Proof<E>> {
+        let prover_time = start_timer!(|| "Groth16::Prover with context");
+        let (cs, h) = self.synthesize(circuit)?;
+
+        let prover = cs.borrow().unwrap();
+        let proof = create_proof_and_committed_witnesses_with_assignment::<E, QAP, _>(
+            self.pk_common,
+            self.vk,
+            &self.bases,
+            r,
+            s,
+            v,
+            &h,
+            &prover.instance_assignment,
+            &prover.witness_assignment,
+            &NoObserver,
+        )
+        .map(|(proof, _comm_wits)| proof);
+
+        drop(prover);
+        zeroize_witness_assignment(cs);
+
+        end_timer!(prover_time);
+
+        proof
+    }
+
+    /// Same as `create_random_proof_incl_cp_link`. The context must have been created with `new_incl_cp_link`.
+    pub fn prove_incl_cp_link<C, R>(
+        &self,
+        circuit: C,
+        v: E::ScalarField,
+        link_v: E::ScalarField,
+        rng: &mut R,
+    ) -> crate::Result<ProofWithLink<E, L>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        R: Rng,
+    {
+        self.prove_incl_cp_links(circuit, v, &[link_v], rng)
+    }
+
+    /// Same as `create_random_proof_incl_cp_links`. The context must have been created with `new_incl_cp_link`.
+    pub fn prove_incl_cp_links<C, R>(
+        &self,
+        circuit: C,
+        v: E::ScalarField,
+        link_v: &[E::ScalarField],
+        rng: &mut R,
+    ) -> crate::Result<ProofWithLink<E, L>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        R: Rng,
+    {
+        let r = Zeroizing::new(E::ScalarField::rand(rng));
+        let s = Zeroizing::new(E::ScalarField::rand(rng));
+
+        self.prove_incl_cp_links_with_randomness(circuit, *r, *s, v, link_v)
+    }
+
+    /// Same as `create_proof_incl_cp_links`
+    fn prove_incl_cp_links_with_randomness<C: ConstraintSynthesizer<E::ScalarField>>(
+        &self,
+        circuit: C,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        v: E::ScalarField,
+        link_v: &[E::ScalarField],
+    ) -> crate::Result<ProofWithLink<E, L>> {
+        let pk = self.pk_with_link.ok_or(Error::MissingLinkProvingKey)?;
+
+        let prover_time = start_timer!(|| "Groth16::Prover with context");
+        let (cs, h) = self.synthesize(circuit)?;
+
+        let prover = cs.borrow().unwrap();
+        let proof = create_proof_incl_cp_link_with_assignment::<E, QAP, L, _>(
+            pk,
+            &self.bases,
+            r,
+            s,
+            v,
+            link_v,
+            &h,
+            &prover.instance_assignment,
+            &prover.witness_assignment,
+            &NoObserver,
+        );
+
+        drop(prover);
+        zeroize_witness_assignment(cs);
+
+        end_timer!(prover_time);
+
+        proof
+    }
+
+    /// Same as `synthesize_circuit` but uses the constraint matrices of the context to check that the
+    /// assignment satisfies the constraints and to compute the QAP witness
+    #[allow(clippy::type_complexity)]
+    fn synthesize<C: ConstraintSynthesizer<E::ScalarField>>(
+        &self,
+        circuit: C,
+    ) -> crate::Result<(
+        ConstraintSystemRef<E::ScalarField>,
+        Zeroizing<Vec<E::ScalarField>>,
+    )> {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_mode(SynthesisMode::Prove {
+            construct_matrices: false,
+        });
+
+        let synthesis_time = start_timer!(|| "Constraint synthesis");
+        circuit.generate_constraints(cs.clone())?;
+        end_timer!(synthesis_time);
+
+        let h = {
+            let prover = cs.borrow().unwrap();
+            if prover.instance_assignment.len() != self.matrices.num_instance_variables
+                || prover.witness_assignment.len() != self.matrices.num_witness_variables
+            {
+                drop(prover);
+                zeroize_witness_assignment(cs);
+                return Err(Error::CircuitAndProvingKeyMismatch);
+            }
+            let full_assignment = Zeroizing::new(
+                [
+                    prover.instance_assignment.as_slice(),
+                    prover.witness_assignment.as_slice(),
+                ]
+                .concat(),
+            );
+            if !is_satisfied(&self.matrices, &full_assignment) {
+                drop(prover);
+                zeroize_witness_assignment(cs);
+                return Err(Error::SynthesisError(SynthesisError::Unsatisfiable));
+            }
+
+            let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
+            let h = QAP::witness_map_from_matrices_with_domain(
+                &self.matrices,
+                &full_assignment,
+                &self.domain,
+            )?;
+            end_timer!(witness_map_time);
+            Zeroizing::new(h)
+        };
+        Ok((cs, h))
+    }
+}
+
+/// Create proofs for many assignments of the same circuit, sharing the constraint matrices, evaluation
+/// domain and fixed-base tables through a `ProverContext`. Each item of `instances` is a circuit with
+/// its assignment and the blinding `v` of the commitment in its proof. `instances` is consumed
+/// `max_concurrency` items at a time, which are proved in parallel with the `parallel` feature, so
+/// only that many assignments, witnesses and QAP polynomials are in memory at once.
+pub fn create_random_proofs_batch<E, C, I, R>(
+    instances: I,
+    pk: &ProvingKey<E>,
+    max_concurrency: usize,
+    rng: &mut R,
+) -> crate::Result<Vec<Proof<E>>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField> + Clone + Send,
+    I: IntoIterator<Item = (C, E::ScalarField)>,
+    R: Rng,
+{
+    let mut instances = instances.into_iter().peekable();
+    let context = match instances.peek() {
+        Some((circuit, _)) => ProverContext::<E>::new(circuit.clone(), pk)?,
+        None => return Ok(vec![]),
+    };
+    prove_in_batches::<E, _, _, _, _>(instances, max_concurrency, rng, |(circuit, v), r, s| {
+        context.prove_with_randomness(circuit, r, s, v)
+    })
+}
+
+/// Same as `create_random_proofs_batch` but each proof includes a CP_link and each item of
+/// `instances` also has the blinding `link_v` of the CP_link
+pub fn create_random_proofs_batch_incl_cp_link<E, C, I, R, L>(
+    instances: I,
+    pk: &ProvingKeyWithLink<E, L>,
+    max_concurrency: usize,
+    rng: &mut R,
+) -> crate::Result<Vec<ProofWithLink<E, L>>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField> + Clone + Send,
+    I: IntoIterator<Item = (C, E::ScalarField, E::ScalarField)>,
+    R: Rng,
+    L: LinkSnark<E>,
+    ProvingKeyWithLink<E, L>: Sync,
+    ProofWithLink<E, L>: Send,
+{
+    create_random_proofs_batch_incl_cp_links(
+        instances
+            .into_iter()
+            .map(|(circuit, v, link_v)| (circuit, v, vec![link_v])),
+        pk,
+        max_concurrency,
+        rng,
+    )
+}
+
+/// Same as `create_random_proofs_batch` but each proof includes several CP_links and each item of
+/// `instances` also has the blindings `link_v` of the CP_links
+pub fn create_random_proofs_batch_incl_cp_links<E, C, I, R, L>(
+    instances: I,
+    pk: &ProvingKeyWithLink<E, L>,
+    max_concurrency: usize,
+    rng: &mut R,
+) -> crate::Result<Vec<ProofWithLink<E, L>>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField> + Clone + Send,
+    I: IntoIterator<Item = (C, E::ScalarField, Vec<E::ScalarField>)>,
+    R: Rng,
+    L: LinkSnark<E>,
+    ProvingKeyWithLink<E, L>: Sync,
+    ProofWithLink<E, L>: Send,
+{
+    let mut instances = instances.into_iter().peekable();
+    let context = match instances.peek() {
+        Some((circuit, _, _)) => {
+            ProverContext::<E, LibsnarkReduction, L>::new_incl_cp_link(circuit.clone(), pk)?
+        }
+        None => return Ok(vec![]),
+    };
+    prove_in_batches::<E, _, _, _, _>(
+        instances,
+        max_concurrency,
+        rng,
+        |(circuit, v, link_v), r, s| {
+            context.prove_incl_cp_links_with_randomness(circuit, r, s, v, &link_v)
+        },
+    )
+}
+
+/// Take at most `max_concurrency` instances at a time and call `prove` on each with its `r` and `s`.
+/// These are sampled in the order of the instances so that the proofs do not depend on the order in
+/// which they are created.
+fn prove_in_batches<E, T, P, R, F>(
+    mut instances: impl Iterator<Item = T>,
+    max_concurrency: usize,
+    rng: &mut R,
+    prove: F,
+) -> crate::Result<Vec<P>>
+where
+    E: Pairing,
+    T: Send,
+    P: Send,
+    R: Rng,
+    F: Fn(T, E::ScalarField, E::ScalarField) -> crate::Result<P> + Sync,
+{
+    let mut proofs = Vec::new();
+    loop {
+        let batch = instances
+            .by_ref()
+            .take(max_concurrency.max(1))
+            .collect::<Vec<_>>();
+        if batch.is_empty() {
+            break;
+        }
+        let randomness = Zeroizing::new(
+            (0..batch.len())
+                .map(|_| [E::ScalarField::rand(rng), E::ScalarField::rand(rng)])
+                .collect::<Vec<_>>(),
+        );
+        let batch_proofs = cfg_into_iter!(batch)
+            .zip(cfg_iter!(randomness))
+            .map(|(instance, [r, s])| prove(instance, *r, *s))
+            .collect::<crate::Result<Vec<_>>>()?;
+        proofs.extend(batch_proofs);
+    }
+    Ok(proofs)
+}
+
+/// Synthesize the circuit without assignments to get the constraint matrices. These are the same as the
+/// ones used by the generator.
+pub fn constraint_matrices<E, C>(circuit: C) -> crate::Result<ConstraintMatrices<E::ScalarField>>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+{
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone())?;
+    cs.finalize();
+    cs.to_matrices()
+        .ok_or(Error::SynthesisError(SynthesisError::MissingCS))
 }
 
 /// Create the proof including CP_link and its corresponding proof given the public and private input assignments
 #[inline]
-fn create_proof_incl_cp_link_with_assignment<E, QAP>(
-    pk: &ProvingKeyWithLink<E>,
+fn create_proof_incl_cp_link_with_assignment<E, QAP, L, O>(
+    pk: &ProvingKeyWithLink<E, L>,
+    bases: &ProverBases<E>,
     r: E::ScalarField,
     s: E::ScalarField,
     v: E::ScalarField,
-    link_v: E::ScalarField,
+    link_v: &[E::ScalarField],
     h: &[E::ScalarField],
     input_assignment: &[E::ScalarField],
     witness_assignment: &[E::ScalarField],
-) -> crate::Result<ProofWithLink<E>>
+    observer: &O,
+) -> crate::Result<ProofWithLink<E, L>>
 where
     E: Pairing,
     QAP: R1CStoQAP,
+    L: LinkSnark<E>,
+    O: ProgressObserver + ?Sized,
 {
-    let (proof, comm_wits) = create_proof_and_committed_witnesses_with_assignment::<E, QAP>(
+    if link_v.len() != pk.vk.link_bases.len() {
+        return Err(
+            LinkError::VectorWithUnexpectedLength(link_v.len(), pk.vk.link_bases.len()).into(),
+        );
+    }
+
+    let (proof, comm_wits) = create_proof_and_committed_witnesses_with_assignment::<E, QAP, O>(
         &pk.common,
         &pk.vk.groth16_vk,
+        bases,
         r,
         s,
         v,
         &h,
         input_assignment,
         witness_assignment,
+        observer,
     )?;
 
-    let mut comm_wits_with_link_hider = cfg_iter!(comm_wits)
-        .map(|w| w.into_bigint())
-        .collect::<Vec<_>>();
-    comm_wits_with_link_hider.push(link_v.into_bigint());
-
-    let g_d_link = E::G1::msm_bigint(&pk.vk.link_bases, &comm_wits_with_link_hider);
+    let link_d = observe::<_, _, Error>(observer, Phase::LinkCommitments, link_v.len(), || {
+        pk.vk
+            .link_bases
+            .iter()
+            .zip(pk.vk.link_witness_ranges.iter())
+            .zip(link_v.iter())
+            .map(|((bases, (start, end)), link_v)| {
+                let mut comm_wits_with_link_hider = Zeroizing::new(
+                    cfg_iter!(comm_wits[*start..*end])
+                        .map(|w| w.into_bigint())
+                        .collect::<Vec<_>>(),
+                );
+                comm_wits_with_link_hider.push(link_v.into_bigint());
+                E::G1::msm_bigint(bases, &comm_wits_with_link_hider)
+            })
+            .collect::<Vec<_>>()
+    })?;
 
     let mut ss_snark_witness = comm_wits;
-    ss_snark_witness.push(link_v);
+    ss_snark_witness.extend_from_slice(link_v);
     ss_snark_witness.push(v);
 
     let link_time = start_timer!(|| "Compute CP_{link}");
-    let link_pi = PESubspaceSnark::<E>::prove(&pk.vk.link_pp, &pk.link_ek, &ss_snark_witness)?;
+    let link_pi = observe_phase(
+        observer,
+        Phase::LinkProof,
+        ss_snark_witness.len(),
+        || L::prove(&pk.vk.link_pp, &pk.link_ek, &ss_snark_witness).map_err(Error::from),
+        |_| ss_snark_witness.len(),
+    )?;
 
     end_timer!(link_time);
 
-    drop(comm_wits_with_link_hider);
     drop(ss_snark_witness);
 
     Ok(ProofWithLink {
         groth16_proof: proof,
-        link_d: g_d_link.into_affine(),
+        link_d: E::G1::normalize_batch(&link_d),
         link_pi,
     })
 }
 
 /// Create the proof given the public and private input assignments
 #[inline]
-fn create_proof_with_assignment<E, QAP>(
+fn create_proof_with_assignment<E, QAP, O>(
     pk: &ProvingKey<E>,
+    bases: &ProverBases<E>,
     r: E::ScalarField,
     s: E::ScalarField,
     v: E::ScalarField,
     h: &[E::ScalarField],
     input_assignment: &[E::ScalarField],
     witness_assignment: &[E::ScalarField],
+    observer: &O,
 ) -> crate::Result<Proof<E>>
 where
     E: Pairing,
     QAP: R1CStoQAP,
+    O: ProgressObserver + ?Sized,
 {
-    let (proof, _comm_wits) = create_proof_and_committed_witnesses_with_assignment::<E, QAP>(
+    let (proof, _comm_wits) = create_proof_and_committed_witnesses_with_assignment::<E, QAP, O>(
         &pk.common,
         &pk.vk,
+        bases,
         r,
         s,
         v,
         &h,
         input_assignment,
         witness_assignment,
+        observer,
     )?;
     drop(_comm_wits);
     Ok(proof)
@@ -265,73 +843,85 @@ where
 
 /// Returns the proof and the committed witnesses.
 #[inline]
-fn create_proof_and_committed_witnesses_with_assignment<E, QAP>(
+fn create_proof_and_committed_witnesses_with_assignment<E, QAP, O>(
     pk_common: &ProvingKeyCommon<E>,
     vk: &VerifyingKey<E>,
+    bases: &ProverBases<E>,
     r: E::ScalarField,
     s: E::ScalarField,
     v: E::ScalarField,
     h: &[E::ScalarField],
     input_assignment: &[E::ScalarField],
     witness_assignment: &[E::ScalarField],
-) -> crate::Result<(Proof<E>, Vec<E::ScalarField>)>
+    observer: &O,
+) -> crate::Result<(Proof<E>, Zeroizing<Vec<E::ScalarField>>)>
 where
     E: Pairing,
     QAP: R1CStoQAP,
+    O: ProgressObserver + ?Sized,
 {
-    let h_assignment = cfg_into_iter!(h)
-        .map(|s| s.into_bigint())
-        .collect::<Vec<_>>();
+    // All values derived from the witnesses and the randomness are zeroized when dropped
+    let h_assignment = Zeroizing::new(
+        cfg_into_iter!(h)
+            .map(|s| s.into_bigint())
+            .collect::<Vec<_>>(),
+    );
     let c_acc_time = start_timer!(|| "Compute C");
 
-    let h_acc = E::G1::msm_bigint(&pk_common.h_query, &h_assignment);
+    let h_acc = observe::<_, _, Error>(observer, Phase::Msm(Query::H), h_assignment.len(), || {
+        E::G1::msm_bigint(&pk_common.h_query, &h_assignment)
+    })?;
     drop(h_assignment);
 
-    let v_repr = v.into_bigint();
+    let v_repr = Zeroizing::new(v.into_bigint());
 
     // Compute C
-    let aux_assignment = cfg_iter!(witness_assignment)
-        .map(|s| s.into_bigint())
-        .collect::<Vec<_>>();
+    let aux_assignment = Zeroizing::new(
+        cfg_iter!(witness_assignment)
+            .map(|s| s.into_bigint())
+            .collect::<Vec<_>>(),
+    );
 
     let committed_witnesses = &aux_assignment[..vk.commit_witness_count];
     let uncommitted_witnesses = &aux_assignment[vk.commit_witness_count..];
 
-    let l_aux_acc = E::G1::msm_bigint(&pk_common.l_query, uncommitted_witnesses);
+    let l_aux_acc = observe::<_, _, Error>(
+        observer,
+        Phase::Msm(Query::L),
+        uncommitted_witnesses.len(),
+        || E::G1::msm_bigint(&pk_common.l_query, uncommitted_witnesses),
+    )?;
 
-    let v_eta_delta_inv = pk_common.eta_delta_inv_g1.mul_bigint(v_repr);
+    let v_eta_delta_inv = pk_common.eta_delta_inv_g1.mul_bigint(*v_repr);
 
     end_timer!(c_acc_time);
 
-    let s_repr = s.into_bigint();
-    let delta_g1_proj = pk_common.delta_g1.into_group();
-
-    // There will be multiple multiplications with delta_g1_proj so creating a table
-    let window_size = 3; // 3 because number of multiplications is < 32, see `FixedBase::get_mul_window_size`
-    let scalar_size =
-        <<E as Pairing>::G1Affine as AffineRepr>::ScalarField::MODULUS_BIT_SIZE as usize;
-    let outerc = (scalar_size + window_size - 1) / window_size;
-    let delta_g1_table = FixedBase::get_window_table(scalar_size, window_size, delta_g1_proj);
+    let s_repr = Zeroizing::new(s.into_bigint());
 
     let input_assignment_wth_one = cfg_iter!(input_assignment)
         .map(|s| s.into_bigint())
         .collect::<Vec<_>>();
 
-    let mut assignment = vec![];
+    let mut assignment = Zeroizing::new(vec![]);
     assignment.extend_from_slice(&input_assignment_wth_one[1..]);
     assignment.extend_from_slice(&aux_assignment);
 
     // Compute A
     let a_acc_time = start_timer!(|| "Compute A");
-    let r_g1 = FixedBase::windowed_mul(outerc, window_size, &delta_g1_table, &r);
-    let g_a = calculate_coeff(r_g1, &pk_common.a_query, vk.alpha_g1, &assignment);
+    let r_g1 = bases.delta_g1_mul(&r);
+    let g_a = observe::<_, _, Error>(observer, Phase::Msm(Query::A), assignment.len(), || {
+        calculate_coeff(r_g1, &pk_common.a_query, bases.a_g1, &assignment)
+    })?;
     end_timer!(a_acc_time);
 
     // Compute B in G1 if needed
     let g1_b = if !r.is_zero() {
         let b_g1_acc_time = start_timer!(|| "Compute B in G1");
-        let s_g1 = FixedBase::windowed_mul(outerc, window_size, &delta_g1_table, &s);
-        let g1_b = calculate_coeff(s_g1, &pk_common.b_g1_query, pk_common.beta_g1, &assignment);
+        let s_g1 = bases.delta_g1_mul(&s);
+        let g1_b =
+            observe::<_, _, Error>(observer, Phase::Msm(Query::BG1), assignment.len(), || {
+                calculate_coeff(s_g1, &pk_common.b_g1_query, bases.b_g1, &assignment)
+            })?;
         end_timer!(b_g1_acc_time);
 
         g1_b
@@ -341,16 +931,18 @@ where
 
     // Compute B in G2
     let b_g2_acc_time = start_timer!(|| "Compute B in G2");
-    let s_g2 = vk.delta_g2.into_group().mul_bigint(s_repr);
-    let g2_b = calculate_coeff(s_g2, &pk_common.b_g2_query, vk.beta_g2, &assignment);
+    let s_g2 = bases.delta_g2.mul_bigint(*s_repr);
+    let g2_b = observe::<_, _, Error>(observer, Phase::Msm(Query::BG2), assignment.len(), || {
+        calculate_coeff(s_g2, &pk_common.b_g2_query, bases.b_g2, &assignment)
+    })?;
     drop(assignment);
 
     end_timer!(b_g2_acc_time);
 
     let c_time = start_timer!(|| "Finish C");
-    let mut g_c = g_a.mul_bigint(s_repr);
-    g_c += &g1_b.mul_bigint(r.into_bigint());
-    g_c -= &FixedBase::windowed_mul::<E::G1>(outerc, window_size, &delta_g1_table, &(r * s));
+    let mut g_c = g_a.mul_bigint(*s_repr);
+    g_c += &g1_b.mul_bigint(*Zeroizing::new(r.into_bigint()));
+    g_c -= &bases.delta_g1_mul(&Zeroizing::new(r * s));
     g_c += &l_aux_acc;
     g_c += &h_acc;
     g_c -= &v_eta_delta_inv;
@@ -361,15 +953,21 @@ where
 
     let gamma_abc_inputs_source = &vk.gamma_abc_g1[input_assignment_wth_one.len()
         ..input_assignment_wth_one.len() + committed_witnesses.len()];
-    let gamma_abc_inputs_acc = E::G1::msm_bigint(gamma_abc_inputs_source, &committed_witnesses);
+    let gamma_abc_inputs_acc = observe::<_, _, Error>(
+        observer,
+        Phase::Msm(Query::D),
+        committed_witnesses.len(),
+        || E::G1::msm_bigint(gamma_abc_inputs_source, committed_witnesses),
+    )?;
 
-    let v_eta_gamma_inv = vk.eta_gamma_inv_g1.into_group().mul_bigint(v_repr);
+    let v_eta_gamma_inv = vk.eta_gamma_inv_g1.into_group().mul_bigint(*v_repr);
 
     let mut g_d = gamma_abc_inputs_acc;
     g_d += &v_eta_gamma_inv;
     end_timer!(d_acc_time);
 
-    let committed_witnesses = witness_assignment[..vk.commit_witness_count].to_vec();
+    let committed_witnesses =
+        Zeroizing::new(witness_assignment[..vk.commit_witness_count].to_vec());
     drop(aux_assignment);
 
     Ok((
@@ -411,20 +1009,61 @@ pub fn verify_link_commitment<E: Pairing>(
 /// Check that the commitments in the proof open to the public inputs and the witnesses but with different
 /// bases and randomness. This function is only called by the prover, the verifier does not
 /// know `witnesses_expected_in_commitment` or `link_v`.
-pub fn verify_commitments<E: Pairing>(
-    vk: &VerifyingKeyWithLink<E>,
-    proof: &ProofWithLink<E>,
+pub fn verify_commitments<E: Pairing, L: LinkSnark<E>>(
+    vk: &VerifyingKeyWithLink<E, L>,
+    proof: &ProofWithLink<E, L>,
     public_inputs_count: usize,
     witnesses_expected_in_commitment: &[E::ScalarField],
     v: &E::ScalarField,
     link_v: &E::ScalarField,
 ) -> crate::Result<()> {
-    verify_link_commitment::<E>(
-        &vk.link_bases,
-        &proof.link_d,
+    verify_commitments_incl_cp_links(
+        vk,
+        proof,
+        public_inputs_count,
         witnesses_expected_in_commitment,
-        link_v,
-    )?;
+        v,
+        &[*link_v],
+    )
+}
+
+/// Same as `verify_commitments` but for several link commitments. `link_v` has the randomness of
+/// each link commitment and `witnesses_expected_in_commitment` are all the committed witnesses, each
+/// link commitment opens to the witnesses in its range.
+pub fn verify_commitments_incl_cp_links<E: Pairing, L: LinkSnark<E>>(
+    vk: &VerifyingKeyWithLink<E, L>,
+    proof: &ProofWithLink<E, L>,
+    public_inputs_count: usize,
+    witnesses_expected_in_commitment: &[E::ScalarField],
+    v: &E::ScalarField,
+    link_v: &[E::ScalarField],
+) -> crate::Result<()> {
+    if link_v.len() != vk.link_bases.len() {
+        return Err(
+            LinkError::VectorWithUnexpectedLength(link_v.len(), vk.link_bases.len()).into(),
+        );
+    }
+    if proof.link_d.len() != vk.link_bases.len() {
+        return Err(
+            LinkError::VectorWithUnexpectedLength(proof.link_d.len(), vk.link_bases.len()).into(),
+        );
+    }
+    if witnesses_expected_in_commitment.len() > vk.groth16_vk.commit_witness_count {
+        return Err(Error::VectorLongerThanExpected(
+            witnesses_expected_in_commitment.len(),
+            vk.groth16_vk.commit_witness_count,
+        ));
+    }
+    for (i, (start, end)) in vk.link_witness_ranges.iter().enumerate() {
+        // Fewer witnesses than expected will fail the commitment check
+        let count = witnesses_expected_in_commitment.len();
+        verify_link_commitment::<E>(
+            &vk.link_bases[i],
+            &proof.link_d[i],
+            &witnesses_expected_in_commitment[(*start).min(count)..(*end).min(count)],
+            &link_v[i],
+        )?;
+    }
     verify_witness_commitment::<E>(
         &vk.groth16_vk,
         &proof.groth16_proof,
@@ -558,6 +1197,26 @@ where
     E: Pairing,
     C: ConstraintSynthesizer<E::ScalarField>,
     QAP: R1CStoQAP,
+{
+    let (cs, mut h) = synthesize_circuit_with_observer::<E, C, QAP, _>(circuit, &NoObserver)?;
+    Ok((cs, core::mem::take(&mut *h)))
+}
+
+/// Same as `synthesize_circuit` but tells `observer` about the synthesis and the witness map. The
+/// witnesses are zeroized if either fails or is cancelled.
+#[allow(clippy::type_complexity)]
+fn synthesize_circuit_with_observer<E, C, QAP, O>(
+    circuit: C,
+    observer: &O,
+) -> crate::Result<(
+    ConstraintSystemRef<E::ScalarField>,
+    Zeroizing<Vec<E::ScalarField>>,
+)>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    QAP: R1CStoQAP,
+    O: ProgressObserver + ?Sized,
 {
     let cs = ConstraintSystem::new_ref();
 
@@ -565,31 +1224,125 @@ where
     cs.set_optimization_goal(OptimizationGoal::Constraints);
 
     // Synthesize the circuit.
-    let synthesis_time = start_timer!(|| "Constraint synthesis");
-    circuit.generate_constraints(cs.clone())?;
-    if !cs.is_satisfied()? {
-        return Err(Error::SynthesisError(SynthesisError::Unsatisfiable));
+    let synthesized = observe_phase(
+        observer,
+        Phase::Synthesis,
+        0,
+        || {
+            let synthesis_time = start_timer!(|| "Constraint synthesis");
+            circuit.generate_constraints(cs.clone())?;
+            if !cs.is_satisfied()? {
+                return Err(Error::SynthesisError(SynthesisError::Unsatisfiable));
+            }
+            end_timer!(synthesis_time);
+
+            let lc_time = start_timer!(|| "Inlining LCs");
+            cs.finalize();
+            end_timer!(lc_time);
+            Ok(())
+        },
+        |_| cs.num_constraints(),
+    );
+    if let Err(e) = synthesized {
+        zeroize_witness_assignment(cs);
+        return Err(e);
     }
-    end_timer!(synthesis_time);
 
-    let lc_time = start_timer!(|| "Inlining LCs");
-    cs.finalize();
-    end_timer!(lc_time);
+    let domain_size = GeneralEvaluationDomain::<E::ScalarField>::compute_size_of_domain(
+        cs.num_constraints() + cs.num_instance_variables(),
+    )
+    .unwrap_or(0);
+    let h = observe_phase(
+        observer,
+        Phase::WitnessMap,
+        domain_size,
+        || {
+            let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
+            let h = QAP::witness_map::<E::ScalarField, GeneralEvaluationDomain<E::ScalarField>>(
+                cs.clone(),
+            )?;
+            end_timer!(witness_map_time);
+            Ok(Zeroizing::new(h))
+        },
+        |h| h.len(),
+    );
+    match h {
+        Ok(h) => Ok((cs, h)),
+        Err(e) => {
+            zeroize_witness_assignment(cs);
+            Err(e)
+        }
+    }
+}
 
-    let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
-    let h =
-        QAP::witness_map::<E::ScalarField, GeneralEvaluationDomain<E::ScalarField>>(cs.clone())?;
-    end_timer!(witness_map_time);
-    Ok((cs, h))
+/// Whether `full_assignment`, which starts with the public inputs, satisfies all the constraints
+fn is_satisfied<F: PrimeField>(matrices: &ConstraintMatrices<F>, full_assignment: &[F]) -> bool {
+    cfg_into_iter!(0..matrices.num_constraints).all(|i| {
+        let a: F = evaluate_constraint(&matrices.a[i], full_assignment);
+        let b: F = evaluate_constraint(&matrices.b[i], full_assignment);
+        let c: F = evaluate_constraint(&matrices.c[i], full_assignment);
+        a * b == c
+    })
+}
+
+/// Wipe the witnesses from the constraint system once they are no longer needed
+fn zeroize_witness_assignment<F: Field>(cs: ConstraintSystemRef<F>) {
+    if let Some(mut cs) = cs.borrow_mut() {
+        cs.witness_assignment.zeroize();
+    }
 }
 
+/// `initial + constant + sum(query[i+1] * assignment[i])` where `constant` is `query[0]` plus the
+/// element of the verifying key, see `ProverBases`
 fn calculate_coeff<G: AffineRepr>(
     initial: G::Group,
     query: &[G],
-    vk_param: G,
+    constant: G::Group,
     assignment: &[<G::ScalarField as PrimeField>::BigInt],
 ) -> G::Group {
-    let el = query[0];
     let acc = G::Group::msm_bigint(&query[1..], assignment);
-    initial + el + acc + vk_param
+    initial + constant + acc
+}
+
+/// Elements of the proving key that every proof needs, derived once by `ProverContext`
+struct ProverBases<E: Pairing> {
+    /// Window table of `delta_g1` as there are several multiplications with it
+    delta_g1_table: Vec<Vec<E::G1Affine>>,
+    delta_g2: E::G2,
+    /// `a_query[0] + alpha_g1`
+    a_g1: E::G1,
+    /// `b_g1_query[0] + beta_g1`
+    b_g1: E::G1,
+    /// `b_g2_query[0] + beta_g2`
+    b_g2: E::G2,
+}
+
+impl<E: Pairing> ProverBases<E> {
+    /// 3 because number of multiplications with `delta_g1` is < 32, see `FixedBase::get_mul_window_size`
+    const DELTA_G1_WINDOW_SIZE: usize = 3;
+
+    fn new(pk_common: &ProvingKeyCommon<E>, vk: &VerifyingKey<E>) -> Self {
+        Self {
+            delta_g1_table: FixedBase::get_window_table(
+                E::ScalarField::MODULUS_BIT_SIZE as usize,
+                Self::DELTA_G1_WINDOW_SIZE,
+                pk_common.delta_g1.into_group(),
+            ),
+            delta_g2: vk.delta_g2.into_group(),
+            a_g1: pk_common.a_query[0] + vk.alpha_g1,
+            b_g1: pk_common.b_g1_query[0] + pk_common.beta_g1,
+            b_g2: pk_common.b_g2_query[0] + vk.beta_g2,
+        }
+    }
+
+    fn delta_g1_mul(&self, x: &E::ScalarField) -> E::G1 {
+        let scalar_size = E::ScalarField::MODULUS_BIT_SIZE as usize;
+        let outerc = scalar_size.div_ceil(Self::DELTA_G1_WINDOW_SIZE);
+        FixedBase::windowed_mul::<E::G1>(
+            outerc,
+            Self::DELTA_G1_WINDOW_SIZE,
+            &self.delta_g1_table,
+            x,
+        )
+    }
 }
@@ -1,5 +1,5 @@
 use ark_ff::{One, PrimeField, Zero};
-use ark_poly::EvaluationDomain;
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
 use ark_std::{cfg_into_iter, cfg_iter, cfg_iter_mut, end_timer, marker::Sync, start_timer, vec};
 
 use crate::Vec;
@@ -12,7 +12,10 @@ use core::ops::{AddAssign, Deref};
 use rayon::prelude::*;
 
 #[inline]
-fn evaluate_constraint<'a, LHS, RHS, R>(terms: &'a [(LHS, usize)], assignment: &'a [RHS]) -> R
+pub(crate) fn evaluate_constraint<'a, LHS, RHS, R>(
+    terms: &'a [(LHS, usize)],
+    assignment: &'a [RHS],
+) -> R
 where
     LHS: One + Send + Sync + PartialEq,
     RHS: Send + Sync + core::ops::Mul<&'a LHS, Output = RHS> + Copy,
@@ -86,6 +89,15 @@ pub trait R1CStoQAP {
         full_assignment: &[F],
     ) -> R1CSResult<Vec<F>>;
 
+    /// Same as `witness_map_from_matrices` but with the domain precomputed, as in `ProverContext`.
+    /// Implementations should use `domain` rather than create the domain and cosets again, as
+    /// avoiding that work on every proof is what `ProverContext` is for.
+    fn witness_map_from_matrices_with_domain<F: PrimeField>(
+        matrices: &ConstraintMatrices<F>,
+        full_assignment: &[F],
+        domain: &QapDomain<F>,
+    ) -> R1CSResult<Vec<F>>;
+
     /// Computes the exponents that the generator uses to calculate base
     /// elements which the prover later uses to compute `h(x)t(x)/delta`.
     fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
@@ -153,60 +165,42 @@ impl R1CStoQAP for LibsnarkReduction {
         num_constraints: usize,
         full_assignment: &[F],
     ) -> R1CSResult<Vec<F>> {
-        let zero = F::zero();
-
         let domain =
             D::new(num_constraints + num_inputs).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
-        let domain_size = domain.size();
-
-        let mut a = vec![zero; domain_size];
-        let mut b = vec![zero; domain_size];
-
-        cfg_iter_mut!(a[..num_constraints])
-            .zip(cfg_iter_mut!(b[..num_constraints]))
-            .zip(cfg_iter!(&matrices.a))
-            .zip(cfg_iter!(&matrices.b))
-            .for_each(|(((a, b), at_i), bt_i)| {
-                *a = evaluate_constraint(&at_i, &full_assignment);
-                *b = evaluate_constraint(&bt_i, &full_assignment);
-            });
-
-        {
-            let start = num_constraints;
-            let end = start + num_inputs;
-            a[start..end].clone_from_slice(&full_assignment[..num_inputs]);
-        }
-
-        let mut c = vec![zero; domain_size];
-        cfg_iter_mut!(c[..num_constraints])
-            .enumerate()
-            .for_each(|(i, c)| {
-                *c = evaluate_constraint(&matrices.c[i], &full_assignment);
-            });
-
         let coset_domain = domain.get_coset(F::GENERATOR).unwrap();
-
-        let mut arr = [&mut a, &mut b, &mut c];
-        cfg_iter_mut!(arr).for_each(|mut x| domain.ifft_in_place(&mut x));
-        cfg_iter_mut!(arr).for_each(|mut x| coset_domain.fft_in_place(&mut x));
-
-        let mut ab = domain.mul_polynomials_in_evaluation_domain(&a, &b);
-        drop(a);
-        drop(b);
-
         let vanishing_polynomial_over_coset = domain
             .evaluate_vanishing_polynomial(F::GENERATOR)
             .inverse()
             .unwrap();
 
-        cfg_iter_mut!(ab).zip(c).for_each(|(ab_i, c_i)| {
-            *ab_i -= &c_i;
-            *ab_i *= &vanishing_polynomial_over_coset;
-        });
-
-        coset_domain.ifft_in_place(&mut ab);
+        Ok(libsnark_witness_map(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+            &domain,
+            |x| coset_domain.fft_in_place(x),
+            |x| coset_domain.ifft_in_place(x),
+            vanishing_polynomial_over_coset,
+        ))
+    }
 
-        Ok(ab)
+    #[inline]
+    fn witness_map_from_matrices_with_domain<F: PrimeField>(
+        matrices: &ConstraintMatrices<F>,
+        full_assignment: &[F],
+        domain: &QapDomain<F>,
+    ) -> R1CSResult<Vec<F>> {
+        Ok(libsnark_witness_map(
+            matrices,
+            matrices.num_instance_variables,
+            matrices.num_constraints,
+            full_assignment,
+            &domain.domain,
+            |x| domain.coset_fft_in_place(x),
+            |x| domain.coset_ifft_in_place(x),
+            domain.vanishing_polynomial_over_coset,
+        ))
     }
 
     fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
@@ -222,3 +216,122 @@ impl R1CStoQAP for LibsnarkReduction {
         Ok(scalars)
     }
 }
+
+/// The evaluation domain of the QAP of a circuit with the powers of the coset offset `F::GENERATOR`, to
+/// compute them once for all proofs of that circuit.
+#[derive(Clone, Debug)]
+pub struct QapDomain<F: PrimeField> {
+    pub domain: GeneralEvaluationDomain<F>,
+    /// `g^i` for the coset offset `g`
+    coset_powers: Vec<F>,
+    /// `g^{-i}` for the coset offset `g`
+    coset_inverse_powers: Vec<F>,
+    /// Inverse of the vanishing polynomial evaluated at `g`
+    vanishing_polynomial_over_coset: F,
+}
+
+impl<F: PrimeField> QapDomain<F> {
+    pub fn new(num_constraints: usize, num_inputs: usize) -> R1CSResult<Self> {
+        let domain = GeneralEvaluationDomain::new(num_constraints + num_inputs)
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let powers = |g: F| {
+            let mut powers = Vec::with_capacity(domain.size());
+            let mut p = F::one();
+            for _ in 0..domain.size() {
+                powers.push(p);
+                p *= g;
+            }
+            powers
+        };
+        let vanishing_polynomial_over_coset = domain
+            .evaluate_vanishing_polynomial(F::GENERATOR)
+            .inverse()
+            .unwrap();
+        Ok(Self {
+            coset_powers: powers(F::GENERATOR),
+            coset_inverse_powers: powers(F::GENERATOR.inverse().unwrap()),
+            vanishing_polynomial_over_coset,
+            domain,
+        })
+    }
+
+    /// Evaluations over the coset of the polynomial with coefficients `x`
+    pub fn coset_fft_in_place(&self, x: &mut Vec<F>) {
+        cfg_iter_mut!(x)
+            .zip(cfg_iter!(self.coset_powers))
+            .for_each(|(x, p)| *x *= p);
+        self.domain.fft_in_place(x);
+    }
+
+    /// Coefficients of the polynomial with evaluations `x` over the coset
+    pub fn coset_ifft_in_place(&self, x: &mut Vec<F>) {
+        self.domain.ifft_in_place(x);
+        cfg_iter_mut!(x)
+            .zip(cfg_iter!(self.coset_inverse_powers))
+            .for_each(|(x, p)| *x *= p);
+    }
+}
+
+/// The witness map of `LibsnarkReduction` where `coset_fft` and `coset_ifft` move to and from the
+/// evaluations over the coset of `domain` with offset `F::GENERATOR`.
+#[allow(clippy::too_many_arguments)]
+fn libsnark_witness_map<F, D>(
+    matrices: &ConstraintMatrices<F>,
+    num_inputs: usize,
+    num_constraints: usize,
+    full_assignment: &[F],
+    domain: &D,
+    coset_fft: impl Fn(&mut Vec<F>) + Sync,
+    coset_ifft: impl Fn(&mut Vec<F>),
+    vanishing_polynomial_over_coset: F,
+) -> Vec<F>
+where
+    F: PrimeField,
+    D: EvaluationDomain<F> + Sync,
+{
+    let zero = F::zero();
+
+    let domain_size = domain.size();
+
+    let mut a = vec![zero; domain_size];
+    let mut b = vec![zero; domain_size];
+
+    cfg_iter_mut!(a[..num_constraints])
+        .zip(cfg_iter_mut!(b[..num_constraints]))
+        .zip(cfg_iter!(&matrices.a))
+        .zip(cfg_iter!(&matrices.b))
+        .for_each(|(((a, b), at_i), bt_i)| {
+            *a = evaluate_constraint(&at_i, &full_assignment);
+            *b = evaluate_constraint(&bt_i, &full_assignment);
+        });
+
+    {
+        let start = num_constraints;
+        let end = start + num_inputs;
+        a[start..end].clone_from_slice(&full_assignment[..num_inputs]);
+    }
+
+    let mut c = vec![zero; domain_size];
+    cfg_iter_mut!(c[..num_constraints])
+        .enumerate()
+        .for_each(|(i, c)| {
+            *c = evaluate_constraint(&matrices.c[i], &full_assignment);
+        });
+
+    let mut arr = [&mut a, &mut b, &mut c];
+    cfg_iter_mut!(arr).for_each(|mut x| domain.ifft_in_place(&mut x));
+    cfg_iter_mut!(arr).for_each(|x| coset_fft(x));
+
+    let mut ab = domain.mul_polynomials_in_evaluation_domain(&a, &b);
+    drop(a);
+    drop(b);
+
+    cfg_iter_mut!(ab).zip(c).for_each(|(ab_i, c_i)| {
+        *ab_i -= &c_i;
+        *ab_i *= &vanishing_polynomial_over_coset;
+    });
+
+    coset_ifft(&mut ab);
+
+    ab
+}
@@ -0,0 +1,341 @@
+//! Implementation of the `ark_snark` traits for LegoGroth16 so that it can be used by code generic over
+//! SNARKs. The number of witnesses committed in the proof is set by the const generic `COMMIT_WITNESS_COUNT`
+//! of `LegoGroth16` and is then part of the keys. `CommitAndProveSNARK` has the operations related to
+//! the commitments, i.e. checking the opening of the commitment in the proof and the CP_link variants,
+//! with one or several link commitments. The Subspace Snark used for CP_link is the type parameter `L`
+//! of `LegoGroth16`.
+
+use crate::{
+    create_proof_incl_cp_links_with_reduction, create_proof_with_reduction,
+    error::Error,
+    generate_random_parameters_incl_cp_links_with_link_snark,
+    generate_random_parameters_with_reduction,
+    link::{LinkSnark, PESubspaceSnark},
+    prepare_verifying_key,
+    r1cs_to_qap::R1CStoQAP,
+    verify_commitments_incl_cp_links, verify_proof, verify_proof_incl_cp_link,
+    verify_witness_commitment, LibsnarkReduction, LinkPublicGenerators, PreparedVerifyingKey,
+    Proof, ProofWithLink, ProvingKey, ProvingKeyWithLink, VerifyingKey, VerifyingKeyWithLink,
+};
+use ark_ec::pairing::Pairing;
+use ark_ff::{PrimeField, UniformRand};
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::{
+    marker::PhantomData,
+    ops::Range,
+    rand::{CryptoRng, RngCore},
+    vec,
+    vec::Vec,
+};
+
+/// LegoGroth16 committing to the first `COMMIT_WITNESS_COUNT` witnesses in the proof and using the
+/// Subspace Snark `L` for CP_link
+pub struct LegoGroth16<
+    E: Pairing,
+    QAP: R1CStoQAP = LibsnarkReduction,
+    const COMMIT_WITNESS_COUNT: usize = 0,
+    L: LinkSnark<E> = PESubspaceSnark<E>,
+> {
+    _p: PhantomData<(E, QAP, L)>,
+}
+
+/// Operations of a commit-and-prove SNARK, i.e. one where the proof contains a commitment to some of the
+/// witnesses, optionally with another commitment (CP_link) whose opening is proven equal.
+pub trait CommitAndProveSNARK<F: PrimeField>: SNARK<F> {
+    type LinkPublicGenerators;
+    type ProvingKeyWithLink: Clone + CanonicalSerialize + CanonicalDeserialize;
+    type VerifyingKeyWithLink: Clone + CanonicalSerialize + CanonicalDeserialize;
+    type ProofWithLink: Clone + CanonicalSerialize + CanonicalDeserialize;
+
+    /// Same as `SNARK::prove` but `v` is the randomness of the commitment to the witnesses in the proof
+    fn prove_with_commitment_randomness<C: ConstraintSynthesizer<F>, R: RngCore + CryptoRng>(
+        circuit_pk: &Self::ProvingKey,
+        circuit: C,
+        v: F,
+        rng: &mut R,
+    ) -> Result<Self::Proof, Self::Error>;
+
+    /// Check that the commitment in the proof opens to `witnesses` with randomness `v`
+    fn verify_witness_commitment(
+        circuit_vk: &Self::VerifyingKey,
+        proof: &Self::Proof,
+        public_inputs_count: usize,
+        witnesses: &[F],
+        v: &F,
+    ) -> Result<bool, Self::Error>;
+
+    fn circuit_specific_setup_incl_cp_link<C: ConstraintSynthesizer<F>, R: RngCore + CryptoRng>(
+        circuit: C,
+        link_gens: Self::LinkPublicGenerators,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKeyWithLink, Self::VerifyingKeyWithLink), Self::Error>;
+
+    /// Same as `circuit_specific_setup_incl_cp_link` but with several link commitments, each item of
+    /// `link_gens` has the generators of a link commitment and the range of committed witnesses it
+    /// commits to
+    fn circuit_specific_setup_incl_cp_links<C: ConstraintSynthesizer<F>, R: RngCore + CryptoRng>(
+        circuit: C,
+        link_gens: Vec<(Self::LinkPublicGenerators, Range<usize>)>,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKeyWithLink, Self::VerifyingKeyWithLink), Self::Error>;
+
+    /// Create a proof with CP_link where `v` is the randomness of the commitment in the proof and
+    /// `link_v` of the commitment in CP_link
+    fn prove_incl_cp_link<C: ConstraintSynthesizer<F>, R: RngCore + CryptoRng>(
+        circuit_pk: &Self::ProvingKeyWithLink,
+        circuit: C,
+        v: F,
+        link_v: F,
+        rng: &mut R,
+    ) -> Result<Self::ProofWithLink, Self::Error>;
+
+    /// Same as `prove_incl_cp_link` but `link_v` has the randomness of each link commitment
+    fn prove_incl_cp_links<C: ConstraintSynthesizer<F>, R: RngCore + CryptoRng>(
+        circuit_pk: &Self::ProvingKeyWithLink,
+        circuit: C,
+        v: F,
+        link_v: &[F],
+        rng: &mut R,
+    ) -> Result<Self::ProofWithLink, Self::Error>;
+
+    /// Verifies the proof and all the link commitments in it
+    fn verify_incl_cp_link(
+        circuit_vk: &Self::VerifyingKeyWithLink,
+        public_input: &[F],
+        proof: &Self::ProofWithLink,
+    ) -> Result<bool, Self::Error>;
+
+    /// Check that both the commitment in the proof and CP_link open to `witnesses`
+    fn verify_commitments(
+        circuit_vk: &Self::VerifyingKeyWithLink,
+        proof: &Self::ProofWithLink,
+        public_inputs_count: usize,
+        witnesses: &[F],
+        v: &F,
+        link_v: &F,
+    ) -> Result<bool, Self::Error>;
+
+    /// Same as `verify_commitments` but for several link commitments, each opening to the witnesses
+    /// in its range with its randomness in `link_v`
+    fn verify_commitments_incl_cp_links(
+        circuit_vk: &Self::VerifyingKeyWithLink,
+        proof: &Self::ProofWithLink,
+        public_inputs_count: usize,
+        witnesses: &[F],
+        v: &F,
+        link_v: &[F],
+    ) -> Result<bool, Self::Error>;
+}
+
+impl<E: Pairing, QAP: R1CStoQAP, const COMMIT_WITNESS_COUNT: usize, L: LinkSnark<E>>
+    SNARK<E::ScalarField> for LegoGroth16<E, QAP, COMMIT_WITNESS_COUNT, L>
+{
+    type ProvingKey = ProvingKey<E>;
+    type VerifyingKey = VerifyingKey<E>;
+    type Proof = Proof<E>;
+    type ProcessedVerifyingKey = PreparedVerifyingKey<E>;
+    type Error = Error;
+
+    fn circuit_specific_setup<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore + CryptoRng>(
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Self::Error> {
+        let pk = generate_random_parameters_with_reduction::<E, C, R, QAP>(
+            circuit,
+            COMMIT_WITNESS_COUNT,
+            rng,
+        )?;
+        let vk = pk.vk.clone();
+        Ok((pk, vk))
+    }
+
+    fn prove<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore + CryptoRng>(
+        circuit_pk: &Self::ProvingKey,
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<Self::Proof, Self::Error> {
+        let v = E::ScalarField::rand(rng);
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+        create_proof_with_reduction::<E, C, QAP>(circuit, circuit_pk, r, s, v)
+    }
+
+    fn process_vk(
+        circuit_vk: &Self::VerifyingKey,
+    ) -> Result<Self::ProcessedVerifyingKey, Self::Error> {
+        Ok(prepare_verifying_key(circuit_vk))
+    }
+
+    fn verify_with_processed_vk(
+        circuit_pvk: &Self::ProcessedVerifyingKey,
+        public_input: &[E::ScalarField],
+        proof: &Self::Proof,
+    ) -> Result<bool, Self::Error> {
+        to_bool(verify_proof(circuit_pvk, proof, public_input))
+    }
+}
+
+impl<E: Pairing, QAP: R1CStoQAP, const COMMIT_WITNESS_COUNT: usize, L: LinkSnark<E>>
+    CircuitSpecificSetupSNARK<E::ScalarField> for LegoGroth16<E, QAP, COMMIT_WITNESS_COUNT, L>
+{
+}
+
+impl<E: Pairing, QAP: R1CStoQAP, const COMMIT_WITNESS_COUNT: usize, L: LinkSnark<E> + Clone>
+    CommitAndProveSNARK<E::ScalarField> for LegoGroth16<E, QAP, COMMIT_WITNESS_COUNT, L>
+{
+    type LinkPublicGenerators = LinkPublicGenerators<E>;
+    type ProvingKeyWithLink = ProvingKeyWithLink<E, L>;
+    type VerifyingKeyWithLink = VerifyingKeyWithLink<E, L>;
+    type ProofWithLink = ProofWithLink<E, L>;
+
+    fn prove_with_commitment_randomness<
+        C: ConstraintSynthesizer<E::ScalarField>,
+        R: RngCore + CryptoRng,
+    >(
+        circuit_pk: &Self::ProvingKey,
+        circuit: C,
+        v: E::ScalarField,
+        rng: &mut R,
+    ) -> Result<Self::Proof, Self::Error> {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+        create_proof_with_reduction::<E, C, QAP>(circuit, circuit_pk, r, s, v)
+    }
+
+    fn verify_witness_commitment(
+        circuit_vk: &Self::VerifyingKey,
+        proof: &Self::Proof,
+        public_inputs_count: usize,
+        witnesses: &[E::ScalarField],
+        v: &E::ScalarField,
+    ) -> Result<bool, Self::Error> {
+        to_bool(verify_witness_commitment(
+            circuit_vk,
+            proof,
+            public_inputs_count,
+            witnesses,
+            v,
+        ))
+    }
+
+    fn circuit_specific_setup_incl_cp_link<
+        C: ConstraintSynthesizer<E::ScalarField>,
+        R: RngCore + CryptoRng,
+    >(
+        circuit: C,
+        link_gens: Self::LinkPublicGenerators,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKeyWithLink, Self::VerifyingKeyWithLink), Self::Error> {
+        Self::circuit_specific_setup_incl_cp_links(
+            circuit,
+            vec![(link_gens, 0..COMMIT_WITNESS_COUNT)],
+            rng,
+        )
+    }
+
+    fn circuit_specific_setup_incl_cp_links<
+        C: ConstraintSynthesizer<E::ScalarField>,
+        R: RngCore + CryptoRng,
+    >(
+        circuit: C,
+        link_gens: Vec<(Self::LinkPublicGenerators, Range<usize>)>,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKeyWithLink, Self::VerifyingKeyWithLink), Self::Error> {
+        let pk = generate_random_parameters_incl_cp_links_with_link_snark::<E, C, R, QAP, L>(
+            circuit,
+            link_gens,
+            COMMIT_WITNESS_COUNT,
+            rng,
+        )?;
+        let vk = pk.vk.clone();
+        Ok((pk, vk))
+    }
+
+    fn prove_incl_cp_link<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore + CryptoRng>(
+        circuit_pk: &Self::ProvingKeyWithLink,
+        circuit: C,
+        v: E::ScalarField,
+        link_v: E::ScalarField,
+        rng: &mut R,
+    ) -> Result<Self::ProofWithLink, Self::Error> {
+        Self::prove_incl_cp_links(circuit_pk, circuit, v, &[link_v], rng)
+    }
+
+    fn prove_incl_cp_links<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore + CryptoRng>(
+        circuit_pk: &Self::ProvingKeyWithLink,
+        circuit: C,
+        v: E::ScalarField,
+        link_v: &[E::ScalarField],
+        rng: &mut R,
+    ) -> Result<Self::ProofWithLink, Self::Error> {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+        create_proof_incl_cp_links_with_reduction::<E, C, QAP, L>(
+            circuit, circuit_pk, r, s, v, link_v,
+        )
+    }
+
+    fn verify_incl_cp_link(
+        circuit_vk: &Self::VerifyingKeyWithLink,
+        public_input: &[E::ScalarField],
+        proof: &Self::ProofWithLink,
+    ) -> Result<bool, Self::Error> {
+        let pvk = prepare_verifying_key(&circuit_vk.groth16_vk);
+        to_bool(verify_proof_incl_cp_link(
+            &pvk,
+            circuit_vk,
+            proof,
+            public_input,
+        ))
+    }
+
+    fn verify_commitments(
+        circuit_vk: &Self::VerifyingKeyWithLink,
+        proof: &Self::ProofWithLink,
+        public_inputs_count: usize,
+        witnesses: &[E::ScalarField],
+        v: &E::ScalarField,
+        link_v: &E::ScalarField,
+    ) -> Result<bool, Self::Error> {
+        Self::verify_commitments_incl_cp_links(
+            circuit_vk,
+            proof,
+            public_inputs_count,
+            witnesses,
+            v,
+            &[*link_v],
+        )
+    }
+
+    fn verify_commitments_incl_cp_links(
+        circuit_vk: &Self::VerifyingKeyWithLink,
+        proof: &Self::ProofWithLink,
+        public_inputs_count: usize,
+        witnesses: &[E::ScalarField],
+        v: &E::ScalarField,
+        link_v: &[E::ScalarField],
+    ) -> Result<bool, Self::Error> {
+        to_bool(verify_commitments_incl_cp_links(
+            circuit_vk,
+            proof,
+            public_inputs_count,
+            witnesses,
+            v,
+            link_v,
+        ))
+    }
+}
+
+/// Failed checks become `false` as the `ark_snark` traits expect, other errors are returned
+fn to_bool(result: crate::Result<()>) -> Result<bool, Error> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(Error::InvalidProof)
+        | Err(Error::InvalidLinkCommitment)
+        | Err(Error::InvalidWitnessCommitment)
+        | Err(Error::LinkError(crate::link::error::LinkError::InvalidProof)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
@@ -1,20 +1,37 @@
 use crate::{
-    create_random_proof, create_random_proof_incl_cp_link, generate_random_parameters,
-    generate_random_parameters_incl_cp_link, prepare_verifying_key, rerandomize_proof,
+    check_proving_key, check_proving_key_incl_cp_link, create_random_proof,
+    create_random_proof_incl_cp_link, create_random_proof_incl_cp_links,
+    create_random_proof_incl_cp_links_with_observer, create_random_proof_with_observer,
+    create_random_proofs_batch, create_random_proofs_batch_incl_cp_link,
+    generate_parameters_with_qap_and_observer, generate_random_parameters,
+    generate_random_parameters_incl_cp_link, generate_random_parameters_incl_cp_link_with_observer,
+    generate_random_parameters_incl_cp_links,
+    generate_random_parameters_incl_cp_links_with_observer,
+    generate_random_parameters_incl_transparent_cp_link,
+    generate_random_parameters_incl_transparent_cp_link_with_observer,
+    generate_random_parameters_with_observer, prepare_verifying_key, rerandomize_proof,
     rerandomize_proof_1, verify_proof, verify_proof_incl_cp_link, verify_witness_commitment,
-    LinkPublicGenerators,
+    LinkPublicGenerators, Proof, ProofWithLink, ProverContext, ProvingKey, ProvingKeyWithLink,
+    VerifyingKey, VerifyingKeyWithLink,
 };
 use ark_ec::{pairing::Pairing, CurveGroup};
 use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{
     rand::{rngs::StdRng, RngCore, SeedableRng},
     UniformRand,
 };
 
-use core::ops::MulAssign;
+use core::{
+    cell::RefCell,
+    ops::{ControlFlow, MulAssign},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use crate::error::Error;
-use crate::prover::verify_commitments;
+use crate::link::error::LinkError;
+use crate::progress::{Event, Phase, Query};
+use crate::prover::{verify_commitments, verify_commitments_incl_cp_links};
 use ark_relations::r1cs::Variable;
 use ark_relations::{
     lc,
@@ -147,6 +164,20 @@ pub fn get_link_public_gens<R: RngCore, E: Pairing>(
     }
 }
 
+/// Same as `get_link_public_gens` but with the `g1` and `g2` of `other` as all link commitments of a
+/// key share the generators of the subspace SNARK
+pub fn get_link_public_gens_like<R: RngCore, E: Pairing>(
+    rng: &mut R,
+    count: usize,
+    other: &LinkPublicGenerators<E>,
+) -> LinkPublicGenerators<E> {
+    LinkPublicGenerators {
+        g1: other.g1,
+        g2: other.g2,
+        ..get_link_public_gens(rng, count)
+    }
+}
+
 fn test_prove_and_verify<E>(n_iters: usize)
 where
     E: Pairing,
@@ -566,83 +597,1714 @@ where
     }
 }
 
-mod bls12_377 {
-    use super::*;
-    use ark_bls12_377::Bls12_377;
+fn test_prove_and_verify_transparent_cp_link<E>(n_iters: usize)
+where
+    E: Pairing,
+{
+    let mut rng = StdRng::seed_from_u64(0u64);
 
-    #[test]
-    fn prove_and_verify() {
-        test_prove_and_verify::<Bls12_377>(10);
-    }
+    let commit_witness_count = 4;
 
-    #[test]
-    fn prove_and_verify_1() {
-        test_prove_and_verify_1::<Bls12_377>(10);
-    }
+    // Generators for committing to witnesses and 1 more for randomness (`link_v` below)
+    let link_gens = get_link_public_gens(&mut rng, commit_witness_count + 1);
 
-    #[test]
-    fn prove_and_verify_2() {
-        test_prove_and_verify_2::<Bls12_377>(10);
+    let circuit = MyLessSillyCircuit1 {
+        a: None,
+        b: None,
+        c: None,
+        d: None,
+    };
+    // Parameters with CP_link that does not need a trusted setup
+    let params_link = generate_random_parameters_incl_transparent_cp_link::<E, _, _>(
+        circuit,
+        link_gens,
+        commit_witness_count,
+        &mut rng,
+    )
+    .unwrap();
+    let pvk_link = prepare_verifying_key::<E>(&params_link.vk.groth16_vk);
+
+    for _ in 0..n_iters {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let c = E::ScalarField::rand(&mut rng);
+        let d = E::ScalarField::rand(&mut rng);
+
+        let mut e = a;
+        e.mul_assign(&b);
+
+        let mut f = c;
+        f.mul_assign(&d);
+
+        // Randomness for the committed witness in proof.d
+        let v = E::ScalarField::rand(&mut rng);
+        // Randomness for the committed witness in CP_link
+        let link_v = E::ScalarField::rand(&mut rng);
+
+        let circuit = MyLessSillyCircuit1 {
+            a: Some(a),
+            b: Some(b),
+            c: Some(c),
+            d: Some(d),
+        };
+
+        let proof_link =
+            create_random_proof_incl_cp_link(circuit, v, link_v, &params_link, &mut rng).unwrap();
+
+        verify_commitments(&params_link.vk, &proof_link, 2, &[a, b, c, d], &v, &link_v).unwrap();
+        assert!(verify_commitments(&params_link.vk, &proof_link, 2, &[a, b], &v, &link_v).is_err());
+
+        verify_proof_incl_cp_link(&pvk_link, &params_link.vk, &proof_link, &[e, f]).unwrap();
+        assert!(verify_proof_incl_cp_link(&pvk_link, &params_link.vk, &proof_link, &[e]).is_err());
+
+        // Link proof does not verify when CP_link and proof.d commit to different witnesses
+        let mut bad_proof_link = proof_link.clone();
+        bad_proof_link.link_d[0] = E::G1::rand(&mut rng).into_affine();
+        assert!(
+            verify_proof_incl_cp_link(&pvk_link, &params_link.vk, &bad_proof_link, &[e, f])
+                .is_err()
+        );
+
+        let mut bytes = vec![];
+        proof_link.serialize_compressed(&mut bytes).unwrap();
+        let deserialized = CanonicalDeserialize::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(proof_link, deserialized);
     }
 }
 
-mod cp6_782 {
-    use super::*;
+fn test_prove_and_verify_multiple_cp_links<E>(n_iters: usize)
+where
+    E: Pairing,
+{
+    let mut rng = StdRng::seed_from_u64(0u64);
 
-    use ark_cp6_782::CP6_782;
+    let commit_witness_count = 4;
 
-    #[test]
-    fn prove_and_verify() {
-        test_prove_and_verify::<CP6_782>(1);
-    }
+    let circuit = MyLessSillyCircuit1 {
+        a: None,
+        b: None,
+        c: None,
+        d: None,
+    };
 
-    #[test]
-    fn prove_and_verify_1() {
-        test_prove_and_verify_1::<CP6_782>(1);
-    }
+    // 3 link commitments, to `a, b`, to `c, d` and to `b, c`, each with its own bases and 1 more
+    // for randomness
+    let gens = get_link_public_gens(&mut rng, 3);
+    let link_gens = vec![
+        (gens.clone(), 0..2),
+        (get_link_public_gens_like(&mut rng, 3, &gens), 2..4),
+        (get_link_public_gens_like(&mut rng, 3, &gens), 1..3),
+    ];
+
+    assert_eq!(
+        generate_random_parameters_incl_cp_links::<E, _, _>(
+            circuit.clone(),
+            vec![(get_link_public_gens(&mut rng, 3), 3..5)],
+            commit_witness_count,
+            &mut rng,
+        )
+        .unwrap_err(),
+        Error::LinkError(LinkError::InvalidIndex(5, 4))
+    );
+    assert_eq!(
+        generate_random_parameters_incl_cp_links::<E, _, _>(
+            circuit.clone(),
+            vec![(get_link_public_gens(&mut rng, 2), 0..2)],
+            commit_witness_count,
+            &mut rng,
+        )
+        .unwrap_err(),
+        Error::LinkError(LinkError::VectorWithUnexpectedLength(2, 3))
+    );
+    assert_eq!(
+        generate_random_parameters_incl_cp_links::<E, _, _>(
+            circuit.clone(),
+            vec![
+                (gens.clone(), 0..2),
+                (get_link_public_gens(&mut rng, 3), 2..4),
+            ],
+            commit_witness_count,
+            &mut rng,
+        )
+        .unwrap_err(),
+        Error::LinkError(LinkError::DifferentLinkGenerators)
+    );
 
-    #[test]
-    fn prove_and_verify_2() {
-        test_prove_and_verify_2::<CP6_782>(1);
+    let params_link = generate_random_parameters_incl_cp_links::<E, _, _>(
+        circuit,
+        link_gens,
+        commit_witness_count,
+        &mut rng,
+    )
+    .unwrap();
+    let pvk_link = prepare_verifying_key::<E>(&params_link.vk.groth16_vk);
+
+    for _ in 0..n_iters {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let c = E::ScalarField::rand(&mut rng);
+        let d = E::ScalarField::rand(&mut rng);
+
+        let mut e = a;
+        e.mul_assign(&b);
+
+        let mut f = c;
+        f.mul_assign(&d);
+
+        let v = E::ScalarField::rand(&mut rng);
+        // Randomness for each link commitment
+        let link_v = (0..3)
+            .map(|_| E::ScalarField::rand(&mut rng))
+            .collect::<Vec<_>>();
+
+        let circuit = MyLessSillyCircuit1 {
+            a: Some(a),
+            b: Some(b),
+            c: Some(c),
+            d: Some(d),
+        };
+
+        // Need randomness for each link commitment
+        assert!(create_random_proof_incl_cp_link(
+            circuit.clone(),
+            v,
+            link_v[0],
+            &params_link,
+            &mut rng
+        )
+        .is_err());
+
+        let proof_link =
+            create_random_proof_incl_cp_links(circuit, v, &link_v, &params_link, &mut rng).unwrap();
+        assert_eq!(proof_link.link_d.len(), 3);
+
+        verify_commitments_incl_cp_links(
+            &params_link.vk,
+            &proof_link,
+            2,
+            &[a, b, c, d],
+            &v,
+            &link_v,
+        )
+        .unwrap();
+        assert!(verify_commitments_incl_cp_links(
+            &params_link.vk,
+            &proof_link,
+            2,
+            &[a, c, b, d],
+            &v,
+            &link_v,
+        )
+        .is_err());
+        assert!(verify_commitments_incl_cp_links(
+            &params_link.vk,
+            &proof_link,
+            2,
+            &[a, b, c, d],
+            &v,
+            &[link_v[0], link_v[2], link_v[1]],
+        )
+        .is_err());
+        assert!(verify_commitments(
+            &params_link.vk,
+            &proof_link,
+            2,
+            &[a, b, c, d],
+            &v,
+            &link_v[0]
+        )
+        .is_err());
+
+        verify_proof_incl_cp_link(&pvk_link, &params_link.vk, &proof_link, &[e, f]).unwrap();
+        assert!(verify_proof_incl_cp_link(&pvk_link, &params_link.vk, &proof_link, &[e]).is_err());
+
+        // Link proof does not verify when a link commitment is swapped or removed
+        let mut bad_proof_link = proof_link.clone();
+        bad_proof_link.link_d.swap(0, 1);
+        assert!(
+            verify_proof_incl_cp_link(&pvk_link, &params_link.vk, &bad_proof_link, &[e, f])
+                .is_err()
+        );
+        bad_proof_link.link_d.pop();
+        assert!(
+            verify_proof_incl_cp_link(&pvk_link, &params_link.vk, &bad_proof_link, &[e, f])
+                .is_err()
+        );
     }
 }
 
-mod bls12_381 {
-    use super::*;
-    use ark_bls12_381::Bls12_381;
+fn test_prover_context<E>(n_iters: usize)
+where
+    E: Pairing,
+{
+    /// Circuit for `a * b = c` where `c` is given and not computed
+    struct UncheckedProduct<F: Field> {
+        a: Option<F>,
+        b: Option<F>,
+        c: Option<F>,
+    }
 
-    #[test]
-    fn prove_and_verify() {
-        test_prove_and_verify::<Bls12_381>(10);
+    impl<F: Field> ConstraintSynthesizer<F> for UncheckedProduct<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+            let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+            let c = cs.new_input_variable(|| self.c.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+        }
     }
 
-    #[test]
-    fn prove_and_verify_1() {
-        test_prove_and_verify_1::<Bls12_381>(10);
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let circuit = MyLessSillyCircuit1 {
+        a: None,
+        b: None,
+        c: None,
+        d: None,
+    };
+    let params = generate_random_parameters::<E, _, _>(circuit.clone(), 4, &mut rng).unwrap();
+    let params_link = generate_random_parameters_incl_cp_link::<E, _, _>(
+        circuit.clone(),
+        get_link_public_gens(&mut rng, 5),
+        4,
+        &mut rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&params.vk);
+    let pvk_link = prepare_verifying_key::<E>(&params_link.vk.groth16_vk);
+
+    let context = ProverContext::<E>::new(circuit.clone(), &params).unwrap();
+    let context_link = ProverContext::<E>::new_incl_cp_link(circuit, &params_link).unwrap();
+
+    // The circuit must be the one the proving key was generated for
+    assert_eq!(
+        ProverContext::<E>::new(MySillyCircuit { a: None, b: None }, &params)
+            .err()
+            .unwrap(),
+        Error::CircuitAndProvingKeyMismatch
+    );
+
+    for _ in 0..n_iters {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let c = E::ScalarField::rand(&mut rng);
+        let d = E::ScalarField::rand(&mut rng);
+        let v = E::ScalarField::rand(&mut rng);
+        let link_v = E::ScalarField::rand(&mut rng);
+
+        let circuit = MyLessSillyCircuit1 {
+            a: Some(a),
+            b: Some(b),
+            c: Some(c),
+            d: Some(d),
+        };
+
+        // Same proofs as without the context for the same randomness
+        let mut rng_1 = rng.clone();
+        let proof = context.prove(circuit.clone(), v, &mut rng).unwrap();
+        assert_eq!(
+            proof,
+            create_random_proof(circuit.clone(), v, &params, &mut rng_1).unwrap()
+        );
+        verify_proof(&pvk, &proof, &[a * b, c * d]).unwrap();
+
+        let mut rng_1 = rng.clone();
+        let proof_link = context_link
+            .prove_incl_cp_link(circuit.clone(), v, link_v, &mut rng)
+            .unwrap();
+        assert_eq!(
+            proof_link,
+            create_random_proof_incl_cp_link(circuit.clone(), v, link_v, &params_link, &mut rng_1)
+                .unwrap()
+        );
+        verify_proof_incl_cp_link(&pvk_link, &params_link.vk, &proof_link, &[a * b, c * d])
+            .unwrap();
+
+        // A context with a proving key with CP_link can also create proofs without it
+        let proof = context_link.prove(circuit.clone(), v, &mut rng).unwrap();
+        verify_proof(&pvk_link, &proof, &[a * b, c * d]).unwrap();
+
+        assert_eq!(
+            context
+                .prove_incl_cp_link(circuit, v, link_v, &mut rng)
+                .unwrap_err(),
+            Error::MissingLinkProvingKey
+        );
+        let other_circuit = MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        };
+        assert_eq!(
+            context.prove(other_circuit, v, &mut rng).unwrap_err(),
+            Error::CircuitAndProvingKeyMismatch
+        );
     }
 
-    #[test]
-    fn prove_and_verify_2() {
-        test_prove_and_verify_2::<Bls12_381>(10);
+    let empty = || UncheckedProduct::<E::ScalarField> {
+        a: None,
+        b: None,
+        c: None,
+    };
+    let params = generate_random_parameters::<E, _, _>(empty(), 2, &mut rng).unwrap();
+    let context = ProverContext::<E>::new(empty(), &params).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let v = E::ScalarField::rand(&mut rng);
+    let satisfied = UncheckedProduct {
+        a: Some(a),
+        b: Some(b),
+        c: Some(a * b),
+    };
+    context.prove(satisfied, v, &mut rng).unwrap();
+    let unsatisfied = UncheckedProduct {
+        a: Some(a),
+        b: Some(b),
+        c: Some(a + b),
+    };
+    assert_eq!(
+        context.prove(unsatisfied, v, &mut rng).unwrap_err(),
+        Error::SynthesisError(SynthesisError::Unsatisfiable)
+    );
+}
+
+/// Circuit that records how many of its instances are alive at once
+struct CountedCircuit<'a, C> {
+    circuit: C,
+    live: &'a AtomicUsize,
+    max_live: &'a AtomicUsize,
+}
+
+impl<'a, C> CountedCircuit<'a, C> {
+    fn new(circuit: C, live: &'a AtomicUsize, max_live: &'a AtomicUsize) -> Self {
+        let count = live.fetch_add(1, Ordering::SeqCst) + 1;
+        max_live.fetch_max(count, Ordering::SeqCst);
+        Self {
+            circuit,
+            live,
+            max_live,
+        }
     }
 }
 
-mod bn254 {
-    use super::*;
-    use ark_bn254::Bn254;
+impl<C: Clone> Clone for CountedCircuit<'_, C> {
+    fn clone(&self) -> Self {
+        Self::new(self.circuit.clone(), self.live, self.max_live)
+    }
+}
 
-    #[test]
-    fn prove_and_verify() {
-        test_prove_and_verify::<Bn254>(10);
+impl<C> Drop for CountedCircuit<'_, C> {
+    fn drop(&mut self) {
+        self.live.fetch_sub(1, Ordering::SeqCst);
     }
+}
 
-    #[test]
-    fn prove_and_verify_1() {
-        test_prove_and_verify_1::<Bn254>(10);
+impl<F: Field, C: ConstraintSynthesizer<F> + Clone> ConstraintSynthesizer<F>
+    for CountedCircuit<'_, C>
+{
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        self.circuit.clone().generate_constraints(cs)
     }
+}
 
-    #[test]
-    fn prove_and_verify_2() {
-        test_prove_and_verify_2::<Bn254>(10);
+fn test_prove_batch<E>(batch_size: usize)
+where
+    E: Pairing,
+{
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let circuit = MyLessSillyCircuit1 {
+        a: None,
+        b: None,
+        c: None,
+        d: None,
+    };
+    let params = generate_random_parameters::<E, _, _>(circuit.clone(), 4, &mut rng).unwrap();
+    let params_link = generate_random_parameters_incl_cp_link::<E, _, _>(
+        circuit,
+        get_link_public_gens(&mut rng, 5),
+        4,
+        &mut rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key::<E>(&params.vk);
+    let pvk_link = prepare_verifying_key::<E>(&params_link.vk.groth16_vk);
+
+    let witnesses = (0..batch_size)
+        .map(|_| [0; 4].map(|_| E::ScalarField::rand(&mut rng)))
+        .collect::<Vec<_>>();
+    let circuits = witnesses
+        .iter()
+        .map(|[a, b, c, d]| MyLessSillyCircuit1 {
+            a: Some(*a),
+            b: Some(*b),
+            c: Some(*c),
+            d: Some(*d),
+        })
+        .collect::<Vec<_>>();
+    let v = (0..batch_size)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let link_v = (0..batch_size)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+
+    // Only `max_concurrency` circuits are taken from the instances at a time, and the first one is
+    // cloned for the matrices
+    let live = AtomicUsize::new(0);
+    let max_live = AtomicUsize::new(0);
+    let instances = circuits
+        .iter()
+        .zip(v.iter())
+        .map(|(c, v)| (CountedCircuit::new(c.clone(), &live, &max_live), *v));
+    let proofs = create_random_proofs_batch(instances, &params, 1, &mut rng).unwrap();
+    assert_eq!(proofs.len(), batch_size);
+    assert_eq!(live.load(Ordering::SeqCst), 0);
+    assert_eq!(max_live.load(Ordering::SeqCst), 2);
+
+    // Same proofs as when created one at a time, whatever the concurrency
+    for max_concurrency in [1, 2, batch_size] {
+        let mut rng_1 = rng.clone();
+        let proofs = create_random_proofs_batch(
+            circuits.iter().cloned().zip(v.iter().copied()),
+            &params,
+            max_concurrency,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(proofs.len(), batch_size);
+        for (i, proof) in proofs.iter().enumerate() {
+            let [a, b, c, d] = witnesses[i];
+            assert_eq!(
+                *proof,
+                create_random_proof(circuits[i].clone(), v[i], &params, &mut rng_1).unwrap()
+            );
+            verify_proof(&pvk, proof, &[a * b, c * d]).unwrap();
+            verify_witness_commitment(&params.vk, proof, 2, &[a, b, c, d], &v[i]).unwrap();
+        }
+
+        let mut rng_1 = rng.clone();
+        let proofs = create_random_proofs_batch_incl_cp_link(
+            (0..batch_size).map(|i| (circuits[i].clone(), v[i], link_v[i])),
+            &params_link,
+            max_concurrency,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(proofs.len(), batch_size);
+        for (i, proof) in proofs.iter().enumerate() {
+            let [a, b, c, d] = witnesses[i];
+            assert_eq!(
+                *proof,
+                create_random_proof_incl_cp_link(
+                    circuits[i].clone(),
+                    v[i],
+                    link_v[i],
+                    &params_link,
+                    &mut rng_1
+                )
+                .unwrap()
+            );
+            verify_proof_incl_cp_link(&pvk_link, &params_link.vk, proof, &[a * b, c * d]).unwrap();
+        }
+    }
+
+    assert!(
+        create_random_proofs_batch::<E, MyLessSillyCircuit1<E::ScalarField>, _, _>(
+            vec![],
+            &params,
+            2,
+            &mut rng
+        )
+        .unwrap()
+        .is_empty()
+    );
+}
+
+fn test_check_proving_key<E>()
+where
+    E: Pairing,
+{
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let circuit = MyLessSillyCircuit1 {
+        a: None,
+        b: None,
+        c: None,
+        d: None,
+    };
+    let params = generate_random_parameters::<E, _, _>(circuit.clone(), 4, &mut rng).unwrap();
+    check_proving_key(&params, circuit.clone(), &mut rng).unwrap();
+
+    // Generated for another circuit or commit count
+    assert_eq!(
+        check_proving_key(&params, MySillyCircuit { a: None, b: None }, &mut rng).unwrap_err(),
+        Error::CircuitAndProvingKeyMismatch
+    );
+    let params_1 = generate_random_parameters::<E, _, _>(circuit.clone(), 2, &mut rng).unwrap();
+    let mut bad_params = params.clone();
+    bad_params.vk.gamma_abc_g1 = params_1.vk.gamma_abc_g1.clone();
+    assert_eq!(
+        check_proving_key(&bad_params, circuit.clone(), &mut rng).unwrap_err(),
+        Error::CircuitAndProvingKeyMismatch
+    );
+
+    // Elements from another key
+    let mut bad_params = params.clone();
+    bad_params.common.delta_g1 = params_1.common.delta_g1;
+    assert_eq!(
+        check_proving_key(&bad_params, circuit.clone(), &mut rng).unwrap_err(),
+        Error::InvalidProvingKey
+    );
+    let mut bad_params = params.clone();
+    bad_params.vk.beta_g2 = params_1.vk.beta_g2;
+    assert_eq!(
+        check_proving_key(&bad_params, circuit.clone(), &mut rng).unwrap_err(),
+        Error::InvalidProvingKey
+    );
+    let mut bad_params = params.clone();
+    bad_params.common.eta_delta_inv_g1 = params_1.common.eta_delta_inv_g1;
+    assert_eq!(
+        check_proving_key(&bad_params, circuit.clone(), &mut rng).unwrap_err(),
+        Error::InvalidProvingKey
+    );
+    // `b` and `d` are the variables in the right side of the constraints
+    let mut bad_params = params.clone();
+    bad_params.common.b_g2_query.swap(4, 6);
+    assert_eq!(
+        check_proving_key(&bad_params, circuit.clone(), &mut rng).unwrap_err(),
+        Error::InvalidProvingKey
+    );
+
+    let gens = get_link_public_gens(&mut rng, 3);
+    let link_gens = vec![gens.clone(), get_link_public_gens_like(&mut rng, 3, &gens)];
+    let params_link = generate_random_parameters_incl_cp_links::<E, _, _>(
+        circuit.clone(),
+        vec![(link_gens[0].clone(), 0..2), (link_gens[1].clone(), 2..4)],
+        4,
+        &mut rng,
+    )
+    .unwrap();
+    check_proving_key_incl_cp_link(&params_link, circuit.clone(), &link_gens, &mut rng).unwrap();
+
+    let params_transparent_link = generate_random_parameters_incl_transparent_cp_link::<E, _, _>(
+        circuit.clone(),
+        get_link_public_gens(&mut rng, 5),
+        4,
+        &mut rng,
+    )
+    .unwrap();
+    check_proving_key_incl_cp_link(
+        &params_transparent_link,
+        circuit.clone(),
+        &[LinkPublicGenerators {
+            pedersen_gens: params_transparent_link.vk.link_bases[0].clone(),
+            g1: params_transparent_link.vk.link_pp.g1,
+            g2: params_transparent_link.vk.link_pp.g2,
+        }],
+        &mut rng,
+    )
+    .unwrap();
+
+    // Generators other than the ones of the key
+    assert_eq!(
+        check_proving_key_incl_cp_link(
+            &params_link,
+            circuit.clone(),
+            &[link_gens[1].clone(), link_gens[0].clone()],
+            &mut rng
+        )
+        .unwrap_err(),
+        Error::InvalidProvingKey
+    );
+    assert_eq!(
+        check_proving_key_incl_cp_link(&params_link, circuit.clone(), &link_gens[..1], &mut rng)
+            .unwrap_err(),
+        Error::InvalidProvingKey
+    );
+    let mut other_gens = link_gens.clone();
+    other_gens[1].g2 = E::G2::rand(&mut rng).into_affine();
+    assert_eq!(
+        check_proving_key_incl_cp_link(&params_link, circuit.clone(), &other_gens, &mut rng)
+            .unwrap_err(),
+        Error::InvalidProvingKey
+    );
+
+    // Evaluation key of the link SNARK from another key with the same generators
+    let params_link_1 = generate_random_parameters_incl_cp_links::<E, _, _>(
+        circuit.clone(),
+        vec![(link_gens[0].clone(), 0..2), (link_gens[1].clone(), 2..4)],
+        4,
+        &mut rng,
+    )
+    .unwrap();
+    let mut bad_params_link = params_link.clone();
+    bad_params_link.link_ek = params_link_1.link_ek;
+    assert_eq!(
+        check_proving_key_incl_cp_link(&bad_params_link, circuit.clone(), &link_gens, &mut rng)
+            .unwrap_err(),
+        Error::InvalidProvingKey
+    );
+    let mut bad_params_link = params_link.clone();
+    bad_params_link.vk.link_witness_ranges.swap(0, 1);
+    assert_eq!(
+        check_proving_key_incl_cp_link(&bad_params_link, circuit, &link_gens, &mut rng)
+            .unwrap_err(),
+        Error::InvalidProvingKey
+    );
+}
+
+fn test_fingerprint<E>()
+where
+    E: Pairing,
+{
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let circuit = MyLessSillyCircuit1 {
+        a: None,
+        b: None,
+        c: None,
+        d: None,
+    };
+    let params = generate_random_parameters::<E, _, _>(circuit.clone(), 4, &mut rng).unwrap();
+    let params_1 = generate_random_parameters::<E, _, _>(circuit.clone(), 4, &mut rng).unwrap();
+
+    // Computed over the compressed encoding so it does not depend on how the key was stored
+    let mut bytes = vec![];
+    params.serialize_uncompressed(&mut bytes).unwrap();
+    let params_deser = ProvingKey::<E>::deserialize_uncompressed(&bytes[..]).unwrap();
+    assert_eq!(params.fingerprint(), params_deser.fingerprint());
+    assert_eq!(params.vk.fingerprint(), params_deser.vk.fingerprint());
+
+    // The proving key's fingerprint commits to the verifying key's and differs from it
+    assert_eq!(params.vk_fingerprint(), params.vk.fingerprint());
+    assert_ne!(params.fingerprint(), params.vk.fingerprint());
+    assert_ne!(params.fingerprint(), params_1.fingerprint());
+    assert_ne!(params.vk.fingerprint(), params_1.vk.fingerprint());
+    let mut params_2 = params.clone();
+    params_2.common.h_query.pop();
+    assert_eq!(params.vk_fingerprint(), params_2.vk_fingerprint());
+    assert_ne!(params.fingerprint(), params_2.fingerprint());
+
+    let link_gens = get_link_public_gens(&mut rng, 5);
+    let params_link = generate_random_parameters_incl_cp_link::<E, _, _>(
+        circuit.clone(),
+        link_gens.clone(),
+        4,
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(params_link.vk_fingerprint(), params_link.vk.fingerprint());
+    assert_ne!(
+        params_link.vk.fingerprint(),
+        params_link.vk.groth16_vk.fingerprint()
+    );
+    assert_ne!(params_link.fingerprint(), params_link.vk.fingerprint());
+    assert_eq!(params_link.vk.fingerprint().to_string().len(), 64);
+
+    // Link proofs with the transparent CP_link are bound to the Groth16 verifying key
+    let params_transparent_link = generate_random_parameters_incl_transparent_cp_link::<E, _, _>(
+        circuit, link_gens, 4, &mut rng,
+    )
+    .unwrap();
+    let fingerprint = params_transparent_link.vk.groth16_vk.fingerprint();
+    assert_eq!(params_transparent_link.link_ek.context, Some(fingerprint));
+    assert_eq!(
+        params_transparent_link.vk.link_vk.context,
+        Some(fingerprint)
+    );
+
+    let pvk_link = prepare_verifying_key::<E>(&params_transparent_link.vk.groth16_vk);
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = E::ScalarField::rand(&mut rng);
+    let d = E::ScalarField::rand(&mut rng);
+    let circuit = MyLessSillyCircuit1 {
+        a: Some(a),
+        b: Some(b),
+        c: Some(c),
+        d: Some(d),
+    };
+    let v = E::ScalarField::rand(&mut rng);
+    let link_v = E::ScalarField::rand(&mut rng);
+    let proof_link =
+        create_random_proof_incl_cp_link(circuit, v, link_v, &params_transparent_link, &mut rng)
+            .unwrap();
+    verify_proof_incl_cp_link(
+        &pvk_link,
+        &params_transparent_link.vk,
+        &proof_link,
+        &[a * b, c * d],
+    )
+    .unwrap();
+    let mut other_vk = params_transparent_link.vk.clone();
+    other_vk.link_vk.context = Some(params.vk.fingerprint());
+    assert!(verify_proof_incl_cp_link(&pvk_link, &other_vk, &proof_link, &[a * b, c * d]).is_err());
+}
+
+fn test_envelope<E, E2>()
+where
+    E: Pairing,
+    E2: Pairing,
+{
+    use crate::envelope::{curve_id, read_envelope_header, EnvelopeError, Enveloped, ObjectType};
+    use crate::link::{PESubspaceSnark, SigmaSubspaceSnark, SubspaceSnark};
+    use ark_serialize::{Compress, Validate};
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let circuit = MyLessSillyCircuit1 {
+        a: None,
+        b: None,
+        c: None,
+        d: None,
+    };
+    let params = generate_random_parameters::<E, _, _>(circuit.clone(), 4, &mut rng).unwrap();
+    let params_link = generate_random_parameters_incl_cp_link::<E, _, _>(
+        circuit,
+        get_link_public_gens(&mut rng, 5),
+        4,
+        &mut rng,
+    )
+    .unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = E::ScalarField::rand(&mut rng);
+    let d = E::ScalarField::rand(&mut rng);
+    let circuit = MyLessSillyCircuit1 {
+        a: Some(a),
+        b: Some(b),
+        c: Some(c),
+        d: Some(d),
+    };
+    let v = E::ScalarField::rand(&mut rng);
+    let link_v = E::ScalarField::rand(&mut rng);
+    let proof = create_random_proof(circuit.clone(), v, &params, &mut rng).unwrap();
+    let proof_link =
+        create_random_proof_incl_cp_link(circuit.clone(), v, link_v, &params_link, &mut rng)
+            .unwrap();
+
+    for compress in [Compress::Yes, Compress::No] {
+        let bytes = params.to_envelope_bytes(compress).unwrap();
+        assert_eq!(
+            ProvingKey::<E>::from_envelope_bytes(&bytes, Validate::Yes).unwrap(),
+            params
+        );
+        let header = read_envelope_header(&bytes).unwrap();
+        assert_eq!(header.object_type, ObjectType::ProvingKey as u8);
+        assert_eq!(header.curve_id, curve_id::<E>());
+        assert_eq!(header.commit_witness_count, Some(4));
+        assert_eq!(header.compressed, compress == Compress::Yes);
+
+        let bytes = params_link.vk.to_envelope_bytes(compress).unwrap();
+        assert_eq!(
+            VerifyingKeyWithLink::<E>::from_envelope_bytes(&bytes, Validate::Yes).unwrap(),
+            params_link.vk
+        );
+        let bytes = proof_link.to_envelope_bytes(compress).unwrap();
+        assert_eq!(
+            ProofWithLink::<E>::from_envelope_bytes(&bytes, Validate::Yes).unwrap(),
+            proof_link
+        );
+    }
+
+    // Reading as another type or for another curve
+    let bytes = params.vk.to_envelope_bytes(Compress::Yes).unwrap();
+    assert_eq!(
+        Proof::<E>::from_envelope_bytes(&bytes, Validate::Yes).unwrap_err(),
+        EnvelopeError::ObjectTypeMismatch(ObjectType::VerifyingKey as u8, ObjectType::Proof as u8)
+    );
+    assert_eq!(
+        VerifyingKey::<E2>::from_envelope_bytes(&bytes, Validate::Yes).unwrap_err(),
+        EnvelopeError::CurveMismatch(curve_id::<E>(), curve_id::<E2>())
+    );
+
+    // Keys and proofs with CP_link are read only for the Subspace Snark they were created with
+    let params_sigma = generate_random_parameters_incl_transparent_cp_link::<E, _, _>(
+        MyLessSillyCircuit1 {
+            a: None,
+            b: None,
+            c: None,
+            d: None,
+        },
+        get_link_public_gens(&mut rng, 5),
+        4,
+        &mut rng,
+    )
+    .unwrap();
+    let proof_sigma =
+        create_random_proof_incl_cp_link(circuit, v, link_v, &params_sigma, &mut rng).unwrap();
+    let vk_bytes = params_sigma.vk.to_envelope_bytes(Compress::Yes).unwrap();
+    let proof_bytes = proof_sigma.to_envelope_bytes(Compress::Yes).unwrap();
+    assert_eq!(
+        read_envelope_header(&proof_bytes).unwrap().link_snark,
+        Some(SigmaSubspaceSnark::<E>::ID)
+    );
+    assert_eq!(
+        VerifyingKeyWithLink::<E, SigmaSubspaceSnark<E>>::from_envelope_bytes(
+            &vk_bytes,
+            Validate::Yes
+        )
+        .unwrap(),
+        params_sigma.vk
+    );
+    assert_eq!(
+        VerifyingKeyWithLink::<E>::from_envelope_bytes(&vk_bytes, Validate::Yes).unwrap_err(),
+        EnvelopeError::ObjectTypeMismatch(SigmaSubspaceSnark::<E>::ID, PESubspaceSnark::<E>::ID)
+    );
+    assert_eq!(
+        ProofWithLink::<E>::from_envelope_bytes(&proof_bytes, Validate::Yes).unwrap_err(),
+        EnvelopeError::ObjectTypeMismatch(SigmaSubspaceSnark::<E>::ID, PESubspaceSnark::<E>::ID)
+    );
+    let bytes = proof_link.to_envelope_bytes(Compress::Yes).unwrap();
+    assert_eq!(
+        ProofWithLink::<E, SigmaSubspaceSnark<E>>::from_envelope_bytes(&bytes, Validate::Yes)
+            .unwrap_err(),
+        EnvelopeError::ObjectTypeMismatch(PESubspaceSnark::<E>::ID, SigmaSubspaceSnark::<E>::ID)
+    );
+
+    // Corrupted, truncated or not enveloped
+    let mut bad_bytes = bytes.clone();
+    let i = bad_bytes.len() / 2;
+    bad_bytes[i] ^= 1;
+    assert_eq!(
+        VerifyingKey::<E>::from_envelope_bytes(&bad_bytes, Validate::Yes).unwrap_err(),
+        EnvelopeError::InvalidChecksum
+    );
+    assert!(VerifyingKey::<E>::from_envelope_bytes(&bytes[..i], Validate::Yes).is_err());
+    let mut raw = vec![];
+    params.vk.serialize_compressed(&mut raw).unwrap();
+    assert_eq!(
+        VerifyingKey::<E>::from_envelope_bytes(&raw, Validate::Yes).unwrap_err(),
+        EnvelopeError::InvalidMagic
+    );
+    assert_eq!(
+        VerifyingKey::<E>::from_envelope_or_legacy_bytes(&raw, Compress::Yes, Validate::Yes)
+            .unwrap(),
+        params.vk
+    );
+
+    assert_eq!(
+        Proof::<E>::from_envelope_or_legacy_bytes(
+            &proof.to_envelope_bytes(Compress::Yes).unwrap(),
+            Compress::No,
+            Validate::Yes
+        )
+        .unwrap(),
+        proof
+    );
+}
+
+/// Reads the keys and proofs in `test-vectors/legacy/v0.6`, which were written by version 0.6 for a
+/// `MyLessSillyCircuit1` committing to 4 witnesses with a single link commitment
+#[cfg(feature = "std")]
+fn test_envelope_v0_6_fixtures() {
+    use crate::envelope::Enveloped;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_serialize::{CanonicalDeserialize, Compress, Validate};
+
+    let read = |name: &str| {
+        let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("test-vectors/legacy/v0.6/bls12-381");
+        path.push(name);
+        std::fs::read(path).unwrap()
+    };
+    let public_inputs = Vec::<Fr>::deserialize_compressed(&read("public_inputs.bin")[..]).unwrap();
+
+    let vk = VerifyingKey::<Bls12_381>::from_envelope_or_legacy_bytes(
+        &read("verifying_key.bin"),
+        Compress::Yes,
+        Validate::Yes,
+    )
+    .unwrap();
+    let proof = Proof::<Bls12_381>::from_envelope_or_legacy_bytes(
+        &read("proof.bin"),
+        Compress::Yes,
+        Validate::Yes,
+    )
+    .unwrap();
+    verify_proof(&prepare_verifying_key(&vk), &proof, &public_inputs).unwrap();
+
+    let pk_link = ProvingKeyWithLink::<Bls12_381>::from_envelope_or_legacy_bytes(
+        &read("proving_key_with_link.bin"),
+        Compress::No,
+        Validate::Yes,
+    )
+    .unwrap();
+    let vk_link = VerifyingKeyWithLink::<Bls12_381>::from_envelope_or_legacy_bytes(
+        &read("verifying_key_with_link.bin"),
+        Compress::Yes,
+        Validate::Yes,
+    )
+    .unwrap();
+    assert_eq!(pk_link.vk, vk_link);
+    assert_eq!(vk_link.link_witness_ranges, vec![(0, 4)]);
+    let proof_link = ProofWithLink::<Bls12_381>::from_envelope_or_legacy_bytes(
+        &read("proof_with_link.bin"),
+        Compress::Yes,
+        Validate::Yes,
+    )
+    .unwrap();
+    let pvk_link = prepare_verifying_key(&vk_link.groth16_vk);
+    verify_proof_incl_cp_link(&pvk_link, &vk_link, &proof_link, &public_inputs).unwrap();
+
+    // The migrated proving key creates proofs that the migrated verifying key accepts
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let a = Fr::rand(&mut rng);
+    let b = Fr::rand(&mut rng);
+    let c = Fr::rand(&mut rng);
+    let d = Fr::rand(&mut rng);
+    let circuit = MyLessSillyCircuit1 {
+        a: Some(a),
+        b: Some(b),
+        c: Some(c),
+        d: Some(d),
+    };
+    let v = Fr::rand(&mut rng);
+    let link_v = Fr::rand(&mut rng);
+    let proof_link =
+        create_random_proof_incl_cp_link(circuit, v, link_v, &pk_link, &mut rng).unwrap();
+    verify_proof_incl_cp_link(&pvk_link, &vk_link, &proof_link, &[a * b, c * d]).unwrap();
+    verify_commitments(&vk_link, &proof_link, 2, &[a, b, c, d], &v, &link_v).unwrap();
+}
+
+fn test_progress_observer<E>()
+where
+    E: Pairing,
+{
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let circuit = MyLessSillyCircuit1 {
+        a: None,
+        b: None,
+        c: None,
+        d: None,
+    };
+    let gens = get_link_public_gens(&mut rng, 3);
+    let link_gens = vec![
+        (gens.clone(), 0..2),
+        (get_link_public_gens_like(&mut rng, 3, &gens), 2..4),
+    ];
+
+    let events = RefCell::new(vec![]);
+    let recorder = |event: Event| {
+        events.borrow_mut().push(event);
+        ControlFlow::Continue(())
+    };
+
+    let params_link = generate_random_parameters_incl_cp_links_with_observer::<E, _, _, _>(
+        circuit.clone(),
+        link_gens.clone(),
+        4,
+        &mut rng,
+        &recorder,
+    )
+    .unwrap();
+    let setup_events = events.take();
+    assert_eq!(
+        setup_events[0],
+        Event::Started {
+            phase: Phase::Synthesis,
+            size: 0
+        }
+    );
+    for phase in [
+        Phase::QapInstanceMap,
+        Phase::FixedBaseMsm(Query::A),
+        Phase::FixedBaseMsm(Query::BG1),
+        Phase::FixedBaseMsm(Query::BG2),
+        Phase::FixedBaseMsm(Query::H),
+        Phase::FixedBaseMsm(Query::L),
+        Phase::FixedBaseMsm(Query::GammaAbc),
+    ] {
+        assert!(setup_events
+            .iter()
+            .any(|e| matches!(e, Event::Finished { phase: p, .. } if *p == phase)));
+    }
+    assert!(setup_events.contains(&Event::Started {
+        phase: Phase::LinkKeygen,
+        size: 2
+    }));
+
+    let pvk = prepare_verifying_key::<E>(&params_link.vk.groth16_vk);
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = E::ScalarField::rand(&mut rng);
+    let d = E::ScalarField::rand(&mut rng);
+    let circuit = MyLessSillyCircuit1 {
+        a: Some(a),
+        b: Some(b),
+        c: Some(c),
+        d: Some(d),
+    };
+    let v = E::ScalarField::rand(&mut rng);
+    let link_v = vec![
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    ];
+
+    let proof = create_random_proof_incl_cp_links_with_observer(
+        circuit.clone(),
+        v,
+        &link_v,
+        &params_link,
+        &mut rng,
+        &recorder,
+    )
+    .unwrap();
+    verify_proof_incl_cp_link(&pvk, &params_link.vk, &proof, &[a * b, c * d]).unwrap();
+
+    let prover_events = events.take();
+    // Each phase is reported as started and then finished before the next one starts
+    assert_eq!(prover_events.len() % 2, 0);
+    for pair in prover_events.chunks(2) {
+        match (pair[0], pair[1]) {
+            (
+                Event::Started { phase, .. },
+                Event::Finished {
+                    phase: p, elapsed, ..
+                },
+            ) => {
+                assert_eq!(phase, p);
+                assert_eq!(elapsed.is_some(), cfg!(feature = "std"));
+            }
+            _ => panic!("unexpected events {:?}", pair),
+        }
+    }
+    let finished_size = |phase| {
+        prover_events.iter().find_map(|e| match e {
+            Event::Finished { phase: p, size, .. } if *p == phase => Some(*size),
+            _ => None,
+        })
+    };
+    assert!(finished_size(Phase::Synthesis).unwrap() > 0);
+    assert!(finished_size(Phase::WitnessMap).unwrap() > 0);
+    assert_eq!(finished_size(Phase::Msm(Query::D)), Some(4));
+    assert_eq!(finished_size(Phase::LinkCommitments), Some(2));
+    // The Subspace Snark's witnesses are the committed witnesses, the link hiders and `v`
+    assert_eq!(finished_size(Phase::LinkProof), Some(7));
+    for query in [Query::A, Query::BG1, Query::BG2, Query::H, Query::L] {
+        assert!(finished_size(Phase::Msm(query)).is_some());
+    }
+
+    // Cancelling at any point of proving or setup returns an error
+    for cancel_at in [Phase::Synthesis, Phase::Msm(Query::H), Phase::LinkProof] {
+        let cancel = |event: Event| match event {
+            Event::Finished { phase, .. } if phase == cancel_at => ControlFlow::Break(()),
+            _ => ControlFlow::Continue(()),
+        };
+        assert_eq!(
+            create_random_proof_incl_cp_links_with_observer(
+                circuit.clone(),
+                v,
+                &link_v,
+                &params_link,
+                &mut rng,
+                &cancel,
+            )
+            .unwrap_err(),
+            Error::Cancelled
+        );
+    }
+    let cancel = |event: Event| match event {
+        Event::Started {
+            phase: Phase::WitnessMap,
+            ..
+        } => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    };
+    assert_eq!(
+        create_random_proof_with_observer(
+            circuit.clone(),
+            v,
+            &ProvingKey {
+                vk: params_link.vk.groth16_vk.clone(),
+                common: params_link.common.clone(),
+            },
+            &mut rng,
+            &cancel,
+        )
+        .unwrap_err(),
+        Error::Cancelled
+    );
+    for cancel_at in [Phase::FixedBaseMsm(Query::L), Phase::LinkKeygen] {
+        let cancel = |event: Event| match event {
+            Event::Started { phase, .. } if phase == cancel_at => ControlFlow::Break(()),
+            _ => ControlFlow::Continue(()),
+        };
+        assert_eq!(
+            generate_random_parameters_incl_cp_links_with_observer::<E, _, _, _>(
+                circuit.clone(),
+                link_gens.clone(),
+                4,
+                &mut rng,
+                &cancel,
+            )
+            .unwrap_err(),
+            Error::Cancelled
+        );
+    }
+    assert_eq!(
+        generate_random_parameters_with_observer::<E, _, _, _>(
+            circuit.clone(),
+            4,
+            &mut rng,
+            &|_| { ControlFlow::Break(()) }
+        )
+        .unwrap_err(),
+        Error::Cancelled
+    );
+
+    // Generators with a given reduction, toxic waste or link SNARK, and with a single link
+    let cancel_link_keygen = |event: Event| match event {
+        Event::Started {
+            phase: Phase::LinkKeygen,
+            ..
+        } => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    };
+    assert_eq!(
+        generate_random_parameters_incl_cp_link_with_observer::<E, _, _, _>(
+            circuit.clone(),
+            get_link_public_gens(&mut rng, 5),
+            4,
+            &mut rng,
+            &cancel_link_keygen,
+        )
+        .unwrap_err(),
+        Error::Cancelled
+    );
+    assert_eq!(
+        generate_random_parameters_incl_transparent_cp_link_with_observer::<E, _, _, _>(
+            circuit.clone(),
+            get_link_public_gens(&mut rng, 5),
+            4,
+            &mut rng,
+            &cancel_link_keygen,
+        )
+        .unwrap_err(),
+        Error::Cancelled
+    );
+    let params = generate_parameters_with_qap_and_observer::<E, _, _, crate::LibsnarkReduction, _>(
+        circuit.clone(),
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+        E::G1::rand(&mut rng),
+        E::G2::rand(&mut rng),
+        4,
+        &mut rng,
+        &recorder,
+    )
+    .unwrap();
+    assert!(events.take().contains(&Event::Started {
+        phase: Phase::Synthesis,
+        size: 0
+    }));
+    check_proving_key(&params, circuit, &mut rng).unwrap();
+}
+
+#[cfg(feature = "ark-groth16")]
+fn test_groth16_conversion<E>(n_iters: usize)
+where
+    E: Pairing,
+{
+    use ark_ec::AffineRepr;
+    use ark_groth16::Groth16;
+    use ark_std::Zero;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let circuit = MySillyCircuit { a: None, b: None };
+
+    // Keys created by `ark_groth16` are usable by this crate and can be converted back
+    let groth16_pk =
+        Groth16::<E>::generate_random_parameters_with_reduction(circuit.clone(), &mut rng).unwrap();
+    let pk = crate::ProvingKey::<E>::from(groth16_pk.clone());
+    assert_eq!(
+        ark_groth16::ProvingKey::<E>::try_from(pk.clone()).unwrap(),
+        groth16_pk
+    );
+    let pvk = prepare_verifying_key::<E>(&pk.vk);
+    let groth16_pvk = ark_groth16::prepare_verifying_key(&groth16_pk.vk);
+
+    // Keys without committed witnesses created by this crate are usable by `ark_groth16`
+    let lego_pk = generate_random_parameters::<E, _, _>(circuit.clone(), 0, &mut rng).unwrap();
+    let lego_pvk = prepare_verifying_key::<E>(&lego_pk.vk);
+    let lego_groth16_pk = ark_groth16::ProvingKey::<E>::try_from(lego_pk.clone()).unwrap();
+    let lego_groth16_pvk = ark_groth16::prepare_verifying_key(&lego_groth16_pk.vk);
+
+    let pk_with_commitment =
+        generate_random_parameters::<E, _, _>(circuit.clone(), 2, &mut rng).unwrap();
+    assert_eq!(
+        ark_groth16::ProvingKey::<E>::try_from(pk_with_commitment.clone()).unwrap_err(),
+        Error::CannotConvertToGroth16
+    );
+    assert_eq!(
+        ark_groth16::VerifyingKey::<E>::try_from(pk_with_commitment.vk).unwrap_err(),
+        Error::CannotConvertToGroth16
+    );
+
+    for _ in 0..n_iters {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let mut c = a;
+        c.mul_assign(&b);
+        let circuit = MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        };
+        let v = E::ScalarField::rand(&mut rng);
+
+        // Groth16 proof verified by this crate
+        let groth16_proof = Groth16::<E>::create_random_proof_with_reduction(
+            circuit.clone(),
+            &groth16_pk,
+            &mut rng,
+        )
+        .unwrap();
+        let proof = crate::Proof::<E>::from(groth16_proof.clone());
+        verify_proof(&pvk, &proof, &[c]).unwrap();
+        assert!(verify_proof(&pvk, &proof, &[a]).is_err());
+        assert_eq!(
+            ark_groth16::Proof::<E>::try_from(proof.clone()).unwrap(),
+            groth16_proof
+        );
+
+        // Proof created by this crate with the converted key has no commitment whatever the `v`
+        let proof = create_random_proof(circuit.clone(), v, &pk, &mut rng).unwrap();
+        assert!(proof.d.is_zero());
+        verify_proof(&pvk, &proof, &[c]).unwrap();
+        let groth16_proof = ark_groth16::Proof::<E>::try_from(proof.clone()).unwrap();
+        assert!(Groth16::<E>::verify_proof(&groth16_pvk, &groth16_proof, &[c]).unwrap());
+
+        // A commitment can't be used with a key without `eta`
+        let mut bad_proof = proof.clone();
+        bad_proof.d = E::G1::rand(&mut rng).into_affine();
+        assert!(verify_proof(&pvk, &bad_proof, &[c]).is_err());
+
+        // Proof created by `ark_groth16` with the converted key
+        let groth16_proof = Groth16::<E>::create_random_proof_with_reduction(
+            circuit.clone(),
+            &lego_groth16_pk,
+            &mut rng,
+        )
+        .unwrap();
+        assert!(Groth16::<E>::verify_proof(&lego_groth16_pvk, &groth16_proof, &[c]).unwrap());
+        verify_proof(&lego_pvk, &groth16_proof.into(), &[c]).unwrap();
+
+        // Proof with a non-zero `v` needs to be changed to `v = 0` before converting
+        let lego_proof = create_random_proof(circuit, v, &lego_pk, &mut rng).unwrap();
+        assert_eq!(
+            ark_groth16::Proof::<E>::try_from(lego_proof.clone()).unwrap_err(),
+            Error::CannotConvertToGroth16
+        );
+        let lego_proof = rerandomize_proof_1(
+            &lego_proof,
+            v,
+            E::ScalarField::zero(),
+            &lego_pk.vk,
+            &lego_pk.common.eta_delta_inv_g1,
+            &mut rng,
+        );
+        verify_proof(&lego_pvk, &lego_proof, &[c]).unwrap();
+        let groth16_proof = ark_groth16::Proof::<E>::try_from(lego_proof).unwrap();
+        assert!(Groth16::<E>::verify_proof(&lego_groth16_pvk, &groth16_proof, &[c]).unwrap());
+    }
+}
+
+/// Code generic over `ark_snark` traits
+#[cfg(feature = "ark-snark")]
+fn generic_prove_and_verify<F, S, C>(circuit: C, public_input: &[F], bad_public_input: &[F])
+where
+    F: ark_ff::PrimeField,
+    S: ark_snark::CircuitSpecificSetupSNARK<F>,
+    C: ConstraintSynthesizer<F> + Clone,
+{
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let (pk, vk) = S::setup(circuit.clone(), &mut rng).unwrap();
+    let proof = S::prove(&pk, circuit, &mut rng).unwrap();
+    assert!(S::verify(&vk, public_input, &proof).unwrap());
+    assert!(!S::verify(&vk, bad_public_input, &proof).unwrap());
+}
+
+#[cfg(feature = "ark-snark")]
+fn test_snark_traits<E>()
+where
+    E: Pairing,
+{
+    use crate::{link::PESubspaceSnark, CommitAndProveSNARK, LegoGroth16, LibsnarkReduction};
+    use ark_snark::SNARK;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let mut c = a;
+    c.mul_assign(&b);
+    let circuit = MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    generic_prove_and_verify::<_, LegoGroth16<E>, _>(circuit.clone(), &[c], &[a]);
+    generic_prove_and_verify::<_, LegoGroth16<E, LibsnarkReduction, 2>, _>(
+        circuit.clone(),
+        &[c],
+        &[a],
+    );
+
+    // Commitment related operations
+    type S<E> = LegoGroth16<E, LibsnarkReduction, 2>;
+    let (pk, vk) = S::<E>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+    assert_eq!(vk.commit_witness_count, 2);
+    let v = E::ScalarField::rand(&mut rng);
+    let proof =
+        S::<E>::prove_with_commitment_randomness(&pk, circuit.clone(), v, &mut rng).unwrap();
+    assert!(S::<E>::verify(&vk, &[c], &proof).unwrap());
+    assert!(S::<E>::verify_witness_commitment(&vk, &proof, 1, &[a, b], &v).unwrap());
+    assert!(!S::<E>::verify_witness_commitment(&vk, &proof, 1, &[b, a], &v).unwrap());
+
+    let link_gens = get_link_public_gens(&mut rng, 3);
+    let (pk_link, vk_link) =
+        S::<E>::circuit_specific_setup_incl_cp_link(circuit.clone(), link_gens, &mut rng).unwrap();
+    let link_v = E::ScalarField::rand(&mut rng);
+    let proof_link =
+        S::<E>::prove_incl_cp_link(&pk_link, circuit.clone(), v, link_v, &mut rng).unwrap();
+    assert!(S::<E>::verify_incl_cp_link(&vk_link, &[c], &proof_link).unwrap());
+    assert!(!S::<E>::verify_incl_cp_link(&vk_link, &[a], &proof_link).unwrap());
+    assert!(S::<E>::verify_commitments(&vk_link, &proof_link, 1, &[a, b], &v, &link_v).unwrap());
+    assert!(!S::<E>::verify_commitments(&vk_link, &proof_link, 1, &[a, b], &v, &v).unwrap());
+
+    // Several link commitments with the Subspace Snark given explicitly
+    type SL<E> = LegoGroth16<E, LibsnarkReduction, 2, PESubspaceSnark<E>>;
+    let gens = get_link_public_gens(&mut rng, 2);
+    let link_gens = vec![
+        (gens.clone(), 0..1),
+        (get_link_public_gens_like(&mut rng, 2, &gens), 1..2),
+    ];
+    let (pk_links, vk_links) =
+        SL::<E>::circuit_specific_setup_incl_cp_links(circuit.clone(), link_gens, &mut rng)
+            .unwrap();
+    assert_eq!(vk_links.link_bases.len(), 2);
+    let link_vs = [
+        E::ScalarField::rand(&mut rng),
+        E::ScalarField::rand(&mut rng),
+    ];
+    let proof_links =
+        SL::<E>::prove_incl_cp_links(&pk_links, circuit, v, &link_vs, &mut rng).unwrap();
+    assert!(SL::<E>::verify_incl_cp_link(&vk_links, &[c], &proof_links).unwrap());
+    assert!(!SL::<E>::verify_incl_cp_link(&vk_links, &[a], &proof_links).unwrap());
+    assert!(SL::<E>::verify_commitments_incl_cp_links(
+        &vk_links,
+        &proof_links,
+        1,
+        &[a, b],
+        &v,
+        &link_vs
+    )
+    .unwrap());
+    assert!(!SL::<E>::verify_commitments_incl_cp_links(
+        &vk_links,
+        &proof_links,
+        1,
+        &[a, b],
+        &v,
+        &[link_vs[1], link_vs[0]]
+    )
+    .unwrap());
+}
+
+mod bls12_377 {
+    use super::*;
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<Bls12_377>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_1() {
+        test_prove_and_verify_1::<Bls12_377>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_2() {
+        test_prove_and_verify_2::<Bls12_377>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_transparent_cp_link() {
+        test_prove_and_verify_transparent_cp_link::<Bls12_377>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_multiple_cp_links() {
+        test_prove_and_verify_multiple_cp_links::<Bls12_377>(10);
+    }
+
+    #[test]
+    fn prover_context() {
+        test_prover_context::<Bls12_377>(10);
+    }
+
+    #[test]
+    fn prove_batch() {
+        test_prove_batch::<Bls12_377>(5);
+    }
+
+    #[test]
+    fn check_proving_key() {
+        test_check_proving_key::<Bls12_377>();
+    }
+
+    #[test]
+    fn fingerprint() {
+        test_fingerprint::<Bls12_377>();
+    }
+
+    #[test]
+    fn envelope() {
+        test_envelope::<Bls12_377, ark_bls12_381::Bls12_381>();
+    }
+
+    #[test]
+    fn progress_observer() {
+        test_progress_observer::<Bls12_377>();
+    }
+
+    #[cfg(feature = "ark-groth16")]
+    #[test]
+    fn groth16_conversion() {
+        test_groth16_conversion::<Bls12_377>(10);
+    }
+
+    #[cfg(feature = "ark-snark")]
+    #[test]
+    fn snark_traits() {
+        test_snark_traits::<Bls12_377>();
+    }
+}
+
+mod cp6_782 {
+    use super::*;
+
+    use ark_cp6_782::CP6_782;
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<CP6_782>(1);
+    }
+
+    #[test]
+    fn prove_and_verify_1() {
+        test_prove_and_verify_1::<CP6_782>(1);
+    }
+
+    #[test]
+    fn prove_and_verify_2() {
+        test_prove_and_verify_2::<CP6_782>(1);
+    }
+
+    #[test]
+    fn prove_and_verify_transparent_cp_link() {
+        test_prove_and_verify_transparent_cp_link::<CP6_782>(1);
+    }
+
+    #[test]
+    fn prove_and_verify_multiple_cp_links() {
+        test_prove_and_verify_multiple_cp_links::<CP6_782>(1);
+    }
+
+    #[test]
+    fn prover_context() {
+        test_prover_context::<CP6_782>(1);
+    }
+
+    #[test]
+    fn prove_batch() {
+        test_prove_batch::<CP6_782>(3);
+    }
+
+    #[test]
+    fn check_proving_key() {
+        test_check_proving_key::<CP6_782>();
+    }
+
+    #[test]
+    fn fingerprint() {
+        test_fingerprint::<CP6_782>();
+    }
+
+    #[test]
+    fn envelope() {
+        test_envelope::<CP6_782, ark_bls12_377::Bls12_377>();
+    }
+
+    #[test]
+    fn progress_observer() {
+        test_progress_observer::<CP6_782>();
+    }
+
+    #[cfg(feature = "ark-groth16")]
+    #[test]
+    fn groth16_conversion() {
+        test_groth16_conversion::<CP6_782>(1);
+    }
+
+    #[cfg(feature = "ark-snark")]
+    #[test]
+    fn snark_traits() {
+        test_snark_traits::<CP6_782>();
+    }
+}
+
+mod bls12_381 {
+    use super::*;
+    use ark_bls12_381::Bls12_381;
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<Bls12_381>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_1() {
+        test_prove_and_verify_1::<Bls12_381>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_2() {
+        test_prove_and_verify_2::<Bls12_381>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_transparent_cp_link() {
+        test_prove_and_verify_transparent_cp_link::<Bls12_381>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_multiple_cp_links() {
+        test_prove_and_verify_multiple_cp_links::<Bls12_381>(10);
+    }
+
+    #[test]
+    fn prover_context() {
+        test_prover_context::<Bls12_381>(10);
+    }
+
+    #[test]
+    fn prove_batch() {
+        test_prove_batch::<Bls12_381>(5);
+    }
+
+    #[test]
+    fn check_proving_key() {
+        test_check_proving_key::<Bls12_381>();
+    }
+
+    #[test]
+    fn fingerprint() {
+        test_fingerprint::<Bls12_381>();
+    }
+
+    #[test]
+    fn envelope() {
+        test_envelope::<Bls12_381, ark_bn254::Bn254>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn envelope_v0_6_fixtures() {
+        test_envelope_v0_6_fixtures();
+    }
+
+    #[test]
+    fn progress_observer() {
+        test_progress_observer::<Bls12_381>();
+    }
+
+    #[cfg(feature = "ark-groth16")]
+    #[test]
+    fn groth16_conversion() {
+        test_groth16_conversion::<Bls12_381>(10);
+    }
+
+    #[cfg(feature = "ark-snark")]
+    #[test]
+    fn snark_traits() {
+        test_snark_traits::<Bls12_381>();
+    }
+}
+
+mod bn254 {
+    use super::*;
+    use ark_bn254::Bn254;
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<Bn254>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_1() {
+        test_prove_and_verify_1::<Bn254>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_2() {
+        test_prove_and_verify_2::<Bn254>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_transparent_cp_link() {
+        test_prove_and_verify_transparent_cp_link::<Bn254>(10);
+    }
+
+    #[test]
+    fn prove_and_verify_multiple_cp_links() {
+        test_prove_and_verify_multiple_cp_links::<Bn254>(10);
+    }
+
+    #[test]
+    fn prover_context() {
+        test_prover_context::<Bn254>(10);
+    }
+
+    #[test]
+    fn prove_batch() {
+        test_prove_batch::<Bn254>(5);
+    }
+
+    #[test]
+    fn check_proving_key() {
+        test_check_proving_key::<Bn254>();
+    }
+
+    #[test]
+    fn fingerprint() {
+        test_fingerprint::<Bn254>();
+    }
+
+    #[test]
+    fn envelope() {
+        test_envelope::<Bn254, ark_bls12_381::Bls12_381>();
+    }
+
+    #[test]
+    fn progress_observer() {
+        test_progress_observer::<Bn254>();
+    }
+
+    #[cfg(feature = "ark-groth16")]
+    #[test]
+    fn groth16_conversion() {
+        test_groth16_conversion::<Bn254>(10);
+    }
+
+    #[cfg(feature = "ark-snark")]
+    #[test]
+    fn snark_traits() {
+        test_snark_traits::<Bn254>();
     }
 }
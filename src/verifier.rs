@@ -1,4 +1,4 @@
-use crate::link::{PESubspaceSnark, SubspaceSnark};
+use crate::link::LinkSnark;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
 use ark_ff::{One, PrimeField};
 
@@ -10,7 +10,6 @@ use crate::error::Error;
 use crate::{Proof, VerifyingKey};
 use ark_ec::VariableBaseMSM;
 use ark_std::cfg_iter;
-use ark_std::vec;
 use ark_std::vec::Vec;
 use core::ops::{AddAssign, Neg};
 
@@ -53,13 +52,13 @@ pub fn prepare_inputs<E: Pairing>(
 }
 
 /// Verify the proof of the Subspace Snark on the equality of openings of cp_link and proof.d
-pub fn verify_link_proof<E: Pairing>(
-    vk: &VerifyingKeyWithLink<E>,
-    proof: &ProofWithLink<E>,
+pub fn verify_link_proof<E: Pairing, L: LinkSnark<E>>(
+    vk: &VerifyingKeyWithLink<E, L>,
+    proof: &ProofWithLink<E, L>,
 ) -> crate::Result<()> {
-    let commitments = vec![proof.link_d.clone(), proof.groth16_proof.d.clone()];
-    PESubspaceSnark::<E>::verify(&vk.link_pp, &vk.link_vk, &commitments, &proof.link_pi)
-        .map_err(|e| e.into())
+    let mut commitments = proof.link_d.clone();
+    commitments.push(proof.groth16_proof.d);
+    L::verify(&vk.link_pp, &vk.link_vk, &commitments, &proof.link_pi).map_err(|e| e.into())
 }
 
 pub fn verify_qap_proof<E: Pairing>(
@@ -106,16 +105,20 @@ pub fn calculate_d<E: Pairing>(
     proof: &Proof<E>,
     public_inputs: &[E::ScalarField],
 ) -> crate::Result<E::G1Affine> {
+    // A key without `eta`, like one converted from Groth16, cannot have a commitment in the proof
+    if pvk.vk.eta_gamma_inv_g1.is_zero() && !proof.d.is_zero() {
+        return Err(Error::InvalidProof);
+    }
     let mut d = prepare_inputs(pvk, public_inputs)?;
     d += proof.d;
     Ok(d.into_affine())
 }
 
 /// Verify a LegoGroth16 proof `proof` against the prepared verification key `pvk`
-pub fn verify_proof_incl_cp_link<E: Pairing>(
+pub fn verify_proof_incl_cp_link<E: Pairing, L: LinkSnark<E>>(
     pvk: &PreparedVerifyingKey<E>,
-    vk: &VerifyingKeyWithLink<E>,
-    proof: &ProofWithLink<E>,
+    vk: &VerifyingKeyWithLink<E, L>,
+    proof: &ProofWithLink<E, L>,
     public_inputs: &[E::ScalarField],
 ) -> crate::Result<()> {
     verify_link_proof(vk, proof)?;
@@ -6,7 +6,6 @@
     stable_features,
     non_shorthand_field_patterns,
     renamed_and_removed_lints,
-    private_in_public,
     unsafe_code
 )]
 
@@ -30,7 +29,7 @@ use ark_std::rand::{rngs::StdRng, SeedableRng};
 use legogroth16::prover::verify_commitments;
 use legogroth16::{
     create_random_proof, generate_random_parameters, rerandomize_proof, rerandomize_proof_1,
-    verify_proof, verify_witness_commitment,
+    verify_proof, verify_witness_commitment, ProverContext,
 };
 
 const MIMC_ROUNDS: usize = 322;
@@ -185,6 +184,7 @@ fn mimc_legogroth16<E: Pairing>() {
         xr: None,
         constants: &constants,
     };
+    let empty_circuit = c.clone();
 
     // Parameters for generating proof containing CP_link as well
     let params_link = generate_random_parameters_incl_cp_link::<E, _, _>(
@@ -202,6 +202,11 @@ fn mimc_legogroth16<E: Pairing>() {
     // Verifying key for LegoGroth16
     let pvk = prepare_verifying_key(&params.vk);
 
+    // Computes once what all proofs of the circuit need
+    let context_link =
+        ProverContext::<E>::new_incl_cp_link(empty_circuit.clone(), &params_link).unwrap();
+    let context = ProverContext::<E>::new(empty_circuit, &params).unwrap();
+
     println!("Creating proofs...");
 
     // Let's benchmark stuff!
@@ -209,6 +214,8 @@ fn mimc_legogroth16<E: Pairing>() {
     let mut total_proving_inc_link = Duration::new(0, 0);
     let mut total_verifying_inc_link = Duration::new(0, 0);
     let mut total_proving = Duration::new(0, 0);
+    let mut total_proving_inc_link_with_context = Duration::new(0, 0);
+    let mut total_proving_with_context = Duration::new(0, 0);
     let mut total_rerandomizing = Duration::new(0, 0);
     let mut total_rerandomizing_1 = Duration::new(0, 0);
     let mut total_verifying = Duration::new(0, 0);
@@ -242,9 +249,29 @@ fn mimc_legogroth16<E: Pairing>() {
 
             let start = Instant::now();
             // Create a LegoGro16 proof without CP_link.
-            let proof = create_random_proof(c, v, &params, &mut rng).unwrap();
+            let proof = create_random_proof(c.clone(), v, &params, &mut rng).unwrap();
             total_proving += start.elapsed();
 
+            let start = Instant::now();
+            // Same proofs but using the precomputed context
+            let proof_link_with_context = context_link
+                .prove_incl_cp_link(c.clone(), v, link_v, &mut rng)
+                .unwrap();
+            total_proving_inc_link_with_context += start.elapsed();
+
+            let start = Instant::now();
+            let proof_with_context = context.prove(c, v, &mut rng).unwrap();
+            total_proving_with_context += start.elapsed();
+
+            verify_proof_incl_cp_link(
+                &pvk_link,
+                &params_link.vk,
+                &proof_link_with_context,
+                &[image],
+            )
+            .unwrap();
+            verify_proof(&pvk, &proof_with_context, &[image]).unwrap();
+
             // Prover verifies the openings of the commitments in both proof.d and CP_link
             verify_commitments(&params_link.vk, &proof_link, 1, &[xl, xr], &v, &link_v).unwrap();
             // Prover verifies the openings of the commitments in proof.d
@@ -297,7 +324,15 @@ fn mimc_legogroth16<E: Pairing>() {
         "Average verifying time including link proof: {:?} seconds",
         avg(total_verifying_inc_link)
     );
+    println!(
+        "Average proving time including link proof with context: {:?} seconds",
+        avg(total_proving_inc_link_with_context)
+    );
     println!("Average proving time: {:?} seconds", avg(total_proving));
+    println!(
+        "Average proving time with context: {:?} seconds",
+        avg(total_proving_with_context)
+    );
     println!("Average verifying time: {:?} seconds", avg(total_verifying));
     println!(
         "Average re-randomizing proof time: {:?} seconds",